@@ -8,6 +8,7 @@ use crate::shared::{Axis, Direction, Position, Version};
 use cgmath::Point3;
 use collision::Aabb3;
 use std::collections::HashMap;
+use std::sync::OnceLock;
 
 pub mod material;
 pub use self::material::Material;
@@ -29,6 +30,8 @@ enum IDMapKind {
 pub struct VanillaIDMap {
     mapping: IDMapKind,
     modded: HashMap<String, [Option<Block>; 16]>,
+    /// The lowest id of every block, built the first time it's needed.
+    reverse: OnceLock<HashMap<Block, usize>>,
 }
 
 impl VanillaIDMap {
@@ -43,9 +46,35 @@ impl VanillaIDMap {
         Self {
             mapping,
             modded: HashMap::new(),
+            reverse: OnceLock::new(),
         }
     }
 
+    /// The id the block has in this version, the opposite of
+    /// `by_vanilla_id`. Modded blocks and blocks the version doesn't have
+    /// have no id.
+    pub fn to_vanilla_id(&self, block: Block) -> Option<usize> {
+        let reverse = self.reverse.get_or_init(|| {
+            let mut reverse = HashMap::new();
+            match &self.mapping {
+                IDMapKind::Flat(blocks) => {
+                    for (id, block) in blocks.iter().enumerate() {
+                        reverse.entry(*block).or_insert(id);
+                    }
+                }
+                IDMapKind::Hierarchical => {
+                    for id in 0..(4096 << 4) {
+                        if let Some(block) = versions::legacy::resolve(id) {
+                            reverse.entry(block).or_insert(id);
+                        }
+                    }
+                }
+            }
+            reverse
+        });
+        reverse.get(&block).copied()
+    }
+
     pub fn by_vanilla_id(
         &self,
         id: usize,
@@ -77,6 +106,38 @@ impl VanillaIDMap {
     }
 }
 
+/// The states of every block of the newest version, by name.
+type StateTable = HashMap<&'static str, Vec<(Vec<(&'static str, String)>, Block)>>;
+
+static STATES: OnceLock<StateTable> = OnceLock::new();
+
+/// The block with the given name (without namespace) and properties, the
+/// opposite of `Block::get_state`. Properties the block doesn't have, or
+/// doesn't list, fall back to the state matching the most of them.
+pub fn by_state(name: &str, properties: &[(&str, &str)]) -> Option<Block> {
+    let states = STATES
+        .get_or_init(|| {
+            let mut states: StateTable = HashMap::new();
+            for block in versions::get_block_mapping(Version::V1_19) {
+                let (name, properties) = block.get_state();
+                states.entry(name).or_default().push((properties, *block));
+            }
+            states
+        })
+        .get(name)?;
+    // Reversed so the first, default, state wins ties
+    states
+        .iter()
+        .rev()
+        .max_by_key(|(state, _)| {
+            state
+                .iter()
+                .filter(|(key, value)| properties.contains(&(*key, value.as_str())))
+                .count()
+        })
+        .map(|(_, block)| *block)
+}
+
 #[derive(Clone, Copy, Debug)]
 pub enum TintType {
     Default,
@@ -181,6 +242,41 @@ mod tests {
         );
     }
 
+    #[test]
+    fn to_vanilla_id() {
+        let modded = Arc::new(HashMap::new());
+        for &protocol_version in &[5, 340, 404, 754] {
+            let id_map = VanillaIDMap::new(protocol_version);
+            for block in [
+                Stone {},
+                Conduit { waterlogged: true },
+                StructureBlock {
+                    mode: StructureBlockMode::Data,
+                },
+            ] {
+                if let Some(id) = id_map.to_vanilla_id(block) {
+                    assert_eq!(id_map.by_vanilla_id(id, &modded), block);
+                }
+            }
+        }
+        assert_eq!(VanillaIDMap::new(340).to_vanilla_id(Stone {}), Some(1 << 4));
+        assert_eq!(VanillaIDMap::new(340).to_vanilla_id(Missing {}), None);
+    }
+
+    #[test]
+    fn by_state() {
+        let conduit = Conduit { waterlogged: false };
+        let (name, properties) = conduit.get_state();
+        let properties: Vec<_> = properties.iter().map(|(k, v)| (*k, v.as_str())).collect();
+        assert_eq!(super::by_state(name, &properties), Some(conduit));
+        assert_eq!(super::by_state("stone", &[]), Some(Stone {}));
+        assert_eq!(
+            super::by_state("conduit", &[("waterlogged", "true"), ("unknown", "1")]),
+            Some(Conduit { waterlogged: true })
+        );
+        assert_eq!(super::by_state("not_a_block", &[]), None);
+    }
+
     #[test]
     fn verify_blocks() {
         let dirt = Block::Dirt {};
@@ -380,7 +476,7 @@ mod tests {
             //(vine, Some(Tool::Shears), Some(0.3)),
         ];
         for (block, tool, time) in data {
-            let result = block.get_mining_time(&tool).map(|d| d.as_secs_f64());
+            let result = mining_seconds(&block, &tool, &MiningModifiers::default());
             match (time, result) {
                 (Some(time), Some(result)) => assert_eq!(result, time,
                     "Expected to mine block {:?} with {:?} in {} seconds, but it took {} seconds",
@@ -395,6 +491,146 @@ mod tests {
             }
         }
     }
+
+    fn mining_seconds(
+        block: &Block,
+        tool: &Option<Tool>,
+        modifiers: &MiningModifiers,
+    ) -> Option<f64> {
+        match block.get_mining_time(tool, modifiers) {
+            // Breaking still takes a tick
+            MiningTime::Instant => Some(0.05),
+            MiningTime::Time(time) => Some(time.as_secs_f64()),
+            MiningTime::Never => None,
+        }
+    }
+
+    #[test]
+    fn mining_time_modifiers() {
+        let stone = Block::Stone {};
+        let wooden = Some(Tool::Pickaxe(ToolMaterial::Wooden));
+        let normal = MiningModifiers::default();
+        let data = [
+            (
+                stone,
+                Some(Tool::Pickaxe(ToolMaterial::Diamond)),
+                MiningModifiers {
+                    efficiency: 5,
+                    ..normal
+                },
+                Some(0.1),
+            ),
+            (
+                stone,
+                Some(Tool::Pickaxe(ToolMaterial::Iron)),
+                MiningModifiers {
+                    efficiency: 1,
+                    ..normal
+                },
+                Some(0.3),
+            ),
+            // Efficiency doesn't help the hand
+            (
+                stone,
+                None,
+                MiningModifiers {
+                    efficiency: 5,
+                    ..normal
+                },
+                Some(7.5),
+            ),
+            (
+                stone,
+                wooden,
+                MiningModifiers { haste: 2, ..normal },
+                Some(0.85),
+            ),
+            (
+                stone,
+                Some(Tool::Pickaxe(ToolMaterial::Diamond)),
+                MiningModifiers {
+                    mining_fatigue: 1,
+                    ..normal
+                },
+                Some(0.95),
+            ),
+            (
+                stone,
+                wooden,
+                MiningModifiers {
+                    submerged: true,
+                    ..normal
+                },
+                Some(5.65),
+            ),
+            (
+                stone,
+                wooden,
+                MiningModifiers {
+                    submerged: true,
+                    aqua_affinity: true,
+                    ..normal
+                },
+                Some(1.15),
+            ),
+            (
+                stone,
+                wooden,
+                MiningModifiers {
+                    on_ground: false,
+                    ..normal
+                },
+                Some(5.65),
+            ),
+            (
+                stone,
+                wooden,
+                MiningModifiers {
+                    submerged: true,
+                    on_ground: false,
+                    ..normal
+                },
+                Some(28.15),
+            ),
+            (
+                Block::Dirt {},
+                Some(Tool::Shovel(ToolMaterial::Diamond)),
+                MiningModifiers {
+                    efficiency: 5,
+                    ..normal
+                },
+                Some(0.05),
+            ),
+            (
+                Block::Bedrock {},
+                None,
+                MiningModifiers {
+                    creative: true,
+                    ..normal
+                },
+                Some(0.05),
+            ),
+            (
+                stone,
+                Some(Tool::Sword(ToolMaterial::Diamond)),
+                MiningModifiers {
+                    creative: true,
+                    ..normal
+                },
+                None,
+            ),
+        ];
+        for (block, tool, modifiers, time) in data {
+            assert_eq!(
+                mining_seconds(&block, &tool, &modifiers),
+                time,
+                "Mining {:?} with {:?} and {:?}",
+                block,
+                tool,
+                modifiers
+            );
+        }
+    }
 }
 
 pub enum MiningTime {
@@ -403,7 +639,49 @@ pub enum MiningTime {
     Never,
 }
 
-pub fn get_mining_time(block: &Block, tool: &Option<Tool>) -> MiningTime {
+/// What besides the block and tool changes how long mining takes.
+#[derive(Clone, Copy, Debug)]
+pub struct MiningModifiers {
+    /// Level of the held tool's Efficiency enchantment.
+    pub efficiency: u8,
+    /// Levels of the Haste and Mining Fatigue effects, 0 without them.
+    pub haste: u8,
+    pub mining_fatigue: u8,
+    /// Whether the player's eyes are under water.
+    pub submerged: bool,
+    /// Whether the helmet has Aqua Affinity, which makes up for `submerged`.
+    pub aqua_affinity: bool,
+    pub on_ground: bool,
+    /// Creative players break everything at once, but not with a sword.
+    pub creative: bool,
+}
+
+impl Default for MiningModifiers {
+    fn default() -> Self {
+        MiningModifiers {
+            efficiency: 0,
+            haste: 0,
+            mining_fatigue: 0,
+            submerged: false,
+            aqua_affinity: false,
+            on_ground: true,
+            creative: false,
+        }
+    }
+}
+
+pub fn get_mining_time(
+    block: &Block,
+    tool: &Option<Tool>,
+    modifiers: &MiningModifiers,
+) -> MiningTime {
+    if modifiers.creative {
+        return match tool {
+            Some(Tool::Sword(_)) => MiningTime::Never,
+            _ => MiningTime::Instant,
+        };
+    }
+
     let mut speed_multiplier = 1.0;
 
     let tool_multiplier = tool.map(|t| t.get_multiplier()).unwrap_or(1.0);
@@ -415,11 +693,26 @@ pub fn get_mining_time(block: &Block, tool: &Option<Tool>) -> MiningTime {
         speed_multiplier = tool_multiplier;
     }
 
-    // TODO: Apply tool efficiency
-    // TODO: Apply haste effect
-    // TODO: Apply mining fatigue effect
-    // TODO: Apply in water multiplier
-    // TODO: Apply on ground multiplier
+    // Efficiency only helps tools that are fast on the block already
+    if speed_multiplier > 1.0 && modifiers.efficiency > 0 {
+        speed_multiplier += (modifiers.efficiency as f64).powi(2) + 1.0;
+    }
+    if modifiers.haste > 0 {
+        speed_multiplier *= 1.0 + 0.2 * modifiers.haste as f64;
+    }
+    speed_multiplier *= match modifiers.mining_fatigue {
+        0 => 1.0,
+        1 => 0.3,
+        2 => 0.09,
+        3 => 0.0027,
+        _ => 0.00081,
+    };
+    if modifiers.submerged && !modifiers.aqua_affinity {
+        speed_multiplier /= 5.0;
+    }
+    if !modifiers.on_ground {
+        speed_multiplier /= 5.0;
+    }
 
     let mut damage = match block.get_hardness() {
         // Instant mine
@@ -436,7 +729,7 @@ pub fn get_mining_time(block: &Block, tool: &Option<Tool>) -> MiningTime {
     }
 
     // Instant breaking
-    if damage > 1.0 {
+    if damage >= 1.0 {
         return MiningTime::Instant;
     }
 
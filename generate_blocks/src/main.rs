@@ -1093,6 +1093,25 @@ impl BlockMetaInfo {
         format!("{}(\"{}\", \"{}\"),", str, self.model.0, self.model.1)
     }
 
+    fn to_state(&self) -> String {
+        let info = &self.block_info;
+        let str = self.to_match_base();
+        let props: Vec<String> = info
+            .props
+            .iter()
+            .map(|prop| {
+                let value = match prop.state_type {
+                    block::StateType::Enum => {
+                        format!("{}.as_string().into()", prop.get_safe_name())
+                    }
+                    _ => format!("{}.to_string()", prop.get_safe_name()),
+                };
+                format!("(\"{}\", {})", prop.name, value)
+            })
+            .collect();
+        format!("{}(\"{}\", vec![{}]),", str, info.name, props.join(", "))
+    }
+
     fn to_model_variant(&self) -> Option<String> {
         if let Some(variant) = &self.model_variant {
             let str = self.to_match_base();
@@ -1347,6 +1366,16 @@ fn main() -> std::io::Result<()> {
         writeln!(blocks_file, "        }}")?;
         writeln!(blocks_file, "    }}")?;
         writeln!(blocks_file, "")?;
+        writeln!(blocks_file, "    /// The block's name and properties, as stored in 1.13+ world saves.")?;
+        writeln!(blocks_file, "    pub fn get_state(&self) -> (&'static str, Vec<(&'static str, String)>) {{")?;
+        writeln!(blocks_file, "        match *self {{")?;
+        for meta in &block_meta_list {
+            writeln!(blocks_file, "{}", meta.to_state())?;
+        }
+        writeln!(blocks_file, "            Block::Missing {{ }} => (\"air\", vec![]),")?;
+        writeln!(blocks_file, "        }}")?;
+        writeln!(blocks_file, "    }}")?;
+        writeln!(blocks_file, "")?;
         writeln!(blocks_file, "    #[allow(unused_variables)]")?;
         writeln!(blocks_file, "    pub fn get_model_variant(&self) -> String {{")?;
         writeln!(blocks_file, "        match *self {{")?;
@@ -1457,8 +1486,8 @@ fn main() -> std::io::Result<()> {
         writeln!(blocks_file, "        }}")?;
         writeln!(blocks_file, "    }}")?;
         writeln!(blocks_file, "")?;
-        writeln!(blocks_file, "    pub fn get_mining_time(&self, tool: &Option<Tool>) -> Option<std::time::Duration> {{")?;
-        writeln!(blocks_file, "        get_mining_time(self, tool)")?;
+        writeln!(blocks_file, "    pub fn get_mining_time(&self, tool: &Option<Tool>, modifiers: &MiningModifiers) -> MiningTime {{")?;
+        writeln!(blocks_file, "        get_mining_time(self, tool, modifiers)")?;
         writeln!(blocks_file, "    }}")?;
         writeln!(blocks_file, "")?;
         writeln!(blocks_file, "    #[allow(unused_variables, unreachable_code)]")?;
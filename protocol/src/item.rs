@@ -158,6 +158,13 @@ impl ItemMeta {
             .unwrap_or_default()
     }
 
+    /// The color leather armor was dyed, as `0xRRGGBB`.
+    pub fn color(&self) -> Option<i32> {
+        self.display()
+            .and_then(|val| val.get("color").map(|color| color.as_int()))
+            .flatten()
+    }
+
     pub fn repair_cost(&self) -> Option<i32> {
         match self.0.as_ref() {
             Some(tag) => tag
@@ -170,33 +177,57 @@ impl ItemMeta {
     }
 
     pub fn enchantments(&self) -> Vec<Enchantment> {
-        match self.0.as_ref() {
-            Some(tag) => tag
-                .1
-                .as_compound()
-                .and_then(|comp| {
-                    comp.get("ench").map(|ench| {
-                        ench.as_list().map(|enchs| {
-                            enchs
-                                .iter()
-                                .filter_map(|ench| {
-                                    ench.as_compound().and_then(|ench| {
-                                        ench.get("lvl")
-                                            .and_then(|lvl| lvl.as_short())
-                                            .zip(ench.get("id").and_then(|id| id.as_short()))
-                                            .and_then(|(level, id)| {
-                                                Enchantment::new(id as u16, level)
-                                            })
-                                    })
-                                })
-                                .collect::<Vec<_>>()
-                        })
-                    })
-                })
-                .flatten()
-                .unwrap_or(vec![]),
-            None => vec![],
-        }
+        let comp = match self.0.as_ref().and_then(|tag| tag.1.as_compound()) {
+            Some(comp) => comp,
+            None => return vec![],
+        };
+        // 1.13 renamed the list and switched to named ids
+        let (list, named) = match comp.get("Enchantments") {
+            Some(list) => (list, true),
+            None => match comp.get("ench") {
+                Some(list) => (list, false),
+                None => return vec![],
+            },
+        };
+        list.as_list()
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|ench| {
+                let ench = ench.as_compound()?;
+                let level = match ench.get("lvl")? {
+                    Tag::Short(level) => *level,
+                    Tag::Int(level) => *level as i16,
+                    _ => return None,
+                };
+                let ty = if named {
+                    EnchantmentTy::from_name(ench.get("id")?.as_str()?)?
+                } else {
+                    EnchantmentTy::from_id(ench.get("id")?.as_short()? as u16)?
+                };
+                Some(Enchantment { ty, level })
+            })
+            .collect()
+    }
+
+    /// Whether the item has any enchantments, including ones this doesn't
+    /// know about.
+    pub fn is_enchanted(&self) -> bool {
+        self.0
+            .as_ref()
+            .and_then(|tag| tag.1.as_compound())
+            .and_then(|comp| comp.get("Enchantments").or_else(|| comp.get("ench")))
+            .and_then(Tag::as_list)
+            .is_some_and(|list| !list.is_empty())
+    }
+
+    /// The level of the enchantment on the item, 0 if it has none.
+    pub fn enchantment_level(&self, ty: EnchantmentTy) -> i16 {
+        self.enchantments()
+            .iter()
+            .filter(|ench| ench.ty == ty)
+            .map(|ench| ench.level)
+            .max()
+            .unwrap_or(0)
     }
 }
 
@@ -205,17 +236,8 @@ pub struct Enchantment {
     pub level: i16,
 }
 
-impl Enchantment {
-    #[inline]
-    fn new(id: u16, level: i16) -> Option<Self> {
-        Some(Self {
-            ty: EnchantmentTy::from_id(id)?,
-            level,
-        })
-    }
-}
-
 #[repr(u32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum EnchantmentTy {
     Protection = 0,
     FireProtection = 1,
@@ -264,7 +286,7 @@ impl EnchantmentTy {
             21 => Some(Self::Looting),
             32 => Some(Self::Efficiency),
             33 => Some(Self::SilkTouch),
-            34 => Some(Self::Fortune),
+            34 => Some(Self::Unbreaking),
             35 => Some(Self::Fortune),
             48 => Some(Self::Power),
             49 => Some(Self::Punch),
@@ -275,4 +297,91 @@ impl EnchantmentTy {
             _ => None,
         }
     }
+
+    fn from_name(name: &str) -> Option<Self> {
+        match name.strip_prefix("minecraft:").unwrap_or(name) {
+            "protection" => Some(Self::Protection),
+            "fire_protection" => Some(Self::FireProtection),
+            "feather_falling" => Some(Self::FeatherFalling),
+            "blast_protection" => Some(Self::BlastProtection),
+            "projectile_protection" => Some(Self::ProjectileProtection),
+            "respiration" => Some(Self::Respiration),
+            "aqua_affinity" => Some(Self::AquaAffinity),
+            "thorns" => Some(Self::Thorns),
+            "depth_strider" => Some(Self::DepthStrider),
+            "sharpness" => Some(Self::Sharpness),
+            "smite" => Some(Self::Smite),
+            "bane_of_arthropods" => Some(Self::BaneOfArthropods),
+            "knockback" => Some(Self::Knockback),
+            "fire_aspect" => Some(Self::FireAspect),
+            "looting" => Some(Self::Looting),
+            "efficiency" => Some(Self::Efficiency),
+            "silk_touch" => Some(Self::SilkTouch),
+            "unbreaking" => Some(Self::Unbreaking),
+            "fortune" => Some(Self::Fortune),
+            "power" => Some(Self::Power),
+            "punch" => Some(Self::Punch),
+            "flame" => Some(Self::Flame),
+            "infinity" => Some(Self::Infinity),
+            "luck_of_the_sea" => Some(Self::LuckOfTheSea),
+            "lure" => Some(Self::Lure),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn enchanted(list: &str, id: Tag, level: Tag) -> ItemMeta {
+        let mut ench = Tag::new_compound();
+        ench.put("id", id);
+        ench.put("lvl", level);
+        let mut tag = Tag::new_compound();
+        tag.put(list, Tag::List(vec![ench]));
+        ItemMeta(Some(NamedTag("".into(), tag)))
+    }
+
+    #[test]
+    fn enchantments_before_and_after_1_13() {
+        let old = enchanted("ench", Tag::Short(32), Tag::Short(4));
+        assert_eq!(old.enchantment_level(EnchantmentTy::Efficiency), 4);
+        let new = enchanted(
+            "Enchantments",
+            Tag::String("minecraft:efficiency".into()),
+            Tag::Short(5),
+        );
+        assert_eq!(new.enchantment_level(EnchantmentTy::Efficiency), 5);
+        assert_eq!(new.enchantment_level(EnchantmentTy::AquaAffinity), 0);
+        assert_eq!(
+            ItemMeta(None).enchantment_level(EnchantmentTy::Efficiency),
+            0
+        );
+    }
+
+    #[test]
+    fn unknown_enchantments_still_glint() {
+        let unknown = enchanted(
+            "Enchantments",
+            Tag::String("minecraft:mending".into()),
+            Tag::Short(1),
+        );
+        assert!(unknown.enchantments().is_empty());
+        assert!(unknown.is_enchanted());
+        assert!(!ItemMeta(None).is_enchanted());
+    }
+
+    #[test]
+    fn dyed_leather() {
+        let mut display = Tag::new_compound();
+        display.put("color", Tag::Int(0x3b_51_aa));
+        let mut tag = Tag::new_compound();
+        tag.put("display", display);
+        assert_eq!(
+            ItemMeta(Some(NamedTag("".into(), tag))).color(),
+            Some(0x3b_51_aa)
+        );
+        assert_eq!(ItemMeta(None).color(), None);
+    }
 }
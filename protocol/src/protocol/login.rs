@@ -5,6 +5,8 @@ use dashmap::DashMap;
 use lazy_static::lazy_static;
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
+use std::time::Duration;
+
 pub trait AccountImpl {
     fn login(&self, username: &str, password: &str, token: &str) -> Result<Account, super::Error>;
 
@@ -28,6 +30,10 @@ pub struct Account {
     pub verification_tokens: Vec<String>, // this represents the verification tokens used to verify the account, such as hashed passwords, actual tokens, etc
     pub head_img_data: Option<Vec<u8>>,
     pub account_type: AccountType,
+    /// Path to a skin png on disk, only used by offline accounts as they can't
+    /// fetch one from the session server.
+    #[serde(default)]
+    pub skin_path: Option<String>,
 }
 
 impl Account {
@@ -38,12 +44,11 @@ impl Account {
             verification_tokens: vec![],
             head_img_data: None,
             account_type,
+            skin_path: None,
         }
     }
 
     /// Whether the profile is complete(not head-wise)
-    /// Will return false if the account is an offline account,
-    /// due to the uuid field being set to None
     pub fn is_complete(&self) -> bool {
         !self.name.is_empty() && self.uuid.is_some() && !self.verification_tokens.is_empty()
     }
@@ -103,6 +108,7 @@ impl Clone for Account {
             verification_tokens: self.verification_tokens.to_vec(),
             head_img_data: self.head_img_data.as_ref().map(|x| x.to_vec()),
             account_type: self.account_type.clone(),
+            skin_path: self.skin_path.clone(),
         }
     }
 }
@@ -124,3 +130,134 @@ pub enum AccountType {
     Custom(String), // Not implemented yet, this will enable us to support other auth services without implementing every single one specifically
     None,           // aka. unverified or "offline account" (for offline mode servers)
 }
+
+/// Why the session server refused to let us join a server.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuthErrorKind {
+    /// Too many requests (HTTP 429)
+    RateLimited,
+    /// The access token is invalid or has expired (HTTP 401/403)
+    InvalidToken,
+    /// The account isn't allowed to play multiplayer
+    MultiplayerDisabled,
+    /// The account is banned from multiplayer
+    Banned,
+    /// The session server couldn't be reached or had an internal error
+    Unavailable,
+    /// Anything else the session server answered with
+    Other,
+}
+
+#[derive(Debug, Clone)]
+pub struct AuthError {
+    pub kind: AuthErrorKind,
+    pub status: Option<u16>,
+    pub retry_after: Option<Duration>,
+}
+
+impl AuthError {
+    pub fn new(kind: AuthErrorKind, status: Option<u16>) -> Self {
+        AuthError {
+            kind,
+            status,
+            retry_after: None,
+        }
+    }
+
+    /// Classifies an unsuccessful answer of the session server, `error` is
+    /// the `error` field of the json body, if it had any.
+    pub fn from_response(status: u16, error: Option<&str>) -> Self {
+        let kind = match (status, error) {
+            (_, Some("InsufficientPrivilegesException")) => AuthErrorKind::MultiplayerDisabled,
+            (_, Some("UserBannedException")) => AuthErrorKind::Banned,
+            (429, _) => AuthErrorKind::RateLimited,
+            (401, _) | (403, _) => AuthErrorKind::InvalidToken,
+            (500..=599, _) => AuthErrorKind::Unavailable,
+            _ => AuthErrorKind::Other,
+        };
+        AuthError::new(kind, Some(status))
+    }
+
+    /// Whether trying again later might succeed without any user interaction.
+    pub fn is_transient(&self) -> bool {
+        matches!(
+            self.kind,
+            AuthErrorKind::RateLimited | AuthErrorKind::Unavailable
+        )
+    }
+
+    /// How long to wait before the given (1 based) retry.
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        self.retry_after
+            .unwrap_or_else(|| Duration::from_secs(1 << attempt.saturating_sub(1).min(3)))
+    }
+}
+
+impl std::fmt::Display for AuthError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self.kind {
+            AuthErrorKind::RateLimited => write!(
+                f,
+                "The session server is rate limiting your logins, wait a minute and try again."
+            ),
+            AuthErrorKind::InvalidToken => write!(
+                f,
+                "Your session has expired, restart the launcher or re-add the account to log in again."
+            ),
+            AuthErrorKind::MultiplayerDisabled => write!(
+                f,
+                "Multiplayer is disabled for this account, check its privacy settings."
+            ),
+            AuthErrorKind::Banned => write!(f, "This account is banned from multiplayer."),
+            AuthErrorKind::Unavailable => write!(
+                f,
+                "The session server is unavailable right now, try again later."
+            ),
+            AuthErrorKind::Other => match self.status {
+                Some(status) => write!(f, "Failed to auth with server (HTTP {})", status),
+                None => write!(f, "Failed to auth with server"),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classify_join_errors() {
+        assert_eq!(
+            AuthError::from_response(429, None).kind,
+            AuthErrorKind::RateLimited
+        );
+        assert_eq!(
+            AuthError::from_response(403, Some("ForbiddenOperationException")).kind,
+            AuthErrorKind::InvalidToken
+        );
+        assert_eq!(
+            AuthError::from_response(403, Some("InsufficientPrivilegesException")).kind,
+            AuthErrorKind::MultiplayerDisabled
+        );
+        assert_eq!(
+            AuthError::from_response(503, None).kind,
+            AuthErrorKind::Unavailable
+        );
+        assert!(AuthError::from_response(502, None).is_transient());
+        assert!(!AuthError::from_response(401, None).is_transient());
+    }
+
+    #[test]
+    fn join_backoff() {
+        let err = AuthError::new(AuthErrorKind::Unavailable, Some(503));
+        assert_eq!(err.backoff(0), Duration::from_secs(1));
+        assert_eq!(err.backoff(1), Duration::from_secs(1));
+        assert_eq!(err.backoff(3), Duration::from_secs(4));
+        assert_eq!(err.backoff(10), Duration::from_secs(8));
+        let err = AuthError {
+            retry_after: Some(Duration::from_secs(5)),
+            ..AuthError::new(AuthErrorKind::RateLimited, Some(429))
+        };
+        assert_eq!(err.backoff(1), Duration::from_secs(5));
+    }
+}
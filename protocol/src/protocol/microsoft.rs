@@ -12,11 +12,19 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::protocol::login::{Account, AccountImpl};
+use crate::protocol::login::{Account, AccountImpl, AuthError, AuthErrorKind};
+use log::warn;
 use serde_json::json;
 use sha1::Digest;
+use std::thread;
+use std::time::Duration;
 
 const JOIN_URL: &str = "https://sessionserver.mojang.com/session/minecraft/join";
+/// How often joining is attempted when the session server is rate
+/// limiting us or temporarily unavailable.
+const MAX_JOIN_ATTEMPTS: u32 = 4;
+/// Upper bound (in seconds) for waiting on a `Retry-After` header.
+const MAX_RETRY_AFTER: u64 = 30;
 
 pub struct MicrosoftAccount {}
 
@@ -67,17 +75,54 @@ impl AccountImpl for MicrosoftAccount {
         });
         let join = serde_json::to_string(&join_msg).unwrap();
 
-        let client = reqwest::blocking::Client::new();
-        let res = client
-            .post(JOIN_URL)
-            .header(reqwest::header::CONTENT_TYPE, "application/json")
-            .body(join)
-            .send()?;
-
-        if res.status() == reqwest::StatusCode::NO_CONTENT {
-            Ok(())
-        } else {
-            Err(super::Error::Err("Failed to auth with server".to_owned()))
+        let client = reqwest::blocking::Client::builder()
+            .timeout(Duration::from_secs(10))
+            .build()?;
+        let mut attempt = 0;
+        loop {
+            attempt += 1;
+            let err = match client
+                .post(JOIN_URL)
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .body(join.clone())
+                .send()
+            {
+                Ok(res) if res.status() == reqwest::StatusCode::NO_CONTENT => return Ok(()),
+                Ok(res) => {
+                    let status = res.status().as_u16();
+                    let retry_after = res
+                        .headers()
+                        .get(reqwest::header::RETRY_AFTER)
+                        .and_then(|v| v.to_str().ok())
+                        .and_then(|v| v.parse::<u64>().ok())
+                        .map(|secs| Duration::from_secs(secs.min(MAX_RETRY_AFTER)));
+                    let body: Option<serde_json::Value> = res
+                        .text()
+                        .ok()
+                        .and_then(|body| serde_json::from_str(&body).ok());
+                    let error = body
+                        .as_ref()
+                        .and_then(|body| body.get("error"))
+                        .and_then(|error| error.as_str());
+                    AuthError {
+                        retry_after,
+                        ..AuthError::from_response(status, error)
+                    }
+                }
+                Err(err) if err.is_timeout() || err.is_connect() => {
+                    AuthError::new(AuthErrorKind::Unavailable, None)
+                }
+                Err(err) => return Err(err.into()),
+            };
+            if !err.is_transient() || attempt >= MAX_JOIN_ATTEMPTS {
+                return Err(super::Error::Auth(err));
+            }
+            let delay = err.backoff(attempt);
+            warn!(
+                "Failed to join session server ({:?}), retrying in {:?}",
+                err.kind, delay
+            );
+            thread::sleep(delay);
         }
     }
 
@@ -485,6 +485,38 @@ impl Serializable for f64 {
 #[derive(Debug, PartialEq, Eq, Hash, Clone, Serialize, Deserialize, Default)]
 pub struct UUID(u64, u64);
 
+impl UUID {
+    /// Returns the name based (version 3) uuid vanilla servers in
+    /// offline mode assign to a player with the given name.
+    pub fn offline(name: &str) -> UUID {
+        use md5::Digest;
+        let mut hash = md5::Md5::digest(format!("OfflinePlayer:{}", name).as_bytes());
+        hash[6] = (hash[6] & 0x0f) | 0x30; // version 3
+        hash[8] = (hash[8] & 0x3f) | 0x80; // IETF variant
+        let mut high = 0u64;
+        let mut low = 0u64;
+        for i in 0..8 {
+            high |= (hash[i] as u64) << (56 - i * 8);
+            low |= (hash[i + 8] as u64) << (56 - i * 8);
+        }
+        UUID(high, low)
+    }
+}
+
+impl fmt::Display for UUID {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{:08x}-{:04x}-{:04x}-{:04x}-{:012x}",
+            self.0 >> 32,
+            (self.0 >> 16) & 0xffff,
+            self.0 & 0xffff,
+            self.1 >> 48,
+            self.1 & 0xffff_ffff_ffff
+        )
+    }
+}
+
 #[derive(Debug)]
 pub struct UUIDParseError;
 impl std::error::Error for UUIDParseError {}
@@ -560,10 +592,10 @@ impl Default for Biomes3D {
 
 impl Serializable for Biomes3D {
     fn read_from<R: io::Read>(buf: &mut R) -> Result<Biomes3D, Error> {
-        let data: [i32; 1024] = [0; 1024];
+        let mut data: [i32; 1024] = [0; 1024];
 
         // Non-length-prefixed three-dimensional biome data
-        for item in &mut data.to_vec() {
+        for item in data.iter_mut() {
             let b: i32 = Serializable::read_from(buf)?;
             *item = b;
         }
@@ -1053,6 +1085,7 @@ pub enum Error {
     IOError(io::Error),
     Json(serde_json::Error),
     Reqwest(reqwest::Error),
+    Auth(login::AuthError),
 }
 
 impl convert::From<io::Error> for Error {
@@ -1083,6 +1116,7 @@ impl ::std::fmt::Display for Error {
             Error::IOError(ref e) => e.fmt(f),
             Error::Json(ref e) => e.fmt(f),
             Error::Reqwest(ref e) => e.fmt(f),
+            Error::Auth(ref e) => e.fmt(f),
         }
     }
 }
@@ -1634,3 +1668,21 @@ pub trait PacketType {
 
     fn write<W: io::Write>(&self, buf: &mut W) -> Result<(), Error>;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn offline_uuid() {
+        assert_eq!(
+            UUID::offline("Notch").to_string(),
+            "b50ad385-829d-3141-a216-7e7d7539ba7f"
+        );
+        assert_eq!(
+            UUID::offline("jeb_"),
+            UUID::from_str("a762f560-4fce-3236-812a-b80efff0b62b").unwrap()
+        );
+    }
+}
@@ -1,18 +1,24 @@
 use crate::protocol::login::{Account, AccountImpl, AccountType};
+use crate::protocol::UUID;
 pub struct OfflineAccount {}
 
 impl AccountImpl for OfflineAccount {
     fn login(&self, name: &str, _password: &str, _token: &str) -> Result<Account, super::Error> {
         Ok(Account {
             name: name.to_string(),
-            uuid: None,
+            uuid: Some(UUID::offline(name).to_string()),
             verification_tokens: vec![name.to_string(), "".to_string(), "".to_string()],
             head_img_data: None,
             account_type: AccountType::None,
+            skin_path: None,
         })
     }
 
-    fn refresh(&self, account: Account, _token: &str) -> Result<Account, super::Error> {
+    fn refresh(&self, mut account: Account, _token: &str) -> Result<Account, super::Error> {
+        // Accounts saved by older versions don't have a uuid yet
+        if account.uuid.is_none() {
+            account.uuid = Some(UUID::offline(&account.name).to_string());
+        }
         Ok(account)
     }
 
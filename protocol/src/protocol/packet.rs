@@ -3431,6 +3431,70 @@ pub fn send_flying(conn: &mut Conn, on_ground: bool) -> Result<(), Error> {
     conn.write_packet(crate::protocol::packet::play::serverbound::Player { on_ground })
 }
 
+/// steer the vehicle the player is riding, sideways is positive to the left
+pub fn send_steer_vehicle(
+    conn: &mut Conn,
+    sideways: f32,
+    forward: f32,
+    jump: bool,
+    unmount: bool,
+) -> Result<(), Error> {
+    let version = conn.get_version();
+    if version < Version::V1_8 {
+        conn.write_packet(
+            crate::protocol::packet::play::serverbound::SteerVehicle_jump_unmount {
+                sideways,
+                forward,
+                jump,
+                unmount,
+            },
+        )
+    } else {
+        let mut flags = 0;
+        if jump {
+            flags |= 0x1;
+        }
+        if unmount {
+            flags |= 0x2;
+        }
+        conn.write_packet(crate::protocol::packet::play::serverbound::SteerVehicle {
+            sideways,
+            forward,
+            flags,
+        })
+    }
+}
+
+/// report the position of a vehicle driven by the player, only sent since 1.9;
+/// the rotation is in the same form as the one of other entities
+pub fn send_vehicle_move(
+    conn: &mut Conn,
+    position: &Vector3<f64>,
+    yaw: f32,
+    pitch: f32,
+) -> Result<(), Error> {
+    if conn.get_version() < Version::V1_9 {
+        return Ok(());
+    }
+    conn.write_packet(crate::protocol::packet::play::serverbound::VehicleMove {
+        x: position.x,
+        y: position.y,
+        z: position.z,
+        yaw: -yaw * (180.0 / PI),
+        pitch: -pitch * (180.0 / PI),
+    })
+}
+
+pub fn send_steer_boat(conn: &mut Conn, left: bool, right: bool) -> Result<(), Error> {
+    if conn.get_version() < Version::V1_9 {
+        return Ok(());
+    }
+    conn.write_packet(crate::protocol::packet::play::serverbound::SteerBoat {
+        left_paddle_turning: left,
+        right_paddle_turning: right,
+    })
+}
+
 pub fn send_client_status(conn: &mut Conn, status: ClientStatus) -> Result<(), Error> {
     let version = conn.get_version();
     // we don't send any information to the server when opening the inv in newer versions
@@ -3530,6 +3594,76 @@ pub fn send_use_item(
     }
 }
 
+/// What the player does to an entity.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UseEntityAction {
+    Interact,
+    Attack,
+    /// Interacting with the point on the entity, relative to its position.
+    InteractAt(Vector3<f64>),
+}
+
+impl UseEntityAction {
+    pub fn ordinal(&self) -> i32 {
+        match self {
+            UseEntityAction::Interact => 0,
+            UseEntityAction::Attack => 1,
+            UseEntityAction::InteractAt(_) => 2,
+        }
+    }
+}
+
+pub fn send_use_entity(
+    conn: &mut Conn,
+    entity_id: i32,
+    action: UseEntityAction,
+    hand: Hand,
+    sneaking: bool,
+) -> Result<(), Error> {
+    let version = conn.get_version();
+    let target = match action {
+        UseEntityAction::InteractAt(target) => target,
+        _ => Vector3::zero(),
+    };
+    if version < Version::V1_8 {
+        // Interacting at a point came with 1.8
+        if let UseEntityAction::InteractAt(_) = action {
+            return Ok(());
+        }
+        conn.write_packet(packet::play::serverbound::UseEntity_Handsfree_i32 {
+            target_id: entity_id,
+            ty: action.ordinal() as u8,
+        })
+    } else if version < Version::V1_9 {
+        conn.write_packet(packet::play::serverbound::UseEntity_Handsfree {
+            target_id: VarInt(entity_id),
+            ty: VarInt(action.ordinal()),
+            target_x: target.x as f32,
+            target_y: target.y as f32,
+            target_z: target.z as f32,
+        })
+    } else if version < Version::V1_16 {
+        conn.write_packet(packet::play::serverbound::UseEntity_Hand {
+            target_id: VarInt(entity_id),
+            ty: VarInt(action.ordinal()),
+            target_x: target.x as f32,
+            target_y: target.y as f32,
+            target_z: target.z as f32,
+            hand: VarInt(hand.ordinal()),
+        })
+    } else {
+        conn.write_packet(packet::play::serverbound::UseEntity_Sneakflag {
+            target_id: VarInt(entity_id),
+            ty: VarInt(action.ordinal()),
+            target_x: target.x as f32,
+            target_y: target.y as f32,
+            target_z: target.z as f32,
+            hand: VarInt(hand.ordinal()),
+            sneaking,
+        })
+    }
+}
+
 pub fn send_drop_item(conn: &mut Conn, whole_stack: bool) -> Result<(), Error> {
     send_digging(
         conn,
@@ -51,19 +51,34 @@ fn test_map_odd() {
     }
 }
 
+#[test]
+fn test_map_raw_round_trip() {
+    for &padded in &[false, true] {
+        let mut map = Map::from_raw(vec![0; 4096 * 5 / 60 + 1], 5, padded);
+        for i in 0..4096 {
+            map.set(i, i % 31);
+        }
+        let map = Map::from_raw(map.into_raw(), 5, padded);
+        for i in 0..4096 {
+            assert_eq!(map.get(i), i % 31);
+        }
+    }
+}
+
+#[test]
+fn test_map_memory_size() {
+    assert_eq!(Map::new(4096, 4).memory_size(), 2048);
+    assert_eq!(Map::new(4096, 5).memory_size(), 2560);
+}
+
 impl Map {
     pub fn new(len: usize, size: usize) -> Map {
-        let mut map = Map {
+        Map {
             bit_size: size,
             length: len,
-            bits: Vec::with_capacity((len * size) / 64),
+            bits: vec![0; (len * size + 63) / 64],
             padded: false,
-        };
-        for _ in 0..len {
-            // TODO: Try finding a O(1) solution to this
-            map.bits.push(0)
         }
-        map
     }
 
     pub fn from_raw(bits: Vec<u64>, size: usize, padded: bool) -> Map {
@@ -75,6 +90,16 @@ impl Map {
         }
     }
 
+    /// The packed entries, laid out like `from_raw` expects them.
+    pub fn into_raw(self) -> Vec<u64> {
+        self.bits
+    }
+
+    /// The number of bytes the packed entries take up.
+    pub fn memory_size(&self) -> usize {
+        self.bits.len() * std::mem::size_of::<u64>()
+    }
+
     pub fn resize(&self, size: usize) -> Map {
         let mut n = Map::new(self.length, size);
         for i in 0..self.length {
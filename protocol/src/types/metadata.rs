@@ -58,6 +58,12 @@ impl Metadata {
         self.map.insert(key.index, val.wrap());
     }
 
+    /// Iterates over the indices and values without knowing their types
+    /// up front.
+    pub fn iter(&self) -> impl Iterator<Item = (i32, &Value)> {
+        self.map.iter().map(|(index, value)| (*index, value))
+    }
+
     fn put_raw<T: MetaValue>(&mut self, index: i32, val: T) {
         self.map.insert(index, val.wrap());
     }
@@ -682,7 +688,7 @@ impl Serializable for VillagerData {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum PoseData {
     Standing,
     FallFlying,
@@ -981,4 +987,16 @@ mod test {
             None => panic!("failed"),
         }
     }
+
+    #[test]
+    fn iter() {
+        let mut m = Metadata::new();
+        m.put_raw(0, 0x02i8);
+        m.put_raw(6, PoseData::Sneaking);
+
+        let mut values: Vec<_> = m.iter().collect();
+        values.sort_by_key(|value| value.0);
+        assert!(matches!(values[0], (0, Value::Byte(0x02))));
+        assert!(matches!(values[1], (6, Value::Pose(PoseData::Sneaking))));
+    }
 }
@@ -0,0 +1,42 @@
+//! Where the launchers Leafish works alongside keep their data.
+
+use std::path::PathBuf;
+
+/// The `.minecraft` directory of the vanilla launcher.
+pub fn mojang_dir() -> Option<PathBuf> {
+    #[cfg(target_os = "linux")]
+    {
+        dirs::home_dir().map(|dir| dir.join(".minecraft"))
+    }
+    // The vanilla launcher leaves the dot out on macOS
+    #[cfg(target_os = "macos")]
+    {
+        dirs::config_dir().map(|dir| dir.join("minecraft"))
+    }
+    #[cfg(not(any(target_os = "linux", target_os = "macos")))]
+    {
+        dirs::config_dir().map(|dir| dir.join(".minecraft"))
+    }
+}
+
+/// All places PrismLauncher may keep its data in, including the flatpak one.
+pub fn prism_dirs() -> Vec<PathBuf> {
+    #[cfg(target_os = "linux")]
+    {
+        let mut prism_dirs = vec![];
+        if let Some(dir) = dirs::data_dir() {
+            prism_dirs.push(dir.join("PrismLauncher"));
+        }
+        if let Some(dir) = dirs::home_dir() {
+            prism_dirs
+                .push(dir.join(".var/app/org.prismlauncher.PrismLauncher/data/PrismLauncher"));
+        }
+        prism_dirs
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        dirs::config_dir()
+            .map(|dir| vec![dir.join("PrismLauncher")])
+            .unwrap_or_default()
+    }
+}
@@ -4,6 +4,8 @@ pub use self::axis::Axis;
 pub mod direction;
 pub use self::direction::Direction;
 
+pub mod launcher;
+
 pub mod position;
 pub use self::position::Position;
 
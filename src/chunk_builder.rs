@@ -53,6 +53,11 @@ impl ChunkBuilder {
         }
     }
 
+    /// The block models the builder meshes sections with.
+    pub fn models(&self) -> Arc<RwLock<model::Factory>> {
+        self.models.clone()
+    }
+
     pub fn tick(&mut self, world: Arc<World>, renderer: Arc<render::Renderer>, version: usize) {
         if version != self.resource_version {
             self.resource_version = version;
@@ -400,3 +405,65 @@ impl CullInfo {
         self.0 |= 1 << (from.index() * 6 + to.index());
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shared::Position;
+
+    /// Builds the cull info of the section at height 8 of chunk 0,0 after
+    /// filling its middle layer with stone, apart from `holes`.
+    fn floor_cull_info(holes: &[(i32, i32)]) -> CullInfo {
+        let world = World::new(754, unbounded().0);
+        for x in 0..16 {
+            for z in 0..16 {
+                if !holes.contains(&(x, z)) {
+                    world.set_block(Position::new(x, 8 * 16 + 8, z), block::Stone {});
+                }
+            }
+        }
+        build_cull_info(&ChunkSectionSnapshotGroup::new(Arc::new(world), 0, 0, 8, 2))
+    }
+
+    #[test]
+    fn empty_sections_are_seen_through() {
+        let world = World::new(754, unbounded().0);
+        world.set_block(Position::new(0, 0, 0), block::Stone {});
+        let info = build_cull_info(&ChunkSectionSnapshotGroup::new(Arc::new(world), 0, 0, 8, 2));
+        for from in Direction::all() {
+            for to in Direction::all() {
+                assert!(info.is_visible(from, to), "{:?} to {:?}", from, to);
+            }
+        }
+    }
+
+    #[test]
+    fn floors_split_sections() {
+        let info = floor_cull_info(&[]);
+        assert!(!info.is_visible(Direction::Up, Direction::Down));
+        assert!(!info.is_visible(Direction::Down, Direction::Up));
+        assert!(info.is_visible(Direction::Up, Direction::North));
+        assert!(info.is_visible(Direction::Down, Direction::West));
+        assert!(info.is_visible(Direction::West, Direction::East));
+
+        let info = floor_cull_info(&[(5, 9)]);
+        assert!(info.is_visible(Direction::Up, Direction::Down));
+        assert!(info.is_visible(Direction::Down, Direction::Up));
+        assert!(info.is_visible(Direction::Up, Direction::South));
+        assert!(info.is_visible(Direction::Down, Direction::East));
+        assert!(info.is_visible(Direction::West, Direction::East));
+    }
+
+    #[test]
+    fn snapshots_see_neighbouring_sections() {
+        let world = World::new(754, unbounded().0);
+        world.set_block(Position::new(-1, 130, 3), block::Stone {});
+        world.set_block(Position::new(16, 130, 3), block::Glowstone {});
+        world.set_block(Position::new(3, 144, 3), block::Bedrock {});
+        let snapshot = ChunkSectionSnapshotGroup::new(Arc::new(world), 0, 0, 8, 2);
+        assert_eq!(snapshot.get_block(-1, 2, 3), block::Stone {});
+        assert_eq!(snapshot.get_block(16, 2, 3), block::Glowstone {});
+        assert_eq!(snapshot.get_block(3, 16, 3), block::Bedrock {});
+        assert_eq!(snapshot.get_block(3, 2, 3), block::Air {});
+    }
+}
@@ -0,0 +1,362 @@
+use super::{box_textures, dye_from_id, dye_rgb};
+use crate::ecs::SystemExecStage;
+use crate::render;
+use crate::render::model;
+use crate::render::Renderer;
+use crate::server::{RendererResource, WorldResource};
+use crate::shared::{Direction, Position};
+use crate::world::block::{Block, ColoredVariant};
+use bevy_ecs::prelude::*;
+use cgmath::{Decomposed, Matrix4, Quaternion, Rad, Rotation3, Vector3};
+use instant::Instant;
+use leafish_protocol::nbt;
+use std::f32::consts::PI;
+use std::sync::Arc;
+
+/// Banners are drawn at two thirds of the size of the model in the texture.
+const SCALE: f32 = 1.0 / 24.0;
+/// Vanilla never shows more patterns than this.
+const MAX_PATTERNS: usize = 16;
+
+pub fn add_systems(sched: &mut Schedule) {
+    sched
+        .add_systems(
+            render_banner
+                .in_set(SystemExecStage::Render)
+                .after(SystemExecStage::Normal),
+        )
+        .add_systems(
+            on_add_banner
+                .in_set(SystemExecStage::Render)
+                .after(SystemExecStage::Normal),
+        );
+}
+
+pub fn init_entity(cmds: &mut Commands, e: Entity) {
+    cmds.get_entity(e).unwrap().insert(BannerInfo {
+        model: None,
+        base: None,
+        patterns: vec![],
+        placement: Placement::Standing(0.0),
+        spawned: Instant::now(),
+        dirty: false,
+    });
+}
+
+#[derive(Component)]
+pub struct BannerInfo {
+    model: Option<model::ModelHandle>,
+
+    /// The base colour from NBT, only sent before 1.13 where it isn't part
+    /// of the block.
+    base: Option<ColoredVariant>,
+    /// The name of each pattern texture and the colour it's drawn in.
+    patterns: Vec<(&'static str, ColoredVariant)>,
+    placement: Placement,
+    spawned: Instant,
+    dirty: bool,
+}
+
+#[derive(Clone, Copy)]
+enum Placement {
+    /// Standing on the ground, rotated in sixteenths of a turn.
+    Standing(f32),
+    Wall(Direction),
+}
+
+impl BannerInfo {
+    pub(crate) fn load_nbt(&mut self, data: &nbt::Tag) {
+        // Before 1.13 the base colour is kept here and colours count
+        // backwards
+        let legacy = data.get("Base").is_some();
+        let color = |id: i32| dye_from_id(if legacy { 15 - id } else { id });
+        self.base = data.get("Base").and_then(|id| id.as_int()).and_then(color);
+        self.patterns = data
+            .get("Patterns")
+            .and_then(|patterns| patterns.as_list())
+            .unwrap_or(&[])
+            .iter()
+            .filter_map(|pattern| {
+                let name = pattern_texture(pattern.get("Pattern")?.as_str()?)?;
+                let color = color(pattern.get("Color")?.as_int()?)?;
+                Some((name, color))
+            })
+            .take(MAX_PATTERNS)
+            .collect();
+        self.dirty = true;
+    }
+}
+
+fn banner_of(block: Block) -> Option<(ColoredVariant, Placement)> {
+    Some(match block {
+        Block::WhiteBanner { rotation } => {
+            (ColoredVariant::White, Placement::Standing(rotation as f32))
+        }
+        Block::WhiteWallBanner { facing } => (ColoredVariant::White, Placement::Wall(facing)),
+        Block::OrangeBanner { rotation } => {
+            (ColoredVariant::Orange, Placement::Standing(rotation as f32))
+        }
+        Block::OrangeWallBanner { facing } => (ColoredVariant::Orange, Placement::Wall(facing)),
+        Block::MagentaBanner { rotation } => (
+            ColoredVariant::Magenta,
+            Placement::Standing(rotation as f32),
+        ),
+        Block::MagentaWallBanner { facing } => (ColoredVariant::Magenta, Placement::Wall(facing)),
+        Block::LightBlueBanner { rotation } => (
+            ColoredVariant::LightBlue,
+            Placement::Standing(rotation as f32),
+        ),
+        Block::LightBlueWallBanner { facing } => {
+            (ColoredVariant::LightBlue, Placement::Wall(facing))
+        }
+        Block::YellowBanner { rotation } => {
+            (ColoredVariant::Yellow, Placement::Standing(rotation as f32))
+        }
+        Block::YellowWallBanner { facing } => (ColoredVariant::Yellow, Placement::Wall(facing)),
+        Block::LimeBanner { rotation } => {
+            (ColoredVariant::Lime, Placement::Standing(rotation as f32))
+        }
+        Block::LimeWallBanner { facing } => (ColoredVariant::Lime, Placement::Wall(facing)),
+        Block::PinkBanner { rotation } => {
+            (ColoredVariant::Pink, Placement::Standing(rotation as f32))
+        }
+        Block::PinkWallBanner { facing } => (ColoredVariant::Pink, Placement::Wall(facing)),
+        Block::GrayBanner { rotation } => {
+            (ColoredVariant::Gray, Placement::Standing(rotation as f32))
+        }
+        Block::GrayWallBanner { facing } => (ColoredVariant::Gray, Placement::Wall(facing)),
+        Block::LightGrayBanner { rotation } => (
+            ColoredVariant::LightGray,
+            Placement::Standing(rotation as f32),
+        ),
+        Block::LightGrayWallBanner { facing } => {
+            (ColoredVariant::LightGray, Placement::Wall(facing))
+        }
+        Block::CyanBanner { rotation } => {
+            (ColoredVariant::Cyan, Placement::Standing(rotation as f32))
+        }
+        Block::CyanWallBanner { facing } => (ColoredVariant::Cyan, Placement::Wall(facing)),
+        Block::PurpleBanner { rotation } => {
+            (ColoredVariant::Purple, Placement::Standing(rotation as f32))
+        }
+        Block::PurpleWallBanner { facing } => (ColoredVariant::Purple, Placement::Wall(facing)),
+        Block::BlueBanner { rotation } => {
+            (ColoredVariant::Blue, Placement::Standing(rotation as f32))
+        }
+        Block::BlueWallBanner { facing } => (ColoredVariant::Blue, Placement::Wall(facing)),
+        Block::BrownBanner { rotation } => {
+            (ColoredVariant::Brown, Placement::Standing(rotation as f32))
+        }
+        Block::BrownWallBanner { facing } => (ColoredVariant::Brown, Placement::Wall(facing)),
+        Block::GreenBanner { rotation } => {
+            (ColoredVariant::Green, Placement::Standing(rotation as f32))
+        }
+        Block::GreenWallBanner { facing } => (ColoredVariant::Green, Placement::Wall(facing)),
+        Block::RedBanner { rotation } => {
+            (ColoredVariant::Red, Placement::Standing(rotation as f32))
+        }
+        Block::RedWallBanner { facing } => (ColoredVariant::Red, Placement::Wall(facing)),
+        Block::BlackBanner { rotation } => {
+            (ColoredVariant::Black, Placement::Standing(rotation as f32))
+        }
+        Block::BlackWallBanner { facing } => (ColoredVariant::Black, Placement::Wall(facing)),
+        _ => return None,
+    })
+}
+
+/// The texture name for a pattern's code in NBT.
+fn pattern_texture(code: &str) -> Option<&'static str> {
+    Some(match code {
+        "b" => "base",
+        "bl" => "square_bottom_left",
+        "br" => "square_bottom_right",
+        "tl" => "square_top_left",
+        "tr" => "square_top_right",
+        "bs" => "stripe_bottom",
+        "ts" => "stripe_top",
+        "ls" => "stripe_left",
+        "rs" => "stripe_right",
+        "cs" => "stripe_center",
+        "ms" => "stripe_middle",
+        "drs" => "stripe_downright",
+        "dls" => "stripe_downleft",
+        "ss" => "small_stripes",
+        "cr" => "cross",
+        "sc" => "straight_cross",
+        "bt" => "triangle_bottom",
+        "tt" => "triangle_top",
+        "bts" => "triangles_bottom",
+        "tts" => "triangles_top",
+        "ld" => "diagonal_left",
+        "rd" => "diagonal_up_right",
+        "lud" => "diagonal_up_left",
+        "rud" => "diagonal_right",
+        "mc" => "circle",
+        "mr" => "rhombus",
+        "vh" => "half_vertical",
+        "hh" => "half_horizontal",
+        "vhr" => "half_vertical_right",
+        "hhb" => "half_horizontal_bottom",
+        "bo" => "border",
+        "cbo" => "curly_border",
+        "gra" => "gradient",
+        "gru" => "gradient_up",
+        "bri" => "bricks",
+        "glb" => "globe",
+        "cre" => "creeper",
+        "sku" => "skull",
+        "flo" => "flower",
+        "moj" => "mojang",
+        "pig" => "piglin",
+        _ => return None,
+    })
+}
+
+pub fn render_banner(
+    renderer: Res<RendererResource>,
+    world: Res<WorldResource>,
+    mut query: Query<(&mut BannerInfo, &Position)>,
+) {
+    let renderer = &renderer.0;
+    let world = &world.0;
+    for (mut info, position) in query.iter_mut() {
+        if info.dirty {
+            add_banner(renderer.clone(), world.clone(), &mut info, position);
+        }
+        if let Some(model) = &info.model {
+            let mut models = renderer.models.lock();
+            let mdl = models.get_model(model).unwrap();
+            mdl.block_light = world.get_block_light(*position) as f32;
+            mdl.sky_light = world.get_sky_light(*position) as f32;
+
+            // The model faces south, wall banners hang further down and
+            // against the wall behind them
+            let (rotation, y, z) = match info.placement {
+                Placement::Standing(rotation) => (-rotation / 16.0 * PI * 2.0, 0.0, 0.0),
+                Placement::Wall(facing) => {
+                    let rotation = match facing {
+                        Direction::West => -PI / 2.0,
+                        Direction::North => PI,
+                        Direction::East => PI / 2.0,
+                        _ => 0.0,
+                    };
+                    (rotation, -(2.0 / 3.0 + 5.0 / 16.0), -7.0 / 16.0)
+                }
+            };
+            let base = Matrix4::from(Decomposed {
+                scale: 1.0,
+                rot: Quaternion::from_angle_y(Rad(rotation)),
+                disp: Vector3::new(
+                    position.x as f32 + 0.5,
+                    -(position.y as f32 + y),
+                    position.z as f32 + 0.5,
+                ),
+            }) * Matrix4::from_translation(Vector3::new(0.0, 0.0, z));
+
+            // Banners sway gently, each slightly out of step with the others
+            let ticks = info.spawned.elapsed().as_secs_f32() * 20.0
+                + (position.x * 7 + position.y * 9 + position.z * 13) as f32;
+            let sway = (-0.0125 + 0.01 * (ticks / 100.0 * PI * 2.0).cos()) * PI;
+            let hinge = Vector3::new(0.0, -44.0 * SCALE, 0.0);
+            mdl.matrix[0] = base;
+            mdl.matrix[1] = base
+                * Matrix4::from_translation(hinge)
+                * Matrix4::from(Quaternion::from_angle_x(Rad(-sway)))
+                * Matrix4::from_translation(-hinge);
+        }
+    }
+}
+
+pub fn on_add_banner(
+    renderer: Res<RendererResource>,
+    world: Res<WorldResource>,
+    mut query: Query<(&mut BannerInfo, &Position), Added<BannerInfo>>,
+) {
+    let renderer = &renderer.0;
+    let world = &world.0;
+    for (mut info, position) in query.iter_mut() {
+        add_banner(renderer.clone(), world.clone(), &mut info, position);
+    }
+}
+
+fn add_banner(
+    renderer: Arc<Renderer>,
+    world: Arc<crate::world::World>,
+    info: &mut BannerInfo,
+    position: &Position,
+) {
+    info.dirty = false;
+    info.model.take();
+    let (color, placement) = match banner_of(world.get_block(*position)) {
+        Some(banner) => banner,
+        None => return,
+    };
+    info.placement = placement;
+    let size = (64.0, 64.0);
+
+    let tex = render::Renderer::get_texture(renderer.get_textures_ref(), "entity/banner_base");
+    let mut stand = vec![];
+    if let Placement::Standing(_) = placement {
+        model::append_box(
+            &mut stand,
+            -SCALE,
+            0.0,
+            -SCALE,
+            2.0 * SCALE,
+            42.0 * SCALE,
+            2.0 * SCALE,
+            box_textures(&tex, size, (44.0, 0.0), (2.0, 42.0, 2.0)),
+        );
+    }
+    model::append_box(
+        &mut stand,
+        -10.0 * SCALE,
+        42.0 * SCALE,
+        -SCALE,
+        20.0 * SCALE,
+        2.0 * SCALE,
+        2.0 * SCALE,
+        box_textures(&tex, size, (0.0, 42.0), (20.0, 2.0, 2.0)),
+    );
+
+    // The base colour is just another pattern, each layer sits slightly
+    // outside the one before so they don't fight over the depth buffer
+    let mut flag = vec![];
+    let base = info.base.unwrap_or(color);
+    let layers = std::iter::once(("base", base)).chain(info.patterns.iter().copied());
+    for (i, (name, color)) in layers.enumerate() {
+        let tex = render::Renderer::get_texture(
+            renderer.get_textures_ref(),
+            &format!("entity/banner/{name}"),
+        );
+        let grow = i as f32 * 0.002;
+        let start = flag.len();
+        model::append_box(
+            &mut flag,
+            -10.0 * SCALE - grow,
+            4.0 * SCALE - grow,
+            SCALE - grow,
+            20.0 * SCALE + grow * 2.0,
+            40.0 * SCALE + grow * 2.0,
+            SCALE + grow * 2.0,
+            box_textures(&tex, size, (0.0, 0.0), (20.0, 40.0, 1.0)),
+        );
+        let (r, g, b) = dye_rgb(color);
+        for vert in &mut flag[start..] {
+            vert.r = (vert.r as u32 * r as u32 / 255) as u8;
+            vert.g = (vert.g as u32 * g as u32 / 255) as u8;
+            vert.b = (vert.b as u32 * b as u32 / 255) as u8;
+        }
+    }
+
+    let mut models = renderer.models.lock();
+    let model = models.create_model(model::DEFAULT, vec![stand, flag], renderer.clone());
+    let mdl = models.get_model(&model).unwrap();
+    mdl.radius = 3.0;
+    mdl.x = position.x as f32 + 0.5;
+    mdl.y = position.y as f32 + 0.5;
+    mdl.z = position.z as f32 + 0.5;
+    drop(models);
+
+    info.model.replace(model);
+}
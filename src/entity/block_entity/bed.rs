@@ -0,0 +1,237 @@
+use super::{box_textures, dye_from_id};
+use crate::ecs::SystemExecStage;
+use crate::render;
+use crate::render::model;
+use crate::render::Renderer;
+use crate::server::{RendererResource, WorldResource};
+use crate::shared::{Direction, Position};
+use crate::world::block::{BedPart, Block, ColoredVariant};
+use bevy_ecs::prelude::*;
+use cgmath::{Decomposed, Matrix4, Quaternion, Rad, Rotation3, Vector3};
+use leafish_protocol::nbt;
+use std::f32::consts::PI;
+use std::sync::Arc;
+
+pub fn add_systems(sched: &mut Schedule) {
+    sched
+        .add_systems(
+            render_bed
+                .in_set(SystemExecStage::Render)
+                .after(SystemExecStage::Normal),
+        )
+        .add_systems(
+            on_add_bed
+                .in_set(SystemExecStage::Render)
+                .after(SystemExecStage::Normal),
+        );
+}
+
+pub fn init_entity(cmds: &mut Commands, e: Entity) {
+    cmds.get_entity(e).unwrap().insert(BedInfo {
+        model: None,
+        color: None,
+        rotation: 0.0,
+        dirty: false,
+    });
+}
+
+#[derive(Component)]
+pub struct BedInfo {
+    model: Option<model::ModelHandle>,
+
+    /// The colour from NBT, only sent before 1.13 where it isn't part of
+    /// the block.
+    color: Option<ColoredVariant>,
+    rotation: f32,
+    dirty: bool,
+}
+
+impl BedInfo {
+    pub(crate) fn load_nbt(&mut self, data: &nbt::Tag) {
+        self.color = data
+            .get("color")
+            .and_then(|color| color.as_int())
+            .and_then(dye_from_id);
+        self.dirty = true;
+    }
+}
+
+fn bed_of(block: Block) -> Option<(ColoredVariant, Direction, BedPart)> {
+    Some(match block {
+        Block::WhiteBed { facing, part, .. } => (ColoredVariant::White, facing, part),
+        Block::OrangeBed { facing, part, .. } => (ColoredVariant::Orange, facing, part),
+        Block::MagentaBed { facing, part, .. } => (ColoredVariant::Magenta, facing, part),
+        Block::LightBlueBed { facing, part, .. } => (ColoredVariant::LightBlue, facing, part),
+        Block::YellowBed { facing, part, .. } => (ColoredVariant::Yellow, facing, part),
+        Block::LimeBed { facing, part, .. } => (ColoredVariant::Lime, facing, part),
+        Block::PinkBed { facing, part, .. } => (ColoredVariant::Pink, facing, part),
+        Block::GrayBed { facing, part, .. } => (ColoredVariant::Gray, facing, part),
+        Block::LightGrayBed { facing, part, .. } => (ColoredVariant::LightGray, facing, part),
+        Block::CyanBed { facing, part, .. } => (ColoredVariant::Cyan, facing, part),
+        Block::PurpleBed { facing, part, .. } => (ColoredVariant::Purple, facing, part),
+        Block::BlueBed { facing, part, .. } => (ColoredVariant::Blue, facing, part),
+        Block::BrownBed { facing, part, .. } => (ColoredVariant::Brown, facing, part),
+        Block::GreenBed { facing, part, .. } => (ColoredVariant::Green, facing, part),
+        Block::RedBed { facing, part, .. } => (ColoredVariant::Red, facing, part),
+        Block::BlackBed { facing, part, .. } => (ColoredVariant::Black, facing, part),
+        _ => return None,
+    })
+}
+
+pub fn render_bed(
+    renderer: Res<RendererResource>,
+    world: Res<WorldResource>,
+    mut query: Query<(&mut BedInfo, &Position)>,
+) {
+    let renderer = &renderer.0;
+    let world = &world.0;
+    for (mut info, position) in query.iter_mut() {
+        if info.dirty {
+            add_bed(renderer.clone(), world.clone(), &mut info, position);
+        }
+        if let Some(model) = &info.model {
+            let mut models = renderer.models.lock();
+            let mdl = models.get_model(model).unwrap();
+            mdl.block_light = world.get_block_light(*position) as f32;
+            mdl.sky_light = world.get_sky_light(*position) as f32;
+            mdl.matrix[0] = Matrix4::from(Decomposed {
+                scale: 1.0,
+                rot: Quaternion::from_angle_y(Rad(info.rotation)),
+                disp: Vector3::new(
+                    position.x as f32 + 0.5,
+                    -position.y as f32,
+                    position.z as f32 + 0.5,
+                ),
+            });
+        }
+    }
+}
+
+pub fn on_add_bed(
+    renderer: Res<RendererResource>,
+    world: Res<WorldResource>,
+    mut query: Query<(&mut BedInfo, &Position), Added<BedInfo>>,
+) {
+    let renderer = &renderer.0;
+    let world = &world.0;
+    for (mut info, position) in query.iter_mut() {
+        add_bed(renderer.clone(), world.clone(), &mut info, position);
+    }
+}
+
+fn add_bed(
+    renderer: Arc<Renderer>,
+    world: Arc<crate::world::World>,
+    info: &mut BedInfo,
+    position: &Position,
+) {
+    info.dirty = false;
+    info.model.take();
+    let (color, facing, part) = match bed_of(world.get_block(*position)) {
+        Some(bed) => bed,
+        None => return,
+    };
+    let color = info.color.unwrap_or(color);
+    // The model has its head towards the north
+    info.rotation = match facing {
+        Direction::South => PI,
+        Direction::West => PI / 2.0,
+        Direction::East => -PI / 2.0,
+        _ => 0.0,
+    };
+
+    let tex = render::Renderer::get_texture(
+        renderer.get_textures_ref(),
+        &format!("entity/bed/{}", color.as_string()),
+    );
+    let size = (64.0, 64.0);
+    let rel = |x: f32, y: f32, w: f32, h: f32| {
+        Some(tex.relative(x / size.0, y / size.1, w / size.0, h / size.1))
+    };
+
+    // The mattress is drawn lying down in the texture, so the top is its
+    // front and the long sides are turned on their side. Only the outer end
+    // of each half is drawn.
+    let (v, head) = match part {
+        BedPart::Head => (0.0, true),
+        BedPart::Foot => (22.0, false),
+    };
+    let mut verts = vec![];
+    model::append_box(
+        &mut verts,
+        -0.5,
+        3.0 / 16.0,
+        -0.5,
+        1.0,
+        6.0 / 16.0,
+        1.0,
+        [
+            rel(28.0, v + 6.0, 16.0, 16.0),
+            rel(6.0, v + 6.0, 16.0, 16.0),
+            if head { rel(6.0, v, 16.0, 6.0) } else { None },
+            if head { None } else { rel(22.0, v, 16.0, 6.0) },
+            None,
+            None,
+        ],
+    );
+    let sides = verts.len();
+    model::append_box(
+        &mut verts,
+        -0.5,
+        3.0 / 16.0,
+        -0.5,
+        1.0,
+        6.0 / 16.0,
+        1.0,
+        [
+            None,
+            None,
+            None,
+            None,
+            rel(0.0, v + 6.0, 6.0, 16.0),
+            rel(22.0, v + 6.0, 6.0, 16.0),
+        ],
+    );
+    // The long sides run down the texture, turn them to match
+    for vert in &mut verts[sides..] {
+        let (x, y) = (vert.texture_x, vert.texture_y);
+        if vert.x < 0.0 {
+            vert.texture_x = y;
+            vert.texture_y = x;
+        } else {
+            vert.texture_x = y;
+            vert.texture_y = 1.0 - x;
+        }
+    }
+
+    // Legs sit under the outer corners
+    let z = if head { -0.5 } else { 0.5 - 3.0 / 16.0 };
+    let legs = if head {
+        [(50.0, 0.0), (50.0, 6.0)]
+    } else {
+        [(50.0, 12.0), (50.0, 18.0)]
+    };
+    for (&x, &uv) in [-0.5, 0.5 - 3.0 / 16.0].iter().zip(legs.iter()) {
+        model::append_box(
+            &mut verts,
+            x,
+            0.0,
+            z,
+            3.0 / 16.0,
+            3.0 / 16.0,
+            3.0 / 16.0,
+            box_textures(&tex, size, uv, (3.0, 3.0, 3.0)),
+        );
+    }
+
+    let mut models = renderer.models.lock();
+    let model = models.create_model(model::DEFAULT, vec![verts], renderer.clone());
+    let mdl = models.get_model(&model).unwrap();
+    mdl.radius = 2.0;
+    mdl.x = position.x as f32 + 0.5;
+    mdl.y = position.y as f32 + 0.5;
+    mdl.z = position.z as f32 + 0.5;
+    drop(models);
+
+    info.model.replace(model);
+}
@@ -0,0 +1,169 @@
+use super::box_textures;
+use crate::ecs::SystemExecStage;
+use crate::render;
+use crate::render::model;
+use crate::render::Renderer;
+use crate::server::{DeltaResource, RendererResource, WorldResource};
+use crate::shared::{Direction, Position};
+use bevy_ecs::prelude::*;
+use bevy_ecs::system::Command;
+use cgmath::{Matrix4, Quaternion, Rad, Rotation3, Vector3};
+use std::f32::consts::PI;
+use std::sync::Arc;
+
+/// How many ticks a bell swings for after being rung.
+const RING_TICKS: f32 = 50.0;
+
+pub fn add_systems(sched: &mut Schedule) {
+    sched
+        .add_systems(
+            render_bell
+                .in_set(SystemExecStage::Render)
+                .after(SystemExecStage::Normal),
+        )
+        .add_systems(
+            on_add_bell
+                .in_set(SystemExecStage::Render)
+                .after(SystemExecStage::Normal),
+        );
+}
+
+pub fn init_entity(cmds: &mut Commands, e: Entity) {
+    cmds.get_entity(e).unwrap().insert(BellInfo {
+        model: None,
+        ringing: None,
+    });
+}
+
+#[derive(Component)]
+pub struct BellInfo {
+    model: Option<model::ModelHandle>,
+
+    /// The side the bell was hit from and the ticks since.
+    ringing: Option<(Direction, f32)>,
+}
+
+/// Applies a bell block action, which rings the bell from the side given as
+/// the parameter.
+pub struct BellActionCmd {
+    pub entity: Entity,
+    pub action: u8,
+    pub param: u8,
+}
+
+impl Command for BellActionCmd {
+    fn apply(self, world: &mut bevy_ecs::world::World) {
+        if self.action != 1 {
+            return;
+        }
+        let mut entity = world.get_entity_mut(self.entity);
+        if let Some(mut info) = entity
+            .as_mut()
+            .and_then(|entity| entity.get_mut::<BellInfo>())
+        {
+            info.ringing = super::direction_from_id(self.param).map(|side| (side, 0.0));
+            // TODO: Play "block.bell.use" once sounds are supported
+        }
+    }
+}
+
+pub fn render_bell(
+    renderer: Res<RendererResource>,
+    world: Res<WorldResource>,
+    delta: Res<DeltaResource>,
+    mut query: Query<(&mut BellInfo, &Position)>,
+) {
+    let renderer = &renderer.0;
+    let world = &world.0;
+    for (mut info, position) in query.iter_mut() {
+        if let Some((side, ticks)) = info.ringing {
+            let ticks = ticks + (delta.0 / 3.0) as f32;
+            info.ringing = if ticks < RING_TICKS {
+                Some((side, ticks))
+            } else {
+                None
+            };
+        }
+        if let Some(model) = &info.model {
+            let mut models = renderer.models.lock();
+            let mdl = models.get_model(model).unwrap();
+            mdl.block_light = world.get_block_light(*position) as f32;
+            mdl.sky_light = world.get_sky_light(*position) as f32;
+
+            // The swing dies down over time, away from the side it was hit
+            // on
+            let (pitch, roll) = match info.ringing {
+                Some((side, ticks)) => {
+                    let swing = (ticks / PI).sin() / (4.0 + ticks / 3.0);
+                    match side {
+                        Direction::North => (-swing, 0.0),
+                        Direction::South => (swing, 0.0),
+                        Direction::East => (0.0, -swing),
+                        Direction::West => (0.0, swing),
+                        _ => (0.0, 0.0),
+                    }
+                }
+                None => (0.0, 0.0),
+            };
+            let pivot = Vector3::new(
+                position.x as f32 + 0.5,
+                -position.y as f32 - 12.0 / 16.0,
+                position.z as f32 + 0.5,
+            );
+            mdl.matrix[0] = Matrix4::from_translation(pivot)
+                * Matrix4::from(Quaternion::from_angle_x(Rad(-pitch)))
+                * Matrix4::from(Quaternion::from_angle_z(Rad(-roll)))
+                * Matrix4::from_translation(Vector3::new(0.0, 12.0 / 16.0, 0.0));
+        }
+    }
+}
+
+pub fn on_add_bell(
+    renderer: Res<RendererResource>,
+    mut query: Query<(&mut BellInfo, &Position), Added<BellInfo>>,
+) {
+    let renderer = &renderer.0;
+    for (mut info, position) in query.iter_mut() {
+        add_bell(renderer.clone(), &mut info, position);
+    }
+}
+
+fn add_bell(renderer: Arc<Renderer>, info: &mut BellInfo, position: &Position) {
+    let tex = render::Renderer::get_texture(renderer.get_textures_ref(), "entity/bell/bell_body");
+    let size = (32.0, 32.0);
+
+    // The frame is part of the block model, only the bell itself is drawn
+    // here
+    let mut verts = vec![];
+    model::append_box(
+        &mut verts,
+        -3.0 / 16.0,
+        6.0 / 16.0,
+        -3.0 / 16.0,
+        6.0 / 16.0,
+        7.0 / 16.0,
+        6.0 / 16.0,
+        box_textures(&tex, size, (0.0, 0.0), (6.0, 7.0, 6.0)),
+    );
+    model::append_box(
+        &mut verts,
+        -4.0 / 16.0,
+        4.0 / 16.0,
+        -4.0 / 16.0,
+        8.0 / 16.0,
+        2.0 / 16.0,
+        8.0 / 16.0,
+        box_textures(&tex, size, (0.0, 13.0), (8.0, 2.0, 8.0)),
+    );
+
+    let mut models = renderer.models.lock();
+    let model = models.create_model(model::DEFAULT, vec![verts], renderer.clone());
+    let mdl = models.get_model(&model).unwrap();
+    mdl.radius = 2.0;
+    mdl.x = position.x as f32 + 0.5;
+    mdl.y = position.y as f32 + 0.5;
+    mdl.z = position.z as f32 + 0.5;
+    drop(models);
+
+    info.model.replace(model);
+}
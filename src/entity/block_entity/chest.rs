@@ -0,0 +1,213 @@
+use super::box_textures;
+use crate::ecs::SystemExecStage;
+use crate::render;
+use crate::render::model;
+use crate::render::Renderer;
+use crate::server::{DeltaResource, RendererResource, WorldResource};
+use crate::shared::{Direction, Position};
+use crate::world::block::{Block, ChestType};
+use bevy_ecs::prelude::*;
+use bevy_ecs::system::Command;
+use cgmath::{Decomposed, Matrix4, Quaternion, Rad, Rotation3, Vector3};
+use std::f32::consts::PI;
+use std::sync::Arc;
+
+pub fn add_systems(sched: &mut Schedule) {
+    sched
+        .add_systems(
+            render_chest
+                .in_set(SystemExecStage::Render)
+                .after(SystemExecStage::Normal),
+        )
+        .add_systems(
+            on_add_chest
+                .in_set(SystemExecStage::Render)
+                .after(SystemExecStage::Normal),
+        );
+}
+
+pub fn init_entity(cmds: &mut Commands, e: Entity) {
+    cmds.get_entity(e).unwrap().insert(ChestLid {
+        model: None,
+        rotation: 0.0,
+        open: false,
+        progress: 0.0,
+    });
+}
+
+#[derive(Component)]
+pub struct ChestLid {
+    model: Option<model::ModelHandle>,
+    rotation: f32,
+
+    /// Whether anyone is looking into the chest.
+    open: bool,
+    /// How far the lid is open, from 0 (closed) to 1.
+    progress: f32,
+}
+
+/// The position of the other half of a double chest.
+pub fn other_half(block: Block, pos: Position) -> Option<Position> {
+    match block {
+        Block::Chest { facing, type_, .. } | Block::TrappedChest { facing, type_, .. } => {
+            match type_ {
+                ChestType::Single => None,
+                ChestType::Left => Some(pos.shift(facing.clockwise())),
+                ChestType::Right => Some(pos.shift(facing.counter_clockwise())),
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Applies a chest block action. The only one there is tells how many
+/// players have the chest open.
+pub struct ChestActionCmd {
+    pub entity: Entity,
+    pub action: u8,
+    pub param: u8,
+}
+
+impl Command for ChestActionCmd {
+    fn apply(self, world: &mut bevy_ecs::world::World) {
+        if self.action != 1 {
+            return;
+        }
+        let mut entity = world.get_entity_mut(self.entity);
+        if let Some(mut lid) = entity
+            .as_mut()
+            .and_then(|entity| entity.get_mut::<ChestLid>())
+        {
+            lid.open = self.param > 0;
+        }
+    }
+}
+
+pub fn render_chest(
+    renderer: Res<RendererResource>,
+    world: Res<WorldResource>,
+    delta: Res<DeltaResource>,
+    mut query: Query<(&mut ChestLid, &Position)>,
+) {
+    let renderer = &renderer.0;
+    let world = &world.0;
+    // Lids open and close over 10 ticks
+    let step = (delta.0 / 3.0 * 0.1) as f32;
+    for (mut lid, position) in query.iter_mut() {
+        lid.progress = if lid.open {
+            (lid.progress + step).min(1.0)
+        } else {
+            (lid.progress - step).max(0.0)
+        };
+        if let Some(model) = &lid.model {
+            let mut models = renderer.models.lock();
+            let mdl = models.get_model(model).unwrap();
+            mdl.block_light = world.get_block_light(*position) as f32;
+            mdl.sky_light = world.get_sky_light(*position) as f32;
+
+            let base = Matrix4::from(Decomposed {
+                scale: 1.0,
+                rot: Quaternion::from_angle_y(Rad(lid.rotation)),
+                disp: Vector3::new(
+                    position.x as f32 + 0.5,
+                    -position.y as f32,
+                    position.z as f32 + 0.5,
+                ),
+            });
+            // The lid swings open quickly and slows down towards the end
+            let angle = (1.0 - (1.0 - lid.progress).powi(3)) * PI / 2.0;
+            let hinge = Vector3::new(0.0, -9.0 / 16.0, 1.0 / 16.0 - 0.5);
+            mdl.matrix[0] = base;
+            mdl.matrix[1] = base
+                * Matrix4::from_translation(hinge)
+                * Matrix4::from(Quaternion::from_angle_x(Rad(angle)))
+                * Matrix4::from_translation(-hinge);
+        }
+    }
+}
+
+pub fn on_add_chest(
+    renderer: Res<RendererResource>,
+    world: Res<WorldResource>,
+    mut query: Query<(&mut ChestLid, &Position), Added<ChestLid>>,
+) {
+    let renderer = &renderer.0;
+    let world = &world.0;
+    for (mut lid, position) in query.iter_mut() {
+        add_chest(renderer.clone(), world.clone(), &mut lid, position);
+    }
+}
+
+fn add_chest(
+    renderer: Arc<Renderer>,
+    world: Arc<crate::world::World>,
+    lid: &mut ChestLid,
+    position: &Position,
+) {
+    let (facing, type_, name) = match world.get_block(*position) {
+        Block::Chest { facing, type_, .. } => (facing, type_, "normal"),
+        Block::TrappedChest { facing, type_, .. } => (facing, type_, "trapped"),
+        Block::EnderChest { facing, .. } => (facing, ChestType::Single, "ender"),
+        _ => return,
+    };
+    // The model faces south
+    lid.rotation = match facing {
+        Direction::West => -PI / 2.0,
+        Direction::North => -PI,
+        Direction::East => PI / 2.0,
+        _ => 0.0,
+    };
+
+    // Double chests reach all the way to the side they are joined at
+    let (path, x, w, latch_x, latch_w) = match type_ {
+        ChestType::Single => (format!("entity/chest/{name}"), 1.0, 14.0, 7.0, 2.0),
+        ChestType::Left => (format!("entity/chest/{name}_left"), 0.0, 15.0, 0.0, 1.0),
+        ChestType::Right => (format!("entity/chest/{name}_right"), 1.0, 15.0, 15.0, 1.0),
+    };
+    let tex = render::Renderer::get_texture(renderer.get_textures_ref(), &path);
+    let size = (64.0, 64.0);
+
+    let mut body = vec![];
+    model::append_box(
+        &mut body,
+        x / 16.0 - 0.5,
+        0.0,
+        1.0 / 16.0 - 0.5,
+        w / 16.0,
+        10.0 / 16.0,
+        14.0 / 16.0,
+        box_textures(&tex, size, (0.0, 19.0), (w, 10.0, 14.0)),
+    );
+    let mut top = vec![];
+    model::append_box(
+        &mut top,
+        x / 16.0 - 0.5,
+        9.0 / 16.0,
+        1.0 / 16.0 - 0.5,
+        w / 16.0,
+        5.0 / 16.0,
+        14.0 / 16.0,
+        box_textures(&tex, size, (0.0, 0.0), (w, 5.0, 14.0)),
+    );
+    model::append_box(
+        &mut top,
+        latch_x / 16.0 - 0.5,
+        7.0 / 16.0,
+        15.0 / 16.0 - 0.5,
+        latch_w / 16.0,
+        4.0 / 16.0,
+        1.0 / 16.0,
+        box_textures(&tex, size, (0.0, 0.0), (latch_w, 4.0, 1.0)),
+    );
+
+    let mut models = renderer.models.lock();
+    let model = models.create_model(model::DEFAULT, vec![body, top], renderer.clone());
+    let mdl = models.get_model(&model).unwrap();
+    mdl.radius = 2.0;
+    mdl.x = position.x as f32 + 0.5;
+    mdl.y = position.y as f32 + 0.5;
+    mdl.z = position.z as f32 + 0.5;
+    drop(models);
+
+    lid.model.replace(model);
+}
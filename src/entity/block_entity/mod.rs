@@ -1,15 +1,42 @@
+pub mod banner;
+pub mod bed;
+pub mod bell;
+pub mod chest;
+pub mod piston;
+pub mod shulker_box;
 pub mod sign;
+pub mod skull;
 
-use crate::shared::Position;
-use crate::world::block::Block;
+use crate::particle::note::NoteData;
+use crate::particle::ParticleType;
+use crate::render;
+use crate::shared::{Direction, Position};
+use crate::world;
+use crate::world::block::{Block, ColoredVariant};
 use bevy_ecs::prelude::*;
+use bevy_ecs::system::Command;
+use cgmath::Vector3;
+use leafish_protocol::nbt;
 
 pub fn add_systems(sched: &mut Schedule) {
     sign::add_systems(sched);
+    chest::add_systems(sched);
+    bell::add_systems(sched);
+    piston::add_systems(sched);
+    banner::add_systems(sched);
+    skull::add_systems(sched);
+    bed::add_systems(sched);
+    shulker_box::add_systems(sched);
 }
 
 pub enum BlockEntityType {
     Sign,
+    Chest,
+    Bell,
+    Banner,
+    Skull,
+    Bed,
+    ShulkerBox,
 }
 
 impl BlockEntityType {
@@ -33,6 +60,87 @@ impl BlockEntityType {
             | Block::MangroveWallSign { .. }
             | Block::CrimsonWallSign { .. }
             | Block::WarpedWallSign { .. } => Some(BlockEntityType::Sign),
+            Block::Chest { .. } | Block::TrappedChest { .. } | Block::EnderChest { .. } => {
+                Some(BlockEntityType::Chest)
+            }
+            Block::Bell { .. } => Some(BlockEntityType::Bell),
+            Block::WhiteBanner { .. }
+            | Block::OrangeBanner { .. }
+            | Block::MagentaBanner { .. }
+            | Block::LightBlueBanner { .. }
+            | Block::YellowBanner { .. }
+            | Block::LimeBanner { .. }
+            | Block::PinkBanner { .. }
+            | Block::GrayBanner { .. }
+            | Block::LightGrayBanner { .. }
+            | Block::CyanBanner { .. }
+            | Block::PurpleBanner { .. }
+            | Block::BlueBanner { .. }
+            | Block::BrownBanner { .. }
+            | Block::GreenBanner { .. }
+            | Block::RedBanner { .. }
+            | Block::BlackBanner { .. }
+            | Block::WhiteWallBanner { .. }
+            | Block::OrangeWallBanner { .. }
+            | Block::MagentaWallBanner { .. }
+            | Block::LightBlueWallBanner { .. }
+            | Block::YellowWallBanner { .. }
+            | Block::LimeWallBanner { .. }
+            | Block::PinkWallBanner { .. }
+            | Block::GrayWallBanner { .. }
+            | Block::LightGrayWallBanner { .. }
+            | Block::CyanWallBanner { .. }
+            | Block::PurpleWallBanner { .. }
+            | Block::BlueWallBanner { .. }
+            | Block::BrownWallBanner { .. }
+            | Block::GreenWallBanner { .. }
+            | Block::RedWallBanner { .. }
+            | Block::BlackWallBanner { .. } => Some(BlockEntityType::Banner),
+            Block::SkeletonSkull { .. }
+            | Block::SkeletonWallSkull { .. }
+            | Block::WitherSkeletonSkull { .. }
+            | Block::WitherSkeletonWallSkull { .. }
+            | Block::ZombieHead { .. }
+            | Block::ZombieWallHead { .. }
+            | Block::PlayerHead { .. }
+            | Block::PlayerWallHead { .. }
+            | Block::CreeperHead { .. }
+            | Block::CreeperWallHead { .. }
+            | Block::DragonHead { .. }
+            | Block::DragonWallHead { .. } => Some(BlockEntityType::Skull),
+            Block::WhiteBed { .. }
+            | Block::OrangeBed { .. }
+            | Block::MagentaBed { .. }
+            | Block::LightBlueBed { .. }
+            | Block::YellowBed { .. }
+            | Block::LimeBed { .. }
+            | Block::PinkBed { .. }
+            | Block::GrayBed { .. }
+            | Block::LightGrayBed { .. }
+            | Block::CyanBed { .. }
+            | Block::PurpleBed { .. }
+            | Block::BlueBed { .. }
+            | Block::BrownBed { .. }
+            | Block::GreenBed { .. }
+            | Block::RedBed { .. }
+            | Block::BlackBed { .. } => Some(BlockEntityType::Bed),
+            Block::ShulkerBox { .. }
+            | Block::WhiteShulkerBox { .. }
+            | Block::OrangeShulkerBox { .. }
+            | Block::MagentaShulkerBox { .. }
+            | Block::LightBlueShulkerBox { .. }
+            | Block::YellowShulkerBox { .. }
+            | Block::LimeShulkerBox { .. }
+            | Block::PinkShulkerBox { .. }
+            | Block::GrayShulkerBox { .. }
+            | Block::LightGrayShulkerBox { .. }
+            | Block::CyanShulkerBox { .. }
+            | Block::PurpleShulkerBox { .. }
+            | Block::BlueShulkerBox { .. }
+            | Block::BrownShulkerBox { .. }
+            | Block::GreenShulkerBox { .. }
+            | Block::RedShulkerBox { .. }
+            | Block::BlackShulkerBox { .. } => Some(BlockEntityType::ShulkerBox),
             _ => None,
         }
     }
@@ -43,7 +151,185 @@ impl BlockEntityType {
         let e = e.id();
         match *self {
             BlockEntityType::Sign => sign::init_entity(cmds, e),
+            BlockEntityType::Chest => chest::init_entity(cmds, e),
+            BlockEntityType::Bell => bell::init_entity(cmds, e),
+            BlockEntityType::Banner => banner::init_entity(cmds, e),
+            BlockEntityType::Skull => skull::init_entity(cmds, e),
+            BlockEntityType::Bed => bed::init_entity(cmds, e),
+            BlockEntityType::ShulkerBox => shulker_box::init_entity(cmds, e),
         }
         e
     }
 }
+
+/// Hands NBT data sent for a block entity to whichever kind of block entity
+/// it is.
+pub struct UpdateDataCmd {
+    pub entity: Entity,
+    pub nbt: nbt::NamedTag,
+}
+
+impl Command for UpdateDataCmd {
+    fn apply(self, world: &mut bevy_ecs::world::World) {
+        let mut entity = match world.get_entity_mut(self.entity) {
+            Some(entity) => entity,
+            None => return,
+        };
+        let data = &self.nbt.1;
+        if let Some(mut info) = entity.get_mut::<banner::BannerInfo>() {
+            info.load_nbt(data);
+        } else if let Some(mut info) = entity.get_mut::<skull::SkullInfo>() {
+            info.load_nbt(data);
+        } else if let Some(mut info) = entity.get_mut::<bed::BedInfo>() {
+            info.load_nbt(data);
+        }
+    }
+}
+
+/// Handles a block action sent by the server for the block at the position.
+pub fn on_block_action(
+    cmds: &mut Commands,
+    world: &world::World,
+    pos: Position,
+    action: u8,
+    param: u8,
+) {
+    let block = world.get_block(pos);
+    match block {
+        Block::Chest { .. } | Block::TrappedChest { .. } | Block::EnderChest { .. } => {
+            // Both halves of a double chest open together
+            for pos in std::iter::once(pos).chain(chest::other_half(block, pos)) {
+                if let Some(entity) = world.get_block_entity(pos) {
+                    cmds.add(chest::ChestActionCmd {
+                        entity,
+                        action,
+                        param,
+                    });
+                }
+            }
+        }
+        Block::Bell { .. } => {
+            if let Some(entity) = world.get_block_entity(pos) {
+                cmds.add(bell::BellActionCmd {
+                    entity,
+                    action,
+                    param,
+                });
+            }
+        }
+        Block::ShulkerBox { .. }
+        | Block::WhiteShulkerBox { .. }
+        | Block::OrangeShulkerBox { .. }
+        | Block::MagentaShulkerBox { .. }
+        | Block::LightBlueShulkerBox { .. }
+        | Block::YellowShulkerBox { .. }
+        | Block::LimeShulkerBox { .. }
+        | Block::PinkShulkerBox { .. }
+        | Block::GrayShulkerBox { .. }
+        | Block::LightGrayShulkerBox { .. }
+        | Block::CyanShulkerBox { .. }
+        | Block::PurpleShulkerBox { .. }
+        | Block::BlueShulkerBox { .. }
+        | Block::BrownShulkerBox { .. }
+        | Block::GreenShulkerBox { .. }
+        | Block::RedShulkerBox { .. }
+        | Block::BlackShulkerBox { .. } => {
+            if let Some(entity) = world.get_block_entity(pos) {
+                cmds.add(shulker_box::ShulkerActionCmd {
+                    entity,
+                    action,
+                    param,
+                });
+            }
+        }
+        Block::Piston { .. } | Block::StickyPiston { .. } => {
+            piston::on_action(cmds, world, pos, action, param);
+        }
+        Block::NoteBlock { .. } => {
+            // TODO: Play the instrument once sounds are supported
+            cmds.spawn((
+                NoteData {
+                    position: Vector3::new(
+                        pos.x as f64 + 0.5,
+                        pos.y as f64 + 1.2,
+                        pos.z as f64 + 0.5,
+                    ),
+                    pitch: param,
+                },
+                ParticleType::Note,
+            ));
+        }
+        _ => {}
+    }
+}
+
+/// The direction for the id used in block actions.
+fn direction_from_id(id: u8) -> Option<Direction> {
+    Direction::all().get(id as usize).copied()
+}
+
+/// The dye colour for its id, as used in NBT data since 1.13. Older
+/// versions store `15 - id`.
+fn dye_from_id(id: i32) -> Option<ColoredVariant> {
+    match id {
+        0 => Some(ColoredVariant::White),
+        1 => Some(ColoredVariant::Orange),
+        2 => Some(ColoredVariant::Magenta),
+        3 => Some(ColoredVariant::LightBlue),
+        4 => Some(ColoredVariant::Yellow),
+        5 => Some(ColoredVariant::Lime),
+        6 => Some(ColoredVariant::Pink),
+        7 => Some(ColoredVariant::Gray),
+        8 => Some(ColoredVariant::LightGray),
+        9 => Some(ColoredVariant::Cyan),
+        10 => Some(ColoredVariant::Purple),
+        11 => Some(ColoredVariant::Blue),
+        12 => Some(ColoredVariant::Brown),
+        13 => Some(ColoredVariant::Green),
+        14 => Some(ColoredVariant::Red),
+        15 => Some(ColoredVariant::Black),
+        _ => None,
+    }
+}
+
+/// The colour dyed things are tinted with.
+fn dye_rgb(color: ColoredVariant) -> (u8, u8, u8) {
+    match color {
+        ColoredVariant::White => (0xF9, 0xFF, 0xFE),
+        ColoredVariant::Orange => (0xF9, 0x80, 0x1D),
+        ColoredVariant::Magenta => (0xC7, 0x4E, 0xBD),
+        ColoredVariant::LightBlue => (0x3A, 0xB3, 0xDA),
+        ColoredVariant::Yellow => (0xFE, 0xD8, 0x3D),
+        ColoredVariant::Lime => (0x80, 0xC7, 0x1F),
+        ColoredVariant::Pink => (0xF3, 0x8B, 0xAA),
+        ColoredVariant::Gray => (0x47, 0x4F, 0x52),
+        ColoredVariant::LightGray => (0x9D, 0x9D, 0x97),
+        ColoredVariant::Cyan => (0x16, 0x9C, 0x9C),
+        ColoredVariant::Purple => (0x89, 0x32, 0xB8),
+        ColoredVariant::Blue => (0x3C, 0x44, 0xAA),
+        ColoredVariant::Brown => (0x83, 0x54, 0x32),
+        ColoredVariant::Green => (0x5E, 0x7C, 0x16),
+        ColoredVariant::Red => (0xB0, 0x2E, 0x26),
+        ColoredVariant::Black => (0x1D, 0x1D, 0x21),
+    }
+}
+
+/// The textures for the faces of a box in the usual entity texture layout,
+/// in the order `model::append_box` takes them. Sizes are in texture pixels.
+pub(crate) fn box_textures(
+    tex: &render::Texture,
+    (tw, th): (f32, f32),
+    (u, v): (f32, f32),
+    (w, h, d): (f32, f32, f32),
+) -> [Option<render::Texture>; 6] {
+    let part =
+        |x: f32, y: f32, pw: f32, ph: f32| Some(tex.relative(x / tw, y / th, pw / tw, ph / th));
+    [
+        part(u + d + w, v, w, d),
+        part(u + d, v, w, d),
+        part(u + d, v + d, w, h),
+        part(u + d * 2.0 + w, v + d, w, h),
+        part(u, v + d, d, h),
+        part(u + d + w, v + d, d, h),
+    ]
+}
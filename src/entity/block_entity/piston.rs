@@ -0,0 +1,182 @@
+use crate::ecs::SystemExecStage;
+use crate::model;
+use crate::render;
+use crate::server::{BlockModelsResource, DeltaResource, RendererResource, WorldResource};
+use crate::shared::{Direction, Position};
+use crate::world;
+use crate::world::block::{Block, PistonType};
+use bevy_ecs::prelude::*;
+use cgmath::{Matrix4, Vector3};
+
+/// The most blocks a piston can push at once.
+const PUSH_LIMIT: i32 = 12;
+
+pub fn add_systems(sched: &mut Schedule) {
+    sched
+        .add_systems(
+            render_piston
+                .in_set(SystemExecStage::Render)
+                .after(SystemExecStage::Normal),
+        )
+        .add_systems(
+            on_add_piston
+                .in_set(SystemExecStage::Render)
+                .after(SystemExecStage::Normal),
+        );
+}
+
+/// A piston head and the blocks it moves, animated between where they were
+/// and where they end up.
+#[derive(Component)]
+pub struct PistonMotion {
+    model: Option<render::model::ModelHandle>,
+    direction: Direction,
+    extending: bool,
+    /// The moving blocks and their offset from the piston.
+    blocks: Vec<(Position, Block)>,
+    /// How far the move is along, from 0 to 1.
+    progress: f32,
+}
+
+/// Starts animating a piston block action. The world still holds the blocks
+/// as they were before the move, the server sends the changes afterwards.
+pub fn on_action(cmds: &mut Commands, world: &world::World, pos: Position, action: u8, param: u8) {
+    let sticky = match world.get_block(pos) {
+        Block::Piston { .. } => false,
+        Block::StickyPiston { .. } => true,
+        _ => return,
+    };
+    let direction = match super::direction_from_id(param) {
+        Some(direction) => direction,
+        None => return,
+    };
+    let (dx, dy, dz) = direction.get_offset();
+    let head = Block::PistonHead {
+        facing: direction,
+        short: false,
+        type_: if sticky {
+            PistonType::Sticky
+        } else {
+            PistonType::Normal
+        },
+    };
+    let extending = action == 0;
+
+    let mut blocks = vec![];
+    if extending {
+        blocks.push((Position::new(0, 0, 0), head));
+        for i in 1..=PUSH_LIMIT + 1 {
+            let other = world.get_block(pos.shift_by(direction, i));
+            if !other.get_material().collidable {
+                break;
+            }
+            if i > PUSH_LIMIT || !is_movable(other) {
+                // The piston can't push this far, nothing moves
+                return;
+            }
+            blocks.push((Position::new(dx * i, dy * i, dz * i), other));
+        }
+    } else {
+        blocks.push((Position::new(dx, dy, dz), head));
+        if sticky {
+            let other = world.get_block(pos.shift_by(direction, 2));
+            if other.get_material().collidable && is_movable(other) {
+                blocks.push((Position::new(dx * 2, dy * 2, dz * 2), other));
+            }
+        }
+    }
+    // TODO: Play "block.piston.extend"/"block.piston.contract" once sounds are supported
+
+    cmds.spawn((
+        PistonMotion {
+            model: None,
+            direction,
+            extending,
+            blocks,
+            progress: 0.0,
+        },
+        pos,
+    ));
+}
+
+fn is_movable(block: Block) -> bool {
+    !matches!(
+        block,
+        Block::Obsidian {}
+            | Block::Bedrock {}
+            | Block::Piston { extended: true, .. }
+            | Block::StickyPiston { extended: true, .. }
+            | Block::PistonHead { .. }
+            | Block::MovingPiston { .. }
+    )
+}
+
+pub fn render_piston(
+    renderer: Res<RendererResource>,
+    world: Res<WorldResource>,
+    delta: Res<DeltaResource>,
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut PistonMotion, &Position)>,
+) {
+    let renderer = &renderer.0;
+    let world = &world.0;
+    // A move takes two ticks
+    let step = (delta.0 / 3.0 * 0.5) as f32;
+    for (entity, mut motion, position) in query.iter_mut() {
+        motion.progress += step;
+        if motion.progress >= 1.0 {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        if let Some(model) = &motion.model {
+            let mut models = renderer.models.lock();
+            let mdl = models.get_model(model).unwrap();
+            let light_pos = position.shift(motion.direction);
+            mdl.block_light = world.get_block_light(light_pos) as f32;
+            mdl.sky_light = world.get_sky_light(light_pos) as f32;
+
+            let (dx, dy, dz) = motion.direction.get_offset();
+            let offset = if motion.extending {
+                motion.progress
+            } else {
+                -motion.progress
+            };
+            mdl.matrix[0] = Matrix4::from_translation(Vector3::new(
+                position.x as f32 + dx as f32 * offset,
+                -(position.y as f32 + dy as f32 * offset),
+                position.z as f32 + dz as f32 * offset,
+            ));
+        }
+    }
+}
+
+pub fn on_add_piston(
+    renderer: Res<RendererResource>,
+    block_models: Res<BlockModelsResource>,
+    mut query: Query<(&mut PistonMotion, &Position), Added<PistonMotion>>,
+) {
+    let renderer = &renderer.0;
+    let mut rng = rand::thread_rng();
+    for (mut motion, position) in query.iter_mut() {
+        let mut verts = vec![];
+        for (offset, block) in &motion.blocks {
+            for mut vert in model::Factory::get_block_vertices(&block_models.0, *block, &mut rng) {
+                vert.x += offset.x as f32;
+                vert.y += offset.y as f32;
+                vert.z += offset.z as f32;
+                verts.push(vert);
+            }
+        }
+
+        let mut models = renderer.models.lock();
+        let model = models.create_model(render::model::DEFAULT, vec![verts], renderer.clone());
+        let mdl = models.get_model(&model).unwrap();
+        mdl.radius = PUSH_LIMIT as f32 + 2.0;
+        mdl.x = position.x as f32 + 0.5;
+        mdl.y = position.y as f32 + 0.5;
+        mdl.z = position.z as f32 + 0.5;
+        drop(models);
+
+        motion.model.replace(model);
+    }
+}
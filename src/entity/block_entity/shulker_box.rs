@@ -0,0 +1,204 @@
+use super::box_textures;
+use crate::ecs::SystemExecStage;
+use crate::render;
+use crate::render::model;
+use crate::render::Renderer;
+use crate::server::{DeltaResource, RendererResource, WorldResource};
+use crate::shared::{Direction, Position};
+use crate::world::block::{Block, ColoredVariant};
+use bevy_ecs::prelude::*;
+use bevy_ecs::system::Command;
+use cgmath::{Matrix4, Quaternion, Rad, Rotation3, Vector3};
+use std::f32::consts::PI;
+use std::sync::Arc;
+
+pub fn add_systems(sched: &mut Schedule) {
+    sched
+        .add_systems(
+            render_shulker_box
+                .in_set(SystemExecStage::Render)
+                .after(SystemExecStage::Normal),
+        )
+        .add_systems(
+            on_add_shulker_box
+                .in_set(SystemExecStage::Render)
+                .after(SystemExecStage::Normal),
+        );
+}
+
+pub fn init_entity(cmds: &mut Commands, e: Entity) {
+    cmds.get_entity(e).unwrap().insert(ShulkerBoxInfo {
+        model: None,
+        facing: Direction::Up,
+        open: false,
+        progress: 0.0,
+    });
+}
+
+#[derive(Component)]
+pub struct ShulkerBoxInfo {
+    model: Option<model::ModelHandle>,
+    facing: Direction,
+
+    /// Whether anyone is looking into the box.
+    open: bool,
+    /// How far the lid is open, from 0 (closed) to 1.
+    progress: f32,
+}
+
+fn shulker_box_of(block: Block) -> Option<(Option<ColoredVariant>, Direction)> {
+    Some(match block {
+        Block::ShulkerBox { facing } => (None, facing),
+        Block::WhiteShulkerBox { facing } => (Some(ColoredVariant::White), facing),
+        Block::OrangeShulkerBox { facing } => (Some(ColoredVariant::Orange), facing),
+        Block::MagentaShulkerBox { facing } => (Some(ColoredVariant::Magenta), facing),
+        Block::LightBlueShulkerBox { facing } => (Some(ColoredVariant::LightBlue), facing),
+        Block::YellowShulkerBox { facing } => (Some(ColoredVariant::Yellow), facing),
+        Block::LimeShulkerBox { facing } => (Some(ColoredVariant::Lime), facing),
+        Block::PinkShulkerBox { facing } => (Some(ColoredVariant::Pink), facing),
+        Block::GrayShulkerBox { facing } => (Some(ColoredVariant::Gray), facing),
+        Block::LightGrayShulkerBox { facing } => (Some(ColoredVariant::LightGray), facing),
+        Block::CyanShulkerBox { facing } => (Some(ColoredVariant::Cyan), facing),
+        Block::PurpleShulkerBox { facing } => (Some(ColoredVariant::Purple), facing),
+        Block::BlueShulkerBox { facing } => (Some(ColoredVariant::Blue), facing),
+        Block::BrownShulkerBox { facing } => (Some(ColoredVariant::Brown), facing),
+        Block::GreenShulkerBox { facing } => (Some(ColoredVariant::Green), facing),
+        Block::RedShulkerBox { facing } => (Some(ColoredVariant::Red), facing),
+        Block::BlackShulkerBox { facing } => (Some(ColoredVariant::Black), facing),
+        _ => return None,
+    })
+}
+
+/// Applies a shulker box block action, which like a chest's tells how many
+/// players have it open.
+pub struct ShulkerActionCmd {
+    pub entity: Entity,
+    pub action: u8,
+    pub param: u8,
+}
+
+impl Command for ShulkerActionCmd {
+    fn apply(self, world: &mut bevy_ecs::world::World) {
+        if self.action != 1 {
+            return;
+        }
+        let mut entity = world.get_entity_mut(self.entity);
+        if let Some(mut info) = entity
+            .as_mut()
+            .and_then(|entity| entity.get_mut::<ShulkerBoxInfo>())
+        {
+            info.open = self.param > 0;
+        }
+    }
+}
+
+pub fn render_shulker_box(
+    renderer: Res<RendererResource>,
+    world: Res<WorldResource>,
+    delta: Res<DeltaResource>,
+    mut query: Query<(&mut ShulkerBoxInfo, &Position)>,
+) {
+    let renderer = &renderer.0;
+    let world = &world.0;
+    // Lids open and close over 10 ticks
+    let step = (delta.0 / 3.0 * 0.1) as f32;
+    for (mut info, position) in query.iter_mut() {
+        info.progress = if info.open {
+            (info.progress + step).min(1.0)
+        } else {
+            (info.progress - step).max(0.0)
+        };
+        if let Some(model) = &info.model {
+            let mut models = renderer.models.lock();
+            let mdl = models.get_model(model).unwrap();
+            mdl.block_light = world.get_block_light(*position) as f32;
+            mdl.sky_light = world.get_sky_light(*position) as f32;
+
+            // The model opens upwards, turn it towards the way the box faces
+            let turn = match info.facing {
+                Direction::Down => Quaternion::from_angle_x(Rad(PI)),
+                Direction::North => Quaternion::from_angle_x(Rad(PI / 2.0)),
+                Direction::South => Quaternion::from_angle_x(Rad(-PI / 2.0)),
+                Direction::West => Quaternion::from_angle_z(Rad(-PI / 2.0)),
+                Direction::East => Quaternion::from_angle_z(Rad(PI / 2.0)),
+                _ => Quaternion::from_angle_y(Rad(0.0)),
+            };
+            let base = Matrix4::from_translation(Vector3::new(
+                position.x as f32 + 0.5,
+                -(position.y as f32 + 0.5),
+                position.z as f32 + 0.5,
+            )) * Matrix4::from(turn);
+            // The lid rises half a block while twisting three quarters of
+            // a turn
+            mdl.matrix[0] = base;
+            mdl.matrix[1] = base
+                * Matrix4::from_translation(Vector3::new(0.0, -0.5 * info.progress, 0.0))
+                * Matrix4::from(Quaternion::from_angle_y(Rad(info.progress * PI * 1.5)));
+        }
+    }
+}
+
+pub fn on_add_shulker_box(
+    renderer: Res<RendererResource>,
+    world: Res<WorldResource>,
+    mut query: Query<(&mut ShulkerBoxInfo, &Position), Added<ShulkerBoxInfo>>,
+) {
+    let renderer = &renderer.0;
+    let world = &world.0;
+    for (mut info, position) in query.iter_mut() {
+        add_shulker_box(renderer.clone(), world.clone(), &mut info, position);
+    }
+}
+
+fn add_shulker_box(
+    renderer: Arc<Renderer>,
+    world: Arc<crate::world::World>,
+    info: &mut ShulkerBoxInfo,
+    position: &Position,
+) {
+    let (color, facing) = match shulker_box_of(world.get_block(*position)) {
+        Some(shulker_box) => shulker_box,
+        None => return,
+    };
+    info.facing = facing;
+    let path = match color {
+        Some(color) => format!("entity/shulker/shulker_{}", color.as_string()),
+        None => "entity/shulker/shulker".to_owned(),
+    };
+    let tex = render::Renderer::get_texture(renderer.get_textures_ref(), &path);
+    let size = (64.0, 64.0);
+
+    let mut base = vec![];
+    model::append_box(
+        &mut base,
+        -0.5,
+        -0.5,
+        -0.5,
+        1.0,
+        8.0 / 16.0,
+        1.0,
+        box_textures(&tex, size, (0.0, 28.0), (16.0, 8.0, 16.0)),
+    );
+    let mut lid = vec![];
+    model::append_box(
+        &mut lid,
+        -0.5,
+        -4.0 / 16.0,
+        -0.5,
+        1.0,
+        12.0 / 16.0,
+        1.0,
+        box_textures(&tex, size, (0.0, 0.0), (16.0, 12.0, 16.0)),
+    );
+
+    let mut models = renderer.models.lock();
+    let model = models.create_model(model::DEFAULT, vec![base, lid], renderer.clone());
+    let mdl = models.get_model(&model).unwrap();
+    mdl.radius = 2.0;
+    mdl.x = position.x as f32 + 0.5;
+    mdl.y = position.y as f32 + 0.5;
+    mdl.z = position.z as f32 + 0.5;
+    drop(models);
+
+    info.model.replace(model);
+}
@@ -0,0 +1,328 @@
+use super::box_textures;
+use crate::ecs::SystemExecStage;
+use crate::render;
+use crate::render::model;
+use crate::render::Renderer;
+use crate::server::{RendererResource, WorldResource};
+use crate::shared::{Direction, Position};
+use crate::world::block::Block;
+use bevy_ecs::prelude::*;
+use cgmath::{Decomposed, Matrix4, Quaternion, Rad, Rotation3, Vector3};
+use leafish_protocol::nbt;
+use std::f32::consts::PI;
+use std::sync::Arc;
+
+pub fn add_systems(sched: &mut Schedule) {
+    sched
+        .add_systems(
+            render_skull
+                .in_set(SystemExecStage::Render)
+                .after(SystemExecStage::Normal),
+        )
+        .add_systems(
+            on_add_skull
+                .in_set(SystemExecStage::Render)
+                .after(SystemExecStage::Normal),
+        );
+}
+
+pub fn init_entity(cmds: &mut Commands, e: Entity) {
+    cmds.get_entity(e).unwrap().insert(SkullInfo {
+        model: None,
+        skin_url: None,
+        legacy_kind: None,
+        legacy_rotation: None,
+        rotation: 0.0,
+        offset: (0.0, 0.0, 0.0),
+        dirty: false,
+    });
+}
+
+#[derive(Component)]
+pub struct SkullInfo {
+    model: Option<model::ModelHandle>,
+
+    /// The skin of the owner of a player head.
+    skin_url: Option<String>,
+    /// Before 1.13 the kind of skull and how it's turned are kept in NBT
+    /// rather than the block.
+    legacy_kind: Option<SkullKind>,
+    legacy_rotation: Option<f32>,
+
+    rotation: f32,
+    offset: (f32, f32, f32),
+    dirty: bool,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum SkullKind {
+    Skeleton,
+    WitherSkeleton,
+    Zombie,
+    Player,
+    Creeper,
+    Dragon,
+}
+
+impl SkullKind {
+    fn from_id(id: i8) -> Option<SkullKind> {
+        Some(match id {
+            0 => SkullKind::Skeleton,
+            1 => SkullKind::WitherSkeleton,
+            2 => SkullKind::Zombie,
+            3 => SkullKind::Player,
+            4 => SkullKind::Creeper,
+            5 => SkullKind::Dragon,
+            _ => return None,
+        })
+    }
+}
+
+enum Placement {
+    /// Standing on the ground, rotated in sixteenths of a turn.
+    Floor(f32),
+    Wall(Direction),
+}
+
+impl SkullInfo {
+    pub(crate) fn load_nbt(&mut self, data: &nbt::Tag) {
+        let owner = data.get("SkullOwner").or_else(|| data.get("Owner"));
+        let textures = owner
+            .and_then(|owner| owner.get("Properties"))
+            .and_then(|properties| properties.get("textures"))
+            .and_then(|textures| textures.as_list())
+            .and_then(|textures| textures.first())
+            .and_then(|texture| texture.get("Value"))
+            .and_then(|value| value.as_str());
+        self.skin_url = textures.and_then(crate::server::skin_url_from_textures);
+        self.legacy_kind = data
+            .get("SkullType")
+            .and_then(|kind| kind.as_byte())
+            .and_then(SkullKind::from_id);
+        self.legacy_rotation = data
+            .get("Rot")
+            .and_then(|rotation| rotation.as_byte())
+            .map(|rotation| rotation as f32);
+        self.dirty = true;
+    }
+}
+
+fn skull_of(block: Block) -> Option<(SkullKind, Placement)> {
+    Some(match block {
+        Block::SkeletonSkull { rotation } => {
+            (SkullKind::Skeleton, Placement::Floor(rotation as f32))
+        }
+        Block::SkeletonWallSkull { facing } => (SkullKind::Skeleton, Placement::Wall(facing)),
+        Block::WitherSkeletonSkull { rotation } => {
+            (SkullKind::WitherSkeleton, Placement::Floor(rotation as f32))
+        }
+        Block::WitherSkeletonWallSkull { facing } => {
+            (SkullKind::WitherSkeleton, Placement::Wall(facing))
+        }
+        Block::ZombieHead { rotation } => (SkullKind::Zombie, Placement::Floor(rotation as f32)),
+        Block::ZombieWallHead { facing } => (SkullKind::Zombie, Placement::Wall(facing)),
+        Block::PlayerHead { rotation } => (SkullKind::Player, Placement::Floor(rotation as f32)),
+        Block::PlayerWallHead { facing } => (SkullKind::Player, Placement::Wall(facing)),
+        Block::CreeperHead { rotation } => (SkullKind::Creeper, Placement::Floor(rotation as f32)),
+        Block::CreeperWallHead { facing } => (SkullKind::Creeper, Placement::Wall(facing)),
+        Block::DragonHead { rotation } => (SkullKind::Dragon, Placement::Floor(rotation as f32)),
+        Block::DragonWallHead { facing } => (SkullKind::Dragon, Placement::Wall(facing)),
+        _ => return None,
+    })
+}
+
+pub fn render_skull(
+    renderer: Res<RendererResource>,
+    world: Res<WorldResource>,
+    mut query: Query<(&mut SkullInfo, &Position)>,
+) {
+    let renderer = &renderer.0;
+    let world = &world.0;
+    for (mut info, position) in query.iter_mut() {
+        if info.dirty {
+            add_skull(renderer.clone(), world.clone(), &mut info, position);
+        }
+        if let Some(model) = &info.model {
+            let mut models = renderer.models.lock();
+            let mdl = models.get_model(model).unwrap();
+            mdl.block_light = world.get_block_light(*position) as f32;
+            mdl.sky_light = world.get_sky_light(*position) as f32;
+
+            let (x, y, z) = info.offset;
+            mdl.matrix[0] = Matrix4::from(Decomposed {
+                scale: 1.0,
+                rot: Quaternion::from_angle_y(Rad(info.rotation)),
+                disp: Vector3::new(
+                    position.x as f32 + 0.5 + x,
+                    -(position.y as f32 + y),
+                    position.z as f32 + 0.5 + z,
+                ),
+            });
+        }
+    }
+}
+
+pub fn on_add_skull(
+    renderer: Res<RendererResource>,
+    world: Res<WorldResource>,
+    mut query: Query<(&mut SkullInfo, &Position), Added<SkullInfo>>,
+) {
+    let renderer = &renderer.0;
+    let world = &world.0;
+    for (mut info, position) in query.iter_mut() {
+        add_skull(renderer.clone(), world.clone(), &mut info, position);
+    }
+}
+
+fn add_skull(
+    renderer: Arc<Renderer>,
+    world: Arc<crate::world::World>,
+    info: &mut SkullInfo,
+    position: &Position,
+) {
+    info.dirty = false;
+    info.model.take();
+    let (kind, placement) = match skull_of(world.get_block(*position)) {
+        Some(skull) => skull,
+        None => return,
+    };
+    let kind = info.legacy_kind.unwrap_or(kind);
+
+    // The model faces north, skulls on walls sit against the wall behind
+    // them
+    match placement {
+        Placement::Floor(rotation) => {
+            let rotation = info.legacy_rotation.unwrap_or(rotation);
+            info.rotation = -rotation / 16.0 * PI * 2.0;
+            info.offset = (0.0, 0.0, 0.0);
+        }
+        Placement::Wall(facing) => {
+            info.rotation = match facing {
+                Direction::South => PI,
+                Direction::West => PI / 2.0,
+                Direction::East => -PI / 2.0,
+                _ => 0.0,
+            };
+            let (ox, _, oz) = facing.get_offset();
+            info.offset = (-ox as f32 * 0.25, 0.25, -oz as f32 * 0.25);
+        }
+    }
+
+    let mut verts = vec![];
+    if kind == SkullKind::Dragon {
+        let tex =
+            render::Renderer::get_texture(renderer.get_textures_ref(), "entity/enderdragon/dragon");
+        let size = (256.0, 256.0);
+        let s = 0.75 / 16.0;
+        model::append_box(
+            &mut verts,
+            -8.0 * s,
+            0.0,
+            -8.0 * s,
+            16.0 * s,
+            16.0 * s,
+            16.0 * s,
+            box_textures(&tex, size, (112.0, 30.0), (16.0, 16.0, 16.0)),
+        );
+        model::append_box(
+            &mut verts,
+            -6.0 * s,
+            4.0 * s,
+            -22.0 * s,
+            12.0 * s,
+            5.0 * s,
+            16.0 * s,
+            box_textures(&tex, size, (176.0, 44.0), (12.0, 5.0, 16.0)),
+        );
+        model::append_box(
+            &mut verts,
+            -6.0 * s,
+            0.0,
+            -22.0 * s,
+            12.0 * s,
+            4.0 * s,
+            16.0 * s,
+            box_textures(&tex, size, (176.0, 65.0), (12.0, 4.0, 16.0)),
+        );
+    } else {
+        let (tex, size, has_hat) = match kind {
+            SkullKind::Player => {
+                let tex = if let Some(url) = &info.skin_url {
+                    renderer.get_skin(renderer.get_textures_ref(), url)
+                } else {
+                    render::Renderer::get_texture(renderer.get_textures_ref(), "entity/steve")
+                };
+                (tex, (64.0, 64.0), true)
+            }
+            SkullKind::Zombie => (
+                render::Renderer::get_texture(renderer.get_textures_ref(), "entity/zombie/zombie"),
+                (64.0, 64.0),
+                true,
+            ),
+            SkullKind::WitherSkeleton => (
+                render::Renderer::get_texture(
+                    renderer.get_textures_ref(),
+                    "entity/skeleton/wither_skeleton",
+                ),
+                (64.0, 32.0),
+                false,
+            ),
+            SkullKind::Creeper => (
+                render::Renderer::get_texture(
+                    renderer.get_textures_ref(),
+                    "entity/creeper/creeper",
+                ),
+                (64.0, 32.0),
+                false,
+            ),
+            _ => (
+                render::Renderer::get_texture(
+                    renderer.get_textures_ref(),
+                    "entity/skeleton/skeleton",
+                ),
+                (64.0, 32.0),
+                false,
+            ),
+        };
+        model::append_box(
+            &mut verts,
+            -4.0 / 16.0,
+            0.0,
+            -4.0 / 16.0,
+            8.0 / 16.0,
+            8.0 / 16.0,
+            8.0 / 16.0,
+            box_textures(&tex, size, (0.0, 0.0), (8.0, 8.0, 8.0)),
+        );
+        if has_hat {
+            model::append_box(
+                &mut verts,
+                -4.25 / 16.0,
+                -0.25 / 16.0,
+                -4.25 / 16.0,
+                8.5 / 16.0,
+                8.5 / 16.0,
+                8.5 / 16.0,
+                box_textures(&tex, size, (32.0, 0.0), (8.0, 8.0, 8.0)),
+            );
+        }
+    }
+
+    let mut models = renderer.models.lock();
+    let mut model = models.create_model(model::DEFAULT, vec![verts], renderer.clone());
+    let mdl = models.get_model(&model).unwrap();
+    mdl.radius = 2.0;
+    mdl.x = position.x as f32 + 0.5;
+    mdl.y = position.y as f32 + 0.5;
+    mdl.z = position.z as f32 + 0.5;
+    drop(models);
+
+    if kind == SkullKind::Player {
+        if let Some(url) = info.skin_url.clone() {
+            model.2 = Some(Arc::new(move |renderer: Arc<Renderer>| {
+                renderer.get_textures_ref().read().release_skin(&url);
+            }));
+        }
+    }
+    info.model.replace(model);
+}
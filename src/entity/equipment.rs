@@ -0,0 +1,316 @@
+use super::mob::{append_cube, Cube};
+use crate::inventory::{Item, Material};
+use crate::render::model::{self, Vertex};
+use crate::render::Renderer;
+use bevy_ecs::prelude::*;
+use cgmath::Matrix4;
+use shared::Version;
+use std::sync::Arc;
+
+/// The color of leather armor that wasn't dyed.
+const LEATHER_COLOR: i32 = 0xa0_65_40;
+/// How much the glint is larger than the armor it covers, in pixels, so
+/// they don't fight over which is in front. The same goes for the undyed
+/// parts of leather armor.
+const GLINT_INFLATE: f32 = 0.05;
+const OVERLAY_INFLATE: f32 = 0.01;
+const GLINT_COLOR: [f32; 3] = [0.5, 0.25, 0.8];
+
+/// Where an entity wears or holds an item.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Slot {
+    MainHand = 0,
+    OffHand = 1,
+    Feet = 2,
+    Legs = 3,
+    Chest = 4,
+    Head = 5,
+}
+
+impl Slot {
+    /// The slot for its id in the equipment packets.
+    pub fn by_id(id: i32, version: Version) -> Option<Slot> {
+        // There was no off hand before 1.9
+        let slots: &[Slot] = if version < Version::V1_9 {
+            &[
+                Slot::MainHand,
+                Slot::Feet,
+                Slot::Legs,
+                Slot::Chest,
+                Slot::Head,
+            ]
+        } else {
+            &[
+                Slot::MainHand,
+                Slot::OffHand,
+                Slot::Feet,
+                Slot::Legs,
+                Slot::Chest,
+                Slot::Head,
+            ]
+        };
+        if id < 0 {
+            return None;
+        }
+        slots.get(id as usize).copied()
+    }
+}
+
+/// The items an entity wears and holds, as far as other players can see
+/// them.
+#[derive(Component, Clone, Debug, Default)]
+pub struct Equipment {
+    items: [Option<Item>; 6],
+}
+
+impl Equipment {
+    pub fn get(&self, slot: Slot) -> Option<&Item> {
+        self.items[slot as usize].as_ref()
+    }
+
+    pub fn set(&mut self, slot: Slot, item: Option<Item>) {
+        self.items[slot as usize] = item;
+    }
+}
+
+/// The parts of a player or humanoid mob that armor is drawn around.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BodyPart {
+    Head,
+    Body,
+    RightArm,
+    LeftArm,
+    RightLeg,
+    LeftLeg,
+}
+
+impl BodyPart {
+    /// The body part a part of a mob model is, by its name.
+    pub fn by_name(name: &str) -> Option<BodyPart> {
+        Some(match name {
+            "head" => BodyPart::Head,
+            "body" => BodyPart::Body,
+            "right_arm" => BodyPart::RightArm,
+            "left_arm" => BodyPart::LeftArm,
+            "right_leg" => BodyPart::RightLeg,
+            "left_leg" => BodyPart::LeftLeg,
+            _ => return None,
+        })
+    }
+
+    /// Where the box of the part starts in the vanilla humanoid model,
+    /// relative to its pivot. Armor is shaped after it whatever the mob's
+    /// own parts look like.
+    pub fn humanoid_from(self) -> [f32; 3] {
+        match self {
+            BodyPart::Head => [-4.0, -8.0, -4.0],
+            BodyPart::Body => [-4.0, 0.0, -2.0],
+            BodyPart::RightArm => [-3.0, -2.0, -2.0],
+            BodyPart::LeftArm => [-1.0, -2.0, -2.0],
+            BodyPart::RightLeg | BodyPart::LeftLeg => [-2.0, 0.0, -2.0],
+        }
+    }
+
+    fn size(self) -> [f32; 3] {
+        match self {
+            BodyPart::Head => [8.0, 8.0, 8.0],
+            BodyPart::Body => [8.0, 12.0, 4.0],
+            _ => [4.0, 12.0, 4.0],
+        }
+    }
+
+    /// The armor pieces covering the part, with the texture layer, where
+    /// the box is in the texture and how far it grows around the part.
+    fn pieces(self) -> &'static [(Slot, u8, [f32; 2], f32)] {
+        match self {
+            BodyPart::Head => &[(Slot::Head, 1, [0.0, 0.0], 1.0)],
+            BodyPart::Body => &[
+                (Slot::Chest, 1, [16.0, 16.0], 1.0),
+                (Slot::Legs, 2, [16.0, 16.0], 0.5),
+            ],
+            BodyPart::RightArm | BodyPart::LeftArm => &[(Slot::Chest, 1, [40.0, 16.0], 1.0)],
+            BodyPart::RightLeg | BodyPart::LeftLeg => &[
+                (Slot::Legs, 2, [0.0, 16.0], 0.5),
+                (Slot::Feet, 1, [0.0, 16.0], 1.0),
+            ],
+        }
+    }
+}
+
+/// The name armor textures use for the material of the item, if it's
+/// armor.
+fn armor_material(material: Material) -> Option<&'static str> {
+    Some(match material {
+        Material::LeatherHelmet
+        | Material::LeatherChestplate
+        | Material::LeatherLeggings
+        | Material::LeatherBoots => "leather",
+        Material::ChainmailHelmet
+        | Material::ChainmailChestplate
+        | Material::ChainmailLeggings
+        | Material::ChainmailBoots => "chainmail",
+        Material::IronHelmet
+        | Material::IronChestplate
+        | Material::IronLeggings
+        | Material::IronBoots => "iron",
+        Material::DiamondHelmet
+        | Material::DiamondChestplate
+        | Material::DiamondLeggings
+        | Material::DiamondBoots => "diamond",
+        Material::GoldHelmet
+        | Material::GoldChestplate
+        | Material::GoldLeggings
+        | Material::GoldBoots
+        | Material::GoldenHelmet
+        | Material::GoldenChestplate
+        | Material::GoldenLeggings
+        | Material::GoldenBoots => "gold",
+        Material::NetheriteHelmet
+        | Material::NetheriteChestplate
+        | Material::NetheriteLeggings
+        | Material::NetheriteBoots => "netherite",
+        Material::TurtleHelmet => "turtle",
+        _ => return None,
+    })
+}
+
+/// The color leather armor is tinted with, white for anything else.
+fn armor_color(item: &Item, material: &str) -> [u8; 3] {
+    if material != "leather" {
+        return [255; 3];
+    }
+    let color = item.stack.meta.color().unwrap_or(LEATHER_COLOR);
+    [(color >> 16) as u8, (color >> 8) as u8, color as u8]
+}
+
+/// The armor drawn around a part whose box starts at `from`, and the glint
+/// drawn over the enchanted pieces.
+pub fn armor_vertices(
+    equipment: &Equipment,
+    part: BodyPart,
+    from: [f32; 3],
+    renderer: &Arc<Renderer>,
+) -> (Vec<Vertex>, Vec<Vertex>) {
+    let mut verts = vec![];
+    let mut glint = vec![];
+    for &(slot, layer, uv, inflate) in part.pieces() {
+        let item = match equipment.get(slot) {
+            Some(item) => item,
+            None => continue,
+        };
+        let material = match armor_material(item.material) {
+            Some(material) => material,
+            None => continue,
+        };
+        let cube = Cube {
+            uv,
+            from,
+            size: part.size(),
+            inflate,
+        };
+        let name = format!("minecraft:models/armor/{}_layer_{}", material, layer);
+        let tex = Renderer::get_texture(renderer.get_textures_ref(), &name);
+        let start = verts.len();
+        append_cube(&mut verts, &cube, &tex, [64.0, 32.0]);
+        let [r, g, b] = armor_color(item, material);
+        for vert in &mut verts[start..] {
+            vert.r = (vert.r as u32 * r as u32 / 255) as u8;
+            vert.g = (vert.g as u32 * g as u32 / 255) as u8;
+            vert.b = (vert.b as u32 * b as u32 / 255) as u8;
+        }
+        // The parts of leather armor that aren't dyed
+        if material == "leather" {
+            let overlay =
+                Renderer::get_texture(renderer.get_textures_ref(), &format!("{}_overlay", name));
+            let cube = Cube {
+                inflate: inflate + OVERLAY_INFLATE,
+                ..cube
+            };
+            append_cube(&mut verts, &cube, &overlay, [64.0, 32.0]);
+        }
+
+        if item.stack.meta.is_enchanted() {
+            let tex = Renderer::get_texture(
+                renderer.get_textures_ref(),
+                "minecraft:misc/enchanted_item_glint",
+            );
+            let cube = Cube {
+                inflate: inflate + GLINT_INFLATE,
+                ..cube
+            };
+            append_cube(&mut glint, &cube, &tex, [64.0, 32.0]);
+        }
+    }
+    (verts, glint)
+}
+
+/// Creates the model for the glint if any part has one.
+pub fn create_glint(
+    parts: Vec<Vec<Vertex>>,
+    renderer: &Arc<Renderer>,
+) -> Option<model::ModelHandle> {
+    if parts.iter().all(Vec::is_empty) {
+        return None;
+    }
+    Some(
+        renderer
+            .clone()
+            .models
+            .lock()
+            .create_model(model::GLINT, parts, renderer.clone()),
+    )
+}
+
+/// Moves the glint along with the model it covers, pulsing with `time`.
+pub fn update_glint(
+    models: &mut model::Manager,
+    glint: Option<&model::ModelHandle>,
+    matrix: &[Matrix4<f32>],
+    time: f64,
+) {
+    let glint = match glint.and_then(|glint| models.get_model(glint)) {
+        Some(glint) => glint,
+        None => return,
+    };
+    let strength = (time.sin() * 0.25 + 0.75) as f32;
+    let [r, g, b] = GLINT_COLOR;
+    for (i, matrix) in matrix.iter().enumerate().take(glint.matrix.len()) {
+        glint.matrix[i] = *matrix;
+        glint.colors[i] = [r, g, b, strength];
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn equipment_slots_by_version() {
+        assert_eq!(Slot::by_id(0, Version::V1_8), Some(Slot::MainHand));
+        assert_eq!(Slot::by_id(1, Version::V1_8), Some(Slot::Feet));
+        assert_eq!(Slot::by_id(4, Version::V1_8), Some(Slot::Head));
+        assert_eq!(Slot::by_id(5, Version::V1_8), None);
+        assert_eq!(Slot::by_id(1, Version::V1_9), Some(Slot::OffHand));
+        assert_eq!(Slot::by_id(5, Version::V1_16_2), Some(Slot::Head));
+        assert_eq!(Slot::by_id(-1, Version::V1_16_2), None);
+    }
+
+    #[test]
+    fn armor_materials() {
+        assert_eq!(armor_material(Material::GoldHelmet), Some("gold"));
+        assert_eq!(armor_material(Material::GoldenBoots), Some("gold"));
+        assert_eq!(armor_material(Material::TurtleHelmet), Some("turtle"));
+        assert_eq!(armor_material(Material::DiamondPickaxe), None);
+    }
+
+    #[test]
+    fn chestplates_cover_the_arms() {
+        for part in [BodyPart::RightArm, BodyPart::LeftArm] {
+            assert!(part.pieces().iter().any(|piece| piece.0 == Slot::Chest));
+        }
+        assert!(BodyPart::Head
+            .pieces()
+            .iter()
+            .all(|piece| piece.0 == Slot::Head));
+    }
+}
@@ -0,0 +1,145 @@
+use super::{GameInfo, Light, Position};
+use crate::ecs::SystemExecStage;
+use crate::render::model;
+use crate::render::Renderer;
+use crate::server::RendererResource;
+use bevy_ecs::prelude::*;
+use cgmath::{Decomposed, Matrix4, Quaternion, Rad, Rotation3, Vector3};
+
+pub fn add_systems(render_sched: &mut Schedule) {
+    render_sched.add_systems(
+        (added_experience_orb, update_experience_orbs)
+            .chain()
+            .in_set(SystemExecStage::Render)
+            .after(SystemExecStage::Normal),
+    );
+}
+
+#[derive(Component)]
+pub struct ExperienceOrbModel {
+    /// The amount of experience the orb is worth, which decides its size.
+    count: i16,
+    model: Option<model::ModelHandle>,
+    /// How long the orb has been around, in ticks.
+    age: f64,
+}
+
+impl ExperienceOrbModel {
+    pub fn new(count: i16) -> Self {
+        Self {
+            count,
+            model: None,
+            age: 0.0,
+        }
+    }
+}
+
+/// Which of the orb sizes in the texture is used for the amount of
+/// experience, from 0 to 10.
+fn icon(count: i16) -> i32 {
+    match count {
+        c if c >= 2477 => 10,
+        c if c >= 1237 => 9,
+        c if c >= 617 => 8,
+        c if c >= 307 => 7,
+        c if c >= 149 => 6,
+        c if c >= 73 => 5,
+        c if c >= 37 => 4,
+        c if c >= 17 => 3,
+        c if c >= 7 => 2,
+        c if c >= 3 => 1,
+        _ => 0,
+    }
+}
+
+pub fn added_experience_orb(
+    renderer: Res<RendererResource>,
+    mut query: Query<&mut ExperienceOrbModel, Added<ExperienceOrbModel>>,
+) {
+    let renderer = &renderer.0;
+    for mut orb in query.iter_mut() {
+        let tex = Renderer::get_texture(
+            renderer.get_textures_ref(),
+            "minecraft:entity/experience_orb",
+        );
+        // The sizes are laid out 4 to a row
+        let icon = icon(orb.count);
+        let tex = tex.relative(
+            (icon % 4) as f32 * 0.25,
+            (icon / 4) as f32 * 0.25,
+            0.25,
+            0.25,
+        );
+        let mut verts = vec![];
+        model::append_box(
+            &mut verts,
+            -0.15,
+            0.025,
+            0.0,
+            0.3,
+            0.3,
+            0.0,
+            [None, None, Some(tex), None, None, None],
+        );
+        orb.model = Some(renderer.clone().models.lock().create_model(
+            model::DEFAULT,
+            vec![verts],
+            renderer.clone(),
+        ));
+    }
+}
+
+pub fn update_experience_orbs(
+    game_info: Res<GameInfo>,
+    renderer: Res<RendererResource>,
+    mut query: Query<(&mut ExperienceOrbModel, &Position, &Light)>,
+) {
+    let renderer = &renderer.0;
+    let camera = renderer.camera.lock().pos;
+    let mut models = renderer.models.lock();
+    for (mut orb, position, light) in query.iter_mut() {
+        orb.age += game_info.delta / 3.0;
+        let handle = match &orb.model {
+            Some(handle) => handle,
+            None => continue,
+        };
+        let mdl = models.get_model(handle).unwrap();
+        mdl.block_light = light.block_light;
+        mdl.sky_light = light.sky_light;
+
+        // Always faces the camera
+        let ang = (position.position.x - camera.x).atan2(position.position.z - camera.z) as f32;
+        mdl.matrix[0] = Matrix4::from(Decomposed {
+            scale: 1.0,
+            rot: Quaternion::from_angle_y(Rad(ang)),
+            disp: Vector3::new(
+                position.position.x as f32,
+                -position.position.y as f32,
+                position.position.z as f32,
+            ),
+        });
+        // Pulses between green and yellow
+        let time = (orb.age / 2.0) as f32;
+        mdl.colors[0] = [
+            (time.sin() + 1.0) * 0.5,
+            1.0,
+            ((time + std::f32::consts::PI * 4.0 / 3.0).sin() + 1.0) * 0.1,
+            1.0,
+        ];
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bigger_orbs_for_more_experience() {
+        assert_eq!(icon(1), 0);
+        assert_eq!(icon(3), 1);
+        assert_eq!(icon(16), 2);
+        assert_eq!(icon(17), 3);
+        assert_eq!(icon(2477), 10);
+        assert_eq!(icon(i16::MAX), 10);
+    }
+}
@@ -0,0 +1,235 @@
+use super::metadata::ItemStack;
+use super::{GameInfo, Light, Position};
+use crate::ecs::SystemExecStage;
+use crate::inventory::Item;
+use crate::render::model::{self, Vertex};
+use crate::render::{Renderer, Texture};
+use crate::server::RendererResource;
+use bevy_ecs::prelude::*;
+use cgmath::{Decomposed, Matrix4, Point3, Quaternion, Rad, Rotation3, Transform, Vector3};
+use rand::Rng;
+use std::sync::Arc;
+
+pub fn add_systems(render_sched: &mut Schedule) {
+    render_sched.add_systems(
+        (added_item_model, update_item_models)
+            .chain()
+            .in_set(SystemExecStage::Render)
+            .after(SystemExecStage::Normal),
+    );
+}
+
+/// A dropped item, spinning and bobbing above the ground.
+#[derive(Component)]
+pub struct ItemModel {
+    model: Option<model::ModelHandle>,
+    /// How long the item has been around, in ticks.
+    age: f64,
+    /// Keeps items dropped together from moving in step.
+    bob_offset: f64,
+}
+
+impl Default for ItemModel {
+    fn default() -> Self {
+        Self {
+            model: None,
+            age: 0.0,
+            bob_offset: rand::thread_rng().gen_range(0.0..std::f64::consts::PI * 2.0),
+        }
+    }
+}
+
+/// How many copies of the item are drawn for the size of the stack.
+fn render_amount(count: isize) -> usize {
+    match count {
+        c if c > 48 => 5,
+        c if c > 32 => 4,
+        c if c > 16 => 3,
+        c if c > 1 => 2,
+        _ => 1,
+    }
+}
+
+/// Where the copies of a block item sit relative to the first one.
+const BLOCK_OFFSETS: [[f32; 3]; 5] = [
+    [0.0, 0.0, 0.0],
+    [0.06, 0.03, 0.04],
+    [-0.05, 0.06, -0.03],
+    [0.04, 0.09, -0.06],
+    [-0.03, 0.12, 0.05],
+];
+
+pub fn added_item_model(
+    renderer: Res<RendererResource>,
+    mut query: Query<(&mut ItemModel, &ItemStack), Changed<ItemStack>>,
+) {
+    let renderer = &renderer.0;
+    for (mut item_model, stack) in query.iter_mut() {
+        // The old model has to be gone before the models are locked
+        item_model.model = None;
+        let item = match &stack.0 {
+            Some(item) => item,
+            None => continue,
+        };
+        let verts = item_vertices(item, renderer);
+        item_model.model = Some(renderer.clone().models.lock().create_model(
+            model::DEFAULT,
+            vec![verts],
+            renderer.clone(),
+        ));
+    }
+}
+
+/// How an item is drawn in the world.
+enum Shape {
+    /// Flat with its icon.
+    Icon(Texture),
+    /// Blocks without an icon are a small cube with the texture of the block.
+    Cube(Texture),
+}
+
+fn shape(item: &Item, renderer: &Arc<Renderer>) -> Shape {
+    let (item_texture, block_texture) = item.material.texture_locations();
+    match Renderer::get_texture_optional(renderer.get_textures_ref(), &item_texture) {
+        Some(tex) if !tex.is_dummy() => Shape::Icon(tex),
+        _ => Shape::Cube(Renderer::get_texture(
+            renderer.get_textures_ref(),
+            &block_texture,
+        )),
+    }
+}
+
+fn item_vertices(item: &Item, renderer: &Arc<Renderer>) -> Vec<Vertex> {
+    let copies = render_amount(item.stack.count);
+    let mut verts = vec![];
+    match shape(item, renderer) {
+        Shape::Icon(tex) => {
+            for i in 0..copies {
+                let z = i as f32 / 24.0 - 1.0 / 64.0;
+                let face = Some(tex.clone());
+                model::append_box(
+                    &mut verts,
+                    -0.25,
+                    0.0,
+                    z,
+                    0.5,
+                    0.5,
+                    1.0 / 32.0,
+                    [None, None, face.clone(), face, None, None],
+                );
+            }
+        }
+        Shape::Cube(tex) => {
+            for offset in &BLOCK_OFFSETS[..copies] {
+                model::append_box(
+                    &mut verts,
+                    offset[0] - 0.125,
+                    offset[1],
+                    offset[2] - 0.125,
+                    0.25,
+                    0.25,
+                    0.25,
+                    [(); 6].map(|_| Some(tex.clone())),
+                );
+            }
+        }
+    }
+    verts
+}
+
+/// The item as it's held in a hand at `hand`, pointing forward.
+pub fn held_item_vertices(
+    item: &Item,
+    hand: Vector3<f32>,
+    renderer: &Arc<Renderer>,
+) -> Vec<Vertex> {
+    let mut verts = vec![];
+    let grip = match shape(item, renderer) {
+        Shape::Icon(tex) => {
+            let face = Some(tex);
+            model::append_box(
+                &mut verts,
+                0.0,
+                0.0,
+                -1.0 / 64.0,
+                10.0 / 16.0,
+                10.0 / 16.0,
+                1.0 / 32.0,
+                [None, None, face.clone(), face, None, None],
+            );
+            // Held near the bottom left corner, where tools have their handle
+            Vector3::new(3.0 / 16.0, 3.0 / 16.0, 0.0)
+        }
+        Shape::Cube(tex) => {
+            model::append_box(
+                &mut verts,
+                -0.125,
+                -0.125,
+                -0.125,
+                0.25,
+                0.25,
+                0.25,
+                [(); 6].map(|_| Some(tex.clone())),
+            );
+            Vector3::new(0.0, 0.0, 0.0)
+        }
+    };
+    // Turned so the side of the icon faces outwards and its right edge
+    // points forward
+    let matrix = Matrix4::from_translation(hand)
+        * Matrix4::from_angle_y(Rad(std::f32::consts::PI / 2.0))
+        * Matrix4::from_translation(-grip);
+    for vert in &mut verts {
+        let pos = matrix.transform_point(Point3::new(vert.x, vert.y, vert.z));
+        vert.x = pos.x;
+        vert.y = pos.y;
+        vert.z = pos.z;
+    }
+    verts
+}
+
+pub fn update_item_models(
+    game_info: Res<GameInfo>,
+    renderer: Res<RendererResource>,
+    mut query: Query<(&mut ItemModel, &Position, &Light)>,
+) {
+    let renderer = &renderer.0;
+    let mut models = renderer.models.lock();
+    for (mut item_model, position, light) in query.iter_mut() {
+        item_model.age += game_info.delta / 3.0;
+        let handle = match &item_model.model {
+            Some(handle) => handle,
+            None => continue,
+        };
+        let mdl = models.get_model(handle).unwrap();
+        mdl.block_light = light.block_light;
+        mdl.sky_light = light.sky_light;
+
+        let bob = (item_model.age / 10.0 + item_model.bob_offset).sin() * 0.1 + 0.1;
+        let spin = item_model.age / 20.0 + item_model.bob_offset;
+        mdl.matrix[0] = Matrix4::from(Decomposed {
+            scale: 1.0,
+            rot: Quaternion::from_angle_y(Rad(spin as f32)),
+            disp: Vector3::new(
+                position.position.x as f32,
+                -(position.position.y + bob) as f32,
+                position.position.z as f32,
+            ),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bigger_stacks_draw_more_copies() {
+        assert_eq!(render_amount(1), 1);
+        assert_eq!(render_amount(2), 2);
+        assert_eq!(render_amount(17), 3);
+        assert_eq!(render_amount(33), 4);
+        assert_eq!(render_amount(64), 5);
+        assert!(render_amount(64) <= BLOCK_OFFSETS.len());
+    }
+}
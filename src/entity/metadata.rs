@@ -0,0 +1,544 @@
+use crate::entity::EntityType;
+use crate::inventory::material::versions::to_material;
+use crate::inventory::Item;
+use crate::types::metadata::{Metadata, PoseData, Value};
+use bevy_ecs::prelude::*;
+use bevy_ecs::world::EntityWorldMut;
+use leafish_protocol::format::Component as TextComponent;
+use shared::Version;
+
+/// The state an entity's flags byte describes.
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct EntityFlags {
+    pub on_fire: bool,
+    pub sneaking: bool,
+    pub sprinting: bool,
+    pub swimming: bool,
+    pub invisible: bool,
+    pub glowing: bool,
+    pub flying_with_elytra: bool,
+}
+
+impl EntityFlags {
+    pub fn from_bits(bits: u8, version: Version) -> Self {
+        Self {
+            on_fire: bits & 0x01 != 0,
+            sneaking: bits & 0x02 != 0,
+            sprinting: bits & 0x08 != 0,
+            // Used for eating, drinking and blocking before 1.13
+            swimming: version >= Version::V1_13 && bits & 0x10 != 0,
+            invisible: bits & 0x20 != 0,
+            glowing: bits & 0x40 != 0,
+            flying_with_elytra: bits & 0x80 != 0,
+        }
+    }
+}
+
+/// How an entity holds its body. Before 1.14 this is derived from the flags.
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Pose {
+    #[default]
+    Standing,
+    FallFlying,
+    Sleeping,
+    Swimming,
+    SpinAttack,
+    Sneaking,
+    Dying,
+}
+
+impl Pose {
+    fn from_flags(flags: &EntityFlags) -> Pose {
+        if flags.flying_with_elytra {
+            Pose::FallFlying
+        } else if flags.swimming {
+            Pose::Swimming
+        } else if flags.sneaking {
+            Pose::Sneaking
+        } else {
+            Pose::Standing
+        }
+    }
+}
+
+impl From<PoseData> for Pose {
+    fn from(pose: PoseData) -> Self {
+        match pose {
+            PoseData::Standing => Pose::Standing,
+            PoseData::FallFlying => Pose::FallFlying,
+            PoseData::Sleeping => Pose::Sleeping,
+            PoseData::Swimming => Pose::Swimming,
+            PoseData::SpinAttack => Pose::SpinAttack,
+            PoseData::Sneaking => Pose::Sneaking,
+            PoseData::Dying => Pose::Dying,
+        }
+    }
+}
+
+#[derive(Component, Clone, Debug, Default)]
+pub struct CustomName {
+    pub name: Option<TextComponent>,
+    /// Whether the name tag shows without looking at the entity.
+    pub visible: bool,
+}
+
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Baby(pub bool);
+
+/// The size of slimes and magma cubes, 1 being the smallest.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Size(pub i32);
+
+/// The stack a dropped item entity shows.
+#[derive(Component, Clone, Debug)]
+pub struct ItemStack(pub Option<Item>);
+
+/// Whether the paddles of a boat are rowing.
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Paddles {
+    pub left: bool,
+    pub right: bool,
+}
+
+/// Whether a horse wears a saddle, which lets its rider steer it.
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Saddled(pub bool);
+
+/// What a metadata entry means.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Field {
+    Flags,
+    CustomName,
+    CustomNameVisible,
+    Pose,
+    /// Whether the entity is a baby.
+    Baby,
+    /// The age of an animal, negative while it's a baby.
+    Age,
+    Size,
+    /// The stack of a dropped item.
+    Item,
+    LeftPaddle,
+    RightPaddle,
+    /// The flags of a horse, including whether it's saddled.
+    HorseFlags,
+}
+
+/// The metadata indices the entity type uses in the version, and what they
+/// mean. Only the ones the client makes use of are listed.
+pub fn schema(entity_type: EntityType, version: Version) -> Vec<(i32, Field)> {
+    let mut fields = vec![(0, Field::Flags)];
+    if version <= Version::V1_7 {
+        // Only living entities could be named
+        if is_living(entity_type) {
+            fields.push((10, Field::CustomName));
+            fields.push((11, Field::CustomNameVisible));
+        }
+    } else {
+        fields.push((2, Field::CustomName));
+        fields.push((3, Field::CustomNameVisible));
+    }
+    if version >= Version::V1_14 {
+        fields.push((6, Field::Pose));
+    }
+
+    if entity_type == EntityType::DroppedItem {
+        let index = match version {
+            Version::V1_7 | Version::V1_8 => 10,
+            Version::V1_9 => 5,
+            Version::V1_10
+            | Version::V1_11
+            | Version::V1_12
+            | Version::V1_13
+            | Version::V1_13_2 => 6,
+            Version::V1_14 | Version::V1_15 | Version::V1_16 | Version::V1_16_2 => 7,
+            Version::V1_17 | Version::V1_18 | Version::V1_19 | Version::New => 8,
+            Version::Other | Version::Old => return fields,
+        };
+        fields.push((index, Field::Item));
+        return fields;
+    }
+
+    if entity_type == EntityType::Boat {
+        // The first index after the fields every entity has, boats only
+        // show their paddles since 1.9
+        let base = match version {
+            Version::V1_9 => 5,
+            Version::V1_10
+            | Version::V1_11
+            | Version::V1_12
+            | Version::V1_13
+            | Version::V1_13_2 => 6,
+            Version::V1_14 | Version::V1_15 | Version::V1_16 | Version::V1_16_2 => 7,
+            Version::V1_17 | Version::V1_18 | Version::V1_19 | Version::New => 8,
+            _ => return fields,
+        };
+        fields.push((base + 4, Field::LeftPaddle));
+        fields.push((base + 5, Field::RightPaddle));
+        return fields;
+    }
+
+    // The first index after the fields every mob has
+    let mob = match version {
+        Version::V1_7 | Version::V1_8 => 12,
+        Version::V1_9 => 11,
+        Version::V1_10 | Version::V1_11 | Version::V1_12 | Version::V1_13 | Version::V1_13_2 => 12,
+        Version::V1_14 => 14,
+        Version::V1_15 | Version::V1_16 | Version::V1_16_2 => 15,
+        Version::V1_17 | Version::V1_18 | Version::V1_19 | Version::New => 16,
+        Version::Other | Version::Old => return fields,
+    };
+    match entity_type {
+        EntityType::Slime | EntityType::MagmaCube => {
+            let index = if version <= Version::V1_8 { 16 } else { mob };
+            fields.push((index, Field::Size));
+        }
+        _ if is_zombie(entity_type) => fields.push((mob, Field::Baby)),
+        _ if is_ageable(entity_type) => {
+            let field = if version <= Version::V1_8 {
+                Field::Age
+            } else {
+                Field::Baby
+            };
+            fields.push((mob, field));
+        }
+        _ => {}
+    }
+    if is_horse(entity_type) {
+        // Right after the age, but an int further back before 1.9
+        let index = if version <= Version::V1_8 {
+            16
+        } else {
+            mob + 1
+        };
+        fields.push((index, Field::HorseFlags));
+    }
+    fields
+}
+
+/// Updates the components of the entity from the metadata the server sent.
+/// Components are only inserted for the entries sent, which are usually the
+/// ones that changed.
+pub fn apply(entity: &mut EntityWorldMut, version: Version, metadata: &Metadata) {
+    let entity_type = match entity.get::<EntityType>() {
+        Some(entity_type) => *entity_type,
+        None => return,
+    };
+    let schema = schema(entity_type, version);
+    // Both parts of the name are kept in one component, which only changes
+    // when one of them was sent, the paddles too
+    let mut name = None;
+    let mut paddles = None;
+    for (index, value) in metadata.iter() {
+        let field = match schema.iter().find(|field| field.0 == index) {
+            Some(field) => field.1,
+            None => continue,
+        };
+        match field {
+            Field::Flags => {
+                if let Some(bits) = as_int(value) {
+                    let flags = EntityFlags::from_bits(bits as u8, version);
+                    if version < Version::V1_14 {
+                        entity.insert(Pose::from_flags(&flags));
+                    }
+                    entity.insert(flags);
+                }
+            }
+            Field::CustomName => {
+                let text = match value {
+                    Value::String(text) if text.is_empty() => None,
+                    Value::String(text) => Some(TextComponent::from_str(text)),
+                    Value::FormatComponent(text) => Some(text.clone()),
+                    Value::OptionalFormatComponent(text) => text.data.first().cloned(),
+                    _ => continue,
+                };
+                name.get_or_insert_with(|| current_name(entity)).name = text;
+            }
+            Field::CustomNameVisible => {
+                if let Some(visible) = as_int(value) {
+                    name.get_or_insert_with(|| current_name(entity)).visible = visible != 0;
+                }
+            }
+            Field::Pose => {
+                if let Value::Pose(pose) = value {
+                    entity.insert(Pose::from(*pose));
+                }
+            }
+            Field::Baby => {
+                if let Some(baby) = as_int(value) {
+                    entity.insert(Baby(baby != 0));
+                }
+            }
+            Field::Age => {
+                if let Some(age) = as_int(value) {
+                    entity.insert(Baby(age < 0));
+                }
+            }
+            Field::Size => {
+                if let Some(size) = as_int(value) {
+                    entity.insert(Size(size.max(1)));
+                }
+            }
+            Field::Item => {
+                if let Value::OptionalItemStack(stack) = value {
+                    let item = stack.clone().map(|stack| Item {
+                        material: to_material(
+                            stack.id as u16,
+                            Some(stack.damage.unwrap_or(0)),
+                            None,
+                            version,
+                        ),
+                        stack,
+                    });
+                    entity.insert(ItemStack(item));
+                }
+            }
+            Field::HorseFlags => {
+                if let Some(bits) = as_int(value) {
+                    entity.insert(Saddled(bits & 0x04 != 0));
+                }
+            }
+            Field::LeftPaddle | Field::RightPaddle => {
+                if let Some(rowing) = as_int(value) {
+                    let paddles = paddles.get_or_insert_with(|| {
+                        entity.get::<Paddles>().copied().unwrap_or_default()
+                    });
+                    if field == Field::LeftPaddle {
+                        paddles.left = rowing != 0;
+                    } else {
+                        paddles.right = rowing != 0;
+                    }
+                }
+            }
+        }
+    }
+    if let Some(name) = name {
+        entity.insert(name);
+    }
+    if let Some(paddles) = paddles {
+        entity.insert(paddles);
+    }
+}
+
+fn current_name(entity: &EntityWorldMut) -> CustomName {
+    entity.get::<CustomName>().cloned().unwrap_or_default()
+}
+
+fn as_int(value: &Value) -> Option<i32> {
+    match *value {
+        Value::Byte(val) => Some(val as i32),
+        Value::Short(val) => Some(val as i32),
+        Value::Int(val) => Some(val),
+        Value::Bool(val) => Some(val as i32),
+        _ => None,
+    }
+}
+
+fn is_living(entity_type: EntityType) -> bool {
+    !matches!(
+        entity_type,
+        EntityType::DroppedItem
+            | EntityType::ExperienceOrb
+            | EntityType::LeashHitch
+            | EntityType::Painting
+            | EntityType::Arrow
+            | EntityType::Snowball
+            | EntityType::Fireball
+            | EntityType::SmallFireball
+            | EntityType::EnderPearl
+            | EntityType::EnderSignal
+            | EntityType::ThrownExpBottle
+            | EntityType::ItemFrame
+            | EntityType::WitherSkull
+            | EntityType::PrimedTnt
+            | EntityType::FallingBlock
+            | EntityType::Firework
+            | EntityType::TippedArrow
+            | EntityType::SpectralArrow
+            | EntityType::ShulkerBullet
+            | EntityType::DragonFireball
+            | EntityType::MinecartCommand
+            | EntityType::Boat
+            | EntityType::Minecart
+            | EntityType::MinecartChest
+            | EntityType::MinecartFurnace
+            | EntityType::MinecartTnt
+            | EntityType::MinecartHopper
+            | EntityType::MinecartMobSpawner
+            | EntityType::EnderCrystal
+            | EntityType::SplashPotion
+            | EntityType::LingeringPotion
+            | EntityType::AreaEffectCloud
+            | EntityType::Egg
+            | EntityType::FishingHook
+            | EntityType::Lightning
+            | EntityType::Weather
+            | EntityType::ComplexPart
+            | EntityType::EvokerFangs
+            | EntityType::LlamaSpit
+            | EntityType::Trident
+            | EntityType::Unknown
+    )
+}
+
+/// Zombies keep whether they're a baby at the same index as animals, but
+/// as a flag rather than an age.
+fn is_zombie(entity_type: EntityType) -> bool {
+    matches!(
+        entity_type,
+        EntityType::Zombie
+            | EntityType::PigZombie
+            | EntityType::ZombieVillager
+            | EntityType::Husk
+            | EntityType::Drowned
+            | EntityType::ZombifiedPiglin
+            | EntityType::Zoglin
+    )
+}
+
+/// The horses a saddle can be put on.
+pub fn is_horse(entity_type: EntityType) -> bool {
+    matches!(
+        entity_type,
+        EntityType::Horse
+            | EntityType::SkeletonHorse
+            | EntityType::ZombieHorse
+            | EntityType::Donkey
+            | EntityType::Mule
+    )
+}
+
+fn is_ageable(entity_type: EntityType) -> bool {
+    matches!(
+        entity_type,
+        EntityType::Pig
+            | EntityType::Sheep
+            | EntityType::Cow
+            | EntityType::Chicken
+            | EntityType::Wolf
+            | EntityType::MushroomCow
+            | EntityType::Ocelot
+            | EntityType::Horse
+            | EntityType::SkeletonHorse
+            | EntityType::ZombieHorse
+            | EntityType::Donkey
+            | EntityType::Mule
+            | EntityType::Llama
+            | EntityType::TraderLlama
+            | EntityType::Rabbit
+            | EntityType::PolarBear
+            | EntityType::Villager
+            | EntityType::WanderingTrader
+            | EntityType::Parrot
+            | EntityType::Turtle
+            | EntityType::Cat
+            | EntityType::Panda
+            | EntityType::Fox
+            | EntityType::Bee
+            | EntityType::Hoglin
+            | EntityType::Strider
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn field(entity_type: EntityType, version: Version, index: i32) -> Option<Field> {
+        schema(entity_type, version)
+            .into_iter()
+            .find(|field| field.0 == index)
+            .map(|field| field.1)
+    }
+
+    #[test]
+    fn indices_move_between_versions() {
+        assert_eq!(
+            field(EntityType::Zombie, Version::V1_8, 12),
+            Some(Field::Baby)
+        );
+        assert_eq!(field(EntityType::Cow, Version::V1_8, 12), Some(Field::Age));
+        assert_eq!(field(EntityType::Cow, Version::V1_9, 11), Some(Field::Baby));
+        assert_eq!(
+            field(EntityType::Slime, Version::V1_8, 16),
+            Some(Field::Size)
+        );
+        assert_eq!(
+            field(EntityType::Slime, Version::V1_12, 12),
+            Some(Field::Size)
+        );
+        assert_eq!(
+            field(EntityType::Slime, Version::V1_16_2, 15),
+            Some(Field::Size)
+        );
+        assert_eq!(
+            field(EntityType::Zombie, Version::V1_18, 16),
+            Some(Field::Baby)
+        );
+        assert_eq!(field(EntityType::Player, Version::V1_13_2, 6), None);
+        assert_eq!(
+            field(EntityType::Player, Version::V1_14, 6),
+            Some(Field::Pose)
+        );
+        assert_eq!(
+            field(EntityType::Zombie, Version::V1_7, 10),
+            Some(Field::CustomName)
+        );
+        // Not a name, only living entities could be named
+        assert_eq!(
+            field(EntityType::DroppedItem, Version::V1_7, 10),
+            Some(Field::Item)
+        );
+        assert_eq!(
+            field(EntityType::DroppedItem, Version::V1_8, 2),
+            Some(Field::CustomName)
+        );
+        assert_eq!(
+            field(EntityType::DroppedItem, Version::V1_12, 6),
+            Some(Field::Item)
+        );
+        assert_eq!(
+            field(EntityType::DroppedItem, Version::V1_16_2, 7),
+            Some(Field::Item)
+        );
+        assert_eq!(
+            field(EntityType::Boat, Version::V1_9, 9),
+            Some(Field::LeftPaddle)
+        );
+        assert_eq!(
+            field(EntityType::Boat, Version::V1_12, 11),
+            Some(Field::RightPaddle)
+        );
+        assert_eq!(
+            field(EntityType::Boat, Version::V1_19, 12),
+            Some(Field::LeftPaddle)
+        );
+        assert_eq!(field(EntityType::Boat, Version::V1_8, 9), None);
+        assert_eq!(
+            field(EntityType::Horse, Version::V1_8, 16),
+            Some(Field::HorseFlags)
+        );
+        assert_eq!(
+            field(EntityType::Donkey, Version::V1_12, 13),
+            Some(Field::HorseFlags)
+        );
+        assert_eq!(
+            field(EntityType::ZombieHorse, Version::V1_16_2, 16),
+            Some(Field::HorseFlags)
+        );
+        assert_eq!(
+            field(EntityType::Horse, Version::V1_16_2, 15),
+            Some(Field::Baby)
+        );
+    }
+
+    #[test]
+    fn flags_and_pose() {
+        let flags = EntityFlags::from_bits(0x02 | 0x20, Version::V1_12);
+        assert!(flags.sneaking && flags.invisible && !flags.on_fire);
+        assert_eq!(Pose::from_flags(&flags), Pose::Sneaking);
+        // Eating, not swimming
+        assert!(!EntityFlags::from_bits(0x10, Version::V1_8).swimming);
+        assert!(EntityFlags::from_bits(0x10, Version::V1_13_2).swimming);
+    }
+}
@@ -0,0 +1,852 @@
+use super::equipment::{self, BodyPart, Equipment, Slot};
+use super::item::held_item_vertices;
+use super::metadata::{Baby, CustomName, EntityFlags, Paddles, Size};
+use super::player_like::{self, compute_name_tag};
+use super::{Bounds, GameInfo, HeadYaw, Light, Position, Rotation};
+use crate::ecs::SystemExecStage;
+use crate::render::model::{self, Vertex};
+use crate::render::{Renderer, Texture};
+use crate::resources;
+use crate::server::RendererResource;
+use bevy_ecs::prelude::*;
+use cgmath::{Decomposed, Matrix4, Point3, Quaternion, Rad, Rotation3, Vector3};
+use collision::Aabb3;
+use leafish_protocol::format::Component;
+use log::error;
+use parking_lot::RwLock;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::f32::consts::PI;
+use std::sync::Arc;
+
+/// The most parts a model can have, one more is needed for the name tag.
+/// Limited by the size of the matrix array in the model shader.
+pub const MAX_PARTS: usize = 15;
+
+/// How long a mob flashes red after being hurt, in ticks.
+const HURT_TICKS: f64 = 10.0;
+const HURT_COLOR: [f32; 4] = [1.0, 0.6, 0.6, 1.0];
+
+pub fn add_systems(render_sched: &mut Schedule) {
+    render_sched.add_systems(
+        (added_mob_model, light_flames, animate, update_mob_models)
+            .chain()
+            .in_set(SystemExecStage::Render)
+            .after(SystemExecStage::Normal),
+    );
+}
+
+/// A mob model loaded from `assets/leafish/models/entity/<name>.json`.
+///
+/// Positions are in pixels with y pointing down and the feet at y 24,
+/// rotations are in degrees, the same way the vanilla models are written:
+///
+/// ```json
+/// {
+///   "parent": "zombie",
+///   "texture": "minecraft:entity/zombie/husk",
+///   "texture_size": [64, 64],
+///   "scale": 1.0625,
+///   "width": 0.6,
+///   "height": 1.95,
+///   "equipment": true,
+///   "parts": [{
+///     "name": "right_leg",
+///     "pivot": [-1.9, 12, 0],
+///     "rotation": [0, 0, 0],
+///     "look": false,
+///     "walk": { "axis": "x", "speed": 1, "phase": 0, "amplitude": 1.4 },
+///     "cubes": [{ "uv": [0, 16], "from": [-2, 0, -2], "size": [4, 12, 4], "inflate": 0 }]
+///   }]
+/// }
+/// ```
+///
+/// Everything the parent defines can be left out, the parts are replaced as
+/// a whole. `texture_size` defaults to the size of the texture. Mobs with
+/// `equipment` wear armor around and hold items in the parts named like
+/// the ones of a player, `head`, `body`, `right_arm` and so on. `yaw` turns
+/// the whole model, for models not facing the way the entity does, and
+/// parts with `"row": "left"` or `"right"` swing like the paddles of a
+/// boat.
+#[derive(Clone, Debug)]
+pub struct Definition {
+    pub texture: String,
+    pub texture_size: Option<[f32; 2]>,
+    pub scale: f32,
+    /// The size of the hitbox in blocks, before scaling.
+    pub width: f32,
+    pub height: f32,
+    /// Whether the mob is drawn with the armor and items it has.
+    pub equipment: bool,
+    /// How far the model is turned from where the entity faces, in radians.
+    pub yaw: f32,
+    pub parts: Vec<Part>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Part {
+    pub name: String,
+    /// Where the part rotates around, in model space.
+    pub pivot: Vector3<f32>,
+    /// The resting rotation around the x, y and z axis in radians.
+    pub rotation: [f32; 3],
+    /// Whether the part follows where the mob looks.
+    pub look: bool,
+    pub walk: Option<Walk>,
+    /// The paddle the part rows with, 0 on the left and 1 on the right.
+    pub row: Option<usize>,
+    pub cubes: Vec<Cube>,
+}
+
+/// How a part swings while the mob walks.
+#[derive(Clone, Copy, Debug)]
+pub struct Walk {
+    pub axis: usize,
+    pub speed: f32,
+    /// The offset into the swing, in multiples of pi.
+    pub phase: f32,
+    /// The largest angle in radians.
+    pub amplitude: f32,
+}
+
+impl Walk {
+    fn angle(&self, limb_swing: f32, limb_swing_amount: f32) -> f32 {
+        (limb_swing * 0.6662 * self.speed + self.phase * PI).cos()
+            * self.amplitude
+            * limb_swing_amount
+    }
+}
+
+/// How a paddle is turned around x and y, `time` into rowing with it.
+fn row_angles(side: usize, time: f32) -> (f32, f32) {
+    let lerp = |from: f32, to: f32, t: f32| from + (to - from) * t;
+    let x = lerp(-PI / 3.0, -PI / 12.0, ((-time).sin() + 1.0) / 2.0);
+    let y = lerp(-PI / 4.0, PI / 4.0, ((1.0 - time).sin() + 1.0) / 2.0);
+    if side == 1 {
+        (x, PI - y)
+    } else {
+        (x, y)
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Cube {
+    /// The top left corner of the cube's texture layout, in pixels.
+    pub uv: [f32; 2],
+    pub from: [f32; 3],
+    pub size: [f32; 3],
+    /// How far the cube grows in every direction without changing its
+    /// texture, used for hats and other overlays.
+    pub inflate: f32,
+}
+
+/// Parses a definition, filling in what it leaves out from the parent.
+pub fn parse(value: &Value, parent: Option<&Definition>) -> Result<Definition, String> {
+    let mut definition = match parent {
+        Some(parent) => parent.clone(),
+        None => Definition {
+            texture: String::new(),
+            texture_size: None,
+            scale: 1.0,
+            width: 0.6,
+            height: 1.8,
+            equipment: false,
+            yaw: 0.0,
+            parts: vec![],
+        },
+    };
+    if let Some(texture) = value.get("texture").and_then(Value::as_str) {
+        definition.texture = texture.to_owned();
+    }
+    if let Some(size) = value.get("texture_size") {
+        definition.texture_size = Some(floats(size, "texture_size")?);
+    }
+    if let Some(scale) = value.get("scale").and_then(Value::as_f64) {
+        definition.scale = scale as f32;
+    }
+    if let Some(width) = value.get("width").and_then(Value::as_f64) {
+        definition.width = width as f32;
+    }
+    if let Some(height) = value.get("height").and_then(Value::as_f64) {
+        definition.height = height as f32;
+    }
+    if let Some(equipment) = value.get("equipment").and_then(Value::as_bool) {
+        definition.equipment = equipment;
+    }
+    if let Some(yaw) = value.get("yaw").and_then(Value::as_f64) {
+        definition.yaw = (yaw as f32).to_radians();
+    }
+    if let Some(parts) = value.get("parts") {
+        let parts = parts.as_array().ok_or("parts isn't a list")?;
+        definition.parts = parts.iter().map(parse_part).collect::<Result<_, _>>()?;
+    }
+
+    if definition.texture.is_empty() {
+        return Err("no texture".to_owned());
+    }
+    if definition.parts.is_empty() || definition.parts.len() > MAX_PARTS {
+        return Err(format!(
+            "{} parts, between 1 and {} are allowed",
+            definition.parts.len(),
+            MAX_PARTS
+        ));
+    }
+    for (i, part) in definition.parts.iter().enumerate() {
+        if definition.parts[..i]
+            .iter()
+            .any(|other| other.name == part.name)
+        {
+            return Err(format!("part {} is defined twice", part.name));
+        }
+    }
+    Ok(definition)
+}
+
+fn parse_part(value: &Value) -> Result<Part, String> {
+    let name = value
+        .get("name")
+        .and_then(Value::as_str)
+        .ok_or("part without a name")?
+        .to_owned();
+    let pivot: [f32; 3] = match value.get("pivot") {
+        Some(pivot) => floats(pivot, "pivot")?,
+        None => [0.0; 3],
+    };
+    let rotation: [f32; 3] = match value.get("rotation") {
+        Some(rotation) => floats(rotation, "rotation")?,
+        None => [0.0; 3],
+    };
+    let walk = match value.get("walk") {
+        Some(walk) => Some(Walk {
+            axis: match walk.get("axis").and_then(Value::as_str).unwrap_or("x") {
+                "x" => 0,
+                "y" => 1,
+                "z" => 2,
+                axis => return Err(format!("{}: unknown axis {}", name, axis)),
+            },
+            speed: walk.get("speed").and_then(Value::as_f64).unwrap_or(1.0) as f32,
+            phase: walk.get("phase").and_then(Value::as_f64).unwrap_or(0.0) as f32,
+            amplitude: walk.get("amplitude").and_then(Value::as_f64).unwrap_or(1.4) as f32,
+        }),
+        None => None,
+    };
+    let row = match value.get("row").and_then(Value::as_str) {
+        Some("left") => Some(0),
+        Some("right") => Some(1),
+        Some(side) => return Err(format!("{}: unknown paddle {}", name, side)),
+        None => None,
+    };
+    let cubes = value
+        .get("cubes")
+        .and_then(Value::as_array)
+        .ok_or_else(|| format!("{}: no cubes", name))?
+        .iter()
+        .map(|cube| {
+            Ok(Cube {
+                uv: floats(cube.get("uv").unwrap_or(&Value::Null), "uv")?,
+                from: floats(cube.get("from").unwrap_or(&Value::Null), "from")?,
+                size: floats(cube.get("size").unwrap_or(&Value::Null), "size")?,
+                inflate: cube.get("inflate").and_then(Value::as_f64).unwrap_or(0.0) as f32,
+            })
+        })
+        .collect::<Result<_, String>>()
+        .map_err(|err| format!("{}: {}", name, err))?;
+    Ok(Part {
+        // The model is drawn mirrored on the x axis with the feet at 0
+        pivot: Vector3::new(-pivot[0] / 16.0, (pivot[1] - 24.0) / 16.0, pivot[2] / 16.0),
+        rotation: rotation.map(f32::to_radians),
+        look: value.get("look").and_then(Value::as_bool).unwrap_or(false),
+        walk,
+        row,
+        cubes,
+        name,
+    })
+}
+
+fn floats<const N: usize>(value: &Value, field: &str) -> Result<[f32; N], String> {
+    let mut ret = [0.0; N];
+    match value.as_array() {
+        Some(values) if values.len() == N => {
+            for (slot, value) in ret.iter_mut().zip(values) {
+                *slot = value
+                    .as_f64()
+                    .ok_or_else(|| format!("{} isn't a number", field))?
+                    as f32;
+            }
+            Ok(ret)
+        }
+        _ => Err(format!("{} needs {} numbers", field, N)),
+    }
+}
+
+/// The mob definitions in use, loaded the first time they're needed.
+#[derive(Resource)]
+pub struct MobDefinitions {
+    resources: Arc<RwLock<resources::Manager>>,
+    loaded: HashMap<String, Option<Arc<Definition>>>,
+}
+
+impl MobDefinitions {
+    pub fn new(resources: Arc<RwLock<resources::Manager>>) -> Self {
+        Self {
+            resources,
+            loaded: HashMap::new(),
+        }
+    }
+
+    pub fn get(&mut self, name: &str) -> Option<Arc<Definition>> {
+        if let Some(definition) = self.loaded.get(name) {
+            return definition.clone();
+        }
+        // Stops a definition from being its own ancestor
+        self.loaded.insert(name.to_owned(), None);
+        let definition = self.load(name).map(Arc::new);
+        self.loaded.insert(name.to_owned(), definition.clone());
+        definition
+    }
+
+    fn load(&mut self, name: &str) -> Option<Definition> {
+        let file = match self
+            .resources
+            .read()
+            .open("leafish", &format!("models/entity/{}.json", name))
+        {
+            Some(file) => file,
+            None => {
+                error!("Couldn't find entity model {}", name);
+                return None;
+            }
+        };
+        let value: Value = match serde_json::from_reader(file) {
+            Ok(value) => value,
+            Err(err) => {
+                error!("Error loading entity model {}: {}", name, err);
+                return None;
+            }
+        };
+        let parent = match value.get("parent").and_then(Value::as_str) {
+            Some(parent) => Some(self.get(parent)?),
+            None => None,
+        };
+        match parse(&value, parent.as_deref()) {
+            Ok(definition) => Some(definition),
+            Err(err) => {
+                error!("Error loading entity model {}: {}", name, err);
+                None
+            }
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct MobModel {
+    name: &'static str,
+    model: Option<model::ModelHandle>,
+    /// Drawn over enchanted armor.
+    glint: Option<model::ModelHandle>,
+    /// Drawn around the mob while it burns.
+    flames: Option<model::ModelHandle>,
+    definition: Option<Arc<Definition>>,
+    display_name: Option<Component>,
+}
+
+impl MobModel {
+    pub fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            model: None,
+            glint: None,
+            flames: None,
+            definition: None,
+            display_name: None,
+        }
+    }
+}
+
+/// The state of the animations every mob shares.
+#[derive(Component, Debug)]
+pub struct Animation {
+    /// How far the limbs have swung, grows faster the faster the mob walks.
+    pub limb_swing: f64,
+    /// How wide the limbs swing, from 0 standing still to 1 walking.
+    pub limb_swing_amount: f64,
+    /// The ticks left of being shown as hurt.
+    pub hurt_time: f64,
+    /// How long the mob has been around, in ticks.
+    pub age: f64,
+    /// How far into rowing the left and right paddle are.
+    pub rowing: [f64; 2],
+    last_position: Vector3<f64>,
+}
+
+impl Animation {
+    pub fn new(position: Vector3<f64>) -> Self {
+        Self {
+            limb_swing: 0.0,
+            limb_swing_amount: 0.0,
+            hurt_time: 0.0,
+            age: 0.0,
+            rowing: [0.0; 2],
+            last_position: position,
+        }
+    }
+
+    pub fn hurt(&mut self) {
+        self.hurt_time = HURT_TICKS;
+    }
+}
+
+fn size_scale(baby: Option<&Baby>, size: Option<&Size>) -> f32 {
+    let baby = if baby.is_some_and(|baby| baby.0) {
+        0.5
+    } else {
+        1.0
+    };
+    baby * size.map_or(1, |size| size.0) as f32
+}
+
+#[allow(clippy::type_complexity)]
+pub fn added_mob_model(
+    renderer: Res<RendererResource>,
+    mut definitions: ResMut<MobDefinitions>,
+    mut commands: Commands,
+    mut query: Query<
+        (
+            Entity,
+            &mut MobModel,
+            Option<&CustomName>,
+            Option<&Baby>,
+            Option<&Size>,
+            Option<&Equipment>,
+        ),
+        Or<(
+            Added<MobModel>,
+            Changed<CustomName>,
+            Changed<Baby>,
+            Changed<Size>,
+            Changed<Equipment>,
+        )>,
+    >,
+) {
+    let renderer = &renderer.0;
+    for (entity, mut mob_model, custom_name, baby, size, equipment) in query.iter_mut() {
+        mob_model.display_name = custom_name
+            .filter(|custom_name| custom_name.visible)
+            .and_then(|custom_name| custom_name.name.clone());
+        // The old model has to be gone before the models are locked
+        mob_model.model = None;
+        mob_model.glint = None;
+        mob_model.flames = None;
+        let definition = match definitions.get(mob_model.name) {
+            Some(definition) => definition,
+            None => continue,
+        };
+
+        let scale = (definition.scale * size_scale(baby, size)) as f64;
+        let half_width = definition.width as f64 * scale / 2.0;
+        commands.entity(entity).insert(Bounds::new(Aabb3::new(
+            Point3::new(-half_width, 0.0, -half_width),
+            Point3::new(half_width, definition.height as f64 * scale, half_width),
+        )));
+
+        let tex = Renderer::get_texture(renderer.get_textures_ref(), &definition.texture);
+        let texture_size = definition
+            .texture_size
+            .unwrap_or([tex.get_width() as f32, tex.get_height() as f32]);
+        let mut parts: Vec<Vec<Vertex>> = definition
+            .parts
+            .iter()
+            .map(|part| part_vertices(part, &tex, texture_size))
+            .collect();
+        let mut glint = vec![vec![]; parts.len()];
+        if let Some(equipment) = equipment.filter(|_| definition.equipment) {
+            for (i, part) in definition.parts.iter().enumerate() {
+                if let Some(body_part) = BodyPart::by_name(&part.name) {
+                    let (armor, armor_glint) = equipment::armor_vertices(
+                        equipment,
+                        body_part,
+                        body_part.humanoid_from(),
+                        renderer,
+                    );
+                    parts[i].extend(armor);
+                    glint[i] = armor_glint;
+                }
+                let held = match BodyPart::by_name(&part.name) {
+                    Some(BodyPart::RightArm) => equipment.get(Slot::MainHand),
+                    Some(BodyPart::LeftArm) => equipment.get(Slot::OffHand),
+                    _ => None,
+                };
+                if let Some(item) = held {
+                    parts[i].extend(held_item_vertices(item, hand(part), renderer));
+                }
+            }
+        }
+        parts.push(match &mob_model.display_name {
+            Some(display_name) => compute_name_tag(display_name, renderer.clone()),
+            None => vec![],
+        });
+
+        mob_model.model = Some(renderer.clone().models.lock().create_model(
+            model::DEFAULT,
+            parts,
+            renderer.clone(),
+        ));
+        mob_model.glint = equipment::create_glint(glint, renderer);
+        mob_model.definition = Some(definition);
+    }
+}
+
+/// Lights the flames of mobs that start burning, and puts them out once
+/// they stop. Mobs that changed size get them again.
+#[allow(clippy::type_complexity)]
+pub fn light_flames(
+    renderer: Res<RendererResource>,
+    mut query: Query<
+        (&mut MobModel, &EntityFlags, Option<&Baby>, Option<&Size>),
+        Or<(Changed<MobModel>, Changed<EntityFlags>)>,
+    >,
+) {
+    for (mut mob_model, flags, baby, size) in query.iter_mut() {
+        if !flags.on_fire {
+            if mob_model.flames.is_some() {
+                mob_model.flames = None;
+            }
+            continue;
+        }
+        let definition = match &mob_model.definition {
+            Some(definition) if mob_model.flames.is_none() => definition.clone(),
+            _ => continue,
+        };
+        let scale = definition.scale * size_scale(baby, size);
+        mob_model.flames = Some(player_like::create_flames(
+            definition.width * scale,
+            definition.height * scale,
+            &renderer.0,
+        ));
+    }
+}
+
+fn part_vertices(part: &Part, tex: &Texture, texture_size: [f32; 2]) -> Vec<Vertex> {
+    let mut verts = vec![];
+    for cube in &part.cubes {
+        append_cube(&mut verts, cube, tex, texture_size);
+    }
+    verts
+}
+
+/// Adds a cube written the way the vanilla models are, relative to the
+/// pivot of its part.
+pub fn append_cube(verts: &mut Vec<Vertex>, cube: &Cube, tex: &Texture, texture_size: [f32; 2]) {
+    let [x, y, z] = cube.from;
+    let [w, h, d] = cube.size;
+    let inflate = cube.inflate;
+    model::append_box(
+        verts,
+        -(x + w + inflate) / 16.0,
+        -(y + h + inflate) / 16.0,
+        (z - inflate) / 16.0,
+        (w + inflate * 2.0) / 16.0,
+        (h + inflate * 2.0) / 16.0,
+        (d + inflate * 2.0) / 16.0,
+        cube_textures(cube, tex, texture_size),
+    );
+}
+
+/// Where an arm holds items, near the bottom of its first cube.
+fn hand(part: &Part) -> Vector3<f32> {
+    let cube = match part.cubes.first() {
+        Some(cube) => cube,
+        None => return Vector3::new(0.0, 0.0, 0.0),
+    };
+    let [x, y, z] = cube.from;
+    let [w, h, d] = cube.size;
+    Vector3::new(
+        -(x + w / 2.0) / 16.0,
+        -(y + h - 2.0) / 16.0,
+        (z + d / 2.0 - 1.0) / 16.0,
+    )
+}
+
+/// Cuts the faces of the cube out of the texture, laid out the way vanilla
+/// lays out boxes.
+fn cube_textures(cube: &Cube, tex: &Texture, texture_size: [f32; 2]) -> [Option<Texture>; 6] {
+    let [u, v] = cube.uv;
+    let [w, h, d] = cube.size;
+    let rel = |x: f32, y: f32, width: f32, height: f32| {
+        Some(tex.relative(
+            x / texture_size[0],
+            y / texture_size[1],
+            width / texture_size[0],
+            height / texture_size[1],
+        ))
+    };
+    [
+        rel(u + d + w, v, w, d),           // Down
+        rel(u + d, v, w, d),               // Up
+        rel(u + d, v + d, w, h),           // North
+        rel(u + d * 2.0 + w, v + d, w, h), // South
+        rel(u + d + w, v + d, d, h),       // West
+        rel(u, v + d, d, h),               // East
+    ]
+}
+
+pub fn animate(
+    game_info: Res<GameInfo>,
+    mut query: Query<(&mut Animation, &Position, Option<&Paddles>)>,
+) {
+    // The animations are written in ticks
+    let ticks = game_info.delta / 3.0;
+    if ticks <= 0.0 {
+        return;
+    }
+    for (mut animation, position, paddles) in query.iter_mut() {
+        let moved = position.position - animation.last_position;
+        animation.last_position = position.position;
+        let speed = (moved.x * moved.x + moved.z * moved.z).sqrt() / ticks;
+        let target = (speed * 4.0).min(1.0);
+        animation.limb_swing_amount +=
+            (target - animation.limb_swing_amount) * (0.4 * ticks).min(1.0);
+        animation.limb_swing += animation.limb_swing_amount * ticks;
+        animation.hurt_time = (animation.hurt_time - ticks).max(0.0);
+        animation.age += ticks;
+        let paddles = paddles.copied().unwrap_or_default();
+        for (time, rowing) in animation
+            .rowing
+            .iter_mut()
+            .zip([paddles.left, paddles.right])
+        {
+            *time = if rowing {
+                *time + std::f64::consts::FRAC_PI_8 * ticks
+            } else {
+                0.0
+            };
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+pub fn update_mob_models(
+    renderer: Res<RendererResource>,
+    query: Query<(
+        &MobModel,
+        &Animation,
+        &Position,
+        &Rotation,
+        Option<&HeadYaw>,
+        &Light,
+        Option<&Baby>,
+        Option<&Size>,
+        Option<&EntityFlags>,
+    )>,
+) {
+    use std::f64::consts::PI as PI64;
+    let renderer = &renderer.0;
+    let (camera, camera_yaw) = {
+        let camera = renderer.camera.lock();
+        (camera.pos, camera.yaw)
+    };
+    let mut models = renderer.models.lock();
+    for (mob_model, animation, position, rotation, head_yaw, light, baby, size, flags) in
+        query.iter()
+    {
+        let (handle, definition) = match (&mob_model.model, &mob_model.definition) {
+            (Some(handle), Some(definition)) => (handle, definition),
+            _ => continue,
+        };
+        player_like::update_flames(
+            &mut models,
+            mob_model.flames.as_ref(),
+            position.position,
+            camera_yaw,
+        );
+        let mdl = models.get_model(handle).unwrap();
+
+        // Glowing mobs stand out at full brightness
+        if flags.is_some_and(|flags| flags.glowing) {
+            mdl.block_light = 15.0;
+            mdl.sky_light = 15.0;
+        } else {
+            mdl.block_light = light.block_light;
+            mdl.sky_light = light.sky_light;
+        }
+
+        if flags.is_some_and(|flags| flags.invisible) {
+            for matrix in &mut mdl.matrix {
+                *matrix = Matrix4::from_scale(0.0);
+            }
+            let matrix = mdl.matrix.clone();
+            equipment::update_glint(&mut models, mob_model.glint.as_ref(), &matrix, 0.0);
+            continue;
+        }
+
+        let scale = definition.scale * size_scale(baby, size);
+        let offset = Vector3::new(
+            position.position.x as f32,
+            -position.position.y as f32,
+            position.position.z as f32,
+        );
+        let offset_matrix = Matrix4::from(Decomposed {
+            scale,
+            rot: Quaternion::from_angle_y(Rad(PI + rotation.yaw as f32 - definition.yaw)),
+            disp: offset,
+        });
+
+        // Where the head looks relative to the body
+        let head_yaw = head_yaw.map_or(0.0, |head_yaw| {
+            let yaw = (rotation.yaw - head_yaw.0).rem_euclid(PI64 * 2.0);
+            if yaw > PI64 {
+                yaw - PI64 * 2.0
+            } else {
+                yaw
+            }
+        }) as f32;
+        let limb_swing = animation.limb_swing as f32;
+        let limb_swing_amount = animation.limb_swing_amount as f32;
+        let color = if animation.hurt_time > 0.0 {
+            HURT_COLOR
+        } else {
+            [1.0; 4]
+        };
+
+        for (i, part) in definition.parts.iter().enumerate() {
+            let mut angles = part.rotation;
+            if part.look {
+                angles[0] -= rotation.pitch as f32;
+                angles[1] += head_yaw;
+            }
+            if let Some(walk) = &part.walk {
+                angles[walk.axis] += walk.angle(limb_swing, limb_swing_amount);
+            }
+            if let Some(side) = part.row {
+                let (x, y) = row_angles(side, animation.rowing[side] as f32);
+                angles[0] = x;
+                angles[1] = y;
+            }
+            // Mirroring the x axis flips the rotations around y and z
+            mdl.matrix[i] = offset_matrix
+                * Matrix4::from_translation(part.pivot)
+                * Matrix4::from(Quaternion::from_angle_z(Rad(-angles[2])))
+                * Matrix4::from(Quaternion::from_angle_y(Rad(-angles[1])))
+                * Matrix4::from(Quaternion::from_angle_x(Rad(angles[0])));
+            mdl.colors[i] = color;
+        }
+
+        if mob_model.display_name.is_some() {
+            let ang = (position.position.x - camera.x).atan2(position.position.z - camera.z) as f32;
+            mdl.matrix[definition.parts.len()] = Matrix4::from(Decomposed {
+                scale: 1.0,
+                rot: Quaternion::from_angle_y(Rad(ang)),
+                disp: offset + Vector3::new(0.0, -definition.height * scale - 0.3, 0.0),
+            });
+        }
+
+        let matrix = mdl.matrix.clone();
+        equipment::update_glint(
+            &mut models,
+            mob_model.glint.as_ref(),
+            &matrix,
+            animation.age / 5.0,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn load(name: &str) -> Definition {
+        let file =
+            leafish_resources::get_file(&format!("assets/leafish/models/entity/{}.json", name))
+                .unwrap_or_else(|| panic!("{} is missing", name));
+        let value: Value = serde_json::from_slice(file).unwrap();
+        let parent = value.get("parent").and_then(Value::as_str).map(load);
+        parse(&value, parent.as_ref()).unwrap_or_else(|err| panic!("{}: {}", name, err))
+    }
+
+    #[test]
+    fn bundled_definitions_parse() {
+        for name in [
+            "pig",
+            "cow",
+            "mooshroom",
+            "sheep",
+            "chicken",
+            "zombie",
+            "husk",
+            "drowned",
+            "giant",
+            "skeleton",
+            "stray",
+            "wither_skeleton",
+            "creeper",
+            "spider",
+            "cave_spider",
+            "enderman",
+            "slime",
+            "boat",
+            "minecart",
+            "horse",
+            "donkey",
+            "mule",
+            "skeleton_horse",
+            "zombie_horse",
+        ] {
+            load(name);
+        }
+
+        let husk = load("husk");
+        assert_eq!(husk.texture, "minecraft:entity/zombie/husk");
+        assert_eq!(husk.parts.len(), load("zombie").parts.len());
+        assert_eq!(load("spider").parts.len(), 10);
+        assert!(husk.equipment);
+        assert!(load("stray").equipment);
+        assert!(!load("pig").equipment);
+
+        let boat = load("boat");
+        assert!((boat.yaw - PI / 2.0).abs() < 1e-6);
+        let rows: Vec<_> = boat.parts.iter().filter_map(|part| part.row).collect();
+        assert_eq!(rows, [0, 1]);
+    }
+
+    #[test]
+    fn paddles_mirror_each_other() {
+        for time in [0.0, 1.0, 2.5] {
+            let (left_x, left_y) = row_angles(0, time);
+            let (right_x, right_y) = row_angles(1, time);
+            assert_eq!(left_x, right_x);
+            assert!((left_y + right_y - PI).abs() < 1e-6);
+        }
+        // Swung back and forth over a stroke
+        let (_, start) = row_angles(0, 0.0);
+        let (_, half) = row_angles(0, PI);
+        assert!((start - half).abs() > 0.5);
+    }
+
+    #[test]
+    fn parts_are_mirrored_with_the_feet_at_zero() {
+        let value = serde_json::json!({
+            "texture": "minecraft:entity/pig/pig",
+            "parts": [{
+                "name": "leg",
+                "pivot": [-3, 18, 7],
+                "rotation": [90, 0, 0],
+                "walk": { "phase": 1 },
+                "cubes": [{ "uv": [0, 16], "from": [-2, 0, -2], "size": [4, 6, 4] }]
+            }]
+        });
+        let definition = parse(&value, None).unwrap();
+        let leg = &definition.parts[0];
+        assert_eq!(leg.pivot, Vector3::new(3.0 / 16.0, -6.0 / 16.0, 7.0 / 16.0));
+        assert!((leg.rotation[0] - PI / 2.0).abs() < 1e-6);
+        let walk = leg.walk.unwrap();
+        assert_eq!(walk.axis, 0);
+        assert!((walk.angle(0.0, 1.0) + 1.4).abs() < 1e-6);
+
+        assert!(parse(&serde_json::json!({ "parts": [] }), None).is_err());
+        let duplicate = serde_json::json!({
+            "texture": "minecraft:entity/pig/pig",
+            "parts": [value["parts"][0], value["parts"][0]]
+        });
+        assert!(parse(&duplicate, None).is_err());
+    }
+}
@@ -1,23 +1,31 @@
 pub mod block_entity;
+pub mod equipment;
+pub mod experience_orb;
+pub mod item;
+pub mod metadata;
+pub mod mob;
+pub mod painting;
+pub mod physics;
 pub mod player;
+pub mod vehicle;
 
 use crate::ecs::{Manager, SystemExecStage};
-use crate::entity::slime::SlimeModel;
-use crate::entity::zombie::ZombieModel;
+use crate::entity::item::ItemModel;
+use crate::entity::mob::{Animation, MobModel};
 use crate::render::Texture;
 use crate::world::block;
 use bevy_ecs::component::Component;
 use bevy_ecs::prelude::*;
 use block::MiningTime;
-use cgmath::Vector3;
+use cgmath::{InnerSpace, Vector3};
 use collision::Aabb3;
+use leafish_protocol::protocol::mapped_packet::EntityProperty;
+use std::collections::HashMap;
 use std::sync::Arc;
 
 pub mod player_like;
-pub mod slime;
 mod systems;
 pub mod versions;
-pub mod zombie;
 
 // TODO: There may be wrong entries in this!
 // 1.0, 1.0, 0.0 | 0.0, 0.0, 0.0
@@ -60,19 +68,16 @@ pub fn add_systems(sched: &mut Schedule, render_sched: &mut Schedule) {
     sched.add_systems(systems::update_last_position.in_set(SystemExecStage::Normal));
 
     player::add_systems(sched, render_sched);
-    sched
-        .add_systems(systems::apply_velocity.in_set(SystemExecStage::Normal))
-        .add_systems(systems::apply_gravity.in_set(SystemExecStage::Normal))
-        .add_systems(systems::apply_digging.in_set(SystemExecStage::Normal));
+    sched.add_systems(systems::apply_digging.in_set(SystemExecStage::Normal));
 
     render_sched /*sync*/
         .add_systems(
-            systems::lerp_position
+            systems::movement()
                 .in_set(SystemExecStage::Render)
                 .after(SystemExecStage::Normal),
         )
         .add_systems(
-            systems::lerp_rotation
+            systems::follow_local_player
                 .in_set(SystemExecStage::Render)
                 .after(SystemExecStage::Normal),
         )
@@ -80,10 +85,23 @@ pub fn add_systems(sched: &mut Schedule, render_sched: &mut Schedule) {
             systems::light_entity
                 .in_set(SystemExecStage::Render)
                 .after(SystemExecStage::Normal),
+        )
+        .add_systems(
+            systems::animate_pickup
+                .in_set(SystemExecStage::Render)
+                .after(SystemExecStage::Normal),
         );
 
+    mob::add_systems(render_sched);
+    item::add_systems(render_sched);
+    experience_orb::add_systems(render_sched);
+    painting::add_systems(render_sched);
+    vehicle::add_systems(sched, render_sched);
     block_entity::add_systems(sched);
     crate::particle::block_break_effect::add_systems(sched);
+    crate::particle::explosion::add_systems(sched);
+    crate::particle::note::add_systems(sched);
+    crate::sound::add_systems(sched);
 }
 
 /// Location of an entity in the world.
@@ -108,10 +126,21 @@ impl Position {
     }
 }
 
+/// How many ticks entities take to reach where the server moved them.
+pub const INTERPOLATION_TICKS: f64 = 3.0;
+/// Entities moved further than this are put there right away instead of
+/// interpolated, the same as vanilla does.
+const TELEPORT_DISTANCE: f64 = 64.0;
+
+/// Where the server last put an entity.
 #[derive(Component, Debug, Clone)]
 pub struct TargetPosition {
     pub position: Vector3<f64>,
+    /// How far the local player catches up with its own movement each
+    /// frame. Other entities interpolate over `ticks` instead.
     pub lerp_amount: f64,
+    /// The ticks left until the entity gets there.
+    pub ticks: f64,
 }
 
 impl TargetPosition {
@@ -119,6 +148,7 @@ impl TargetPosition {
         TargetPosition {
             position: Vector3::new(x, y, z),
             lerp_amount: 0.2,
+            ticks: 0.0,
         }
     }
 
@@ -161,15 +191,22 @@ impl Rotation {
         Self::new(0.0, 0.0)
     }
 }
+/// Where the server last turned an entity.
 #[derive(Component, Debug)]
 pub struct TargetRotation {
     pub yaw: f64,
     pub pitch: f64,
+    /// The ticks left until the entity faces there.
+    pub ticks: f64,
 }
 
 impl TargetRotation {
     pub fn new(yaw: f64, pitch: f64) -> Self {
-        Self { yaw, pitch }
+        Self {
+            yaw,
+            pitch,
+            ticks: 0.0,
+        }
     }
 
     pub fn zero() -> Self {
@@ -177,6 +214,86 @@ impl TargetRotation {
     }
 }
 
+/// Where the head of a mob looks, which can differ from where its body
+/// faces.
+#[derive(Component, Debug)]
+pub struct HeadYaw(pub f64);
+
+/// An entity flying towards whoever picked it up, after which it's removed.
+#[derive(Component)]
+pub struct Pickup {
+    pub collector: Entity,
+    start: Vector3<f64>,
+    /// How long the pickup has been going on, in ticks.
+    ticks: f64,
+}
+
+impl Pickup {
+    /// How many ticks it takes to reach the collector.
+    const DURATION: f64 = 3.0;
+
+    pub fn new(collector: Entity, start: Vector3<f64>) -> Self {
+        Self {
+            collector,
+            start,
+            ticks: 0.0,
+        }
+    }
+}
+
+/// Moves an entity to where the server says it is, over the ticks its type
+/// interpolates for. Entities moved very far are put there right away.
+pub fn move_to(entity: &mut EntityWorldMut, position: Vector3<f64>) {
+    // The local player moves the vehicle it steers itself
+    if entity.contains::<vehicle::Driven>() {
+        return;
+    }
+    let ticks = entity
+        .get::<EntityType>()
+        .map_or(INTERPOLATION_TICKS, EntityType::interpolation_ticks);
+    let current = match entity.get::<Position>() {
+        Some(current) => current.position,
+        None => return,
+    };
+    let snap = ticks <= 0.0 || (position - current).magnitude2() > TELEPORT_DISTANCE.powi(2);
+    if let Some(mut target) = entity.get_mut::<TargetPosition>() {
+        target.position = position;
+        target.ticks = if snap { 0.0 } else { ticks };
+    }
+    if snap {
+        entity.get_mut::<Position>().unwrap().position = position;
+    }
+}
+
+/// Turns an entity to where the server says it faces, like `move_to`.
+pub fn rotate_to(entity: &mut EntityWorldMut, yaw: f64, pitch: f64) {
+    if entity.contains::<vehicle::Driven>() {
+        return;
+    }
+    let ticks = entity
+        .get::<EntityType>()
+        .map_or(INTERPOLATION_TICKS, EntityType::interpolation_ticks);
+    if let Some(mut target) = entity.get_mut::<TargetRotation>() {
+        target.yaw = yaw;
+        target.pitch = pitch;
+        target.ticks = ticks;
+    }
+    if ticks <= 0.0 {
+        if let Some(mut rotation) = entity.get_mut::<Rotation>() {
+            rotation.yaw = yaw;
+            rotation.pitch = pitch;
+        }
+    }
+}
+
+/// How an entity the client moves between updates falls and slows down,
+/// per tick.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Physics {
+    pub gravity: f64,
+    pub drag: f64,
+}
+
 #[derive(Component, Default)]
 pub struct Gravity {
     pub on_ground: bool,
@@ -188,6 +305,57 @@ impl Gravity {
     }
 }
 
+/// The ids of the effects applied by the server to mining.
+pub const HASTE_EFFECT: i8 = 3;
+pub const MINING_FATIGUE_EFFECT: i8 = 4;
+
+/// Status effects the server applied, by effect id with their amplifier.
+#[derive(Component, Default)]
+pub struct Effects {
+    pub amplifiers: HashMap<i8, i8>,
+}
+
+impl Effects {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The level of the effect, 0 if it isn't applied.
+    pub fn level(&self, effect_id: i8) -> u8 {
+        self.amplifiers
+            .get(&effect_id)
+            .map_or(0, |&amplifier| (amplifier as u8).saturating_add(1))
+    }
+}
+
+/// How fast the entity walks, its `generic.movement_speed` attribute with
+/// the modifiers the server sent applied.
+#[derive(Component, Clone, Copy, Debug, PartialEq)]
+pub struct MovementSpeed(pub f64);
+
+impl MovementSpeed {
+    /// Reads the speed out of the attributes of the entity, if they list it.
+    pub fn from_properties(properties: &[EntityProperty]) -> Option<Self> {
+        let property = properties.iter().find(|property| {
+            property.key == "generic.movementSpeed"
+                || property.key == "minecraft:generic.movement_speed"
+        })?;
+        // Additions come first, then multiplications of the sum, then
+        // multiplications of the result
+        let amounts = |operation: i8| {
+            property
+                .modifiers
+                .iter()
+                .filter(move |modifier| modifier.operation == operation)
+                .map(|modifier| modifier.amount)
+        };
+        let base = property.value + amounts(0).sum::<f64>();
+        let speed = base + base * amounts(1).sum::<f64>();
+        let speed = amounts(2).fold(speed, |speed, amount| speed * (1.0 + amount));
+        Some(MovementSpeed(speed.max(0.0)))
+    }
+}
+
 #[derive(Component)]
 pub struct Bounds {
     pub bounds: Aabb3<f64>,
@@ -373,6 +541,45 @@ impl EntityType {
         None
     }
 
+    /// How the entity moves on its own between updates, for the entities
+    /// the client simulates like vanilla does. Everything else only moves
+    /// where the server puts it.
+    pub fn physics(&self) -> Option<Physics> {
+        let (gravity, drag) = match self {
+            EntityType::DroppedItem | EntityType::FallingBlock | EntityType::PrimedTnt => {
+                (0.04, 0.98)
+            }
+            EntityType::ExperienceOrb => (0.03, 0.98),
+            EntityType::Arrow
+            | EntityType::TippedArrow
+            | EntityType::SpectralArrow
+            | EntityType::Trident => (0.05, 0.99),
+            EntityType::Snowball | EntityType::Egg | EntityType::EnderPearl => (0.03, 0.99),
+            EntityType::ThrownExpBottle => (0.07, 0.99),
+            EntityType::SplashPotion | EntityType::LingeringPotion => (0.05, 0.99),
+            EntityType::LlamaSpit => (0.06, 0.99),
+            EntityType::FishingHook => (0.03, 0.92),
+            EntityType::Fireball
+            | EntityType::SmallFireball
+            | EntityType::DragonFireball
+            | EntityType::WitherSkull
+            | EntityType::ShulkerBullet => (0.0, 1.0),
+            _ => return None,
+        };
+        Some(Physics { gravity, drag })
+    }
+
+    /// How many ticks the entity takes to reach where the server moved it.
+    /// Entities with physics are put there right away and carry on with
+    /// their velocity.
+    pub fn interpolation_ticks(&self) -> f64 {
+        if self.physics().is_some() {
+            0.0
+        } else {
+            INTERPOLATION_TICKS
+        }
+    }
+
     fn create_entity_internally(
         &self,
         m: &mut Manager,
@@ -391,23 +598,80 @@ impl EntityType {
             .insert(TargetRotation::new(yaw, pitch))
             .insert(Light::new())
             .insert(*self);
+        if self.physics().is_some() {
+            entity.insert(Gravity::new());
+        }
         entity.id()
     }
 
     fn create_model(&self, m: &mut Manager, entity: Entity) {
-        match self {
-            EntityType::Zombie => {
-                m.world.entity_mut(entity).insert(ZombieModel::new(None));
-            }
-            EntityType::Slime => {
-                m.world.entity_mut(entity).insert(SlimeModel::new(None));
-            }
-            _ => {}
-        };
+        if *self == EntityType::DroppedItem {
+            m.world.entity_mut(entity).insert(ItemModel::default());
+        }
+        if let Some(name) = self.model_name() {
+            let mut entity = m.world.entity_mut(entity);
+            let position = entity.get::<Position>().unwrap().position;
+            let yaw = entity.get::<Rotation>().unwrap().yaw;
+            entity
+                .insert(MobModel::new(name))
+                .insert(Animation::new(position))
+                .insert(HeadYaw(yaw));
+        }
+    }
+
+    /// The name of the model in `assets/leafish/models/entity` the entity
+    /// is drawn with.
+    fn model_name(&self) -> Option<&'static str> {
+        Some(match self {
+            EntityType::Pig => "pig",
+            EntityType::Cow => "cow",
+            EntityType::MushroomCow => "mooshroom",
+            EntityType::Sheep => "sheep",
+            EntityType::Chicken => "chicken",
+            EntityType::Zombie => "zombie",
+            EntityType::Husk => "husk",
+            EntityType::Drowned => "drowned",
+            EntityType::Giant => "giant",
+            EntityType::Skeleton => "skeleton",
+            EntityType::Stray => "stray",
+            EntityType::WitherSkeleton => "wither_skeleton",
+            EntityType::Creeper => "creeper",
+            EntityType::Spider => "spider",
+            EntityType::CaveSpider => "cave_spider",
+            EntityType::Enderman => "enderman",
+            EntityType::Slime => "slime",
+            EntityType::Boat => "boat",
+            EntityType::Horse => "horse",
+            EntityType::Donkey => "donkey",
+            EntityType::Mule => "mule",
+            EntityType::SkeletonHorse => "skeleton_horse",
+            EntityType::ZombieHorse => "zombie_horse",
+            _ if self.is_minecart() => "minecart",
+            _ => return None,
+        })
+    }
+
+    /// Whether the entity is any of the minecarts, which are drawn alike
+    /// whatever they carry.
+    pub fn is_minecart(&self) -> bool {
+        matches!(
+            self,
+            EntityType::Minecart
+                | EntityType::MinecartChest
+                | EntityType::MinecartFurnace
+                | EntityType::MinecartTnt
+                | EntityType::MinecartHopper
+                | EntityType::MinecartMobSpawner
+                | EntityType::MinecartCommand
+        )
     }
 
     fn supported(&self) -> bool {
-        matches!(self, EntityType::Zombie)
+        self.model_name().is_some()
+            || matches!(
+                self,
+                EntityType::DroppedItem | EntityType::ExperienceOrb | EntityType::Painting
+            )
     }
 }
 
@@ -500,14 +764,18 @@ pub enum DiggingFinishState {
 }
 
 impl DiggingState {
-    pub fn is_finished(&self, tool: &Option<block::Tool>) -> DiggingFinishState {
+    pub fn is_finished(
+        &self,
+        tool: &Option<block::Tool>,
+        modifiers: &block::MiningModifiers,
+    ) -> DiggingFinishState {
         // If marked as finished, we don't need to calculate the mining time
         // again.
         if self.finished {
             return DiggingFinishState::Finished;
         }
 
-        let mining_time = self.block.get_mining_time(tool);
+        let mining_time = self.block.get_mining_time(tool, modifiers);
         match mining_time {
             block::MiningTime::Instant => DiggingFinishState::FinishedInstant,
             block::MiningTime::Time(mining_time) => {
@@ -522,14 +790,14 @@ impl DiggingState {
         }
     }
 
-    pub fn get_ratio(&self, tool: &Option<block::Tool>) -> f32 {
+    pub fn get_ratio(&self, tool: &Option<block::Tool>, modifiers: &block::MiningModifiers) -> f32 {
         // If marked as finished, we don't need to calculate the mining time
         // again.
         if self.finished {
             return 1.0;
         }
 
-        let mining_time = self.block.get_mining_time(tool);
+        let mining_time = self.block.get_mining_time(tool, modifiers);
         let mining_time = match mining_time {
             MiningTime::Instant => return 1.0,
             MiningTime::Never => return 0.0,
@@ -0,0 +1,306 @@
+use super::{Light, Position};
+use crate::ecs::SystemExecStage;
+use crate::render::model;
+use crate::render::Renderer;
+use crate::server::RendererResource;
+use crate::shared::Position as BPosition;
+use bevy_ecs::prelude::*;
+use cgmath::{Decomposed, Matrix4, Quaternion, Rad, Rotation3, Vector3};
+
+pub fn add_systems(render_sched: &mut Schedule) {
+    render_sched.add_systems(
+        (added_painting, update_paintings)
+            .chain()
+            .in_set(SystemExecStage::Render)
+            .after(SystemExecStage::Normal),
+    );
+}
+
+/// What a painting shows and its size in blocks.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Motive {
+    pub name: &'static str,
+    pub width: i32,
+    pub height: i32,
+}
+
+/// The paintings in the order of their ids since 1.13.
+static MOTIVES: &[Motive] = &[
+    Motive {
+        name: "kebab",
+        width: 1,
+        height: 1,
+    },
+    Motive {
+        name: "aztec",
+        width: 1,
+        height: 1,
+    },
+    Motive {
+        name: "alban",
+        width: 1,
+        height: 1,
+    },
+    Motive {
+        name: "aztec2",
+        width: 1,
+        height: 1,
+    },
+    Motive {
+        name: "bomb",
+        width: 1,
+        height: 1,
+    },
+    Motive {
+        name: "plant",
+        width: 1,
+        height: 1,
+    },
+    Motive {
+        name: "wasteland",
+        width: 1,
+        height: 1,
+    },
+    Motive {
+        name: "pool",
+        width: 2,
+        height: 1,
+    },
+    Motive {
+        name: "courbet",
+        width: 2,
+        height: 1,
+    },
+    Motive {
+        name: "sea",
+        width: 2,
+        height: 1,
+    },
+    Motive {
+        name: "sunset",
+        width: 2,
+        height: 1,
+    },
+    Motive {
+        name: "creebet",
+        width: 2,
+        height: 1,
+    },
+    Motive {
+        name: "wanderer",
+        width: 1,
+        height: 2,
+    },
+    Motive {
+        name: "graham",
+        width: 1,
+        height: 2,
+    },
+    Motive {
+        name: "match",
+        width: 2,
+        height: 2,
+    },
+    Motive {
+        name: "bust",
+        width: 2,
+        height: 2,
+    },
+    Motive {
+        name: "stage",
+        width: 2,
+        height: 2,
+    },
+    Motive {
+        name: "void",
+        width: 2,
+        height: 2,
+    },
+    Motive {
+        name: "skull_and_roses",
+        width: 2,
+        height: 2,
+    },
+    Motive {
+        name: "wither",
+        width: 2,
+        height: 2,
+    },
+    Motive {
+        name: "fighters",
+        width: 4,
+        height: 2,
+    },
+    Motive {
+        name: "pointer",
+        width: 4,
+        height: 4,
+    },
+    Motive {
+        name: "pigscene",
+        width: 4,
+        height: 4,
+    },
+    Motive {
+        name: "burning_skull",
+        width: 4,
+        height: 4,
+    },
+    Motive {
+        name: "skeleton",
+        width: 4,
+        height: 3,
+    },
+    Motive {
+        name: "donkey_kong",
+        width: 4,
+        height: 3,
+    },
+];
+
+impl Motive {
+    pub fn by_id(id: i32) -> Option<&'static Motive> {
+        if id < 0 {
+            return None;
+        }
+        MOTIVES.get(id as usize)
+    }
+
+    /// Finds the motive by the title servers before 1.13 send, like
+    /// `SkullAndRoses`.
+    pub fn by_title(title: &str) -> Option<&'static Motive> {
+        let title = title.to_lowercase();
+        MOTIVES
+            .iter()
+            .find(|motive| motive.name.replace('_', "") == title)
+    }
+}
+
+#[derive(Component)]
+pub struct PaintingModel {
+    motive: &'static Motive,
+    /// The direction the painting faces: 0 south, 1 west, 2 north, 3 east.
+    facing: i32,
+    model: Option<model::ModelHandle>,
+}
+
+impl PaintingModel {
+    pub fn new(motive: &'static Motive, facing: i32) -> Self {
+        Self {
+            motive,
+            facing,
+            model: None,
+        }
+    }
+}
+
+/// Where the center of a painting hanging in the block at `location` is.
+/// It's flat against the wall behind it, and paintings with an even size
+/// are moved half a block to the side and up.
+pub fn center(location: BPosition, motive: &Motive, facing: i32) -> Vector3<f64> {
+    const STEPS: [(f64, f64); 4] = [(0.0, 1.0), (-1.0, 0.0), (0.0, -1.0), (1.0, 0.0)];
+    let offset = |size: i32| if size % 2 == 0 { 0.5 } else { 0.0 };
+    let (fx, fz) = STEPS[facing.rem_euclid(4) as usize];
+    // Turned counter clockwise from the facing
+    let (sx, sz) = STEPS[(facing + 3).rem_euclid(4) as usize];
+    Vector3::new(
+        location.x as f64 + 0.5 - fx * 0.46875 + sx * offset(motive.width),
+        location.y as f64 + 0.5 + offset(motive.height),
+        location.z as f64 + 0.5 - fz * 0.46875 + sz * offset(motive.width),
+    )
+}
+
+pub fn added_painting(
+    renderer: Res<RendererResource>,
+    mut query: Query<&mut PaintingModel, Added<PaintingModel>>,
+) {
+    let renderer = &renderer.0;
+    for mut painting in query.iter_mut() {
+        let front = Renderer::get_texture(
+            renderer.get_textures_ref(),
+            &format!("minecraft:painting/{}", painting.motive.name),
+        );
+        let back = Renderer::get_texture(renderer.get_textures_ref(), "minecraft:painting/back");
+        let width = painting.motive.width as f32;
+        let height = painting.motive.height as f32;
+        let mut verts = vec![];
+        model::append_box(
+            &mut verts,
+            -width / 2.0,
+            -height / 2.0,
+            -1.0 / 32.0,
+            width,
+            height,
+            1.0 / 16.0,
+            [
+                Some(back.clone()),
+                Some(back.clone()),
+                Some(back.clone()),
+                Some(front),
+                Some(back.clone()),
+                Some(back),
+            ],
+        );
+        painting.model = Some(renderer.clone().models.lock().create_model(
+            model::DEFAULT,
+            vec![verts],
+            renderer.clone(),
+        ));
+    }
+}
+
+pub fn update_paintings(
+    renderer: Res<RendererResource>,
+    query: Query<(&PaintingModel, &Position, &Light)>,
+) {
+    use std::f32::consts::PI;
+    let renderer = &renderer.0;
+    let mut models = renderer.models.lock();
+    for (painting, position, light) in query.iter() {
+        let handle = match &painting.model {
+            Some(handle) => handle,
+            None => continue,
+        };
+        let mdl = models.get_model(handle).unwrap();
+        mdl.block_light = light.block_light;
+        mdl.sky_light = light.sky_light;
+        // The front of the model faces south
+        mdl.matrix[0] = Matrix4::from(Decomposed {
+            scale: 1.0,
+            rot: Quaternion::from_angle_y(Rad(-painting.facing as f32 * PI / 2.0)),
+            disp: Vector3::new(
+                position.position.x as f32,
+                -position.position.y as f32,
+                position.position.z as f32,
+            ),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn motives_by_id_and_title() {
+        assert_eq!(Motive::by_id(0).unwrap().name, "kebab");
+        assert_eq!(Motive::by_id(25).unwrap().name, "donkey_kong");
+        assert_eq!(Motive::by_id(26), None);
+        assert_eq!(Motive::by_title("SkullAndRoses").unwrap().width, 2);
+        assert_eq!(Motive::by_title("Pigscene").unwrap().height, 4);
+        assert_eq!(Motive::by_title("Mona Lisa"), None);
+    }
+
+    #[test]
+    fn paintings_hang_against_the_wall() {
+        let kebab = Motive::by_id(0).unwrap();
+        // Facing north, so the wall is to the south
+        let pos = center(BPosition::new(0, 64, 0), kebab, 2);
+        assert_eq!(pos, Vector3::new(0.5, 64.5, 0.96875));
+
+        // Even sizes move to the west when facing north, and up
+        let bust = Motive::by_title("Bust").unwrap();
+        let pos = center(BPosition::new(0, 64, 0), bust, 2);
+        assert_eq!(pos, Vector3::new(0.0, 65.0, 0.96875));
+    }
+}
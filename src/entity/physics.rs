@@ -0,0 +1,1064 @@
+//! The local player's movement, worked out tick by tick the way vanilla
+//! does, so servers checking how players move agree with where we are. The
+//! boats and horses the player steers move the same way.
+
+use crate::shared::Position as BPosition;
+use crate::world;
+use crate::world::block::{self, Block};
+use cgmath::{Point3, Vector3, Zero};
+use collision::{Aabb, Aabb3};
+use instant::Instant;
+
+/// How high the player walks up without jumping.
+const STEP_HEIGHT: f64 = 0.6;
+const GRAVITY: f64 = 0.08;
+const AIR_DRAG: f64 = 0.98;
+/// How much of its speed the player keeps each tick in the air, and over
+/// the ground times the slipperiness of the block.
+const AIR_FRICTION: f64 = 0.91;
+const WALK_SPEED: f64 = 0.1;
+const SPRINT_MULTIPLIER: f64 = 1.3;
+const AIR_ACCELERATION: f64 = 0.02;
+const SPRINT_AIR_ACCELERATION: f64 = 0.026;
+const FLY_SPEED: f64 = 0.05;
+const JUMP_VELOCITY: f64 = 0.42;
+const SPRINT_JUMP_BOOST: f64 = 0.2;
+/// How long the player stays on the ground before jumping again, in ticks.
+const JUMP_COOLDOWN: u8 = 10;
+/// Below this speed along an axis the player stops.
+const MIN_SPEED: f64 = 0.005;
+/// How far sneaking moves towards an edge at a time to find where it stops.
+const SNEAK_STEP: f64 = 0.05;
+const CLIMB_SPEED: f64 = 0.2;
+/// How fast the player moves along a ladder, at most.
+const LADDER_SPEED: f64 = 0.15;
+
+/// The blocks and other obstacles the player moves among.
+pub trait Surroundings {
+    fn block(&self, pos: BPosition) -> Block;
+
+    /// Obstacles that aren't blocks, like the world border, around `area`.
+    fn obstacles(&self, area: &Aabb3<f64>) -> Vec<Aabb3<f64>>;
+}
+
+impl Surroundings for world::World {
+    fn block(&self, pos: BPosition) -> Block {
+        self.get_block(pos)
+    }
+
+    fn obstacles(&self, area: &Aabb3<f64>) -> Vec<Aabb3<f64>> {
+        self.world_border
+            .read()
+            .collision_boxes_at(Instant::now(), area.min.y - 1.0, area.max.y + 1.0)
+            .to_vec()
+    }
+}
+
+/// What the player wants to do this tick, from the keys held.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Input {
+    /// 1 moving forwards, -1 backwards.
+    pub forward: f64,
+    /// 1 moving left, -1 right.
+    pub strafe: f64,
+    pub jump: bool,
+    pub sneak: bool,
+    pub sprint: bool,
+}
+
+/// The player's movement, carried over from one tick to the next.
+#[derive(Clone, Debug)]
+pub struct Body {
+    pub position: Vector3<f64>,
+    pub velocity: Vector3<f64>,
+    /// The player's box around its position.
+    pub bounds: Aabb3<f64>,
+    pub on_ground: bool,
+    pub collided_horizontally: bool,
+    pub flying: bool,
+    /// Moves through blocks, like spectators.
+    pub noclip: bool,
+    /// How fast it walks on the ground, sprinting is a bit faster.
+    pub walk_speed: f64,
+    /// How high it walks up without jumping.
+    pub step_height: f64,
+    jump_ticks: u8,
+}
+
+impl Default for Body {
+    fn default() -> Self {
+        Self {
+            position: Vector3::zero(),
+            velocity: Vector3::zero(),
+            bounds: Aabb3::new(Point3::new(-0.3, 0.0, -0.3), Point3::new(0.3, 1.8, 0.3)),
+            on_ground: false,
+            collided_horizontally: false,
+            flying: false,
+            noclip: false,
+            walk_speed: WALK_SPEED,
+            step_height: STEP_HEIGHT,
+            jump_ticks: 0,
+        }
+    }
+}
+
+impl Body {
+    fn area(&self) -> Aabb3<f64> {
+        self.bounds.add_v(self.position)
+    }
+
+    /// The block the player is standing in.
+    fn feet(&self) -> BPosition {
+        block_at(self.position)
+    }
+
+    /// The block under the player that decides how slippery the ground is.
+    fn ground(&self) -> BPosition {
+        let feet = self.feet();
+        BPosition::new(feet.x, self.area().min.y.floor() as i32 - 1, feet.z)
+    }
+}
+
+/// Moves the player by one tick. `yaw` is in degrees the way vanilla counts
+/// them, 0 facing south and growing clockwise.
+pub fn tick(body: &mut Body, input: Input, yaw: f64, world: &impl Surroundings) {
+    body.jump_ticks = body.jump_ticks.saturating_sub(1);
+    for axis in 0..3 {
+        if body.velocity[axis].abs() < MIN_SPEED {
+            body.velocity[axis] = 0.0;
+        }
+    }
+
+    let sprinting =
+        input.sprint && input.forward > 0.0 && !input.sneak && !body.collided_horizontally;
+    let (mut forward, mut strafe) = (input.forward, input.strafe);
+    if input.sneak {
+        forward *= 0.3;
+        strafe *= 0.3;
+    }
+    let in_water = in_water(world, &body.area());
+    let in_lava = in_lava(world, &body.area());
+
+    if body.flying {
+        if input.sneak {
+            body.velocity.y -= FLY_SPEED * 3.0;
+        }
+        if input.jump {
+            body.velocity.y += FLY_SPEED * 3.0;
+        }
+    } else if input.jump {
+        if in_water || in_lava {
+            body.velocity.y += 0.04;
+        } else if body.on_ground && body.jump_ticks == 0 {
+            jump(body, yaw, sprinting, world);
+            body.jump_ticks = JUMP_COOLDOWN;
+        }
+    } else {
+        body.jump_ticks = 0;
+    }
+    forward *= 0.98;
+    strafe *= 0.98;
+
+    let fly_velocity = body.velocity.y;
+    if (in_water || in_lava) && !body.flying {
+        let start = body.position.y;
+        accelerate(body, strafe, forward, 0.02, yaw);
+        move_body(body, input.sneak, world);
+        body.velocity *= if in_water { 0.8 } else { 0.5 };
+        body.velocity.y -= 0.02;
+        // Swimming against the side of a pool lets the player climb out
+        let offset = Vector3::new(
+            body.velocity.x,
+            body.velocity.y + 0.6 - body.position.y + start,
+            body.velocity.z,
+        );
+        if body.collided_horizontally && is_free(world, &body.area().add_v(offset)) {
+            body.velocity.y = 0.3;
+        }
+    } else {
+        let friction = if body.on_ground {
+            slipperiness(world.block(body.ground())) * AIR_FRICTION
+        } else {
+            AIR_FRICTION
+        };
+        let acceleration = if body.on_ground {
+            let speed = if sprinting {
+                body.walk_speed * SPRINT_MULTIPLIER
+            } else {
+                body.walk_speed
+            };
+            speed * 0.16277136 / friction.powi(3)
+        } else if body.flying {
+            FLY_SPEED * if sprinting { 2.0 } else { 1.0 }
+        } else if sprinting {
+            SPRINT_AIR_ACCELERATION
+        } else {
+            AIR_ACCELERATION
+        };
+        accelerate(body, strafe, forward, acceleration, yaw);
+
+        let climbing = is_climbable(world.block(body.feet()));
+        if climbing {
+            body.velocity.x = body.velocity.x.clamp(-LADDER_SPEED, LADDER_SPEED);
+            body.velocity.z = body.velocity.z.clamp(-LADDER_SPEED, LADDER_SPEED);
+            body.velocity.y = body.velocity.y.max(-LADDER_SPEED);
+            // Sneaking holds on to the ladder
+            if input.sneak && body.velocity.y < 0.0 {
+                body.velocity.y = 0.0;
+            }
+        }
+        move_body(body, input.sneak, world);
+        if body.collided_horizontally && is_climbable(world.block(body.feet())) {
+            body.velocity.y = CLIMB_SPEED;
+        }
+
+        body.velocity.y -= GRAVITY;
+        body.velocity.y *= AIR_DRAG;
+        body.velocity.x *= friction;
+        body.velocity.z *= friction;
+    }
+    if body.flying {
+        body.velocity.y = fly_velocity * 0.6;
+    }
+}
+
+fn jump(body: &mut Body, yaw: f64, sprinting: bool, world: &impl Surroundings) {
+    body.velocity.y = JUMP_VELOCITY * surface_factor(body, world, jump_factor);
+    if sprinting {
+        let yaw = yaw.to_radians();
+        body.velocity.x -= yaw.sin() * SPRINT_JUMP_BOOST;
+        body.velocity.z += yaw.cos() * SPRINT_JUMP_BOOST;
+    }
+}
+
+/// Speeds the player up in the direction it's heading.
+fn accelerate(body: &mut Body, strafe: f64, forward: f64, acceleration: f64, yaw: f64) {
+    let length = (strafe * strafe + forward * forward).sqrt();
+    if length < 0.01 {
+        return;
+    }
+    // Moving diagonally isn't any faster
+    let scale = acceleration / length.max(1.0);
+    let (strafe, forward) = (strafe * scale, forward * scale);
+    let (sin, cos) = yaw.to_radians().sin_cos();
+    body.velocity.x += strafe * cos - forward * sin;
+    body.velocity.z += forward * cos + strafe * sin;
+}
+
+/// Where a boat is, which decides how it floats.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum BoatStatus {
+    /// Floating on water that's this high.
+    InWater(f64),
+    UnderWater,
+    /// On blocks this slippery.
+    OnLand(f64),
+    InAir,
+}
+
+/// A boat the local player rows, carried over from one tick to the next.
+#[derive(Clone, Debug)]
+pub struct Boat {
+    pub body: Body,
+    /// In degrees the way vanilla counts them.
+    pub yaw: f64,
+    /// How fast the boat turns, in degrees per tick.
+    turn: f64,
+    /// Whether the left and right paddle are rowing.
+    pub paddles: [bool; 2],
+    status: BoatStatus,
+}
+
+impl Boat {
+    pub fn new(position: Vector3<f64>, yaw: f64) -> Self {
+        Self {
+            body: Body {
+                position,
+                bounds: Aabb3::new(
+                    Point3::new(-0.6875, 0.0, -0.6875),
+                    Point3::new(0.6875, 0.5625, 0.6875),
+                ),
+                step_height: 0.0,
+                ..Body::default()
+            },
+            yaw,
+            turn: 0.0,
+            paddles: [false; 2],
+            status: BoatStatus::InAir,
+        }
+    }
+
+    /// Moves the boat by one tick, rowing the way the keys held ask to.
+    /// Returns how far it turned, in degrees, which turns whoever is in it
+    /// as well.
+    pub fn tick(&mut self, input: Input, world: &impl Surroundings) -> f64 {
+        let last = self.status;
+        self.status = boat_status(world, &self.body.area());
+        self.float(last);
+
+        // Turning on the spot still moves the boat a little
+        let mut speed = 0.0;
+        let left = input.strafe > 0.0;
+        let right = input.strafe < 0.0;
+        let forward = input.forward > 0.0;
+        let backward = input.forward < 0.0;
+        if left {
+            self.turn -= 1.0;
+        }
+        if right {
+            self.turn += 1.0;
+        }
+        if right != left && !forward && !backward {
+            speed += 0.005;
+        }
+        self.yaw += self.turn;
+        if forward {
+            speed += 0.04;
+        }
+        if backward {
+            speed -= 0.005;
+        }
+        let (sin, cos) = self.yaw.to_radians().sin_cos();
+        self.body.velocity.x -= sin * speed;
+        self.body.velocity.z += cos * speed;
+        self.paddles = [right && !left || forward, left && !right || forward];
+
+        move_body(&mut self.body, false, world);
+        self.turn
+    }
+
+    /// Slows the boat down and keeps it on top of the water.
+    fn float(&mut self, last: BoatStatus) {
+        let body = &mut self.body;
+        let height = body.bounds.max.y - body.bounds.min.y;
+        if let (BoatStatus::InAir, BoatStatus::InWater(level)) = (last, self.status) {
+            // Landing in water puts it right on the surface
+            body.position.y = level - height + 0.101;
+            body.velocity.y = 0.0;
+            return;
+        }
+        let mut buoyancy = 0.0;
+        let friction = match self.status {
+            BoatStatus::InWater(level) => {
+                buoyancy = (level - body.position.y) / height;
+                0.9
+            }
+            BoatStatus::UnderWater => {
+                buoyancy = 0.01;
+                0.45
+            }
+            BoatStatus::OnLand(slipperiness) => slipperiness,
+            BoatStatus::InAir => 0.9,
+        };
+        body.velocity.x *= friction;
+        body.velocity.z *= friction;
+        body.velocity.y -= 0.04;
+        self.turn *= friction;
+        if buoyancy > 0.0 {
+            body.velocity.y = (body.velocity.y + buoyancy * 0.06153846) * 0.75;
+        }
+    }
+}
+
+fn boat_status(world: &impl Surroundings, area: &Aabb3<f64>) -> BoatStatus {
+    let top = Aabb3::new(
+        Point3::new(area.min.x, area.max.y, area.min.z),
+        Point3::new(area.max.x, area.max.y + 0.001, area.max.z),
+    );
+    let under_water = cells(&top).any(|pos| match water_height(world.block(pos)) {
+        Some(height) => top.max.y < pos.y as f64 + height,
+        None => false,
+    });
+    if under_water {
+        return BoatStatus::UnderWater;
+    }
+
+    let bottom = Aabb3::new(
+        area.min,
+        Point3::new(area.max.x, area.min.y + 0.001, area.max.z),
+    );
+    let level = cells(&bottom)
+        .filter_map(|pos| Some(pos.y as f64 + water_height(world.block(pos))?))
+        .fold(f64::NEG_INFINITY, f64::max);
+    if bottom.min.y < level {
+        return BoatStatus::InWater(level);
+    }
+
+    // The blocks right under the boat, averaged
+    let under = Aabb3::new(
+        Point3::new(area.min.x, area.min.y - 0.001, area.min.z),
+        Point3::new(area.max.x, area.min.y, area.max.z),
+    );
+    let mut total = 0.0;
+    let mut count = 0;
+    for pos in cells(&under) {
+        let block = world.block(pos);
+        if !block.get_material().collidable {
+            continue;
+        }
+        let origin = Vector3::new(pos.x as f64, pos.y as f64, pos.z as f64);
+        if block
+            .get_collision_boxes()
+            .iter()
+            .any(|bb| overlaps(&bb.add_v(origin), &under))
+        {
+            total += slipperiness(block);
+            count += 1;
+        }
+    }
+    if count > 0 {
+        BoatStatus::OnLand(total / count as f64)
+    } else {
+        BoatStatus::InAir
+    }
+}
+
+/// Moves the player by its velocity as far as the blocks let it, updating
+/// what it collided with.
+fn move_body(body: &mut Body, sneaking: bool, world: &impl Surroundings) {
+    if body.noclip {
+        body.position += body.velocity;
+        body.on_ground = false;
+        body.collided_horizontally = false;
+        return;
+    }
+    let bounds = body.area();
+    let mut wanted = body.velocity;
+    if in_cobweb(world, &bounds) {
+        wanted.x *= 0.25;
+        wanted.y *= 0.05;
+        wanted.z *= 0.25;
+        body.velocity = Vector3::zero();
+    }
+
+    // Sneaking stops the player before it would fall off an edge
+    if body.on_ground && sneaking {
+        let supported = |x: f64, z: f64| {
+            !collision_boxes(world, &bounds.add_v(Vector3::new(x, -1.0, z))).is_empty()
+        };
+        while wanted.x != 0.0 && !supported(wanted.x, 0.0) {
+            wanted.x = towards_zero(wanted.x);
+        }
+        while wanted.z != 0.0 && !supported(0.0, wanted.z) {
+            wanted.z = towards_zero(wanted.z);
+        }
+        while wanted.x != 0.0 && wanted.z != 0.0 && !supported(wanted.x, wanted.z) {
+            wanted.x = towards_zero(wanted.x);
+            wanted.z = towards_zero(wanted.z);
+        }
+    }
+
+    let boxes = collision_boxes(world, &expand_towards(&bounds, wanted));
+    let (mut moved, mut offset) = slide(&boxes, bounds, wanted);
+    let landing = wanted.y < 0.0 && offset.y != wanted.y;
+    if body.step_height > 0.0
+        && (body.on_ground || landing)
+        && (offset.x != wanted.x || offset.z != wanted.z)
+    {
+        // Walk up onto anything low enough instead of stopping against it
+        let up = Vector3::new(wanted.x, body.step_height, wanted.z);
+        let boxes = collision_boxes(world, &expand_towards(&bounds, up));
+        let (stepped, mut step) = slide(&boxes, bounds, up);
+        let down = clip_all(&boxes, &stepped, 1, -step.y);
+        step.y += down;
+        if step.x * step.x + step.z * step.z > offset.x * offset.x + offset.z * offset.z {
+            moved = stepped.add_v(Vector3::new(0.0, down, 0.0));
+            offset = step;
+        }
+    }
+
+    body.position = Vector3::new(
+        moved.min.x - body.bounds.min.x,
+        moved.min.y - body.bounds.min.y,
+        moved.min.z - body.bounds.min.z,
+    );
+    body.collided_horizontally = offset.x != wanted.x || offset.z != wanted.z;
+    body.on_ground = wanted.y < 0.0 && offset.y != wanted.y;
+    if offset.x != wanted.x {
+        body.velocity.x = 0.0;
+    }
+    if offset.z != wanted.z {
+        body.velocity.z = 0.0;
+    }
+
+    let below = world.block(block_at(body.position - Vector3::new(0.0, 0.2, 0.0)));
+    let on_slime = matches!(below, block::SlimeBlock {});
+    if offset.y != wanted.y {
+        if on_slime && !sneaking && body.velocity.y < 0.0 {
+            body.velocity.y = -body.velocity.y;
+        } else {
+            body.velocity.y = 0.0;
+        }
+    }
+    if body.on_ground && on_slime && !sneaking && body.velocity.y.abs() < 0.1 {
+        let factor = 0.4 + body.velocity.y.abs() * 0.2;
+        body.velocity.x *= factor;
+        body.velocity.z *= factor;
+    }
+    let factor = surface_factor(body, world, speed_factor);
+    body.velocity.x *= factor;
+    body.velocity.z *= factor;
+}
+
+/// Moves `bounds` by `offset` along y, x and z in turn, stopping each at the
+/// boxes in the way. Returns where it ended up and how far it went.
+fn slide(
+    boxes: &[Aabb3<f64>],
+    mut bounds: Aabb3<f64>,
+    offset: Vector3<f64>,
+) -> (Aabb3<f64>, Vector3<f64>) {
+    let mut moved = Vector3::zero();
+    for &axis in &[1, 0, 2] {
+        moved[axis] = clip_all(boxes, &bounds, axis, offset[axis]);
+        let mut step = Vector3::zero();
+        step[axis] = moved[axis];
+        bounds = bounds.add_v(step);
+    }
+    (bounds, moved)
+}
+
+fn clip_all(boxes: &[Aabb3<f64>], moving: &Aabb3<f64>, axis: usize, offset: f64) -> f64 {
+    boxes.iter().fold(offset, |offset, obstacle| {
+        clip(obstacle, moving, axis, offset)
+    })
+}
+
+/// How far `moving` can go by `offset` along `axis` before it runs into
+/// `obstacle`. Boxes it's already inside of don't stop it.
+fn clip(obstacle: &Aabb3<f64>, moving: &Aabb3<f64>, axis: usize, offset: f64) -> f64 {
+    let beside = (0..3).filter(|&other| other != axis).all(|other| {
+        moving.max[other] > obstacle.min[other] && moving.min[other] < obstacle.max[other]
+    });
+    if !beside {
+        offset
+    } else if offset > 0.0 && moving.max[axis] <= obstacle.min[axis] {
+        offset.min(obstacle.min[axis] - moving.max[axis])
+    } else if offset < 0.0 && moving.min[axis] >= obstacle.max[axis] {
+        offset.max(obstacle.max[axis] - moving.min[axis])
+    } else {
+        offset
+    }
+}
+
+fn towards_zero(offset: f64) -> f64 {
+    if offset.abs() <= SNEAK_STEP {
+        0.0
+    } else {
+        offset - SNEAK_STEP.copysign(offset)
+    }
+}
+
+fn expand_towards(bounds: &Aabb3<f64>, offset: Vector3<f64>) -> Aabb3<f64> {
+    Aabb3::new(
+        Point3::new(
+            bounds.min.x + offset.x.min(0.0),
+            bounds.min.y + offset.y.min(0.0),
+            bounds.min.z + offset.z.min(0.0),
+        ),
+        Point3::new(
+            bounds.max.x + offset.x.max(0.0),
+            bounds.max.y + offset.y.max(0.0),
+            bounds.max.z + offset.z.max(0.0),
+        ),
+    )
+}
+
+fn shrink(bounds: &Aabb3<f64>, x: f64, y: f64, z: f64) -> Aabb3<f64> {
+    Aabb3::new(
+        Point3::new(bounds.min.x + x, bounds.min.y + y, bounds.min.z + z),
+        Point3::new(bounds.max.x - x, bounds.max.y - y, bounds.max.z - z),
+    )
+}
+
+fn overlaps(a: &Aabb3<f64>, b: &Aabb3<f64>) -> bool {
+    (0..3).all(|axis| a.max[axis] > b.min[axis] && a.min[axis] < b.max[axis])
+}
+
+fn block_at(position: Vector3<f64>) -> BPosition {
+    BPosition::new(
+        position.x.floor() as i32,
+        position.y.floor() as i32,
+        position.z.floor() as i32,
+    )
+}
+
+/// The blocks `area` reaches into.
+fn cells(area: &Aabb3<f64>) -> impl Iterator<Item = BPosition> {
+    let min = block_at(Vector3::new(area.min.x, area.min.y, area.min.z));
+    let max = block_at(Vector3::new(area.max.x, area.max.y, area.max.z));
+    (min.y..=max.y).flat_map(move |y| {
+        (min.z..=max.z).flat_map(move |z| (min.x..=max.x).map(move |x| BPosition::new(x, y, z)))
+    })
+}
+
+/// The top of the first solid block a point falling from `from` to `to`
+/// lands on, for entities that don't collide with more than the ground.
+pub fn floor_below(world: &impl Surroundings, from: Vector3<f64>, to: Vector3<f64>) -> Option<f64> {
+    let (x, z) = (to.x.floor() as i32, to.z.floor() as i32);
+    for y in (to.y.floor() as i32..=from.y.floor() as i32).rev() {
+        let block = world.block(BPosition::new(x, y, z));
+        if !block.get_material().collidable {
+            continue;
+        }
+        let top = block
+            .get_collision_boxes()
+            .iter()
+            .map(|bb| y as f64 + bb.max.y)
+            .filter(|&top| top <= from.y && top >= to.y)
+            .fold(None, |highest: Option<f64>, top| {
+                Some(highest.map_or(top, |highest| highest.max(top)))
+            });
+        if top.is_some() {
+            return top;
+        }
+    }
+    None
+}
+
+/// The boxes of everything solid in `area`.
+fn collision_boxes(world: &impl Surroundings, area: &Aabb3<f64>) -> Vec<Aabb3<f64>> {
+    let mut boxes: Vec<_> = world
+        .obstacles(area)
+        .into_iter()
+        .filter(|obstacle| overlaps(obstacle, area))
+        .collect();
+    // Fences and walls reach up out of the block below
+    let below = Aabb3::new(
+        Point3::new(area.min.x, area.min.y - 1.0, area.min.z),
+        area.max,
+    );
+    for pos in cells(&below) {
+        let block = world.block(pos);
+        if !block.get_material().collidable {
+            continue;
+        }
+        let origin = Vector3::new(pos.x as f64, pos.y as f64, pos.z as f64);
+        for bb in block.get_collision_boxes() {
+            let bb = bb.add_v(origin);
+            if overlaps(&bb, area) {
+                boxes.push(bb);
+            }
+        }
+    }
+    boxes
+}
+
+/// Whether nothing solid or liquid is in `area`.
+fn is_free(world: &impl Surroundings, area: &Aabb3<f64>) -> bool {
+    collision_boxes(world, area).is_empty()
+        && !cells(area).any(|pos| {
+            let block = world.block(pos);
+            water_height(block).is_some() || matches!(block, block::Lava { .. })
+        })
+}
+
+/// How high water fills the block, if there's any.
+fn water_height(block: Block) -> Option<f64> {
+    match block {
+        block::Water { level } => {
+            // Falling water fills the whole block
+            let level = if level >= 8 { 0.0 } else { level as f64 };
+            Some(1.0 - (level + 1.0) / 9.0)
+        }
+        block if block.is_waterlogged() => Some(1.0 - 1.0 / 9.0),
+        _ => None,
+    }
+}
+
+fn in_water(world: &impl Surroundings, bounds: &Aabb3<f64>) -> bool {
+    let area = shrink(bounds, 0.001, 0.401, 0.001);
+    cells(&area).any(|pos| match water_height(world.block(pos)) {
+        Some(height) => area.min.y < pos.y as f64 + height,
+        None => false,
+    })
+}
+
+fn in_lava(world: &impl Surroundings, bounds: &Aabb3<f64>) -> bool {
+    cells(&shrink(bounds, 0.1, 0.4, 0.1)).any(|pos| matches!(world.block(pos), block::Lava { .. }))
+}
+
+fn in_cobweb(world: &impl Surroundings, bounds: &Aabb3<f64>) -> bool {
+    cells(&shrink(bounds, 0.001, 0.001, 0.001))
+        .any(|pos| matches!(world.block(pos), block::Cobweb {}))
+}
+
+fn is_climbable(block: Block) -> bool {
+    matches!(
+        block,
+        block::Ladder { .. }
+            | block::Vine { .. }
+            | block::TwistingVines { .. }
+            | block::TwistingVinesPlant {}
+            | block::WeepingVines { .. }
+            | block::WeepingVinesPlant {}
+    )
+}
+
+/// How much of their speed players keep sliding over the block.
+fn slipperiness(block: Block) -> f64 {
+    match block {
+        block::Ice {} | block::PackedIce {} | block::FrostedIce { .. } => 0.98,
+        block::BlueIce {} => 0.989,
+        block::SlimeBlock {} => 0.8,
+        _ => 0.6,
+    }
+}
+
+fn speed_factor(block: Block) -> f64 {
+    match block {
+        block::SoulSand {} | block::HoneyBlock {} => 0.4,
+        _ => 1.0,
+    }
+}
+
+fn jump_factor(block: Block) -> f64 {
+    match block {
+        block::HoneyBlock {} => 0.5,
+        _ => 1.0,
+    }
+}
+
+/// How the block the player is in changes it, or else the one below it.
+fn surface_factor(body: &Body, world: &impl Surroundings, factor: fn(Block) -> f64) -> f64 {
+    let feet = factor(world.block(body.feet()));
+    if feet != 1.0 {
+        return feet;
+    }
+    factor(world.block(block_at(
+        body.position - Vector3::new(0.0, 0.500_000_1, 0.0),
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shared::Direction;
+    use std::collections::HashMap;
+
+    /// A floor of `floor` under y 0 with `blocks` on top.
+    struct TestWorld {
+        floor: Block,
+        blocks: HashMap<(i32, i32, i32), Block>,
+        obstacles: Vec<Aabb3<f64>>,
+    }
+
+    impl TestWorld {
+        fn new(floor: Block) -> Self {
+            Self {
+                floor,
+                blocks: HashMap::new(),
+                obstacles: vec![],
+            }
+        }
+
+        fn with(mut self, x: i32, y: i32, z: i32, block: Block) -> Self {
+            self.blocks.insert((x, y, z), block);
+            self
+        }
+    }
+
+    impl Surroundings for TestWorld {
+        fn block(&self, pos: BPosition) -> Block {
+            match self.blocks.get(&(pos.x, pos.y, pos.z)) {
+                Some(block) => *block,
+                None if pos.y < 0 => self.floor,
+                None => block::Air {},
+            }
+        }
+
+        fn obstacles(&self, _area: &Aabb3<f64>) -> Vec<Aabb3<f64>> {
+            self.obstacles.clone()
+        }
+    }
+
+    fn standing_at(x: f64, y: f64, z: f64) -> Body {
+        Body {
+            position: Vector3::new(x, y, z),
+            on_ground: true,
+            ..Body::default()
+        }
+    }
+
+    const FORWARD: Input = Input {
+        forward: 1.0,
+        strafe: 0.0,
+        jump: false,
+        sneak: false,
+        sprint: false,
+    };
+
+    /// How far the player moves in the last of `ticks` ticks with `input`.
+    fn top_speed(world: &TestWorld, input: Input, ticks: usize) -> f64 {
+        let mut body = standing_at(0.5, 0.0, 0.5);
+        let mut last = body.position;
+        for _ in 0..ticks {
+            last = body.position;
+            tick(&mut body, input, 0.0, world);
+        }
+        (body.position - last).z
+    }
+
+    #[test]
+    fn walking_and_sprinting_speeds() {
+        let world = TestWorld::new(block::Stone {});
+        assert!((top_speed(&world, FORWARD, 50) - 0.21586).abs() < 1e-4);
+        let sprint = Input {
+            sprint: true,
+            ..FORWARD
+        };
+        assert!((top_speed(&world, sprint, 50) - 0.28061).abs() < 1e-4);
+        let sneak = Input {
+            sneak: true,
+            ..FORWARD
+        };
+        assert!((top_speed(&world, sneak, 50) - 0.21586 * 0.3).abs() < 1e-4);
+    }
+
+    #[test]
+    fn soul_sand_slows_down() {
+        let world = TestWorld::new(block::SoulSand {});
+        assert!(top_speed(&world, FORWARD, 50) < 0.21586 * 0.6);
+    }
+
+    #[test]
+    fn jump_trajectory() {
+        let world = TestWorld::new(block::Stone {});
+        let mut body = standing_at(0.5, 0.0, 0.5);
+        let jump = Input {
+            jump: true,
+            ..Input::default()
+        };
+        tick(&mut body, jump, 0.0, &world);
+        assert!((body.position.y - 0.42).abs() < 1e-9);
+        let mut heights = vec![body.position.y];
+        while !body.on_ground {
+            tick(&mut body, Input::default(), 0.0, &world);
+            heights.push(body.position.y);
+        }
+        let peak = heights.iter().cloned().fold(0.0, f64::max);
+        assert!((peak - 1.249_187).abs() < 1e-5);
+        assert_eq!(heights.len(), 12);
+        assert_eq!(body.position.y, 0.0);
+    }
+
+    #[test]
+    fn sprint_jumping_boosts() {
+        let world = TestWorld::new(block::Stone {});
+        let mut body = standing_at(0.5, 0.0, 0.5);
+        let sprint_jump = Input {
+            jump: true,
+            sprint: true,
+            ..FORWARD
+        };
+        tick(&mut body, sprint_jump, 0.0, &world);
+        assert!(body.position.z - 0.5 > 0.2);
+    }
+
+    #[test]
+    fn falls_at_terminal_velocity() {
+        let world = TestWorld::new(block::Air {});
+        let mut body = standing_at(0.5, 1000.0, 0.5);
+        body.on_ground = false;
+        for _ in 0..400 {
+            tick(&mut body, Input::default(), 0.0, &world);
+        }
+        assert!((body.velocity.y + 3.92).abs() < 0.01);
+    }
+
+    #[test]
+    fn sinks_slowly_in_water() {
+        let world = TestWorld::new(block::Water { level: 0 });
+        let mut body = standing_at(0.5, -50.0, 0.5);
+        body.on_ground = false;
+        for _ in 0..100 {
+            tick(&mut body, Input::default(), 0.0, &world);
+        }
+        assert!((body.velocity.y + 0.1).abs() < 1e-6);
+    }
+
+    #[test]
+    fn ice_slides_further() {
+        let slide = |floor: Block| {
+            let world = TestWorld::new(floor);
+            let mut body = standing_at(0.5, 0.0, 0.5);
+            for _ in 0..20 {
+                tick(&mut body, FORWARD, 0.0, &world);
+            }
+            let start = body.position.z;
+            for _ in 0..100 {
+                tick(&mut body, Input::default(), 0.0, &world);
+            }
+            body.position.z - start
+        };
+        assert!(slide(block::Ice {}) > slide(block::Stone {}) * 3.0);
+    }
+
+    #[test]
+    fn steps_up_half_blocks_but_not_full_ones() {
+        let mut world = TestWorld::new(block::Stone {});
+        world.obstacles.push(Aabb3::new(
+            Point3::new(-5.0, 0.0, 1.0),
+            Point3::new(5.0, 0.5, 5.0),
+        ));
+        let mut body = standing_at(0.5, 0.0, 0.5);
+        for _ in 0..15 {
+            tick(&mut body, FORWARD, 0.0, &world);
+        }
+        assert!((body.position.y - 0.5).abs() < 1e-9);
+        assert!(body.position.z > 1.5);
+
+        let world = TestWorld::new(block::Stone {}).with(0, 0, 1, block::Stone {});
+        let mut body = standing_at(0.5, 0.0, 0.5);
+        for _ in 0..10 {
+            tick(&mut body, FORWARD, 0.0, &world);
+        }
+        assert_eq!(body.position.y, 0.0);
+        assert!(body.position.z <= 0.7);
+        assert!(body.collided_horizontally);
+    }
+
+    #[test]
+    fn sneaking_stops_at_edges() {
+        let world = TestWorld::new(block::Air {}).with(0, -1, 0, block::Stone {});
+        let sneak = Input {
+            sneak: true,
+            ..FORWARD
+        };
+        let mut body = standing_at(0.5, 0.0, 0.5);
+        for _ in 0..40 {
+            tick(&mut body, sneak, 0.0, &world);
+        }
+        assert_eq!(body.position.y, 0.0);
+        assert!(body.position.z < 1.3);
+    }
+
+    #[test]
+    fn slime_bounces() {
+        let world = TestWorld::new(block::SlimeBlock {});
+        let mut body = standing_at(0.5, 5.0, 0.5);
+        body.on_ground = false;
+        while !body.on_ground {
+            tick(&mut body, Input::default(), 0.0, &world);
+        }
+        assert!(body.velocity.y > 0.3);
+    }
+
+    #[test]
+    fn climbs_ladders() {
+        let ladder = block::Ladder {
+            facing: Direction::North,
+            waterlogged: false,
+        };
+        let world = TestWorld::new(block::Stone {})
+            .with(0, 0, 0, ladder)
+            .with(0, 1, 0, ladder)
+            .with(0, 0, 1, block::Stone {})
+            .with(0, 1, 1, block::Stone {});
+        let mut body = standing_at(0.5, 0.0, 0.5);
+        for _ in 0..10 {
+            tick(&mut body, FORWARD, 0.0, &world);
+        }
+        assert!(body.position.y > 0.5);
+    }
+
+    #[test]
+    fn flying_holds_height() {
+        let world = TestWorld::new(block::Stone {});
+        let mut body = standing_at(0.5, 10.0, 0.5);
+        body.on_ground = false;
+        body.flying = true;
+        for _ in 0..20 {
+            tick(&mut body, Input::default(), 0.0, &world);
+        }
+        assert_eq!(body.position.y, 10.0);
+        let up = Input {
+            jump: true,
+            ..Input::default()
+        };
+        tick(&mut body, up, 0.0, &world);
+        assert!((body.position.y - 10.0 - FLY_SPEED * 3.0).abs() < 1e-9);
+    }
+
+    /// A pool of still water two blocks deep over stone.
+    fn pool() -> TestWorld {
+        let mut world = TestWorld::new(block::Stone {});
+        for x in -4..4 {
+            for z in -4..40 {
+                for y in 0..2 {
+                    world = world.with(x, y, z, block::Water { level: 0 });
+                }
+            }
+        }
+        world
+    }
+
+    #[test]
+    fn boats_float_on_water() {
+        let world = pool();
+        let mut boat = Boat::new(Vector3::new(0.5, 3.0, 0.5), 0.0);
+        for _ in 0..100 {
+            boat.tick(Input::default(), &world);
+        }
+        let surface = 1.0 + 8.0 / 9.0;
+        assert!(boat.body.position.y < surface);
+        assert!(boat.body.position.y > surface - 0.5625);
+        assert!(boat.body.velocity.y.abs() < 0.01);
+    }
+
+    #[test]
+    fn boats_row_and_turn() {
+        let world = pool();
+        let mut boat = Boat::new(Vector3::new(0.5, 1.5, 0.5), 0.0);
+        let mut last = boat.body.position;
+        for _ in 0..40 {
+            last = boat.body.position;
+            boat.tick(FORWARD, &world);
+        }
+        assert_eq!(boat.paddles, [true, true]);
+        // Slowed down by a tenth each tick, 0.04 at a time
+        assert!(((boat.body.position - last).z - 0.4).abs() < 0.01);
+
+        let left = Input {
+            strafe: 1.0,
+            ..Input::default()
+        };
+        let turned = boat.tick(left, &world);
+        assert!(turned < 0.0);
+        assert!(boat.yaw < 0.0);
+        assert_eq!(boat.paddles, [false, true]);
+    }
+
+    #[test]
+    fn falling_lands_on_the_floor() {
+        let world = TestWorld::new(block::Stone {}).with(0, 2, 0, block::SoulSand {});
+        let land = |from: (f64, f64), to: (f64, f64)| {
+            floor_below(
+                &world,
+                Vector3::new(from.0, from.1, 0.5),
+                Vector3::new(to.0, to.1, 0.5),
+            )
+        };
+        assert_eq!(land((0.5, 0.5), (0.5, -0.5)), Some(0.0));
+        assert_eq!(land((0.5, 4.0), (0.5, 2.5)), Some(2.875));
+        // Still above it, or already inside it
+        assert_eq!(land((0.5, 4.0), (0.5, 3.0)), None);
+        assert_eq!(land((0.5, 2.5), (0.5, 2.0)), None);
+        assert_eq!(land((1.5, 4.0), (1.5, -3.0)), Some(0.0));
+    }
+
+    #[test]
+    fn horses_step_up_full_blocks() {
+        let mut world = TestWorld::new(block::Stone {});
+        world.obstacles.push(Aabb3::new(
+            Point3::new(-5.0, 0.0, 1.0),
+            Point3::new(5.0, 1.0, 5.0),
+        ));
+        let mut body = standing_at(0.5, 0.0, 0.5);
+        body.step_height = 1.0;
+        for _ in 0..15 {
+            tick(&mut body, FORWARD, 0.0, &world);
+        }
+        assert!((body.position.y - 1.0).abs() < 1e-9);
+        assert!(body.position.z > 1.5);
+    }
+}
@@ -1,25 +1,26 @@
+use super::equipment::{self, BodyPart, Equipment, Slot};
+use super::item::held_item_vertices;
+use super::metadata::{EntityFlags, Pose};
+use super::vehicle::Vehicle;
 use super::{
-    Bounds, Digging, GameInfo, Gravity, Light, MouseButtons, Position, Rotation, TargetPosition,
-    TargetRotation, Velocity,
+    Bounds, Digging, Effects, GameInfo, Gravity, Light, MouseButtons, Position, Rotation,
+    TargetPosition, TargetRotation, Velocity,
 };
 use crate::ecs::{Manager, SystemExecStage};
-use crate::entity::slime::{added_slime, update_slime};
-use crate::entity::zombie::{added_zombie, update_zombie};
+use crate::entity::physics;
+use crate::entity::player_like::{self, compute_name_tag};
 use crate::entity::{resolve_textures, EntityType};
-use crate::format;
 use crate::render;
-use crate::render::model::{self, FormatState};
+use crate::render::model;
 use crate::render::Renderer;
 use crate::server::{RendererResource, ScreenSystemResource, WorldResource};
 use crate::settings::Actionkey;
-use crate::shared::Position as BPosition;
 use crate::types::hash::FNVHash;
 use crate::types::GameMode;
-use crate::world;
 use arc_swap::ArcSwapOption;
 use bevy_ecs::prelude::*;
 use cgmath::{Decomposed, Matrix4, Point3, Quaternion, Rad, Rotation3, Vector3};
-use collision::{Aabb, Aabb3};
+use collision::Aabb3;
 use instant::Instant;
 use leafish_protocol::format::Component;
 use std::collections::HashMap;
@@ -40,32 +41,13 @@ pub fn add_systems(sched: &mut Schedule, render_sched: &mut Schedule) {
         .add_systems(
             update_render_players
                 .in_set(SystemExecStage::Render)
-                .after(SystemExecStage::Normal),
+                .after(SystemExecStage::Normal)
+                .after(super::vehicle::ride),
         )
         .add_systems(
             player_added
                 .in_set(SystemExecStage::Render)
                 .after(SystemExecStage::Normal),
-        )
-        .add_systems(
-            update_slime
-                .in_set(SystemExecStage::Render)
-                .after(SystemExecStage::Normal),
-        )
-        .add_systems(
-            added_slime
-                .in_set(SystemExecStage::Render)
-                .after(SystemExecStage::Normal),
-        )
-        .add_systems(
-            update_zombie
-                .in_set(SystemExecStage::Render)
-                .after(SystemExecStage::Normal),
-        )
-        .add_systems(
-            added_zombie
-                .in_set(SystemExecStage::Render)
-                .after(SystemExecStage::Normal),
         );
 }
 
@@ -80,6 +62,7 @@ pub fn create_local(m: &mut Manager) -> Entity {
         .insert(Velocity::new(0.0, 0.0, 0.0))
         .insert(GameMode::Survival)
         .insert(Gravity::new())
+        .insert(Effects::new())
         .insert(PlayerMovement::new())
         .insert(Bounds::new(Aabb3::new(
             Point3::new(-0.3, 0.0, -0.3),
@@ -120,6 +103,10 @@ pub fn create_remote(m: &mut Manager, name: Component) -> Entity {
 #[derive(Component)]
 pub struct PlayerModel {
     model: Option<model::ModelHandle>,
+    /// Drawn over enchanted armor.
+    glint: Option<model::ModelHandle>,
+    /// Drawn around the player while it burns.
+    flames: Option<model::ModelHandle>,
     skin_url: ArcSwapOption<String>,
     dirty: AtomicBool,
     display_name: Component,
@@ -139,6 +126,8 @@ impl PlayerModel {
     pub fn new(name: Component, has_head: bool, has_name_tag: bool, first_person: bool) -> Self {
         Self {
             model: None,
+            glint: None,
+            flames: None,
             skin_url: ArcSwapOption::new(None),
             dirty: AtomicBool::new(false),
             display_name: name,
@@ -166,40 +155,90 @@ impl PlayerModel {
 fn update_render_players(
     renderer: Res<RendererResource>,
     game_info: Res<GameInfo>,
-    mut query: Query<(&mut PlayerModel, &Position, &Rotation, &Light)>,
+    mut query: Query<(
+        &mut PlayerModel,
+        &Position,
+        &Rotation,
+        &Light,
+        Option<&Pose>,
+        Option<&EntityFlags>,
+        Option<Ref<Equipment>>,
+        Option<&Vehicle>,
+    )>,
 ) {
     let renderer = &renderer.0;
     let delta = game_info.delta;
-    for (mut player_model, position, rotation, light) in query.iter_mut() {
+    for (mut player_model, position, rotation, light, pose, flags, equipment, vehicle) in
+        query.iter_mut()
+    {
         use std::f32::consts::PI;
         use std::f64::consts::PI as PI64;
 
-        if player_model.dirty.load(Ordering::Acquire) {
-            add_player(renderer.clone(), &mut player_model);
+        let equipment_changed = equipment
+            .as_ref()
+            .is_some_and(|equipment| equipment.is_changed());
+        if player_model.dirty.load(Ordering::Acquire) || equipment_changed {
+            add_player(renderer.clone(), &mut player_model, equipment.as_deref());
+        }
+
+        // The local player doesn't see its own flames in first person
+        let burning = flags.is_some_and(|flags| flags.on_fire) && !player_model.first_person;
+        if burning != player_model.flames.is_some() {
+            player_model.flames = burning.then(|| player_like::create_flames(0.6, 1.8, renderer));
         }
 
         if let Some(pmodel) = &player_model.model {
             let renderer = renderer.clone();
             let cam_x = renderer.camera.lock().pos.x;
             let cam_z = renderer.camera.lock().pos.z;
+            let cam_yaw = renderer.camera.lock().yaw;
             let mut models = renderer.models.lock();
+            player_like::update_flames(
+                &mut models,
+                player_model.flames.as_ref(),
+                position.position,
+                cam_yaw,
+            );
             let mdl = models.get_model(pmodel).unwrap();
 
-            mdl.block_light = light.block_light;
-            mdl.sky_light = light.sky_light;
+            // Glowing players stand out at full brightness
+            if flags.is_some_and(|flags| flags.glowing) {
+                mdl.block_light = 15.0;
+                mdl.sky_light = 15.0;
+            } else {
+                mdl.block_light = light.block_light;
+                mdl.sky_light = light.sky_light;
+            }
+
+            if flags.is_some_and(|flags| flags.invisible) {
+                for matrix in &mut mdl.matrix {
+                    *matrix = Matrix4::from_scale(0.0);
+                }
+                let matrix = mdl.matrix.clone();
+                equipment::update_glint(&mut models, player_model.glint.as_ref(), &matrix, 0.0);
+                continue;
+            }
+            // Sneaking lowers the model, bends the upper body forward at the
+            // neck and moves the legs back under it
+            let (crouch, bend, lean, legs) = if pose == Some(&Pose::Sneaking) {
+                (2.0 / 16.0, 3.2 / 16.0, 0.5, 4.0 / 16.0)
+            } else {
+                (0.0, 0.0, 0.0, 0.0)
+            };
+            let neck = -24.0 / 16.0 + bend;
 
             let offset = if player_model.first_person {
                 let ox = (rotation.yaw - PI64 / 2.0).cos() * 0.25;
                 let oz = -(rotation.yaw - PI64 / 2.0).sin() * 0.25;
                 Vector3::new(
                     position.position.x as f32 - ox as f32,
-                    -position.position.y as f32,
+                    -position.position.y as f32 + crouch,
                     position.position.z as f32 - oz as f32,
                 )
             } else {
                 Vector3::new(
                     position.position.x as f32,
-                    -position.position.y as f32,
+                    -position.position.y as f32 + crouch,
                     position.position.z as f32,
                 )
             };
@@ -223,14 +262,12 @@ fn update_render_players(
                 * Matrix4::from(Decomposed {
                     scale: 1.0,
                     rot: Quaternion::from_angle_x(Rad(-rotation.pitch as f32)),
-                    disp: Vector3::new(0.0, -12.0 / 16.0 - 12.0 / 16.0, 0.0),
+                    disp: Vector3::new(0.0, neck, 0.0),
                 });
             mdl.matrix[PlayerModelPart::Body as usize] = offset_matrix
-                * Matrix4::from(Decomposed {
-                    scale: 1.0,
-                    rot: Quaternion::from_angle_x(Rad(0.0)),
-                    disp: Vector3::new(0.0, -12.0 / 16.0 - 6.0 / 16.0, 0.0),
-                });
+                * Matrix4::from_translation(Vector3::new(0.0, neck, 0.0))
+                * Matrix4::from(Quaternion::from_angle_x(Rad(lean)))
+                * Matrix4::from_translation(Vector3::new(0.0, 6.0 / 16.0, 0.0));
 
             let mut time = player_model.time;
             let mut dir = player_model.dir;
@@ -238,19 +275,41 @@ fn update_render_players(
                 dir = 1;
                 time = 15.0;
             }
-            let ang = ((time / 15.0) - 1.0) * (PI64 / 4.0);
+            // Riders don't swing their limbs as they move
+            let ang = if vehicle.is_some() {
+                0.0
+            } else {
+                ((time / 15.0) - 1.0) * (PI64 / 4.0)
+            };
+
+            // Riders sit with their legs out in front, a bit apart, and
+            // their arms forward
+            let (leg_right, leg_left, reach) = if vehicle.is_some() {
+                let sit = |side: f32| {
+                    Quaternion::from_angle_z(Rad(-0.078_539_82 * side))
+                        * Quaternion::from_angle_y(Rad(-PI / 10.0 * side))
+                        * Quaternion::from_angle_x(Rad(-1.413_716_7))
+                };
+                (sit(1.0), sit(-1.0), PI / 5.0)
+            } else {
+                (
+                    Quaternion::from_angle_x(Rad(ang as f32)),
+                    Quaternion::from_angle_x(Rad(-ang as f32)),
+                    0.0,
+                )
+            };
 
             mdl.matrix[PlayerModelPart::LegRight as usize] = offset_matrix
                 * Matrix4::from(Decomposed {
                     scale: 1.0,
-                    rot: Quaternion::from_angle_x(Rad(ang as f32)),
-                    disp: Vector3::new(2.0 / 16.0, -12.0 / 16.0, 0.0),
+                    rot: leg_right,
+                    disp: Vector3::new(2.0 / 16.0, -12.0 / 16.0, legs),
                 });
             mdl.matrix[PlayerModelPart::LegLeft as usize] = offset_matrix
                 * Matrix4::from(Decomposed {
                     scale: 1.0,
-                    rot: Quaternion::from_angle_x(Rad(-ang as f32)),
-                    disp: Vector3::new(-2.0 / 16.0, -12.0 / 16.0, 0.0),
+                    rot: leg_left,
+                    disp: Vector3::new(-2.0 / 16.0, -12.0 / 16.0, legs),
                 });
 
             let mut i_time = player_model.idle_time;
@@ -267,12 +326,10 @@ fn update_render_players(
             }
 
             mdl.matrix[PlayerModelPart::ArmRight as usize] = offset_matrix
-                * Matrix4::from_translation(Vector3::new(
-                    6.0 / 16.0,
-                    -12.0 / 16.0 - 12.0 / 16.0,
-                    0.0,
-                ))
-                * Matrix4::from(Quaternion::from_angle_x(Rad(-(ang * 0.75) as f32)))
+                * Matrix4::from_translation(Vector3::new(6.0 / 16.0, neck, 0.0))
+                * Matrix4::from(Quaternion::from_angle_x(Rad(
+                    -(ang * 0.75) as f32 + lean - reach
+                )))
                 * Matrix4::from(Quaternion::from_angle_z(Rad(
                     (i_time.cos() * 0.06 - 0.06) as f32
                 )))
@@ -281,17 +338,23 @@ fn update_render_players(
                     as f32)));
 
             mdl.matrix[PlayerModelPart::ArmLeft as usize] = offset_matrix
-                * Matrix4::from_translation(Vector3::new(
-                    -6.0 / 16.0,
-                    -12.0 / 16.0 - 12.0 / 16.0,
-                    0.0,
-                ))
-                * Matrix4::from(Quaternion::from_angle_x(Rad((ang * 0.75) as f32)))
+                * Matrix4::from_translation(Vector3::new(-6.0 / 16.0, neck, 0.0))
+                * Matrix4::from(Quaternion::from_angle_x(Rad(
+                    (ang * 0.75) as f32 + lean - reach
+                )))
                 * Matrix4::from(Quaternion::from_angle_z(Rad(
                     -(i_time.cos() * 0.06 - 0.06) as f32
                 )))
                 * Matrix4::from(Quaternion::from_angle_x(Rad(-(i_time.sin() * 0.06) as f32)));
 
+            let matrix = mdl.matrix.clone();
+            equipment::update_glint(
+                &mut models,
+                player_model.glint.as_ref(),
+                &matrix,
+                i_time * 4.0,
+            );
+
             let mut update = true;
             if position.moved {
                 player_model.still_time = 0.0;
@@ -323,16 +386,20 @@ fn update_render_players(
 
 pub fn player_added(
     renderer: Res<RendererResource>,
-    mut query: Query<&mut PlayerModel, Added<PlayerModel>>,
+    mut query: Query<(&mut PlayerModel, Option<&Equipment>), Added<PlayerModel>>,
 ) {
     let renderer = &renderer.0;
-    for mut player_model in query.iter_mut() {
-        add_player(renderer.clone(), &mut player_model);
+    for (mut player_model, equipment) in query.iter_mut() {
+        add_player(renderer.clone(), &mut player_model, equipment);
     }
 }
 
 // TODO: Setup culling
-fn add_player(renderer: Arc<Renderer>, player_model: &mut PlayerModel) {
+fn add_player(
+    renderer: Arc<Renderer>,
+    player_model: &mut PlayerModel,
+    equipment: Option<&Equipment>,
+) {
     player_model.dirty.store(false, Ordering::Release);
 
     let skin = if let Some(url) = player_model.skin_url.load().as_ref() {
@@ -452,37 +519,52 @@ fn add_player(renderer: Arc<Renderer>, player_model: &mut PlayerModel) {
         );
     }
 
-    let mut name_verts = vec![];
-    if player_model.has_name_tag {
-        let mut state = FormatState {
-            width: 0.0,
-            offset: 0.0,
-            text: Vec::new(),
-            renderer: renderer.clone(),
-            y_scale: 0.16,
-            x_scale: 0.01,
-        };
-        state.build(&player_model.display_name, Some(format::Color::Black));
-        // TODO: Remove black shadow and add dark, transparent box around name
-        let width = state.width;
-        // Center align text
-        for vert in &mut state.text {
-            vert.x += width * 0.5;
-            vert.r = 64;
-            vert.g = 64;
-            vert.b = 64;
+    let mut glint = vec![vec![]; 6];
+    if let Some(equipment) = equipment {
+        // Where the boxes of the parts start, written the way the cubes of
+        // mob models are
+        let mut parts = [
+            (&mut head_verts, BodyPart::Head, [-4.0, -8.0, -4.0]),
+            (&mut body_verts, BodyPart::Body, [-4.0, -6.0, -2.0]),
+        ];
+        for (i, (verts, part, from)) in parts.iter_mut().enumerate() {
+            if *part == BodyPart::Head && !player_model.has_head {
+                continue;
+            }
+            let (armor, armor_glint) =
+                equipment::armor_vertices(equipment, *part, *from, &renderer);
+            verts.extend(armor);
+            glint[i] = armor_glint;
+        }
+        for (i, part) in [
+            BodyPart::LeftLeg,
+            BodyPart::RightLeg,
+            BodyPart::LeftArm,
+            BodyPart::RightArm,
+        ]
+        .iter()
+        .enumerate()
+        {
+            let (armor, armor_glint) =
+                equipment::armor_vertices(equipment, *part, [-2.0, 0.0, -2.0], &renderer);
+            part_verts[i].extend(armor);
+            glint[i + 2] = armor_glint;
+        }
+        let hand = Vector3::new(0.0, -10.0 / 16.0, -1.0 / 16.0);
+        if let Some(item) = equipment.get(Slot::OffHand) {
+            part_verts[2].extend(held_item_vertices(item, hand, &renderer));
         }
-        name_verts.extend_from_slice(&state.text);
-        for vert in &mut state.text {
-            vert.x -= 0.01;
-            vert.y -= 0.01;
-            vert.z -= 0.05;
-            vert.r = 255;
-            vert.g = 255;
-            vert.b = 255;
+        if let Some(item) = equipment.get(Slot::MainHand) {
+            part_verts[3].extend(held_item_vertices(item, hand, &renderer));
         }
-        name_verts.extend_from_slice(&state.text);
     }
+
+    let name_verts = if player_model.has_name_tag {
+        compute_name_tag(&player_model.display_name, renderer.clone())
+    } else {
+        vec![]
+    };
+    player_model.glint = equipment::create_glint(glint, &renderer);
     let mut model = renderer.clone().models.lock().create_model(
         model::DEFAULT,
         vec![
@@ -529,6 +611,7 @@ pub struct PlayerMovement {
     pub when_last_jump_released: Option<Instant>,
     pub did_touch_ground: bool,
     pub pressed_keys: HashMap<Actionkey, bool, BuildHasherDefault<FNVHash>>,
+    pub body: physics::Body,
 }
 
 impl PlayerMovement {
@@ -536,46 +619,26 @@ impl PlayerMovement {
         Default::default()
     }
 
-    fn calculate_movement(&self, player_yaw: f64) -> (f64, f64, bool) {
-        use std::f64::consts::PI;
-        let mut forward = 0.0f64;
-        let mut yaw = player_yaw - (PI / 2.0);
-        if self.is_key_pressed(Actionkey::Forward) || self.is_key_pressed(Actionkey::Backward) {
-            // TODO: Make walking backwards slower!
-            forward = 1.0;
-            if self.is_key_pressed(Actionkey::Backward) {
-                yaw += PI;
-            }
-        }
-        let change = if self.is_key_pressed(Actionkey::Left) {
-            (PI / 2.0) / (forward.abs() + 1.0)
-        } else if self.is_key_pressed(Actionkey::Right) {
-            -(PI / 2.0) / (forward.abs() + 1.0)
-        } else {
-            0.0
+    /// What the keys held ask the player to do this tick.
+    pub fn input(&self) -> physics::Input {
+        let axis = |positive, negative| {
+            let pressed = |key| if self.is_key_pressed(key) { 1.0 } else { 0.0 };
+            pressed(positive) - pressed(negative)
         };
-        if self.is_key_pressed(Actionkey::Left) || self.is_key_pressed(Actionkey::Right) {
-            forward = 1.0;
-        }
-        if self.is_key_pressed(Actionkey::Backward) {
-            yaw -= change;
-        } else {
-            yaw += change;
+        physics::Input {
+            forward: axis(Actionkey::Forward, Actionkey::Backward),
+            strafe: axis(Actionkey::Left, Actionkey::Right),
+            jump: self.is_key_pressed(Actionkey::Jump),
+            sneak: self.is_key_pressed(Actionkey::Sneak),
+            sprint: self.is_key_pressed(Actionkey::Sprint),
         }
-
-        (
-            forward,
-            yaw,
-            self.is_key_pressed(Actionkey::Forward) && !self.is_key_pressed(Actionkey::Backward),
-        )
     }
 
-    fn is_key_pressed(&self, key: Actionkey) -> bool {
+    pub fn is_key_pressed(&self, key: Actionkey) -> bool {
         self.pressed_keys.get(&key).map_or(false, |v| *v)
     }
 }
 
-// FIXME: look at this to review our impl: https://www.mcpk.wiki/wiki/Movement_Formulas
 #[allow(clippy::type_complexity)]
 #[allow(unused_mut)] // we ignore this warning, as this case seems to be a clippy bug
 pub fn handle_movement(
@@ -591,6 +654,7 @@ pub fn handle_movement(
         &Rotation,
         &GameMode,
         Option<&mut Gravity>,
+        Option<&Vehicle>,
     )>,
 ) {
     let world = &world.0;
@@ -604,6 +668,7 @@ pub fn handle_movement(
         rotation,
         gamemode,
         mut gravity,
+        vehicle,
     ) in query.iter_mut()
     {
         if movement.flying && gravity.is_some() {
@@ -648,230 +713,39 @@ pub fn handle_movement(
             movement.when_last_jump_pressed = None;
         }
 
-        let player_bounds = bounds.bounds;
-
-        let mut last_position = position.position;
-
-        if world.is_chunk_loaded(
-            (position.position.x as i32) >> 4,
-            (position.position.z as i32) >> 4,
-        ) {
-            let (forward, yaw, is_forward) = movement.calculate_movement(rotation.yaw);
-            let mut speed = 0.21585;
-            let mut additional_speed = if movement.is_key_pressed(Actionkey::Sprint) && is_forward {
-                0.2806 - 0.21585
-            } else {
-                0.0
-            };
-            let looking_vec = calculate_looking_vector(rotation.yaw, rotation.pitch);
-            if movement.flying {
-                speed *= 2.5;
-                additional_speed *= 2.5;
-
-                if movement.is_key_pressed(Actionkey::Jump) {
-                    position.position.y += speed + additional_speed;
-                }
-                if movement.is_key_pressed(Actionkey::Sneak) {
-                    position.position.y -= speed + additional_speed;
-                }
-            } else if gravity.as_ref().map_or(false, |v| v.on_ground) {
-                if movement.is_key_pressed(Actionkey::Jump) && velocity.velocity.y.abs() < 0.001 {
-                    velocity.velocity.y = 0.42;
-                }
-            } else {
-                velocity.velocity.y -= 0.08;
-                if velocity.velocity.y < -3.92 {
-                    velocity.velocity.y = -3.92;
-                }
-            }
-            velocity.velocity.y *= 0.98;
-            velocity.velocity.x *= 0.98;
-            velocity.velocity.z *= 0.98;
-            // position.position.x += look_vec.0 * speed;
-            // position.position.z -= look_vec.1 * speed;
-            position.position.x += forward * yaw.cos() * (speed + looking_vec.0 * additional_speed); // TODO: Multiply with speed only for walking forwards
-            position.position.z -= forward * yaw.sin() * (speed + looking_vec.1 * additional_speed);
-            position.position.y += velocity.velocity.y;
-            if (velocity.velocity.x.abs() * 0.2) < 0.005 {
-                velocity.velocity.x = 0.0;
-            }
-
-            if (velocity.velocity.y.abs() * 0.2) < 0.005 {
-                velocity.velocity.y = 0.0;
-            }
-
-            if (velocity.velocity.z.abs() * 0.2) < 0.005 {
-                velocity.velocity.z = 0.0;
-            }
-
-            if !gamemode.noclip() {
-                let mut target = position.position;
-                position.position.y = last_position.y;
-                position.position.z = last_position.z;
-
-                // We handle each axis separately to allow for a sliding
-                // effect when pushing up against walls.
-
-                let (bounds, xhit) =
-                    check_collisions(world, &mut position, &last_position, player_bounds);
-                position.position.x = bounds.min.x + 0.3;
-                last_position.x = position.position.x;
-
-                position.position.z = target.z;
-                let (bounds, zhit) =
-                    check_collisions(world, &mut position, &last_position, player_bounds);
-                position.position.z = bounds.min.z + 0.3;
-                last_position.z = position.position.z;
-
-                // Half block jumps
-                // Minecraft lets you 'jump' up 0.5 blocks
-                // for slabs and stairs (or smaller blocks).
-                // Currently we implement this as a teleport to the
-                // top of the block if we could move there
-                // but this isn't smooth.
-                if (xhit || zhit) && gravity.as_ref().map_or(false, |v| v.on_ground) {
-                    let mut ox = position.position.x;
-                    let mut oz = position.position.z;
-                    position.position.x = target.x;
-                    position.position.z = target.z;
-                    for offset in 1..9 {
-                        let mini = player_bounds.add_v(cgmath::Vector3::new(
-                            0.0,
-                            offset as f64 / 16.0,
-                            0.0,
-                        ));
-                        let (_, hit) = check_collisions(world, &mut position, &last_position, mini);
-                        if !hit {
-                            target.y += offset as f64 / 16.0;
-                            ox = target.x;
-                            oz = target.z;
-                            break;
-                        }
-                    }
-                    position.position.x = ox;
-                    position.position.z = oz;
-                }
-
-                position.position.y = target.y;
-                let (bounds, yhit) =
-                    check_collisions(world, &mut position, &last_position, player_bounds);
-                position.position.y = bounds.min.y;
-                last_position.y = position.position.y;
-                if yhit {
-                    velocity.velocity.y = 0.0;
-                }
-
-                if let Some(mut gravity) = gravity {
-                    let ground =
-                        Aabb3::new(Point3::new(-0.3, -0.005, -0.3), Point3::new(0.3, 0.0, 0.3));
-                    let prev = gravity.on_ground;
-                    let (_, hit) = check_collisions(world, &mut position, &last_position, ground);
-                    gravity.on_ground = hit;
-                    if !prev && gravity.on_ground {
-                        movement.did_touch_ground = true;
-                    }
-                }
+        // Riders sit wherever their vehicle takes them
+        if vehicle.is_none()
+            && world.is_chunk_loaded(
+                (position.position.x as i32) >> 4,
+                (position.position.z as i32) >> 4,
+            )
+        {
+            let input = movement.input();
+            let flying = movement.flying;
+            let body = &mut movement.body;
+            body.position = position.position;
+            body.velocity = velocity.velocity;
+            body.bounds = bounds.bounds;
+            body.flying = flying;
+            body.noclip = gamemode.noclip();
+            physics::tick(body, input, -rotation.yaw.to_degrees(), world.as_ref());
+            position.position = body.position;
+            velocity.velocity = body.velocity;
+            let on_ground = body.on_ground;
+
+            // Landing ends creative flight
+            if on_ground && movement.flying && !gamemode.always_fly() {
+                movement.flying = false;
+                movement.want_to_fly = false;
             }
-        }
-    }
-}
-
-fn calculate_looking_vector(yaw: f64, pitch: f64) -> (f64, f64) {
-    let xz = pitch.to_radians().cos();
-    let x = -xz * yaw.to_radians().sin();
-    let z = xz * yaw.to_radians().cos();
-    (x, z)
-}
-
-fn check_collisions(
-    world: &world::World,
-    position: &mut TargetPosition,
-    last_position: &Vector3<f64>,
-    bounds: Aabb3<f64>,
-) -> (Aabb3<f64>, bool) {
-    let mut bounds = bounds.add_v(position.position);
-
-    let dir = position.position - last_position;
-
-    let min_x = (bounds.min.x - 1.0) as i32;
-    let min_y = (bounds.min.y - 1.0) as i32;
-    let min_z = (bounds.min.z - 1.0) as i32;
-    let max_x = (bounds.max.x + 1.0) as i32;
-    let max_y = (bounds.max.y + 1.0) as i32;
-    let max_z = (bounds.max.z + 1.0) as i32;
-
-    let mut hit = false;
-    for y in min_y..max_y {
-        for z in min_z..max_z {
-            for x in min_x..max_x {
-                let block = world.get_block(BPosition::new(x, y, z));
-                if block.get_material().collidable {
-                    for bb in block.get_collision_boxes() {
-                        let bb = bb.add_v(cgmath::Vector3::new(x as f64, y as f64, z as f64));
-                        if bb.collides(&bounds) {
-                            bounds = bounds.move_out_of(bb, dir);
-                            hit = true;
-                        }
-                    }
+            if let Some(mut gravity) = gravity {
+                if !gravity.on_ground && on_ground {
+                    movement.did_touch_ground = true;
                 }
+                gravity.on_ground = on_ground;
             }
         }
     }
-
-    (bounds, hit)
-}
-
-trait Collidable<T> {
-    fn collides(&self, t: &T) -> bool;
-    fn move_out_of(self, other: Self, dir: cgmath::Vector3<f64>) -> Self;
-}
-
-impl Collidable<Aabb3<f64>> for Aabb3<f64> {
-    fn collides(&self, t: &Aabb3<f64>) -> bool {
-        !(t.min.x >= self.max.x
-            || t.max.x <= self.min.x
-            || t.min.y >= self.max.y
-            || t.max.y <= self.min.y
-            || t.min.z >= self.max.z
-            || t.max.z <= self.min.z)
-    }
-
-    fn move_out_of(mut self, other: Self, dir: cgmath::Vector3<f64>) -> Self {
-        if dir.x != 0.0 {
-            if dir.x > 0.0 {
-                let ox = self.max.x;
-                self.max.x = other.min.x - 0.0001;
-                self.min.x += self.max.x - ox;
-            } else {
-                let ox = self.min.x;
-                self.min.x = other.max.x + 0.0001;
-                self.max.x += self.min.x - ox;
-            }
-        }
-        if dir.y != 0.0 {
-            if dir.y > 0.0 {
-                let oy = self.max.y;
-                self.max.y = other.min.y - 0.0001;
-                self.min.y += self.max.y - oy;
-            } else {
-                let oy = self.min.y;
-                self.min.y = other.max.y + 0.0001;
-                self.max.y += self.min.y - oy;
-            }
-        }
-        if dir.z != 0.0 {
-            if dir.z > 0.0 {
-                let oz = self.max.z;
-                self.max.z = other.min.z - 0.0001;
-                self.min.z += self.max.z - oz;
-            } else {
-                let oz = self.min.z;
-                self.min.z = other.max.z + 0.0001;
-                self.max.z += self.min.z - oz;
-            }
-        }
-        self
-    }
 }
 
 #[derive(Component)]
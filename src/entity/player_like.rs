@@ -1,19 +1,16 @@
-use leafish_protocol::format::Component;
+use cgmath::{Decomposed, Matrix4, Quaternion, Rad, Rotation3, Vector3};
+use leafish_protocol::format::{self, Component};
 
-use crate::entity::resolve_textures;
 use crate::render::model::{self, FormatState, Vertex};
-use crate::render::{Renderer, Texture};
+use crate::render::Renderer;
+use std::f32::consts::PI;
 use std::sync::Arc;
 
-pub enum PlayerLikeModelPart {
-    Head = 0,
-    Body = 1,
-    LegLeft = 2,
-    LegRight = 3,
-    ArmLeft = 4,
-    ArmRight = 5,
-    NameTag = 6,
-}
+/// How much wider the flames of a burning entity are than the entity.
+const FLAME_SCALE: f32 = 1.4;
+/// How far from the middle of the entity its flames are drawn, so they
+/// aren't hidden inside it.
+const FLAME_DEPTH: f32 = 0.3;
 
 /*
 fn update(
@@ -170,155 +167,99 @@ fn update(
         }
     }*/
 
-pub fn compute_player_model_components(
-    tex: &Texture,
-    display_name: Option<&Component>,
-    renderer: Arc<Renderer>,
-) -> Vec<Vec<Vertex>> {
-    // TODO: Replace this shit entirely!
-    macro_rules! srel {
-        ($x:expr, $y:expr, $w:expr, $h:expr) => {
-            Some(tex.relative(
-                ($x) / (tex.get_width() as f32),
-                ($y) / (tex.get_height() as f32),
-                ($w) / (tex.get_width() as f32),
-                ($h) / (tex.get_height() as f32),
-            ))
-        };
+/// The vertices of a name tag above an entity, centered on it.
+pub fn compute_name_tag(display: &Component, renderer: Arc<Renderer>) -> Vec<Vertex> {
+    let mut name_verts = vec![];
+    let mut state = FormatState {
+        width: 0.0,
+        offset: 0.0,
+        text: Vec::new(),
+        renderer,
+        y_scale: 0.16,
+        x_scale: 0.01,
+    };
+    state.build(display, Some(format::Color::Black));
+    // TODO: Remove black shadow and add dark, transparent box around name
+    let width = state.width;
+    // Center align text
+    for vert in &mut state.text {
+        vert.x += width * 0.5;
+        vert.r = 64;
+        vert.g = 64;
+        vert.b = 64;
     }
-
-    let mut head_verts = vec![];
-    model::append_box(
-        &mut head_verts,
-        -4.0 / 16.0,
-        0.0,
-        -4.0 / 16.0,
-        8.0 / 16.0,
-        8.0 / 16.0,
-        8.0 / 16.0,
-        resolve_textures(tex, 8.0, 8.0, 8.0, 0.0, 0.0),
-    );
-    model::append_box(
-        &mut head_verts,
-        -4.2 / 16.0,
-        -0.2 / 16.0,
-        -4.2 / 16.0,
-        8.4 / 16.0,
-        8.4 / 16.0,
-        8.4 / 16.0,
-        resolve_textures(tex, 8.0, 8.0, 8.0, 32.0, 0.0),
-    );
-
-    let mut body_verts = vec![];
-    model::append_box(
-        &mut body_verts,
-        -4.0 / 16.0,
-        -6.0 / 16.0,
-        -2.0 / 16.0,
-        8.0 / 16.0,
-        12.0 / 16.0,
-        4.0 / 16.0,
-        resolve_textures(tex, 8.0, 12.0, 4.0, 16.0, 16.0),
-    );
-    model::append_box(
-        &mut body_verts,
-        -4.2 / 16.0,
-        -6.2 / 16.0,
-        -2.2 / 16.0,
-        8.4 / 16.0,
-        12.4 / 16.0,
-        4.4 / 16.0,
-        resolve_textures(tex, 8.0, 12.0, 4.0, 16.0, 16.0),
-    );
-
-    let mut part_verts = vec![vec![]; 4];
-
-    for (i, offsets) in [
-        [0.0, 16.0, 0.0, 32.0],   // Left leg
-        [0.0, 16.0, 0.0, 32.0],   // Right Leg
-        [40.0, 16.0, 40.0, 32.0], // Left arm
-        [40.0, 16.0, 40.0, 32.0], // Right arm
-    ]
-    .iter()
-    .enumerate()
-    {
-        let (ox, oy) = (offsets[0], offsets[1]);
-        model::append_box(
-            &mut part_verts[i],
-            -2.0 / 16.0,
-            -12.0 / 16.0,
-            -2.0 / 16.0,
-            4.0 / 16.0,
-            12.0 / 16.0,
-            4.0 / 16.0,
-            [
-                srel!(ox + 8.0, oy + 0.0, 4.0, 4.0),   // Down
-                srel!(ox + 4.0, oy + 0.0, 4.0, 4.0),   // Up
-                srel!(ox + 4.0, oy + 4.0, 4.0, 12.0),  // North
-                srel!(ox + 12.0, oy + 4.0, 4.0, 12.0), // South
-                srel!(ox + 8.0, oy + 4.0, 4.0, 12.0),  // West
-                srel!(ox + 0.0, oy + 4.0, 4.0, 12.0),  // East
-            ],
-        );
-        let (ox, oy) = (offsets[2], offsets[3]);
-        model::append_box(
-            &mut part_verts[i],
-            -2.2 / 16.0,
-            -12.2 / 16.0,
-            -2.2 / 16.0,
-            4.4 / 16.0,
-            12.4 / 16.0,
-            4.4 / 16.0,
-            [
-                srel!(ox + 8.0, oy + 0.0, 4.0, 4.0),   // Down
-                srel!(ox + 4.0, oy + 0.0, 4.0, 4.0),   // Up
-                srel!(ox + 4.0, oy + 4.0, 4.0, 12.0),  // North
-                srel!(ox + 12.0, oy + 4.0, 4.0, 12.0), // South
-                srel!(ox + 8.0, oy + 4.0, 4.0, 12.0),  // West
-                srel!(ox + 0.0, oy + 4.0, 4.0, 12.0),  // East
-            ],
-        );
+    name_verts.extend_from_slice(&state.text);
+    for vert in &mut state.text {
+        vert.x -= 0.01;
+        vert.y -= 0.01;
+        vert.z -= 0.05;
+        vert.r = 255;
+        vert.g = 255;
+        vert.b = 255;
     }
+    name_verts.extend_from_slice(&state.text);
+    name_verts
+}
 
-    let mut name_verts = vec![];
-    if let Some(display) = display_name {
-        let mut state = FormatState {
-            width: 0.0,
-            offset: 0.0,
-            text: Vec::new(),
-            renderer,
-            y_scale: 0.16,
-            x_scale: 0.01,
-        };
-        state.build(display, None);
-        // TODO: Remove black shadow and add dark, transparent box around name
-        let width = state.width;
-        // Center align text
-        for vert in &mut state.text {
-            vert.x += width * 0.5;
-            vert.r = 64;
-            vert.g = 64;
-            vert.b = 64;
-        }
-        name_verts.extend_from_slice(&state.text);
-        for vert in &mut state.text {
-            vert.x -= 0.01;
-            vert.y -= 0.01;
-            vert.z -= 0.05;
-            vert.r = 255;
-            vert.g = 255;
-            vert.b = 255;
+/// Creates the flames around a burning entity of the size, layers of fire
+/// stacked up its height.
+pub fn create_flames(width: f32, height: f32, renderer: &Arc<Renderer>) -> model::ModelHandle {
+    let size = width * FLAME_SCALE;
+    let layers = (height / size).ceil().max(1.0) as usize;
+    let mut verts = vec![];
+    for layer in 0..layers {
+        let tex = Renderer::get_texture(
+            renderer.get_textures_ref(),
+            &format!("minecraft:block/fire_{}", layer % 2),
+        );
+        let bottom = layer as f32 * size;
+        // In front of and behind the entity, the one away from the camera
+        // is hidden by it. Both windings so either can be seen.
+        for &z in &[-FLAME_DEPTH, FLAME_DEPTH] {
+            for &flip in &[false, true] {
+                for &(x, y) in &[(0.0, 0.0), (0.0, 1.0), (1.0, 0.0), (1.0, 1.0)] {
+                    let x = if flip { 1.0 - x } else { x };
+                    verts.push(Vertex {
+                        x: (x as f32 - 0.5) * size,
+                        y: -(bottom + y as f32 * size),
+                        z,
+                        texture_x: x,
+                        texture_y: 1.0 - y,
+                        texture: tex.clone(),
+                        r: 255,
+                        g: 255,
+                        b: 255,
+                        a: 255,
+                        id: 0,
+                    });
+                }
+            }
         }
-        name_verts.extend_from_slice(&state.text);
     }
+    renderer
+        .clone()
+        .models
+        .lock()
+        .create_model(model::DEFAULT, vec![verts], renderer.clone())
+}
 
-    vec![
-        head_verts,
-        body_verts,
-        part_verts[0].clone(),
-        part_verts[1].clone(),
-        part_verts[2].clone(),
-        part_verts[3].clone(),
-        name_verts,
-    ]
+/// Puts the flames on the entity at `position`, turned towards the camera.
+pub fn update_flames(
+    models: &mut model::Manager,
+    flames: Option<&model::ModelHandle>,
+    position: Vector3<f64>,
+    camera_yaw: f64,
+) {
+    let flames = match flames.and_then(|flames| models.get_model(flames)) {
+        Some(flames) => flames,
+        None => return,
+    };
+    // Fire lights itself
+    flames.block_light = 15.0;
+    flames.sky_light = 15.0;
+    flames.matrix[0] = Matrix4::from(Decomposed {
+        scale: 1.0,
+        rot: Quaternion::from_angle_y(Rad(PI + camera_yaw as f32)),
+        disp: Vector3::new(position.x as f32, -position.y as f32, position.z as f32),
+    });
 }
@@ -4,28 +4,73 @@ use crate::particle::block_break_effect::{BlockBreakEffect, BlockEffectData};
 use crate::server::{ConnResource, InventoryContextResource, RendererResource, WorldResource};
 use crate::shared::Position as BPos;
 use crate::world::World;
+use bevy_ecs::schedule::SystemConfigs;
 use cgmath::InnerSpace;
 use leafish_blocks::Block;
+use leafish_protocol::item::EnchantmentTy;
 use leafish_protocol::protocol;
 use leafish_protocol::protocol::packet;
 use leafish_protocol::types::GameMode;
 use parking_lot::RwLock;
 use shared::Direction;
 
-pub fn apply_velocity(mut query: Query<(&mut Position, &Velocity), Without<PlayerMovement>>) {
-    // Player's handle their own physics
-    for (mut pos, vel) in query.iter_mut() {
-        pos.position += vel.velocity;
-    }
+/// Moves remote entities each frame, in this order:
+///
+/// 1. `lerp_position` and `lerp_rotation` bring entities to where the server
+///    last put them, over the ticks their type takes.
+/// 2. `apply_velocity` then moves the entities that got there with their own
+///    velocity, slowed by drag and pulled down by gravity onto the ground,
+///    until the server moves them again.
+pub fn movement() -> SystemConfigs {
+    (lerp_position, lerp_rotation, apply_velocity).chain()
 }
 
-pub fn apply_gravity(mut query: Query<&mut Velocity, (Without<PlayerMovement>, With<Gravity>)>) {
+pub fn apply_velocity(
+    game_info: Res<GameInfo>,
+    world: Option<Res<WorldResource>>,
+    mut query: Query<
+        (
+            &mut Position,
+            &mut Velocity,
+            &TargetPosition,
+            &EntityType,
+            Option<&Gravity>,
+        ),
+        Without<PlayerMovement>,
+    >,
+) {
     // Player's handle their own physics
-    for mut vel in query.iter_mut() {
-        vel.velocity.y -= 0.03;
-        if vel.velocity.y < -0.3 {
-            vel.velocity.y = -0.3;
+    let ticks = game_info.delta / 3.0;
+    for (mut pos, mut vel, target, entity_type, gravity) in query.iter_mut() {
+        // Still catching up with the server, which already moved it
+        if target.ticks > 0.0 {
+            continue;
         }
+        let physics = match entity_type.physics() {
+            Some(physics) => physics,
+            None => continue,
+        };
+        // Only the ground stops entities here, so ones at rest stay put
+        if vel.velocity.magnitude2() == 0.0 {
+            continue;
+        }
+        let from = pos.position;
+        pos.position += vel.velocity * ticks;
+        let floor = match &world {
+            Some(world) if vel.velocity.y < 0.0 => {
+                super::physics::floor_below(world.0.as_ref(), from, pos.position)
+            }
+            _ => None,
+        };
+        if let Some(floor) = floor {
+            pos.position.y = floor;
+            vel.velocity.y = 0.0;
+        } else if gravity.is_some_and(|gravity| gravity.on_ground) {
+            vel.velocity.y = vel.velocity.y.max(0.0);
+        } else {
+            vel.velocity.y -= physics.gravity * ticks;
+        }
+        vel.velocity *= physics.drag.powf(ticks);
     }
 }
 
@@ -36,7 +81,25 @@ pub fn update_last_position(mut query: Query<&mut Position>) {
     }
 }
 
-pub fn lerp_position(game_info: Res<GameInfo>, mut query: Query<(&mut Position, &TargetPosition)>) {
+pub fn lerp_position(
+    game_info: Res<GameInfo>,
+    mut query: Query<(&mut Position, &mut TargetPosition), Without<PlayerMovement>>,
+) {
+    let ticks = game_info.delta / 3.0;
+    for (mut pos, mut target) in query.iter_mut() {
+        if target.ticks <= 0.0 {
+            continue;
+        }
+        let step = (ticks / target.ticks).min(1.0);
+        pos.position = pos.position + (target.position - pos.position) * step;
+        target.ticks = (target.ticks - ticks).max(0.0);
+    }
+}
+
+pub fn follow_local_player(
+    game_info: Res<GameInfo>,
+    mut query: Query<(&mut Position, &TargetPosition), With<PlayerMovement>>,
+) {
     let delta = game_info.delta.min(5.0);
     for (mut pos, target_pos) in query.iter_mut() {
         pos.position =
@@ -48,30 +111,59 @@ pub fn lerp_position(game_info: Res<GameInfo>, mut query: Query<(&mut Position,
     }
 }
 
+pub fn animate_pickup(
+    mut commands: Commands,
+    game_info: Res<GameInfo>,
+    mut query: Query<(Entity, &mut Pickup, &mut Position)>,
+    collectors: Query<&Position, Without<Pickup>>,
+) {
+    for (entity, mut pickup, mut pos) in query.iter_mut() {
+        pickup.ticks += game_info.delta / 3.0;
+        let collector = match collectors.get(pickup.collector) {
+            Ok(collector) => collector,
+            Err(_) => {
+                commands.entity(entity).despawn();
+                continue;
+            }
+        };
+        if pickup.ticks >= Pickup::DURATION {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        // Speeds up as it gets closer, aiming a bit above the collector's feet
+        let target = collector.position + Vector3::new(0.0, 0.5, 0.0);
+        let t = (pickup.ticks / Pickup::DURATION).powi(2);
+        pos.position = pickup.start + (target - pickup.start) * t;
+    }
+}
+
 pub fn lerp_rotation(
     game_info: Res<GameInfo>,
     mut query: Query<(&mut Rotation, &mut TargetRotation)>,
 ) {
     use std::f64::consts::PI;
-    let delta = game_info.delta.min(5.0);
+    let ticks = game_info.delta / 3.0;
     for (mut rot, mut target_rot) in query.iter_mut() {
-        target_rot.yaw = (PI * 2.0 + target_rot.yaw) % (PI * 2.0);
-        target_rot.pitch = (PI * 2.0 + target_rot.pitch) % (PI * 2.0);
-
-        let mut delta_yaw = target_rot.yaw - rot.yaw;
-        let mut delta_pitch = target_rot.pitch - rot.pitch;
-
-        if delta_yaw.abs() > PI {
-            delta_yaw = (PI - delta_yaw.abs()) * delta_yaw.signum();
-        }
-        if delta_pitch.abs() > PI {
-            delta_pitch = (PI - delta_pitch.abs()) * delta_pitch.signum();
+        if target_rot.ticks <= 0.0 {
+            continue;
         }
+        let step = (ticks / target_rot.ticks).min(1.0);
+        rot.yaw += shortest_angle(target_rot.yaw - rot.yaw) * step;
+        rot.pitch += shortest_angle(target_rot.pitch - rot.pitch) * step;
+        rot.yaw = rot.yaw.rem_euclid(PI * 2.0);
+        rot.pitch = rot.pitch.rem_euclid(PI * 2.0);
+        target_rot.ticks = (target_rot.ticks - ticks).max(0.0);
+    }
+}
 
-        rot.yaw += delta_yaw * 0.2 * delta;
-        rot.pitch += delta_pitch * 0.2 * delta;
-        rot.yaw = (PI * 2.0 + rot.yaw) % (PI * 2.0);
-        rot.pitch = (PI * 2.0 + rot.pitch) % (PI * 2.0);
+/// The angle between -pi and pi that turns the same way.
+fn shortest_angle(angle: f64) -> f64 {
+    use std::f64::consts::PI;
+    let angle = angle.rem_euclid(PI * 2.0);
+    if angle > PI {
+        angle - PI * 2.0
+    } else {
+        angle
     }
 }
 
@@ -123,7 +215,7 @@ pub fn apply_digging(
     conn: Res<ConnResource>,
     inventory: Res<InventoryContextResource>,
     commands: Commands,
-    mut query: Query<(&MouseButtons, &GameMode, &mut Digging)>,
+    mut query: Query<(&MouseButtons, &GameMode, &Effects, &Gravity, &mut Digging)>,
     mut effect_query: Query<&mut BlockBreakEffect>,
 ) {
     use crate::server::target::{test_block, trace_ray};
@@ -134,26 +226,51 @@ pub fn apply_digging(
     let conn = &conn.0;
     let inventory = &inventory.0;
 
+    let eye = renderer.camera.lock().pos;
     let target = trace_ray(
         world.as_ref(),
         4.0,
-        renderer.camera.lock().pos.to_vec(),
+        eye.to_vec(),
         renderer.view_vector.lock().cast().unwrap(),
         test_block,
     );
 
-    let tool = {
+    let (tool, efficiency, aqua_affinity) = {
         let inventory = inventory.read();
         let hotbar_index = inventory.hotbar_index;
         let inventory = inventory.base_slots.read();
         let item = inventory.get_item(27 + hotbar_index as u16);
-        item.and_then(|i| i.material.as_tool())
+        let helmet = inventory.get_item(5);
+        (
+            item.as_ref().and_then(|i| i.material.as_tool()),
+            item.map_or(0, |i| {
+                i.stack.meta.enchantment_level(EnchantmentTy::Efficiency)
+            }),
+            helmet.map_or(0, |i| {
+                i.stack.meta.enchantment_level(EnchantmentTy::AquaAffinity)
+            }) > 0,
+        )
     };
+    let eye_block = world.get_block(BPos::new(
+        eye.x.floor() as i32,
+        eye.y.floor() as i32,
+        eye.z.floor() as i32,
+    ));
+    let submerged = matches!(eye_block, Block::Water { .. }) || eye_block.is_waterlogged();
 
     let mut system = ApplyDigging::new(target, conn.clone(), commands, tool);
 
-    for (mouse_buttons, game_mode, mut digging) in query.iter_mut() {
+    for (mouse_buttons, game_mode, effects, gravity, mut digging) in query.iter_mut() {
         if game_mode.can_interact_with_world() {
+            system.modifiers = block::MiningModifiers {
+                efficiency: efficiency.clamp(0, u8::MAX as i16) as u8,
+                haste: effects.level(HASTE_EFFECT),
+                mining_fatigue: effects.level(MINING_FATIGUE_EFFECT),
+                submerged,
+                aqua_affinity,
+                on_ground: gravity.on_ground,
+                creative: *game_mode == GameMode::Creative,
+            };
             if let Some(effect) = digging.effect {
                 if let Ok(mut effect) = effect_query.get_mut(effect) {
                     system.update(
@@ -175,6 +292,7 @@ struct ApplyDigging<'w, 's> {
     conn: Arc<RwLock<Option<protocol::Conn>>>,
     commands: Commands<'w, 's>,
     tool: Option<block::Tool>,
+    modifiers: block::MiningModifiers,
 }
 
 impl ApplyDigging<'_, '_> {
@@ -189,6 +307,7 @@ impl ApplyDigging<'_, '_> {
             conn,
             commands,
             tool,
+            modifiers: Default::default(),
         }
     }
 
@@ -209,14 +328,18 @@ impl ApplyDigging<'_, '_> {
             // Start the new digging operation.
             (None, Some(current)) => {
                 self.start_digging(current, &mut digging.effect);
-                if current.is_finished(&self.tool) == DiggingFinishState::FinishedInstant {
+                if current.is_finished(&self.tool, &self.modifiers)
+                    == DiggingFinishState::FinishedInstant
+                {
                     current.finished = true;
                     self.finish_digging(current, &mut digging.effect, world, false);
                 }
             }
             // Cancel the previous digging operation.
             (Some(last), None) if !last.finished => {
-                if last.is_finished(&self.tool) == DiggingFinishState::FinishedInstant {
+                if last.is_finished(&self.tool, &self.modifiers)
+                    == DiggingFinishState::FinishedInstant
+                {
                     self.start_digging(last, &mut digging.effect);
                     self.finish_digging(last, &mut digging.effect, world, false);
                 } else {
@@ -227,7 +350,9 @@ impl ApplyDigging<'_, '_> {
             (Some(last), Some(current)) if last.position != current.position => {
                 // Cancel the previous digging operation.
                 if !last.finished {
-                    if last.is_finished(&self.tool) == DiggingFinishState::FinishedInstant {
+                    if last.is_finished(&self.tool, &self.modifiers)
+                        == DiggingFinishState::FinishedInstant
+                    {
                         // Finish the previous digging operation
                         self.start_digging(last, &mut digging.effect);
                         self.finish_digging(last, &mut digging.effect, world, false);
@@ -237,13 +362,15 @@ impl ApplyDigging<'_, '_> {
                 }
                 // Start the new digging operation.
                 self.start_digging(current, &mut digging.effect);
-                if current.is_finished(&self.tool) == DiggingFinishState::FinishedInstant {
+                if current.is_finished(&self.tool, &self.modifiers)
+                    == DiggingFinishState::FinishedInstant
+                {
                     current.finished = true;
                     self.finish_digging(current, &mut digging.effect, world, false);
                 }
             }
             // Finish the new digging operation.
-            (Some(_), Some(current)) => match current.is_finished(&self.tool) {
+            (Some(_), Some(current)) => match current.is_finished(&self.tool, &self.modifiers) {
                 DiggingFinishState::Finished => {
                     current.finished = true;
                     self.finish_digging(current, &mut digging.effect, world, true);
@@ -259,7 +386,7 @@ impl ApplyDigging<'_, '_> {
         if let Some(current) = &digging.current {
             // Update the block break animation progress.
             if let Some(effect) = effect {
-                effect.update_ratio(current.get_ratio(&self.tool));
+                effect.update_ratio(current.get_ratio(&self.tool, &self.modifiers));
             }
             self.swing_arm();
         }
@@ -362,3 +489,125 @@ impl ApplyDigging<'_, '_> {
         packet::send_arm_swing(conn.as_mut().unwrap(), packet::Hand::MainHand).unwrap();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Runs the movement systems for `ticks` frames of one tick each.
+    fn run(world: &mut bevy_ecs::world::World, ticks: usize) {
+        let mut sched = Schedule::default();
+        sched.add_systems(movement());
+        for _ in 0..ticks {
+            sched.run(world);
+        }
+    }
+
+    fn spawn(world: &mut bevy_ecs::world::World, entity_type: EntityType) -> Entity {
+        world.insert_resource(GameInfo { delta: 3.0 });
+        let mut entity = world.spawn((
+            Position::zero(),
+            Rotation::zero(),
+            Velocity::zero(),
+            TargetPosition::zero(),
+            TargetRotation::zero(),
+            entity_type,
+        ));
+        if entity_type.physics().is_some() {
+            entity.insert(Gravity::new());
+        }
+        entity.id()
+    }
+
+    #[test]
+    fn interpolates_over_three_ticks() {
+        let mut world = bevy_ecs::world::World::new();
+        let entity = spawn(&mut world, EntityType::Zombie);
+        move_to(&mut world.entity_mut(entity), Vector3::new(3.0, 0.0, 0.0));
+        rotate_to(&mut world.entity_mut(entity), 1.5, 0.0);
+        run(&mut world, 1);
+        let pos = world.get::<Position>(entity).unwrap().position;
+        assert!((pos.x - 1.0).abs() < 1e-9);
+        run(&mut world, 2);
+        let pos = world.get::<Position>(entity).unwrap().position;
+        assert!((pos.x - 3.0).abs() < 1e-9);
+        assert!((world.get::<Rotation>(entity).unwrap().yaw - 1.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn rotates_the_short_way() {
+        use std::f64::consts::PI;
+        assert!((shortest_angle(PI * 1.5) + PI * 0.5).abs() < 1e-9);
+        assert!((shortest_angle(-PI * 1.5) - PI * 0.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn teleports_snap() {
+        let mut world = bevy_ecs::world::World::new();
+        let entity = spawn(&mut world, EntityType::Zombie);
+        // Fast ones are still interpolated
+        move_to(&mut world.entity_mut(entity), Vector3::new(20.0, 0.0, 0.0));
+        assert_eq!(world.get::<Position>(entity).unwrap().position.x, 0.0);
+        move_to(&mut world.entity_mut(entity), Vector3::new(100.0, 0.0, 0.0));
+        assert_eq!(world.get::<Position>(entity).unwrap().position.x, 100.0);
+        assert_eq!(world.get::<TargetPosition>(entity).unwrap().ticks, 0.0);
+    }
+
+    #[test]
+    fn velocity_waits_for_interpolation() {
+        let mut world = bevy_ecs::world::World::new();
+        let entity = spawn(&mut world, EntityType::Zombie);
+        world.get_mut::<Velocity>(entity).unwrap().velocity = Vector3::new(1.0, 0.0, 0.0);
+        move_to(&mut world.entity_mut(entity), Vector3::new(3.0, 0.0, 0.0));
+        run(&mut world, 5);
+        // Mobs without physics only go where the server puts them
+        assert_eq!(world.get::<Position>(entity).unwrap().position.x, 3.0);
+
+        let arrow = spawn(&mut world, EntityType::Arrow);
+        world.get_mut::<TargetPosition>(arrow).unwrap().ticks = 2.0;
+        world.get_mut::<Velocity>(arrow).unwrap().velocity = Vector3::new(1.0, 0.0, 0.0);
+        run(&mut world, 1);
+        assert_eq!(world.get::<Position>(arrow).unwrap().position.x, 0.0);
+    }
+
+    #[test]
+    fn projectiles_fall_and_slow_down() {
+        let mut world = bevy_ecs::world::World::new();
+        let arrow = spawn(&mut world, EntityType::Arrow);
+        world.get_mut::<Velocity>(arrow).unwrap().velocity = Vector3::new(1.0, 0.0, 0.0);
+        run(&mut world, 1);
+        let pos = world.get::<Position>(arrow).unwrap().position;
+        assert_eq!(pos.x, 1.0);
+        let vel = world.get::<Velocity>(arrow).unwrap().velocity;
+        assert!((vel.x - 0.99).abs() < 1e-9);
+        assert!((vel.y + 0.05 * 0.99).abs() < 1e-9);
+
+        world.get_mut::<Gravity>(arrow).unwrap().on_ground = true;
+        run(&mut world, 1);
+        assert_eq!(world.get::<Velocity>(arrow).unwrap().velocity.y, 0.0);
+    }
+
+    #[test]
+    fn falling_lands_on_the_floor() {
+        let blocks = World::new(754, crossbeam_channel::unbounded().0);
+        blocks.set_block(BPos::new(1, 0, 0), Block::Stone {});
+        blocks.set_block(BPos::new(0, 2, 0), Block::SoulSand {});
+        let mut world = bevy_ecs::world::World::new();
+        world.insert_resource(WorldResource(Arc::new(blocks)));
+        let arrow = spawn(&mut world, EntityType::Arrow);
+        world.get_mut::<Position>(arrow).unwrap().position = Vector3::new(0.5, 4.0, 0.5);
+        world.get_mut::<Velocity>(arrow).unwrap().velocity = Vector3::new(0.0, -0.5, 0.0);
+        run(&mut world, 1);
+        // Still above the soul sand
+        assert_eq!(world.get::<Position>(arrow).unwrap().position.y, 3.5);
+        world.get_mut::<Velocity>(arrow).unwrap().velocity = Vector3::new(0.0, -1.0, 0.0);
+        run(&mut world, 1);
+        assert_eq!(world.get::<Position>(arrow).unwrap().position.y, 2.875);
+        assert_eq!(world.get::<Velocity>(arrow).unwrap().velocity.y, 0.0);
+
+        world.get_mut::<Position>(arrow).unwrap().position = Vector3::new(1.5, 4.0, 0.5);
+        world.get_mut::<Velocity>(arrow).unwrap().velocity = Vector3::new(0.0, -5.0, 0.0);
+        run(&mut world, 1);
+        assert_eq!(world.get::<Position>(arrow).unwrap().position.y, 1.0);
+    }
+}
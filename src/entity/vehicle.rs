@@ -0,0 +1,583 @@
+//! Entities riding others, like players in boats and minecarts or on horses
+//! and pigs.
+//!
+//! Before 1.9 the server moves every vehicle, the driver only tells it which
+//! keys are held. Since then the local player moves the boats and horses it
+//! steers itself, and reports where they went. Pigs and striders go where
+//! the server puts them either way.
+
+use super::metadata::{self, Paddles, Saddled};
+use super::physics;
+use super::player::PlayerMovement;
+use super::{
+    Bounds, EntityType, MovementSpeed, Position, Rotation, TargetPosition, TargetRotation, Velocity,
+};
+use crate::ecs::SystemExecStage;
+use crate::server::WorldResource;
+use bevy_ecs::prelude::*;
+use cgmath::{Vector3, Zero};
+use shared::Version;
+
+pub fn add_systems(sched: &mut Schedule, render_sched: &mut Schedule) {
+    sched.add_systems(drive.in_set(SystemExecStage::Normal));
+    render_sched.add_systems(
+        ride.in_set(SystemExecStage::Render)
+            .after(SystemExecStage::Normal)
+            .after(super::systems::apply_velocity)
+            .after(super::systems::follow_local_player)
+            .before(super::mob::update_mob_models),
+    );
+}
+
+/// The entity an entity rides.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Vehicle(pub Entity);
+
+/// The entities riding an entity, the first one steers it.
+#[derive(Component, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Passengers(pub Vec<Entity>);
+
+/// A vehicle the local player moves itself.
+#[derive(Component, Clone, Debug)]
+pub enum Driven {
+    Boat(physics::Boat),
+    Mount(physics::Body),
+}
+
+impl Driven {
+    /// How the local player drives a vehicle of the type, if it does.
+    /// Horses can only be steered with a saddle on.
+    fn new(
+        entity_type: EntityType,
+        saddled: bool,
+        position: Vector3<f64>,
+        yaw: f64,
+    ) -> Option<Self> {
+        if entity_type == EntityType::Boat {
+            return Some(Driven::Boat(physics::Boat::new(
+                position,
+                -yaw.to_degrees(),
+            )));
+        }
+        if !metadata::is_horse(entity_type) || !saddled {
+            return None;
+        }
+        let mut body = physics::Body::default();
+        body.position = position;
+        body.step_height = 1.0;
+        Some(Driven::Mount(body))
+    }
+}
+
+/// Replaces who rides `vehicle`, like the server tells since 1.9.
+pub fn set_passengers(
+    world: &mut World,
+    version: Version,
+    vehicle: Entity,
+    passengers: Vec<Entity>,
+) {
+    let old = world
+        .get::<Passengers>(vehicle)
+        .map(|old| old.0.clone())
+        .unwrap_or_default();
+    for passenger in old {
+        if !passengers.contains(&passenger)
+            && world.get::<Vehicle>(passenger) == Some(&Vehicle(vehicle))
+        {
+            world.entity_mut(passenger).remove::<Vehicle>();
+        }
+    }
+    for &passenger in &passengers {
+        // Getting in means getting out of anything else
+        match world.get::<Vehicle>(passenger) {
+            Some(&Vehicle(other)) if other != vehicle => leave(world, version, other, passenger),
+            _ => {}
+        }
+        world.entity_mut(passenger).insert(Vehicle(vehicle));
+    }
+
+    let driver = passengers.first().copied();
+    let mut vehicle = world.entity_mut(vehicle);
+    if passengers.is_empty() {
+        vehicle.remove::<Passengers>();
+    } else {
+        vehicle.insert(Passengers(passengers));
+    }
+    drive_if_local(&mut vehicle, version, driver);
+}
+
+/// Puts `passenger` in `vehicle`, or takes it out of what it rides with
+/// `None`, like the server tells before 1.9.
+pub fn attach(world: &mut World, version: Version, passenger: Entity, vehicle: Option<Entity>) {
+    if let Some(&Vehicle(old)) = world.get::<Vehicle>(passenger) {
+        leave(world, version, old, passenger);
+    }
+    match vehicle {
+        Some(vehicle) => {
+            world.entity_mut(passenger).insert(Vehicle(vehicle));
+            let mut vehicle = world.entity_mut(vehicle);
+            match vehicle.get_mut::<Passengers>() {
+                Some(mut passengers) => passengers.0.push(passenger),
+                None => {
+                    vehicle.insert(Passengers(vec![passenger]));
+                }
+            }
+            let driver = vehicle
+                .get::<Passengers>()
+                .and_then(|passengers| passengers.0.first().copied());
+            drive_if_local(&mut vehicle, version, driver);
+        }
+        None => {
+            world.entity_mut(passenger).remove::<Vehicle>();
+        }
+    }
+}
+
+/// Unties the entity from what it rides and what rides it, before it's
+/// removed.
+pub fn forget(world: &mut World, version: Version, entity: Entity) {
+    if let Some(&Vehicle(vehicle)) = world.get::<Vehicle>(entity) {
+        leave(world, version, vehicle, entity);
+    }
+    let passengers = world
+        .get::<Passengers>(entity)
+        .map(|passengers| passengers.0.clone())
+        .unwrap_or_default();
+    for passenger in passengers {
+        if world.get::<Vehicle>(passenger) == Some(&Vehicle(entity)) {
+            world.entity_mut(passenger).remove::<Vehicle>();
+        }
+    }
+}
+
+/// Takes `passenger` off the list of `vehicle`.
+fn leave(world: &mut World, version: Version, vehicle: Entity, passenger: Entity) {
+    let mut vehicle = match world.get_entity_mut(vehicle) {
+        Some(vehicle) => vehicle,
+        None => return,
+    };
+    let empty = match vehicle.get_mut::<Passengers>() {
+        Some(mut passengers) => {
+            passengers.0.retain(|&other| other != passenger);
+            passengers.0.is_empty()
+        }
+        None => false,
+    };
+    if empty {
+        vehicle.remove::<Passengers>();
+    }
+    let driver = vehicle
+        .get::<Passengers>()
+        .and_then(|passengers| passengers.0.first().copied());
+    drive_if_local(&mut vehicle, version, driver);
+}
+
+/// Checks again whether the local player drives the vehicle, after its
+/// saddle was put on or taken off.
+pub fn update_driver(vehicle: &mut EntityWorldMut, version: Version) {
+    if let Some(passengers) = vehicle.get::<Passengers>() {
+        let driver = passengers.0.first().copied();
+        drive_if_local(vehicle, version, driver);
+    }
+}
+
+/// Lets the local player move the vehicle while it's the driver, and the
+/// server again once it isn't. Before 1.9 the server always moves it.
+fn drive_if_local(vehicle: &mut EntityWorldMut, version: Version, driver: Option<Entity>) {
+    let local = version >= Version::V1_9
+        && driver.is_some_and(|driver| {
+            vehicle.world_scope(|world| world.get::<PlayerMovement>(driver).is_some())
+        });
+    let entity_type = vehicle.get::<EntityType>().copied();
+    let saddled = vehicle.get::<Saddled>().is_some_and(|saddled| saddled.0);
+    let position = vehicle.get::<Position>().map(|position| position.position);
+    let yaw = vehicle.get::<Rotation>().map(|rotation| rotation.yaw);
+    let driven = match (entity_type, position, yaw) {
+        (Some(entity_type), Some(position), Some(yaw)) if local => {
+            Driven::new(entity_type, saddled, position, yaw)
+        }
+        _ => None,
+    };
+    match driven {
+        Some(_) if vehicle.contains::<Driven>() => {}
+        Some(driven) => {
+            vehicle.insert(driven);
+        }
+        None => {
+            vehicle.remove::<Driven>();
+        }
+    }
+}
+
+/// How high above a vehicle its passengers sit.
+fn seat_height(vehicle_type: EntityType, vehicle_height: f64) -> f64 {
+    match vehicle_type {
+        EntityType::Boat => -0.1,
+        _ if vehicle_type.is_minecart() => 0.0,
+        _ => vehicle_height * 0.75,
+    }
+}
+
+/// Where the `seat`th of `seats` passengers sits, relative to the vehicle.
+fn seat_offset(
+    vehicle_type: EntityType,
+    vehicle_height: f64,
+    yaw: f64,
+    rider_type: EntityType,
+    seat: usize,
+    seats: usize,
+) -> Vector3<f64> {
+    // Players sit lower, with their legs out in front
+    let sitting = if rider_type == EntityType::Player {
+        -0.35
+    } else {
+        0.0
+    };
+    // Two sit in a boat, one behind the other
+    let along = match (vehicle_type, seat) {
+        (EntityType::Boat, 0) if seats > 1 => 0.2,
+        (EntityType::Boat, _) if seats > 1 => -0.6,
+        _ => 0.0,
+    };
+    let angle = yaw - std::f64::consts::FRAC_PI_2;
+    Vector3::new(
+        along * angle.cos(),
+        seat_height(vehicle_type, vehicle_height) + sitting,
+        -along * angle.sin(),
+    )
+}
+
+/// Moves the vehicles the local player drives by a tick, the same way
+/// vanilla does.
+#[allow(clippy::type_complexity)]
+pub fn drive(
+    world: Res<WorldResource>,
+    mut commands: Commands,
+    mut drivers: Query<(&PlayerMovement, &Vehicle, &mut Rotation)>,
+    mut vehicles: Query<
+        (
+            &mut Driven,
+            &mut TargetPosition,
+            &mut TargetRotation,
+            Option<&Bounds>,
+            Option<&Paddles>,
+            Option<&MovementSpeed>,
+        ),
+        Without<PlayerMovement>,
+    >,
+) {
+    let world = &world.0;
+    for (movement, vehicle, mut rotation) in drivers.iter_mut() {
+        let (mut driven, mut target, mut target_rotation, bounds, paddles, speed) =
+            match vehicles.get_mut(vehicle.0) {
+                Ok(driven) => driven,
+                Err(_) => continue,
+            };
+        if !world.is_chunk_loaded(
+            (target.position.x as i32) >> 4,
+            (target.position.z as i32) >> 4,
+        ) {
+            continue;
+        }
+        let input = movement.input();
+        let body = match &mut *driven {
+            Driven::Boat(boat) => {
+                if let Some(bounds) = bounds {
+                    boat.body.bounds = bounds.bounds;
+                }
+                let turned = boat.tick(input, world.as_ref());
+                // The boat turns its passengers along with it
+                rotation.yaw -= turned.to_radians();
+                target_rotation.yaw = -boat.yaw.to_radians();
+                let rowing = Paddles {
+                    left: boat.paddles[0],
+                    right: boat.paddles[1],
+                };
+                if paddles != Some(&rowing) {
+                    commands.entity(vehicle.0).insert(rowing);
+                }
+                &boat.body
+            }
+            Driven::Mount(body) => {
+                if let Some(bounds) = bounds {
+                    body.bounds = bounds.bounds;
+                }
+                // The server picks how fast each horse is
+                if let Some(speed) = speed {
+                    body.walk_speed = speed.0;
+                }
+                // Horses go where the rider looks, slower sideways and
+                // backwards
+                let input = physics::Input {
+                    forward: if input.forward > 0.0 {
+                        input.forward
+                    } else {
+                        input.forward * 0.25
+                    },
+                    strafe: input.strafe * 0.5,
+                    ..physics::Input::default()
+                };
+                physics::tick(body, input, -rotation.yaw.to_degrees(), world.as_ref());
+                target_rotation.yaw = rotation.yaw;
+                &*body
+            }
+        };
+        target.position = body.position;
+        target.ticks = 1.0;
+        target_rotation.ticks = 1.0;
+    }
+}
+
+/// Where the server put the vehicle the local player drives, when it
+/// disagrees with where the player moved it.
+pub fn teleport(world: &mut World, vehicle: Entity, position: Vector3<f64>, yaw: f64) {
+    let mut vehicle = match world.get_entity_mut(vehicle) {
+        Some(vehicle) => vehicle,
+        None => return,
+    };
+    match vehicle.get_mut::<Driven>().as_deref_mut() {
+        Some(Driven::Boat(boat)) => {
+            boat.body.position = position;
+            boat.body.velocity = Vector3::zero();
+            boat.yaw = -yaw.to_degrees();
+        }
+        Some(Driven::Mount(body)) => {
+            body.position = position;
+            body.velocity = Vector3::zero();
+        }
+        None => return,
+    }
+    if let Some(mut target) = vehicle.get_mut::<TargetPosition>() {
+        target.position = position;
+        target.ticks = 0.0;
+    }
+    if let Some(mut target) = vehicle.get_mut::<TargetRotation>() {
+        target.yaw = yaw;
+        target.ticks = 0.0;
+    }
+    if let Some(mut current) = vehicle.get_mut::<Position>() {
+        current.position = position;
+    }
+    if let Some(mut rotation) = vehicle.get_mut::<Rotation>() {
+        rotation.yaw = yaw;
+    }
+}
+
+/// Puts riders on the seat of their vehicle, after the vehicles moved.
+#[allow(clippy::type_complexity)]
+pub fn ride(
+    mut commands: Commands,
+    mut positions: Query<&mut Position>,
+    mut riders: Query<(
+        Entity,
+        &Vehicle,
+        &EntityType,
+        &mut TargetPosition,
+        &mut Velocity,
+    )>,
+    vehicles: Query<(&Passengers, &EntityType, &Rotation, Option<&Bounds>)>,
+) {
+    for (rider, vehicle, rider_type, mut target, mut velocity) in riders.iter_mut() {
+        let (passengers, vehicle_type, rotation, bounds) = match vehicles.get(vehicle.0) {
+            Ok(vehicle) => vehicle,
+            Err(_) => {
+                // The vehicle is gone without the rider getting off
+                commands.entity(rider).remove::<Vehicle>();
+                continue;
+            }
+        };
+        let base = match positions.get(vehicle.0) {
+            Ok(position) => position.position,
+            Err(_) => continue,
+        };
+        let height = bounds.map_or(0.0, |bounds| bounds.bounds.max.y - bounds.bounds.min.y);
+        let seat = passengers
+            .0
+            .iter()
+            .position(|&passenger| passenger == rider)
+            .unwrap_or(0);
+        let seat = base
+            + seat_offset(
+                *vehicle_type,
+                height,
+                rotation.yaw,
+                *rider_type,
+                seat,
+                passengers.0.len(),
+            );
+        if let Ok(mut position) = positions.get_mut(rider) {
+            position.position = seat;
+        }
+        target.position = seat;
+        target.ticks = 0.0;
+        velocity.velocity = Vector3::zero();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spawn(world: &mut World, entity_type: EntityType) -> Entity {
+        world
+            .spawn((entity_type, Position::zero(), Rotation::zero()))
+            .id()
+    }
+
+    #[test]
+    fn passengers_get_on_and_off() {
+        let mut world = World::new();
+        let boat = spawn(&mut world, EntityType::Boat);
+        let first = spawn(&mut world, EntityType::Player);
+        let second = spawn(&mut world, EntityType::Zombie);
+
+        set_passengers(&mut world, Version::V1_16, boat, vec![first, second]);
+        assert_eq!(world.get::<Vehicle>(first), Some(&Vehicle(boat)));
+        assert_eq!(world.get::<Vehicle>(second), Some(&Vehicle(boat)));
+        // Only the local player drives
+        assert!(world.get::<Driven>(boat).is_none());
+
+        set_passengers(&mut world, Version::V1_16, boat, vec![second]);
+        assert_eq!(world.get::<Vehicle>(first), None);
+        assert_eq!(
+            world.get::<Passengers>(boat),
+            Some(&Passengers(vec![second]))
+        );
+
+        set_passengers(&mut world, Version::V1_16, boat, vec![]);
+        assert_eq!(world.get::<Vehicle>(second), None);
+        assert_eq!(world.get::<Passengers>(boat), None);
+    }
+
+    #[test]
+    fn attaching_moves_between_vehicles() {
+        let mut world = World::new();
+        let pig = spawn(&mut world, EntityType::Pig);
+        let minecart = spawn(&mut world, EntityType::Minecart);
+        let rider = spawn(&mut world, EntityType::Player);
+
+        attach(&mut world, Version::V1_8, rider, Some(pig));
+        assert_eq!(world.get::<Passengers>(pig), Some(&Passengers(vec![rider])));
+        attach(&mut world, Version::V1_8, rider, Some(minecart));
+        assert_eq!(world.get::<Passengers>(pig), None);
+        assert_eq!(
+            world.get::<Passengers>(minecart),
+            Some(&Passengers(vec![rider]))
+        );
+        attach(&mut world, Version::V1_8, rider, None);
+        assert_eq!(world.get::<Vehicle>(rider), None);
+        assert_eq!(world.get::<Passengers>(minecart), None);
+    }
+
+    #[test]
+    fn forgetting_unties_both_ways() {
+        let mut world = World::new();
+        let horse = spawn(&mut world, EntityType::Horse);
+        let rider = spawn(&mut world, EntityType::Player);
+        set_passengers(&mut world, Version::V1_16, horse, vec![rider]);
+
+        forget(&mut world, Version::V1_16, horse);
+        world.despawn(horse);
+        assert_eq!(world.get::<Vehicle>(rider), None);
+
+        let boat = spawn(&mut world, EntityType::Boat);
+        set_passengers(&mut world, Version::V1_16, boat, vec![rider]);
+        forget(&mut world, Version::V1_16, rider);
+        assert_eq!(world.get::<Passengers>(boat), None);
+    }
+
+    #[test]
+    fn local_player_drives() {
+        let mut world = World::new();
+        let boat = spawn(&mut world, EntityType::Boat);
+        let pig = spawn(&mut world, EntityType::Pig);
+        let player = spawn(&mut world, EntityType::Player);
+        world.entity_mut(player).insert(PlayerMovement::new());
+
+        set_passengers(&mut world, Version::V1_16, boat, vec![player]);
+        assert!(matches!(world.get::<Driven>(boat), Some(Driven::Boat(_))));
+        set_passengers(&mut world, Version::V1_16, pig, vec![player]);
+        assert!(world.get::<Driven>(boat).is_none());
+        // The server keeps moving pigs
+        assert!(world.get::<Driven>(pig).is_none());
+    }
+
+    #[test]
+    fn horses_need_a_saddle() {
+        let mut world = World::new();
+        let horse = spawn(&mut world, EntityType::Horse);
+        let player = spawn(&mut world, EntityType::Player);
+        world.entity_mut(player).insert(PlayerMovement::new());
+
+        set_passengers(&mut world, Version::V1_16, horse, vec![player]);
+        assert!(world.get::<Driven>(horse).is_none());
+
+        let mut entity = world.entity_mut(horse);
+        entity.insert(Saddled(true));
+        update_driver(&mut entity, Version::V1_16);
+        assert!(matches!(world.get::<Driven>(horse), Some(Driven::Mount(_))));
+
+        let mut entity = world.entity_mut(horse);
+        entity.insert(Saddled(false));
+        update_driver(&mut entity, Version::V1_16);
+        assert!(world.get::<Driven>(horse).is_none());
+    }
+
+    #[test]
+    fn horse_speed_from_attributes() {
+        use leafish_protocol::protocol::mapped_packet::EntityProperty;
+        use leafish_protocol::protocol::packet::PropertyModifier;
+
+        let modifier = |amount, operation| PropertyModifier {
+            uuid: Default::default(),
+            amount,
+            operation,
+        };
+        let properties = vec![
+            EntityProperty {
+                key: "minecraft:generic.max_health".to_owned(),
+                value: 20.0,
+                modifiers: vec![],
+            },
+            EntityProperty {
+                key: "minecraft:generic.movement_speed".to_owned(),
+                value: 0.2,
+                modifiers: vec![modifier(0.5, 2), modifier(0.1, 0), modifier(0.5, 1)],
+            },
+        ];
+        let speed = MovementSpeed::from_properties(&properties).unwrap();
+        assert!((speed.0 - 0.675).abs() < 1e-9);
+        assert_eq!(MovementSpeed::from_properties(&properties[..1]), None);
+    }
+
+    #[test]
+    fn server_drives_before_1_9() {
+        let mut world = World::new();
+        let boat = spawn(&mut world, EntityType::Boat);
+        let horse = spawn(&mut world, EntityType::Horse);
+        let player = spawn(&mut world, EntityType::Player);
+        world.entity_mut(player).insert(PlayerMovement::new());
+
+        attach(&mut world, Version::V1_8, player, Some(boat));
+        assert_eq!(world.get::<Vehicle>(player), Some(&Vehicle(boat)));
+        assert!(world.get::<Driven>(boat).is_none());
+        set_passengers(&mut world, Version::V1_8, horse, vec![player]);
+        assert!(world.get::<Driven>(horse).is_none());
+        // The same player drives from 1.9 on
+        attach(&mut world, Version::V1_9, player, Some(boat));
+        assert!(matches!(world.get::<Driven>(boat), Some(Driven::Boat(_))));
+    }
+
+    #[test]
+    fn seats() {
+        let seat = seat_offset(EntityType::Horse, 1.6, 0.0, EntityType::Player, 0, 1);
+        assert!((seat.y - 0.85).abs() < 1e-9);
+        let seat = seat_offset(EntityType::Minecart, 0.7, 0.0, EntityType::Zombie, 0, 1);
+        assert_eq!(seat, Vector3::zero());
+        // Facing south, the driver sits in front
+        let front = seat_offset(EntityType::Boat, 0.5625, 0.0, EntityType::Player, 0, 2);
+        let back = seat_offset(EntityType::Boat, 0.5625, 0.0, EntityType::Player, 1, 2);
+        assert!(front.z > 0.0 && back.z < 0.0);
+        assert!((front.y + 0.45).abs() < 1e-9);
+    }
+}
@@ -46,3 +46,51 @@ pub fn to_entity_type(id: i16, version: Version) -> EntityType {
         _ => EntityType::Unknown,
     }
 }
+
+/// The entity type of an object or vehicle spawned with `SpawnObject`, which
+/// had ids of its own before 1.14. `data` tells minecarts apart.
+pub fn object_to_entity_type(id: i16, data: i32, version: Version) -> EntityType {
+    if version >= Version::V1_14 {
+        return to_entity_type(id, version);
+    }
+    match id {
+        1 => EntityType::Boat,
+        2 => EntityType::DroppedItem,
+        3 => EntityType::AreaEffectCloud,
+        10 => match data {
+            1 => EntityType::MinecartChest,
+            2 => EntityType::MinecartFurnace,
+            3 => EntityType::MinecartTnt,
+            4 => EntityType::MinecartMobSpawner,
+            5 => EntityType::MinecartHopper,
+            6 => EntityType::MinecartCommand,
+            _ => EntityType::Minecart,
+        },
+        50 => EntityType::PrimedTnt,
+        51 => EntityType::EnderCrystal,
+        60 if version >= Version::V1_9 => EntityType::TippedArrow,
+        60 => EntityType::Arrow,
+        61 => EntityType::Snowball,
+        62 => EntityType::Egg,
+        63 => EntityType::Fireball,
+        64 => EntityType::SmallFireball,
+        65 => EntityType::EnderPearl,
+        66 => EntityType::WitherSkull,
+        67 => EntityType::ShulkerBullet,
+        68 => EntityType::LlamaSpit,
+        70 => EntityType::FallingBlock,
+        71 => EntityType::ItemFrame,
+        72 => EntityType::EnderSignal,
+        73 => EntityType::SplashPotion,
+        75 => EntityType::ThrownExpBottle,
+        76 => EntityType::Firework,
+        77 => EntityType::LeashHitch,
+        78 => EntityType::ArmorStand,
+        79 => EntityType::EvokerFangs,
+        90 => EntityType::FishingHook,
+        91 => EntityType::SpectralArrow,
+        93 => EntityType::DragonFireball,
+        94 => EntityType::Trident,
+        _ => EntityType::Unknown,
+    }
+}
@@ -0,0 +1,196 @@
+use crate::inventory::slot_mapping::SlotMapping;
+use crate::inventory::{Inventory, InventoryType, Item};
+use crate::render::hud::Hud;
+use crate::render::inventory::InventoryWindow;
+use crate::render::Renderer;
+use crate::ui;
+use crate::ui::{Container, HAttach, VAttach};
+use std::sync::Arc;
+
+use parking_lot::RwLock;
+
+const WINDOW_WIDTH: i32 = 176;
+const WINDOW_HEIGHT: i32 = 166;
+
+pub struct HorseInventory {
+    slots: SlotMapping,
+    client_state_id: i16,
+    slot_count: u8,
+    armor: bool,
+    name: String,
+    id: i32,
+}
+
+impl HorseInventory {
+    pub fn new(
+        renderer: &Arc<Renderer>,
+        base_slots: Arc<RwLock<SlotMapping>>,
+        slot_count: u8,
+        armor: bool,
+        name: String,
+        id: i32,
+    ) -> Self {
+        let mut slots = SlotMapping::new((WINDOW_WIDTH, WINDOW_HEIGHT));
+        let count = slot_count as u16;
+        slots.set_child(base_slots, (8, 84), (count..count + 36).collect());
+
+        // Saddle and armor
+        slots.add_slot(0, (8, 18));
+        slots.add_slot(1, (8, 36));
+
+        // Chest, three rows of however many columns the horse carries
+        let columns = chest_columns(slot_count) as u16;
+        for y in 0..3 {
+            for x in 0..columns {
+                slots.add_slot(
+                    2 + x + y * columns,
+                    (80 + x as i32 * 18, 18 + y as i32 * 18),
+                );
+            }
+        }
+
+        slots.update_icons(renderer, (0, 0), None);
+
+        Self {
+            slots,
+            client_state_id: 0,
+            slot_count,
+            armor,
+            name,
+            id,
+        }
+    }
+}
+
+fn chest_columns(slot_count: u8) -> u8 {
+    slot_count.saturating_sub(2) / 3
+}
+
+impl Inventory for HorseInventory {
+    fn size(&self) -> u16 {
+        self.slots.size()
+    }
+
+    fn id(&self) -> i32 {
+        self.id
+    }
+
+    fn get_client_state_id(&self) -> i16 {
+        self.client_state_id
+    }
+
+    fn set_client_state_id(&mut self, client_state_id: i16) {
+        self.client_state_id = client_state_id;
+    }
+
+    fn get_item(&self, slot_id: u16) -> Option<Item> {
+        self.slots.get_item(slot_id)
+    }
+
+    fn set_item(&mut self, slot_id: u16, item: Option<Item>) {
+        self.slots.set_item(slot_id, item);
+    }
+
+    fn get_slot(&self, x: f64, y: f64) -> Option<u16> {
+        self.slots.get_slot(x, y)
+    }
+
+    fn init(
+        &mut self,
+        renderer: &Arc<Renderer>,
+        ui_container: &mut Container,
+        inventory_window: &mut InventoryWindow,
+    ) {
+        inventory_window.elements.push(vec![]); // Window texture
+        inventory_window.elements.push(vec![]); // Horse slots
+        inventory_window.elements.push(vec![]); // Base slots
+        inventory_window.text_elements.push(vec![]);
+
+        let basic_elements = inventory_window.elements.get_mut(0).unwrap();
+        let basic_text_elements = inventory_window.text_elements.get_mut(0).unwrap();
+
+        let center = renderer.screen_data.read().center();
+        let icon_scale = Hud::icon_scale(renderer);
+        let x = center.0 as f64 - icon_scale * WINDOW_WIDTH as f64 / 2.0;
+        let y = center.1 as f64 - icon_scale * WINDOW_HEIGHT as f64 / 2.0;
+
+        // Horse texture
+        basic_elements.push(
+            ui::ImageBuilder::new()
+                .texture_coords((0.0, 0.0, WINDOW_WIDTH as f64, WINDOW_HEIGHT as f64))
+                .position(x, y)
+                .alignment(ui::VAttach::Top, ui::HAttach::Left)
+                .size(
+                    icon_scale * WINDOW_WIDTH as f64,
+                    icon_scale * WINDOW_HEIGHT as f64,
+                )
+                .texture("minecraft:gui/container/horse")
+                .create(ui_container),
+        );
+
+        // Slot backgrounds, the chest ones only as wide as the chest
+        let columns = chest_columns(self.slot_count) as f64;
+        let mut backgrounds = vec![((18.0, 220.0, 18.0, 18.0), (7.0, 17.0))];
+        if self.armor {
+            backgrounds.push(((0.0, 220.0, 18.0, 18.0), (7.0, 35.0)));
+        }
+        if columns > 0.0 {
+            backgrounds.push((
+                (0.0, WINDOW_HEIGHT as f64, columns * 18.0, 54.0),
+                (79.0, 17.0),
+            ));
+        }
+        for (coords, offset) in backgrounds {
+            basic_elements.push(
+                ui::ImageBuilder::new()
+                    .texture_coords(coords)
+                    .position(x + icon_scale * offset.0, y + icon_scale * offset.1)
+                    .alignment(ui::VAttach::Top, ui::HAttach::Left)
+                    .size(icon_scale * coords.2, icon_scale * coords.3)
+                    .texture("minecraft:gui/container/horse")
+                    .create(ui_container),
+            );
+        }
+
+        // Title text
+        basic_text_elements.push(
+            ui::TextBuilder::new()
+                .alignment(VAttach::Top, HAttach::Left)
+                .scale_x(icon_scale / 2.0)
+                .scale_y(icon_scale / 2.0)
+                .position(x + icon_scale * 8.0, y + icon_scale * 6.0)
+                .text(self.name.as_str())
+                .colour((64, 64, 64, 255))
+                .shadow(false)
+                .create(ui_container),
+        );
+
+        // Inventory text
+        basic_text_elements.push(
+            ui::TextBuilder::new()
+                .alignment(VAttach::Top, HAttach::Left)
+                .scale_x(icon_scale / 2.0)
+                .scale_y(icon_scale / 2.0)
+                .position(x + icon_scale * 8.0, y + icon_scale * 72.0)
+                .text("Inventory")
+                .colour((64, 64, 64, 255))
+                .shadow(false)
+                .create(ui_container),
+        );
+
+        self.slots.update_icons(renderer, (0, 0), None);
+    }
+
+    fn tick(
+        &mut self,
+        renderer: &Arc<Renderer>,
+        ui_container: &mut Container,
+        inventory_window: &mut InventoryWindow,
+    ) {
+        self.slots.tick(renderer, ui_container, inventory_window, 1);
+    }
+
+    fn ty(&self) -> InventoryType {
+        InventoryType::Horse(self.slot_count, self.armor)
+    }
+}
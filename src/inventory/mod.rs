@@ -7,6 +7,7 @@ pub mod dropper;
 pub mod enchanting_table;
 pub mod furnace;
 pub mod grindstone;
+pub mod horse;
 pub(crate) mod material;
 pub mod player_inventory;
 pub mod slot_mapping;
@@ -20,6 +21,7 @@ use crate::inventory::dropper::DropperInventory;
 use crate::inventory::enchanting_table::EnchantmentTableInventory;
 use crate::inventory::furnace::FurnaceInventory;
 use crate::inventory::grindstone::GrindStoneInventory;
+use crate::inventory::horse::HorseInventory;
 use crate::inventory::player_inventory::PlayerInventory;
 use crate::inventory::slot_mapping::SlotMapping;
 use crate::render::hud::{Hud, HudContext};
@@ -158,6 +160,14 @@ pub fn inventory_from_type(
         InventoryType::Grindstone => Arc::new(RwLock::new(GrindStoneInventory::new(
             renderer, base_slots, id,
         ))),
+        InventoryType::Horse(slot_count, armor) => Arc::new(RwLock::new(HorseInventory::new(
+            renderer,
+            base_slots,
+            slot_count,
+            armor,
+            title.to_string(),
+            id,
+        ))),
         /*
         InventoryType::Lectern => {}
         InventoryType::Loom => {}
@@ -165,8 +175,7 @@ pub fn inventory_from_type(
         InventoryType::ShulkerBox => {}
         InventoryType::SmithingTable => {}
         InventoryType::CartographyTable => {}
-        InventoryType::Stonecutter => {}
-        InventoryType::Horse => {}*/
+        InventoryType::Stonecutter => {}*/
         _ => return None,
     })
 }
@@ -552,7 +561,7 @@ pub enum InventoryType {
     Smoker,
     CartographyTable,
     Stonecutter,
-    Horse,
+    Horse(u8, bool), // slots, whether it wears armor
 }
 
 impl InventoryType {
@@ -615,6 +624,7 @@ impl InventoryType {
             "minecraft:furnace" => InventoryType::Furnace,
             "minecraft:hopper" => InventoryType::Hopper,
             "minecraft:shulker_box" => InventoryType::ShulkerBox,
+            "EntityHorse" => InventoryType::Horse(slot_count, true),
             _ => {
                 warn!("Unhandled inventory type {name}");
                 return None;
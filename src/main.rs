@@ -36,7 +36,7 @@ use glutin::surface::SwapInterval;
 use glutin_winit::DisplayBuilder;
 use glutin_winit::GlWindow;
 use instant::Duration;
-use leafish_protocol::protocol::login::AccountType;
+use leafish_protocol::protocol::login::{AccountType, AuthErrorKind};
 use log::{debug, error, info, warn};
 use raw_window_handle::HasRawWindowHandle;
 use shared::Version;
@@ -77,7 +77,9 @@ pub mod resources;
 pub mod screen;
 pub mod server;
 pub mod settings;
+pub mod sound;
 pub mod ui;
+pub mod viewer;
 pub mod world;
 
 use crate::entity::Rotation;
@@ -106,10 +108,13 @@ pub struct Game {
     should_close: AtomicBool,
 
     server: ArcSwapOption<server::Server>,
+    /// A local save being looked at instead of playing on a server.
+    viewer: ArcSwapOption<viewer::Viewer>,
     focused: AtomicBool,
     chunk_builder: Mutex<chunk_builder::ChunkBuilder>,
 
     connect_error: ArcSwapOption<Error>,
+    connecting: Mutex<Option<PendingConnection>>,
 
     last_mouse_x: AtomicF64,
     last_mouse_y: AtomicF64,
@@ -203,16 +208,32 @@ impl Game {
         self.ctrl_pressed.store(pressed, Ordering::Release);
     }
 
+    /// Starts connecting to `address` in the background, reporting progress
+    /// through `status`. The result is picked up by `tick_connect`.
     pub fn connect_to(
         &self,
         address: &str,
         hud_context: Arc<RwLock<HudContext>>,
-    ) -> Result<(), Error> {
+        status: Arc<RwLock<String>>,
+    ) {
+        let (send, recv) = crossbeam_channel::bounded(1);
+        *self.connecting.lock() = Some(PendingConnection {
+            result: recv,
+            hud_context: hud_context.clone(),
+        });
         let default_protocol_version = self.settings.get_int(IntSetting::DefaultProtocolVersion);
-        let (protocol_version, forge_mods, fml_network_version) =
-            match protocol::Conn::new(address, default_protocol_version)
-                .and_then(|conn| conn.do_status())
-            {
+        let chunk_cache_size = self.settings.get_int(IntSetting::ChunkCacheSize);
+        let address = address.to_owned();
+        let resources = self.resource_manager.clone();
+        let renderer = self.renderer.clone();
+        let block_models = self.chunk_builder.lock().models();
+        let screen_sys = self.screen_sys.clone();
+        let account = self.current_account.clone();
+        thread::spawn(move || {
+            *status.write() = "Pinging server".to_owned();
+            let ping = protocol::Conn::new(&address, default_protocol_version)
+                .and_then(|conn| conn.do_status());
+            let (protocol_version, forge_mods, fml_network_version) = match ping {
                 Ok(res) => {
                     info!(
                         "Detected server protocol version {}",
@@ -232,49 +253,139 @@ impl Game {
                     (default_protocol_version, vec![], None)
                 }
             };
-        if !Version::from_id(protocol_version as u32).is_supported() {
-            return Err(Error::Err(format!(
-                "The server's version isn't supported!\n(protocol version: {})",
-                protocol_version
-            )));
-        }
-        let address = address.to_owned();
-        let resources = self.resource_manager.clone();
-        let renderer = self.renderer.clone();
-        let screen_sys = self.screen_sys.clone();
-        let account = self.current_account.clone();
-        let result = thread::spawn(move || {
-            server::Server::connect(
-                resources,
-                account.lock().as_ref().unwrap(),
-                &address,
-                protocol_version,
-                forge_mods,
-                fml_network_version,
-                renderer,
-                hud_context.clone(),
-                screen_sys,
-            )
-        })
-        .join();
-        match result {
-            Ok(result) => {
-                match result {
-                    Ok(srv) => {
-                        self.server.store(Some(srv));
-                        Ok(())
-                    }
-                    Err(err) => {
-                        let str = err.to_string();
-                        self.connect_error.store(Some(Arc::new(err)));
-                        // self.server.disconnect_reason = Some(Component::from_string(&*err.to_string()));
-                        Err(Error::Err(str))
+            if !Version::from_id(protocol_version as u32).is_supported() {
+                let _ = send.send(Err(Error::Err(format!(
+                    "The server's version isn't supported!\n(protocol version: {})",
+                    protocol_version
+                ))));
+                return;
+            }
+            let connect = || {
+                *status.write() = "Logging in".to_owned();
+                server::Server::connect(
+                    resources.clone(),
+                    account.lock().as_ref().unwrap(),
+                    &address,
+                    protocol_version,
+                    forge_mods.clone(),
+                    fml_network_version,
+                    renderer.clone(),
+                    block_models.clone(),
+                    hud_context.clone(),
+                    screen_sys.clone(),
+                    chunk_cache_size,
+                )
+            };
+            let mut result = connect();
+            if let Err(Error::Auth(err)) = &result {
+                if err.kind == AuthErrorKind::InvalidToken {
+                    *status.write() = "Reloading session from the launcher".to_owned();
+                    let current = account.lock().clone().unwrap();
+                    if let Some(reloaded) = reload_from_launcher(current) {
+                        *account.lock() = Some(reloaded);
+                        result = connect();
                     }
                 }
             }
-            Err(_) => Err(Error::Err("Unknown".to_string())),
+            let _ = send.send(result);
+        });
+    }
+
+    /// Finishes a connection started by `connect_to` once its thread is
+    /// done, either entering the game or returning to the server list.
+    fn tick_connect(&self) {
+        let mut connecting = self.connecting.lock();
+        let result = match connecting.as_ref().map(|pending| pending.result.try_recv()) {
+            Some(Ok(result)) => result,
+            Some(Err(crossbeam_channel::TryRecvError::Disconnected)) => {
+                Err(Error::Err("Unknown".to_string()))
+            }
+            _ => return,
+        };
+        let pending = connecting.take().unwrap();
+        drop(connecting);
+        self.screen_sys.pop_screen();
+        match result {
+            Ok(srv) => {
+                self.apply_biome_blend(&srv.world);
+                self.server.store(Some(srv));
+                self.screen_sys
+                    .add_screen(Box::new(render::hud::Hud::new(pending.hud_context)));
+            }
+            Err(err) => {
+                let msg = err.to_string();
+                self.connect_error.store(Some(Arc::new(err)));
+                self.screen_sys
+                    .add_screen(Box::new(screen::ServerList::new(Some(
+                        format::Component::new(format::ComponentType::new(&msg, None)),
+                    ))));
+            }
         }
     }
+
+    /// Opens the save in `dir` in the world viewer.
+    pub fn open_viewer(&self, dir: &std::path::Path) -> Result<(), Error> {
+        let viewer = viewer::Viewer::open(dir, self.renderer.clone())?;
+        self.screen_sys
+            .replace_screen(Box::new(screen::local_worlds::Viewing::new(&viewer.name)));
+        self.apply_biome_blend(&viewer.world);
+        self.viewer.store(Some(viewer));
+        Ok(())
+    }
+
+    /// Applies the biome blend setting to `world`, which rebuilds its
+    /// sections if the radius changed.
+    pub fn apply_biome_blend(&self, world: &world::World) {
+        world.set_biome_blend(
+            self.settings
+                .get_int(IntSetting::BiomeBlend)
+                .clamp(0, world::MAX_BIOME_BLEND as i32) as u8,
+        );
+    }
+
+    /// Leaves the world viewer, back to the server list.
+    pub fn close_viewer(&self) {
+        if let Some(viewer) = self.viewer.swap(None) {
+            viewer.close();
+            self.chunk_builder.lock().reset();
+            self.renderer.reset();
+        }
+        self.screen_sys
+            .replace_screen(Box::new(screen::ServerList::new(None)));
+    }
+}
+
+struct PendingConnection {
+    result: crossbeam_channel::Receiver<Result<Arc<server::Server>, Error>>,
+    hud_context: Arc<RwLock<HudContext>>,
+}
+
+/// Picks up a newer copy of a Microsoft account from the launcher it was
+/// imported from, after the session server rejected its access token.
+///
+/// This isn't a token refresh: Microsoft accounts are never logged in by us,
+/// so there's no refresh token to ask for a new one with. It only helps once
+/// the launcher has refreshed the token itself.
+fn reload_from_launcher(account: Account) -> Option<Account> {
+    if account.account_type != AccountType::Microsoft {
+        return None;
+    }
+    let token = account.verification_tokens.get(2).cloned();
+    let reloaded = screen::import::find_accounts()
+        .into_iter()
+        .map(|imported| imported.account)
+        .find(|imported| {
+            imported.account_type == AccountType::Microsoft
+                && imported.uuid == account.uuid
+                && imported.verification_tokens.get(2) != token.as_ref()
+        });
+    if reloaded.is_none() {
+        warn!("The launcher has no newer token for {}", account.name);
+    }
+    reloaded.map(|imported| Account {
+        verification_tokens: imported.verification_tokens,
+        ..account
+    })
 }
 
 #[derive(StructOpt, Debug)]
@@ -306,10 +417,8 @@ struct Opt {
 // TODO: Simplify error messages in server list.
 // TODO: Render skin of players joining after one self.
 // TODO: Implement arm swing animation!
-// TODO: Implement attacking entities!
 // TODO: Fix cursor grabbing/visibility/transparency of window.
 // TODO: Improve clouds.
-// TODO: Fix pistons.
 fn main() {
     let opt = Opt::from_args();
     #[allow(clippy::arc_with_non_send_sync)]
@@ -448,6 +557,7 @@ fn main() {
             verification_tokens: vec![name, "".to_string(), token],
             head_img_data: None,
             account_type: AccountType::Microsoft,
+            skin_path: None,
         });
     }
     screen_sys.add_screen(Box::new(screen::launcher::Launcher::new(
@@ -485,6 +595,7 @@ fn main() {
 
     let game = Game {
         server: ArcSwapOption::empty(),
+        viewer: ArcSwapOption::empty(),
         focused: AtomicBool::new(false),
         renderer: Arc::new(renderer),
         screen_sys,
@@ -493,6 +604,7 @@ fn main() {
         should_close: AtomicBool::new(false),
         chunk_builder: Mutex::new(chunk_builder::ChunkBuilder::new(resource_manager, textures)),
         connect_error: ArcSwapOption::empty(),
+        connecting: Mutex::new(None),
         last_mouse_x: AtomicF64::new(0.0),
         last_mouse_y: AtomicF64::new(0.0),
         last_mouse_xrel: AtomicF64::new(0.0),
@@ -587,6 +699,7 @@ fn tick_all(
     last_resource_version: &mut usize,
     vsync: bool,
 ) {
+    game.tick_connect();
     let server = game.server.load();
     if let Some(server) = server.as_ref() {
         if !server.is_connected() {
@@ -605,7 +718,7 @@ fn tick_all(
             game.server.store(None);
             game.renderer.reset();
         }
-    } else {
+    } else if game.viewer.load().is_none() {
         game.chunk_builder.lock().reset();
     }
     let now = Instant::now();
@@ -644,17 +757,31 @@ fn tick_all(
     if let Some(server) = game.server.load().as_ref() {
         server.tick(delta, game);
     }
+    if let Some(viewer) = game.viewer.load().as_ref() {
+        viewer.tick(delta, game);
+    }
 
     // Check if window is valid, it might be minimized
     if physical_width == 0 || physical_height == 0 {
         return;
     }
 
-    if let Some(server) = game.server.load().as_ref() {
+    let world = game
+        .server
+        .load()
+        .as_ref()
+        .map(|server: &Arc<server::Server>| server.world.clone())
+        .or_else(|| {
+            game.viewer
+                .load()
+                .as_ref()
+                .map(|viewer: &Arc<viewer::Viewer>| viewer.world.clone())
+        });
+    if let Some(world) = world.as_ref() {
         game.renderer.update_camera(physical_width, physical_height);
         game.chunk_builder
             .lock()
-            .tick(server.world.clone(), game.renderer.clone(), version);
+            .tick(world.clone(), game.renderer.clone(), version);
     } else if game.renderer.screen_data.read().safe_width != physical_width
         || game.renderer.screen_data.read().safe_height != physical_height
     {
@@ -688,16 +815,14 @@ fn tick_all(
         .lock()
         .tick(ui_container, game.renderer.clone(), delta, width as f64);
     ui_container.tick(game.renderer.clone(), delta, width as f64, height as f64);
-    let world = game
-        .server
-        .load()
-        .as_ref()
-        .map(|server: &Arc<server::Server>| server.world.clone());
     game.renderer
         .tick(world, delta, width, height, physical_width, physical_height);
     if let Some(server) = game.server.load().as_ref() {
         server.render_list_computer.send(true).unwrap();
     }
+    if let Some(viewer) = game.viewer.load().as_ref() {
+        viewer.compute_render_list();
+    }
 
     if fps_cap > 0 && !vsync {
         let frame_time = now.elapsed();
@@ -765,6 +890,9 @@ fn handle_window_event<T>(
                         }
                     }
                 }
+                if let Some(viewer) = game.viewer.load().as_ref() {
+                    viewer.rotate(rx, ry);
+                }
             }
         }
 
@@ -37,8 +37,9 @@ pub fn render_liquid<W: Write>(
     let (cr, cg, cb) = if lava {
         (255, 255, 255)
     } else {
-        // TODO: Choose water color based on biome
-        (63, 118, 228)
+        super::calculate_biome(snapshot, x, y, z, world::biome::Tint::Water, |biome| {
+            biome.water_color()
+        })
     };
 
     let tex = match snapshot.get_block(x, y, z) {
@@ -15,7 +15,6 @@ use crate::types::hash::FNVHash;
 use log::error;
 use std::hash::BuildHasherDefault;
 
-use image::GenericImageView;
 use parking_lot::RwLock;
 use rand::seq::SliceRandom;
 use rand::Rng;
@@ -214,6 +213,49 @@ impl Factory {
         ret
     }
 
+    /// Builds `block`'s model as vertices for the `render::model` system,
+    /// so blocks can be drawn away from the chunk meshes, e.g. while a
+    /// piston moves them. Faces are never culled and biome tints use the
+    /// plains colours.
+    pub fn get_block_vertices<R: Rng>(
+        models: &Arc<RwLock<Factory>>,
+        block: Block,
+        rng: &mut R,
+    ) -> Vec<render::model::Vertex> {
+        let (plugin, name) = block.get_model();
+        let key = Key(plugin.to_owned(), name.to_owned());
+        if let Some(model) = models.read().block_model(&key, block, rng) {
+            return model.vertices(block.get_tint());
+        }
+        let mut m = models.write();
+        if !m.models.contains_key(&key) && !m.load_model(plugin, name) {
+            error!("Error loading model {}:{}", plugin, name);
+        }
+        m.block_model(&key, block, rng)
+            .map_or_else(Vec::new, |model| model.vertices(block.get_tint()))
+    }
+
+    fn block_model<R: Rng>(&self, key: &Key, block: Block, rng: &mut R) -> Option<Model> {
+        let model = self.models.get(key)?;
+        if model.multipart.is_empty() {
+            let variant = block.get_model_variant();
+            return model
+                .get_variants(&variant)
+                .map(|var| var.choose_model(rng).clone());
+        }
+        let mut res: Option<Model> = None;
+        for rule in &model.multipart {
+            if Self::eval_rules(block, &rule.rules) {
+                if let Some(res) = &mut res {
+                    res.join(rule.apply.choose_model(rng));
+                } else {
+                    res = Some(rule.apply.choose_model(rng).clone());
+                }
+            }
+        }
+        res
+    }
+
     fn load_model(&mut self, plugin: &str, name: &str) -> bool {
         let file = match self
             .resources
@@ -959,6 +1001,44 @@ impl Model {
         self.faces.extend_from_slice(&other.faces);
     }
 
+    fn vertices(&self, tint: TintType) -> Vec<render::model::Vertex> {
+        let mut verts = vec![];
+        for face in &self.faces {
+            let (mut r, mut g, mut b) = if face.tint_index == 0 {
+                match tint {
+                    TintType::Default => (255, 255, 255),
+                    TintType::Color { r, g, b } => (r, g, b),
+                    TintType::Grass => (145, 189, 89),
+                    TintType::Foliage => (119, 171, 47),
+                    TintType::Water => (63, 118, 228),
+                }
+            } else {
+                (255, 255, 255)
+            };
+            if face.facing == Direction::West || face.facing == Direction::East {
+                r = ((r as f64) * 0.8) as u8;
+                g = ((g as f64) * 0.8) as u8;
+                b = ((b as f64) * 0.8) as u8;
+            }
+            for (vert, texture) in face.vertices.iter().zip(&face.vertices_texture) {
+                verts.push(render::model::Vertex {
+                    x: vert.x,
+                    y: vert.y,
+                    z: vert.z,
+                    texture_x: vert.toffsetx as f64 / (texture.get_width() as f64 * 16.0),
+                    texture_y: vert.toffsety as f64 / (texture.get_height() as f64 * 16.0),
+                    texture: texture.clone(),
+                    r,
+                    g,
+                    b,
+                    a: 255,
+                    id: 0,
+                });
+            }
+        }
+        verts
+    }
+
     fn render<W: Write>(
         &self,
         factory: &Factory,
@@ -998,17 +1078,27 @@ impl Model {
                         TintType::Grass => calculate_biome(
                             snapshot,
                             vert.x as i32,
+                            vert.y as i32,
                             vert.z as i32,
-                            &factory.grass_colors,
+                            world::biome::Tint::Grass,
+                            |biome| biome.grass_color(&factory.grass_colors),
                         ),
                         TintType::Foliage => calculate_biome(
                             snapshot,
                             vert.x as i32,
+                            vert.y as i32,
+                            vert.z as i32,
+                            world::biome::Tint::Foliage,
+                            |biome| biome.foliage_color(&factory.foliage_colors),
+                        ),
+                        TintType::Water => calculate_biome(
+                            snapshot,
+                            vert.x as i32,
+                            vert.y as i32,
                             vert.z as i32,
-                            &factory.foliage_colors,
+                            world::biome::Tint::Water,
+                            |biome| biome.water_color(),
                         ),
-                        // TODO: Choose water color based on biome
-                        TintType::Water => (63, 118, 228),
                     }
                 } else {
                     (255, 255, 255)
@@ -1044,35 +1134,50 @@ impl Model {
     }
 }
 
-fn calculate_biome(
+/// Averages the colour `color` picks for each biome within the snapshot's
+/// biome blend radius, skipping columns in chunks that aren't loaded. The
+/// result is cached in the snapshot per `tint`, so neighbouring vertices
+/// don't blend the same column again.
+fn calculate_biome<F>(
     snapshot: &world::ChunkSectionSnapshotGroup,
     x: i32,
+    y: i32,
     z: i32,
-    img: &image::DynamicImage,
-) -> (u8, u8, u8) {
-    let mut count = 0;
-    let mut r = 0;
-    let mut g = 0;
-    let mut b = 0;
-    for xx in -1..2 {
-        for zz in -1..2 {
-            let bi = snapshot.get_biome(x + xx, z + zz);
-            let color_index = bi.get_color_index();
-            let ix = color_index & 0xFF;
-            let iy = color_index >> 8;
-
-            let ix = ix.clamp(0, 255);
-            let iy = iy.clamp(0, 255);
-
-            let col = img.get_pixel(ix as u32, iy as u32);
-            let col = bi.process_color(col);
-            r += col.0[0] as u32;
-            g += col.0[1] as u32;
-            b += col.0[2] as u32;
-            count += 1;
+    tint: world::biome::Tint,
+    color: F,
+) -> (u8, u8, u8)
+where
+    F: Fn(world::biome::Biome) -> image::Rgba<u8>,
+{
+    snapshot.blended_biome(tint, x, y, z, || {
+        let mut count = 0;
+        let mut r = 0;
+        let mut g = 0;
+        let mut b = 0;
+        let radius = snapshot.biome_blend();
+        for xx in -radius..=radius {
+            for zz in -radius..=radius {
+                let col = match snapshot.get_biome(x + xx, y, z + zz) {
+                    Some(biome) => color(biome),
+                    None => continue,
+                };
+                r += col.0[0] as u32;
+                g += col.0[1] as u32;
+                b += col.0[2] as u32;
+                count += 1;
+            }
         }
-    }
-    ((r / count) as u8, (g / count) as u8, (b / count) as u8)
+        if count == 0 {
+            // Vertices on the border of an unloaded chunk use the closest block
+            // of their own section
+            if let Some(biome) = snapshot.get_biome(x.clamp(0, 15), y, z.clamp(0, 15)) {
+                let col = color(biome);
+                return (col.0[0], col.0[1], col.0[2]);
+            }
+            return (255, 255, 255);
+        }
+        ((r / count) as u8, (g / count) as u8, (b / count) as u8)
+    })
 }
 
 fn calculate_light(
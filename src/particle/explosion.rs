@@ -0,0 +1,179 @@
+use crate::ecs::SystemExecStage;
+use crate::render;
+use crate::render::model::ModelHandle;
+use crate::render::{model, Renderer};
+use crate::server::RendererResource;
+use bevy_ecs::prelude::*;
+use cgmath::{Decomposed, Matrix4, Quaternion, Rad, Rotation3, Vector3};
+use instant::Instant;
+use rand::Rng;
+use std::f32::consts::PI;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// The number of animation frames of the explosion particle texture.
+const FRAMES: u32 = 16;
+/// How long the puffs of an explosion stay visible.
+const LIFETIME: Duration = Duration::from_millis(600);
+/// At most this many puffs are drawn per explosion, models have room for
+/// 10 parts.
+const MAX_PUFFS: usize = 10;
+
+pub fn add_systems(sched: &mut Schedule) {
+    sched
+        .add_systems(
+            effect_added
+                .in_set(SystemExecStage::Render)
+                .after(SystemExecStage::Normal),
+        )
+        .add_systems(
+            effect_updated
+                .in_set(SystemExecStage::Render)
+                .after(SystemExecStage::Normal),
+        );
+}
+
+#[derive(Component)]
+pub struct ExplosionEffect {
+    position: Vector3<f64>,
+    /// The offset and size of each puff.
+    puffs: Vec<(Vector3<f32>, f32)>,
+    spawned: Instant,
+    frame: u32,
+    model: Option<ModelHandle>,
+}
+
+impl ExplosionEffect {
+    pub(crate) fn new(data: &ExplosionData) -> Self {
+        let mut rng = rand::thread_rng();
+        let spread = data.radius.clamp(1.0, 4.0);
+        let count = ((data.radius * 2.0) as usize).clamp(1, MAX_PUFFS);
+        let puffs = (0..count)
+            .map(|_| {
+                let offset = Vector3::new(
+                    rng.gen_range(-spread..spread),
+                    rng.gen_range(-spread..spread),
+                    rng.gen_range(-spread..spread),
+                );
+                (offset, rng.gen_range(1.5..3.0))
+            })
+            .collect();
+        Self {
+            position: data.position,
+            puffs,
+            spawned: Instant::now(),
+            frame: 0,
+            model: None,
+        }
+    }
+
+    fn current_frame(&self) -> Option<u32> {
+        let elapsed = self.spawned.elapsed();
+        if elapsed >= LIFETIME {
+            None
+        } else {
+            Some((elapsed.as_secs_f32() / LIFETIME.as_secs_f32() * FRAMES as f32) as u32)
+        }
+    }
+}
+
+pub fn effect_added(
+    renderer: Res<RendererResource>,
+    mut commands: Commands,
+    query: Query<(Entity, &ExplosionData)>,
+) {
+    for (entity, data) in query.iter() {
+        let mut effect = ExplosionEffect::new(data);
+        readd_model(renderer.0.clone(), &mut effect);
+        commands
+            .entity(entity)
+            .remove::<ExplosionData>()
+            .insert(effect);
+    }
+}
+
+pub fn effect_updated(
+    renderer: Res<RendererResource>,
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut ExplosionEffect)>,
+) {
+    let renderer = &renderer.0;
+    let yaw = renderer.camera.lock().yaw;
+    for (entity, mut effect) in query.iter_mut() {
+        let frame = match effect.current_frame() {
+            Some(frame) => frame,
+            None => {
+                commands.entity(entity).despawn();
+                continue;
+            }
+        };
+        if frame != effect.frame {
+            effect.frame = frame;
+            readd_model(renderer.clone(), &mut effect);
+        }
+        if let Some(model) = &effect.model {
+            let mut models = renderer.models.lock();
+            if let Some(mdl) = models.get_model(model) {
+                for (i, (offset, size)) in effect.puffs.iter().enumerate() {
+                    // Puffs always face the camera
+                    mdl.matrix[i] = Matrix4::from(Decomposed {
+                        scale: *size,
+                        rot: Quaternion::from_angle_y(Rad(PI + yaw as f32)),
+                        disp: Vector3::new(
+                            effect.position.x as f32 + offset.x,
+                            -(effect.position.y as f32 + offset.y),
+                            effect.position.z as f32 + offset.z,
+                        ),
+                    });
+                }
+            }
+        }
+    }
+}
+
+fn readd_model(renderer: Arc<Renderer>, effect: &mut ExplosionEffect) {
+    effect.model.take();
+    let tex = render::Renderer::get_texture(
+        renderer.get_textures_ref(),
+        &format!("particle/explosion_{}", effect.frame),
+    );
+    let mut parts = vec![];
+    for _ in &effect.puffs {
+        let mut verts = vec![];
+        // Both windings so the puff can be seen from either side
+        for &flip in &[false, true] {
+            for &(x, y) in &[(0.0, 0.0), (0.0, 1.0), (1.0, 0.0), (1.0, 1.0)] {
+                let x = if flip { 1.0 - x } else { x };
+                verts.push(model::Vertex {
+                    x: x as f32 - 0.5,
+                    y: y as f32 - 0.5,
+                    z: 0.0,
+                    texture_x: x,
+                    texture_y: 1.0 - y,
+                    texture: tex.clone(),
+                    r: 255,
+                    g: 255,
+                    b: 255,
+                    a: 255,
+                    id: 0,
+                });
+            }
+        }
+        parts.push(verts);
+    }
+    effect
+        .model
+        .replace(
+            renderer
+                .clone()
+                .models
+                .lock()
+                .create_model(model::DEFAULT, parts, renderer),
+        );
+}
+
+#[derive(Component)]
+pub struct ExplosionData {
+    pub(crate) position: Vector3<f64>,
+    pub(crate) radius: f32,
+}
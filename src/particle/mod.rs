@@ -1,6 +1,8 @@
 use bevy_ecs::prelude::*;
 
 pub mod block_break_effect;
+pub mod explosion;
+pub mod note;
 
 #[derive(Component, Copy, Clone)]
 pub struct EntityMetadata(pub Entity);
@@ -8,4 +10,6 @@ pub struct EntityMetadata(pub Entity);
 #[derive(Component, Copy, Clone, Eq, PartialEq, Hash, Debug)]
 pub enum ParticleType {
     BlockBreak,
+    Explosion,
+    Note,
 }
@@ -0,0 +1,135 @@
+use crate::ecs::SystemExecStage;
+use crate::render;
+use crate::render::model::ModelHandle;
+use crate::render::{model, Renderer};
+use crate::server::RendererResource;
+use bevy_ecs::prelude::*;
+use cgmath::{Decomposed, Matrix4, Quaternion, Rad, Rotation3, Vector3};
+use instant::Instant;
+use std::f32::consts::PI;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// How long a note stays visible.
+const LIFETIME: Duration = Duration::from_millis(600);
+/// How far a note rises over its lifetime.
+const RISE: f32 = 0.5;
+/// The size of a note.
+const SIZE: f32 = 0.4;
+
+pub fn add_systems(sched: &mut Schedule) {
+    sched
+        .add_systems(
+            effect_added
+                .in_set(SystemExecStage::Render)
+                .after(SystemExecStage::Normal),
+        )
+        .add_systems(
+            effect_updated
+                .in_set(SystemExecStage::Render)
+                .after(SystemExecStage::Normal),
+        );
+}
+
+#[derive(Component)]
+pub struct NoteEffect {
+    position: Vector3<f64>,
+    spawned: Instant,
+    model: Option<ModelHandle>,
+}
+
+/// The colour of a note, which cycles through the rainbow across the 25
+/// pitches of a note block.
+fn note_color(pitch: u8) -> (u8, u8, u8) {
+    let p = pitch.min(24) as f32 / 24.0;
+    let channel = |k: f32| {
+        let c = ((p + k / 3.0) * PI * 2.0).sin() * 0.65 + 0.35;
+        (c.max(0.0) * 255.0) as u8
+    };
+    (channel(0.0), channel(1.0), channel(2.0))
+}
+
+pub fn effect_added(
+    renderer: Res<RendererResource>,
+    mut commands: Commands,
+    query: Query<(Entity, &NoteData)>,
+) {
+    for (entity, data) in query.iter() {
+        let mut effect = NoteEffect {
+            position: data.position,
+            spawned: Instant::now(),
+            model: None,
+        };
+        add_model(renderer.0.clone(), &mut effect, note_color(data.pitch));
+        commands.entity(entity).remove::<NoteData>().insert(effect);
+    }
+}
+
+pub fn effect_updated(
+    renderer: Res<RendererResource>,
+    mut commands: Commands,
+    query: Query<(Entity, &NoteEffect)>,
+) {
+    let renderer = &renderer.0;
+    let yaw = renderer.camera.lock().yaw;
+    for (entity, effect) in query.iter() {
+        let elapsed = effect.spawned.elapsed();
+        if elapsed >= LIFETIME {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        let rise = elapsed.as_secs_f32() / LIFETIME.as_secs_f32() * RISE;
+        if let Some(model) = &effect.model {
+            let mut models = renderer.models.lock();
+            if let Some(mdl) = models.get_model(model) {
+                // Notes always face the camera
+                mdl.matrix[0] = Matrix4::from(Decomposed {
+                    scale: SIZE,
+                    rot: Quaternion::from_angle_y(Rad(PI + yaw as f32)),
+                    disp: Vector3::new(
+                        effect.position.x as f32,
+                        -(effect.position.y as f32 + rise),
+                        effect.position.z as f32,
+                    ),
+                });
+            }
+        }
+    }
+}
+
+fn add_model(renderer: Arc<Renderer>, effect: &mut NoteEffect, (r, g, b): (u8, u8, u8)) {
+    let tex = render::Renderer::get_texture(renderer.get_textures_ref(), "particle/note");
+    let mut verts = vec![];
+    // Both windings so the note can be seen from either side
+    for &flip in &[false, true] {
+        for &(x, y) in &[(0.0, 0.0), (0.0, 1.0), (1.0, 0.0), (1.0, 1.0)] {
+            let x = if flip { 1.0 - x } else { x };
+            verts.push(model::Vertex {
+                x: x as f32 - 0.5,
+                y: y as f32 - 0.5,
+                z: 0.0,
+                texture_x: x,
+                texture_y: 1.0 - y,
+                texture: tex.clone(),
+                r,
+                g,
+                b,
+                a: 255,
+                id: 0,
+            });
+        }
+    }
+    effect
+        .model
+        .replace(renderer.clone().models.lock().create_model(
+            model::DEFAULT,
+            vec![verts],
+            renderer,
+        ));
+}
+
+#[derive(Component)]
+pub struct NoteData {
+    pub(crate) position: Vector3<f64>,
+    pub(crate) pitch: u8,
+}
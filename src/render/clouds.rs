@@ -7,6 +7,10 @@ use cgmath::{Matrix4, Point3};
 use log::error;
 use parking_lot::RwLock;
 
+/// The height the cloud layer is built at, it is moved from there to the
+/// height of the dimension's clouds.
+const CLOUD_BASE_HEIGHT: f32 = 128.0;
+
 pub struct Clouds {
     program: gl::Program,
     // Shader props
@@ -118,7 +122,7 @@ impl Clouds {
         for x in -160..160 {
             for z in -160..160 {
                 let _ = data.write_f32::<NativeEndian>(x as f32);
-                let _ = data.write_f32::<NativeEndian>(128.0);
+                let _ = data.write_f32::<NativeEndian>(CLOUD_BASE_HEIGHT);
                 let _ = data.write_f32::<NativeEndian>(z as f32);
                 num_points += 1;
             }
@@ -178,6 +182,7 @@ impl Clouds {
         camera_matrix: &Matrix4<f32>,
         light_level: f32,
         sky_offset: f32,
+        height: f32,
         delta: f64,
     ) {
         self.offset += delta;
@@ -191,7 +196,7 @@ impl Clouds {
         self.u_light_level.set_float(light_level);
         self.u_offset.set_float3(
             camera_pos.x.floor() as f32,
-            0.0,
+            CLOUD_BASE_HEIGHT - height,
             camera_pos.z.floor() as f32,
         );
         self.u_texture_info.set_float4(
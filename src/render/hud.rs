@@ -31,6 +31,7 @@ use crate::screen::{Screen, ScreenSystem, ScreenType};
 use crate::server::Server;
 use crate::ui;
 use crate::ui::{Container, FormattedRef, HAttach, ImageRef, TextRef, VAttach};
+use crate::world;
 use crate::{format, screen, Game};
 use leafish_protocol::protocol::packet::play::serverbound::HeldItemChange;
 use leafish_protocol::types::GameMode;
@@ -47,6 +48,8 @@ pub struct HudContext {
     pub enabled: bool,
     pub debug: bool,
     fps: u32,
+    chunk_memory: world::MemoryUsage,
+    target: Option<String>,
     dirty_debug: bool,
     hardcore: bool,  // TODO: Update this!
     wither: bool,    // TODO: Update this!
@@ -80,6 +83,8 @@ pub struct HudContext {
     dirty_game_mode: bool,
     pub action_bar: Option<Component>,
     dirty_action_bar: bool,
+    border_warning: f32,
+    dirty_border_warning: bool,
 }
 
 impl Default for render::hud::HudContext {
@@ -93,6 +98,8 @@ impl HudContext {
             enabled: true,
             debug: false,
             fps: 0,
+            chunk_memory: Default::default(),
+            target: None,
             dirty_debug: false,
             hardcore: false,
             wither: false,
@@ -127,6 +134,8 @@ impl HudContext {
             dirty_game_mode: false,
             action_bar: None,
             dirty_action_bar: false,
+            border_warning: 0.0,
+            dirty_border_warning: false,
         }
     }
 
@@ -190,6 +199,23 @@ impl HudContext {
         }
     }
 
+    pub fn update_chunk_memory(&mut self, usage: world::MemoryUsage) {
+        self.chunk_memory = usage;
+        if self.debug {
+            self.dirty_debug = true;
+        }
+    }
+
+    /// Shows what the crosshair points at in the debug overlay.
+    pub fn update_target(&mut self, target: Option<String>) {
+        if self.target != target {
+            self.target = target;
+            if self.debug {
+                self.dirty_debug = true;
+            }
+        }
+    }
+
     pub fn update_game_mode(&mut self, game_mode: GameMode) {
         self.game_mode = game_mode;
         self.dirty_game_mode = true;
@@ -212,6 +238,17 @@ impl HudContext {
         self.action_bar = Some(message);
         self.dirty_action_bar = true;
     }
+
+    /// Sets how strongly the player is warned about the world border, from
+    /// 0 to 1. Small changes are ignored to avoid redrawing every frame.
+    pub fn update_border_warning(&mut self, warning: f32) {
+        if (warning - self.border_warning).abs() >= 0.02
+            || (warning == 0.0) != (self.border_warning == 0.0)
+        {
+            self.border_warning = warning;
+            self.dirty_border_warning = true;
+        }
+    }
 }
 
 #[derive(Clone)]
@@ -232,6 +269,7 @@ pub struct Hud {
     chat_elements: Vec<FormattedRef>,
     chat_background_elements: Vec<ImageRef>,
     action_bar_text_elements: Option<FormattedRef>,
+    border_warning_elements: Vec<ImageRef>,
     hud_context: Arc<RwLock<HudContext>>,
     last_tick: Instant,
     render_chat: bool,
@@ -256,6 +294,7 @@ impl Hud {
             chat_elements: vec![],
             chat_background_elements: vec![],
             action_bar_text_elements: None,
+            border_warning_elements: vec![],
             hud_context,
             last_tick: Instant::now(),
             render_chat: false,
@@ -277,6 +316,7 @@ impl Screen for Hud {
             self.render_crosshair(&renderer, ui_container);
             self.render_chat(&renderer, ui_container);
             self.render_action_bar(&renderer, ui_container);
+            self.render_border_warning(&renderer, ui_container);
             let game_mode = self.hud_context.read().game_mode;
             if matches!(game_mode, GameMode::Adventure | GameMode::Survival) {
                 self.render_health(&renderer, ui_container);
@@ -307,6 +347,7 @@ impl Screen for Hud {
         self.debug_elements.clear();
         self.chat_elements.clear();
         self.chat_background_elements.clear();
+        self.border_warning_elements.clear();
     }
 
     fn on_active(
@@ -422,6 +463,10 @@ impl Screen for Hud {
         if self.hud_context.read().dirty_action_bar {
             self.render_action_bar(&renderer, ui_container);
         }
+        if self.hud_context.read().dirty_border_warning {
+            self.border_warning_elements.clear();
+            self.render_border_warning(&renderer, ui_container);
+        }
         if (self
             .hud_context
             .read()
@@ -967,6 +1012,67 @@ impl Hud {
         self.elements.push(image);
     }
 
+    fn render_border_warning(&mut self, renderer: &Arc<Renderer>, ui_container: &mut Container) {
+        // Each edge fades out towards the middle of the screen in this many
+        // steps
+        const STEPS: usize = 8;
+        let warning = self.hud_context.read().border_warning;
+        self.hud_context.write().dirty_border_warning = false;
+        if warning <= 0.0 {
+            return;
+        }
+        let (width, height) = match ui_container.mode {
+            ui::Mode::Unscaled(scale) => (854.0 / scale, 480.0 / scale),
+            ui::Mode::Scaled => {
+                let screen = renderer.screen_data.read();
+                (screen.width as f64, screen.height as f64)
+            }
+        };
+        let step_x = width * 0.15 / STEPS as f64;
+        let step_y = height * 0.2 / STEPS as f64;
+        for i in 0..STEPS {
+            let alpha = (warning * 96.0 * (1.0 - i as f32 / STEPS as f32)) as u8;
+            let edges = [
+                (
+                    VAttach::Top,
+                    HAttach::Left,
+                    (0.0, i as f64 * step_y),
+                    (width, step_y),
+                ),
+                (
+                    VAttach::Bottom,
+                    HAttach::Left,
+                    (0.0, i as f64 * step_y),
+                    (width, step_y),
+                ),
+                (
+                    VAttach::Top,
+                    HAttach::Left,
+                    (i as f64 * step_x, 0.0),
+                    (step_x, height),
+                ),
+                (
+                    VAttach::Top,
+                    HAttach::Right,
+                    (i as f64 * step_x, 0.0),
+                    (step_x, height),
+                ),
+            ];
+            for (v_attach, h_attach, (x, y), (w, h)) in edges {
+                self.border_warning_elements.push(
+                    ui::ImageBuilder::new()
+                        .draw_index(HUD_PRIORITY)
+                        .texture("leafish:solid")
+                        .alignment(v_attach, h_attach)
+                        .position(x, y)
+                        .size(w, h)
+                        .colour((255, 0, 0, alpha))
+                        .create(ui_container),
+                );
+            }
+        }
+    }
+
     fn render_breath(&mut self, renderer: &Arc<Renderer>, ui_container: &mut Container) {
         let hud_context = self.hud_context.clone();
         let hud_context = hud_context.read();
@@ -1028,6 +1134,39 @@ impl Hud {
                 .shadow(false)
                 .create(ui_container),
         );
+        let memory = &hud_context.chunk_memory;
+        self.debug_elements.push(
+            ui::TextBuilder::new()
+                .draw_index(HUD_PRIORITY)
+                .alignment(VAttach::Top, HAttach::Left)
+                .scale_x(scale)
+                .scale_y(scale)
+                .position(icon_scale, icon_scale + 10.0 * scale)
+                .text(format!(
+                    "Chunks: {} ({:.1} MiB, {:.1} MiB render)",
+                    memory.chunks,
+                    memory.chunk_bytes as f64 / (1024.0 * 1024.0),
+                    memory.render_bytes as f64 / (1024.0 * 1024.0),
+                ))
+                .colour((0, 102, 204, 255))
+                .shadow(false)
+                .create(ui_container),
+        );
+        self.debug_elements.push(
+            ui::TextBuilder::new()
+                .draw_index(HUD_PRIORITY)
+                .alignment(VAttach::Top, HAttach::Left)
+                .scale_x(scale)
+                .scale_y(scale)
+                .position(icon_scale, icon_scale + 20.0 * scale)
+                .text(format!(
+                    "Target: {}",
+                    hud_context.target.as_deref().unwrap_or("none")
+                ))
+                .colour((0, 102, 204, 255))
+                .shadow(false)
+                .create(ui_container),
+        );
     }
 
     fn render_chat(&mut self, renderer: &Arc<Renderer>, ui_container: &mut Container) {
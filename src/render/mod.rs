@@ -35,6 +35,7 @@ use std::collections::HashMap;
 use std::io::Write;
 use std::sync::Arc;
 
+use crate::shared::Position;
 use crate::types::hash::FNVHash;
 use crate::world::World;
 use crossbeam_channel::unbounded;
@@ -129,6 +130,12 @@ impl ChunkBuffer {
     pub fn new() -> ChunkBuffer {
         Default::default()
     }
+
+    /// The number of bytes of vertex data uploaded for this buffer.
+    pub fn memory_size(&self) -> usize {
+        self.solid.as_ref().map_or(0, |info| info.buffer_size)
+            + self.trans.as_ref().map_or(0, |info| info.buffer_size)
+    }
 }
 
 struct ChunkRenderInfo {
@@ -156,6 +163,7 @@ init_shader! {
             required texture => "textures",
             required light_level => "lightLevel",
             required sky_offset => "skyOffset",
+            required ambient_light => "ambientLight",
         },
     }
 }
@@ -178,6 +186,7 @@ init_shader! {
             required texture => "textures",
             required light_level => "lightLevel",
             required sky_offset => "skyOffset",
+            required ambient_light => "ambientLight",
         },
     }
 }
@@ -374,13 +383,18 @@ impl Renderer {
 
             gl::enable(gl::MULTISAMPLE);
 
-            let time_offset = self.light_data.lock().sky_offset * 0.9;
-            gl::clear_color(
-                (122.0 / 255.0) * time_offset,
-                (165.0 / 255.0) * time_offset,
-                (247.0 / 255.0) * time_offset,
-                1.0,
-            );
+            let dimension = world.as_ref().unwrap().dimension.load();
+            let biome = {
+                let pos = self.camera.lock().pos;
+                world.as_ref().unwrap().get_biome(Position::new(
+                    pos.x.floor() as i32,
+                    pos.y.floor() as i32,
+                    pos.z.floor() as i32,
+                ))
+            };
+            let (red, green, blue) =
+                dimension.clear_colour(self.light_data.lock().sky_offset, &biome.colours);
+            gl::clear_color(red, green, blue, 1.0);
             gl::clear(gl::ClearFlags::Color | gl::ClearFlags::Depth);
             // Chunk rendering
             self.chunk_render_data
@@ -414,6 +428,11 @@ impl Renderer {
                 .chunk_shader
                 .sky_offset
                 .set_float(self.light_data.lock().sky_offset);
+            self.chunk_render_data
+                .lock()
+                .chunk_shader
+                .ambient_light
+                .set_float(dimension.ambient_light);
 
             let tmp_world = world.as_ref().unwrap().clone();
 
@@ -445,10 +464,13 @@ impl Renderer {
                 &self.camera_matrix.lock(),
                 light_data.light_level,
                 light_data.sky_offset,
+                dimension.ambient_light,
             );
             let tmp_world = world.as_ref().unwrap().clone();
 
-            if let Some(clouds) = &mut *self.clouds.lock() {
+            if let (Some(clouds), Some(cloud_height)) =
+                (&mut *self.clouds.lock(), dimension.cloud_height())
+            {
                 if tmp_world.copy_cloud_heightmap(&mut clouds.heightmap_data) {
                     clouds.dirty = true;
                 }
@@ -458,6 +480,7 @@ impl Renderer {
                     &self.camera_matrix.lock(),
                     light_data.light_level,
                     light_data.sky_offset,
+                    cloud_height,
                     delta,
                 );
             }
@@ -494,6 +517,11 @@ impl Renderer {
                     .chunk_shader_alpha
                     .sky_offset
                     .set_float(light_data.sky_offset);
+                self.chunk_render_data
+                    .lock()
+                    .chunk_shader_alpha
+                    .ambient_light
+                    .set_float(dimension.ambient_light);
 
                 // Copy the depth buffer
                 let chunk_data = self.chunk_render_data.lock();
@@ -1150,6 +1178,14 @@ impl TransInfo {
     }
 }
 
+const SKIN_URL_PREFIX: &str = "http://textures.minecraft.net/texture/";
+
+/// Returns the key a skin is stored under, which is the texture hash
+/// for skins hosted by Mojang and the full url for local (`file://`) ones.
+fn skin_hash(url: &str) -> &str {
+    url.strip_prefix(SKIN_URL_PREFIX).unwrap_or(url)
+}
+
 pub struct TextureManager {
     textures: HashMap<String, Texture, BuildHasherDefault<FNVHash>>,
     version: usize,
@@ -1253,35 +1289,42 @@ impl TextureManager {
         use std::io::Read;
         use std::io::{Error, ErrorKind};
         use std::path::Path;
-        let path = paths::get_cache_dir().join(format!("skin-cache/{}/{}.png", &hash[..2], hash));
-        let cache_path = Path::new(&path);
-        fs::create_dir_all(cache_path.parent().unwrap())?;
         let mut buf = vec![];
-        if fs::metadata(cache_path).is_ok() {
-            // We have a cached image
-            let mut file = fs::File::open(cache_path)?;
+        if let Some(path) = hash.strip_prefix("file://") {
+            // Skins of offline accounts are read straight from disk
+            let mut file = fs::File::open(path)?;
             file.read_to_end(&mut buf)?;
         } else {
-            // Need to download it
-            let url = &format!("http://textures.minecraft.net/texture/{}", hash);
-            let mut res = match client.get(url).send() {
-                Ok(val) => val,
-                Err(err) => {
-                    return Err(Error::new(ErrorKind::ConnectionAborted, err));
-                }
-            };
+            let path =
+                paths::get_cache_dir().join(format!("skin-cache/{}/{}.png", &hash[..2], hash));
+            let cache_path = Path::new(&path);
+            fs::create_dir_all(cache_path.parent().unwrap())?;
+            if fs::metadata(cache_path).is_ok() {
+                // We have a cached image
+                let mut file = fs::File::open(cache_path)?;
+                file.read_to_end(&mut buf)?;
+            } else {
+                // Need to download it
+                let url = &format!("{}{}", SKIN_URL_PREFIX, hash);
+                let mut res = match client.get(url).send() {
+                    Ok(val) => val,
+                    Err(err) => {
+                        return Err(Error::new(ErrorKind::ConnectionAborted, err));
+                    }
+                };
 
-            match res.read_to_end(&mut buf) {
-                Ok(_) => {}
-                Err(err) => {
-                    // TODO: different error for failure to read?
-                    return Err(Error::new(ErrorKind::InvalidData, err));
+                match res.read_to_end(&mut buf) {
+                    Ok(_) => {}
+                    Err(err) => {
+                        // TODO: different error for failure to read?
+                        return Err(Error::new(ErrorKind::InvalidData, err));
+                    }
                 }
-            }
 
-            // Save to cache
-            let mut file = fs::File::create(cache_path)?;
-            file.write_all(&buf)?;
+                // Save to cache
+                let mut file = fs::File::create(cache_path)?;
+                file.write_all(&buf)?;
+            }
         }
         let mut img = match image::load_from_memory(&buf) {
             Ok(val) => val,
@@ -1374,7 +1417,7 @@ impl TextureManager {
     }
 
     fn get_skin(&self, url: &str) -> Option<Texture> {
-        let hash = &url["http://textures.minecraft.net/texture/".len()..];
+        let hash = skin_hash(url);
         if let Some(skin) = self.skins.get(hash) {
             skin.fetch_add(1, Ordering::Relaxed);
         }
@@ -1382,14 +1425,14 @@ impl TextureManager {
     }
 
     pub fn release_skin(&self, url: &str) {
-        let hash = &url["http://textures.minecraft.net/texture/".len()..];
+        let hash = skin_hash(url);
         if let Some(skin) = self.skins.get(hash) {
             skin.fetch_sub(1, Ordering::Relaxed);
         }
     }
 
     fn load_skin(&mut self, renderer: &Renderer, url: &str) {
-        let hash = &url["http://textures.minecraft.net/texture/".len()..];
+        let hash = skin_hash(url);
         let res = self.resources.clone();
         // TODO: This shouldn't be hardcoded to steve but instead
         // have a way to select alex as a default.
@@ -1776,6 +1819,11 @@ impl Texture {
         }
     }
 
+    /// Whether this is the placeholder for a texture that couldn't be found.
+    pub fn is_dummy(&self) -> bool {
+        self.dummy
+    }
+
     pub fn relative(&self, x: f32, y: f32, width: f32, height: f32) -> Texture {
         Texture {
             name: self.name.clone(),
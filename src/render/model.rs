@@ -24,6 +24,8 @@ pub struct Manager {
 
 pub const DEFAULT: CollectionKey = CollectionKey(0);
 pub const SUN: CollectionKey = CollectionKey(1);
+/// Drawn over other models, lightening them, for the enchantment glint.
+pub const GLINT: CollectionKey = CollectionKey(2);
 
 #[derive(Clone, Copy, PartialEq, Eq, Hash)]
 pub struct CollectionKey(usize);
@@ -87,6 +89,12 @@ impl Manager {
             gl::SRC_ALPHA,
             gl::ONE_FACTOR,
         );
+        m.add_collection(
+            &greg.get("model_vertex"),
+            &greg.get("model_frag"),
+            gl::SRC_ALPHA,
+            gl::ONE_FACTOR,
+        );
         m
     }
 
@@ -287,6 +295,7 @@ impl Manager {
         camera_matrix: &Matrix4<f32>,
         light_level: f32,
         sky_offset: f32,
+        ambient_light: f32,
     ) {
         gl::enable(gl::BLEND);
         for collection in &self.collections {
@@ -306,6 +315,9 @@ impl Manager {
             if let Some(v) = &collection.shader.light_level {
                 v.set_float(light_level)
             }
+            if let Some(v) = &collection.shader.ambient_light {
+                v.set_float(ambient_light)
+            }
             gl::blend_func(collection.blend_s, collection.blend_d);
 
             for model in collection.models.values() {
@@ -397,6 +409,7 @@ init_shader! {
             optional texture => "textures",
             optional light_level => "lightLevel",
             optional sky_offset => "skyOffset",
+            optional ambient_light => "ambientLight",
             optional lighting => "lighting",
             optional color_mul => "colorMul",
         },
@@ -9,6 +9,7 @@ uniform mat4 cameraMatrix;
 uniform ivec3 offset;
 uniform float lightLevel;
 uniform float skyOffset;
+uniform float ambientLight;
 
 out vec3 vColor;
 out vec4 vTextureInfo;
@@ -1,6 +1,7 @@
 
 vec3 getLight(vec2 light) {
     vec2 li = pow(vec2(lightLevel), 15.0 - light);
+    li = mix(li, vec2(1.0), ambientLight);
     float skyTint = skyOffset * 0.95 + 0.05;
     float bl = li.x;
     float sk = li.y * skyTint;
@@ -6,9 +6,10 @@ in int id;
 
 uniform mat4 perspectiveMatrix;
 uniform mat4 cameraMatrix;
-uniform mat4 modelMatrix[10];
+uniform mat4 modelMatrix[16];
 uniform float lightLevel;
 uniform float skyOffset;
+uniform float ambientLight;
 uniform vec2 lighting;
 
 out vec4 vColor;
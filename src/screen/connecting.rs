@@ -15,11 +15,13 @@
 use crate::render;
 use crate::screen::{Screen, ScreenSystem};
 use crate::ui;
+use parking_lot::RwLock;
 use std::sync::Arc;
 
 pub struct Connecting {
     elements: Option<UIElements>,
     target: String,
+    status: Arc<RwLock<String>>,
 }
 
 impl Clone for Connecting {
@@ -27,6 +29,7 @@ impl Clone for Connecting {
         Connecting {
             elements: None,
             target: self.target.clone(),
+            status: self.status.clone(),
         }
     }
 }
@@ -35,6 +38,7 @@ struct UIElements {
     logo: ui::logo::Logo,
     _connect_msg: ui::TextRef,
     _msg: ui::TextRef,
+    status_msg: ui::TextRef,
     _disclaimer: ui::TextRef,
 }
 
@@ -43,8 +47,15 @@ impl Connecting {
         Connecting {
             elements: None,
             target: target.to_owned(),
+            status: Arc::new(RwLock::new(String::new())),
         }
     }
+
+    /// The progress line shown below the target, updated by the thread
+    /// doing the actual connecting.
+    pub fn status(&self) -> Arc<RwLock<String>> {
+        self.status.clone()
+    }
 }
 
 impl super::Screen for Connecting {
@@ -69,6 +80,13 @@ impl super::Screen for Connecting {
             .alignment(ui::VAttach::Middle, ui::HAttach::Center)
             .create(ui_container);
 
+        let status_msg = ui::TextBuilder::new()
+            .text(self.status.read().clone())
+            .position(0.0, 48.0)
+            .colour((170, 170, 170, 255))
+            .alignment(ui::VAttach::Middle, ui::HAttach::Center)
+            .create(ui_container);
+
         // Disclaimer
         let disclaimer = ui::TextBuilder::new()
             .text("Not affiliated with Mojang/Minecraft")
@@ -81,6 +99,7 @@ impl super::Screen for Connecting {
             logo,
             _disclaimer: disclaimer,
             _msg: msg,
+            status_msg,
             _connect_msg: connect_msg,
         });
     }
@@ -103,6 +122,11 @@ impl super::Screen for Connecting {
     ) {
         let elements = self.elements.as_mut().unwrap();
         elements.logo.tick(renderer);
+        let status = self.status.read();
+        let mut status_msg = elements.status_msg.borrow_mut();
+        if status_msg.text != *status {
+            status_msg.text = status.clone();
+        }
     }
 
     fn clone_screen(&self) -> Box<dyn Screen> {
@@ -16,15 +16,22 @@ use crate::{ui, Game};
 
 use crate::render::Renderer;
 use crate::screen::{Screen, ScreenSystem};
+use leafish_protocol::protocol::login::AccountType;
+use leafish_protocol::protocol::UUID;
+use rfd::FileDialog;
+use std::cell::RefCell;
 use std::rc::Rc;
 use std::sync::Arc;
 
-type EditAccountEntryCallback = dyn Fn(&Game, String, String);
+type EditAccountEntryCallback = dyn Fn(&Game, String, String, Option<String>);
 
 pub struct EditAccountEntry {
     elements: Option<UIElements>,
     entry_info: Option<(String, String)>,
-    done_callback: Rc<EditAccountEntryCallback>, // game, name, password
+    account_type: AccountType,
+    uuid: Option<String>,
+    skin_path: Rc<RefCell<Option<String>>>,
+    done_callback: Rc<EditAccountEntryCallback>, // game, name, password, skin path
 }
 
 impl Clone for EditAccountEntry {
@@ -32,6 +39,9 @@ impl Clone for EditAccountEntry {
         Self {
             elements: None,
             entry_info: self.entry_info.clone(),
+            account_type: self.account_type.clone(),
+            uuid: self.uuid.clone(),
+            skin_path: Rc::new(RefCell::new(self.skin_path.borrow().clone())),
             done_callback: self.done_callback.clone(),
         }
     }
@@ -40,23 +50,66 @@ impl Clone for EditAccountEntry {
 struct UIElements {
     logo: ui::logo::Logo,
 
-    _name: ui::TextBoxRef,
+    name: ui::TextBoxRef,
     _password: ui::TextBoxRef,
+    uuid: ui::TextRef,
+    skin: Option<SkinElements>,
     _done: ui::ButtonRef,
     _cancel: ui::ButtonRef,
 }
 
+struct SkinElements {
+    preview: ui::ImageRef,
+    _select: ui::ButtonRef,
+    select_text: ui::TextRef,
+    _clear: ui::ButtonRef,
+}
+
 impl EditAccountEntry {
     pub fn new(
         entry_info: Option<(String, String)>,
+        account_type: AccountType,
+        uuid: Option<String>,
+        skin_path: Option<String>,
         done_callback: Rc<EditAccountEntryCallback>,
     ) -> Self {
         Self {
             elements: None,
             entry_info,
+            account_type,
+            uuid,
+            skin_path: Rc::new(RefCell::new(skin_path)),
             done_callback,
         }
     }
+
+    /// The uuid displayed for the given name, offline accounts use the
+    /// same name based uuid as vanilla does on offline mode servers.
+    fn display_uuid(&self, name: &str) -> String {
+        if self.account_type == AccountType::None {
+            if name.is_empty() {
+                return "-".to_string();
+            }
+            return UUID::offline(name).to_string();
+        }
+        self.uuid.clone().unwrap_or_else(|| "-".to_string())
+    }
+}
+
+fn skin_texture(skin_path: &Option<String>) -> String {
+    skin_path
+        .as_ref()
+        .map_or("leafish:solid".to_string(), |path| format!("#{}", path))
+}
+
+fn skin_button_text(skin_path: &Option<String>) -> String {
+    skin_path
+        .as_ref()
+        .map_or("Select skin".to_string(), |path| {
+            std::path::Path::new(path)
+                .file_name()
+                .map_or(path.clone(), |name| name.to_string_lossy().into_owned())
+        })
 }
 
 impl super::Screen for EditAccountEntry {
@@ -95,9 +148,91 @@ impl super::Screen for EditAccountEntry {
             .position(0.0, -18.0)
             .attach(&mut *account_password.borrow_mut());
 
+        // UUID
+        let uuid = ui::TextBuilder::new()
+            .text(format!(
+                "UUID: {}",
+                self.display_uuid(self.entry_info.as_ref().map_or("", |v| &v.0))
+            ))
+            .position(0.0, 90.0)
+            .colour((200, 200, 200, 255))
+            .alignment(ui::VAttach::Middle, ui::HAttach::Center)
+            .create(ui_container);
+
+        // Skin, only offline accounts can pick their own
+        let skin = if self.account_type == AccountType::None {
+            let skin_path = self.skin_path.borrow().clone();
+            let preview = ui::ImageBuilder::new()
+                .texture(skin_texture(&skin_path))
+                .texture_coords((32.0, 32.0, 32.0, 32.0))
+                .position(-180.0, 130.0)
+                .size(40.0, 40.0)
+                .colour(if skin_path.is_some() {
+                    (255, 255, 255, 255)
+                } else {
+                    (0, 0, 0, 255)
+                })
+                .alignment(ui::VAttach::Middle, ui::HAttach::Center)
+                .create(ui_container);
+            let select = ui::ButtonBuilder::new()
+                .position(10.0, 130.0)
+                .size(300.0, 40.0)
+                .alignment(ui::VAttach::Middle, ui::HAttach::Center)
+                .create(ui_container);
+            let select_text;
+            {
+                let mut select = select.borrow_mut();
+                select_text = ui::TextBuilder::new()
+                    .text(skin_button_text(&skin_path))
+                    .alignment(ui::VAttach::Middle, ui::HAttach::Center)
+                    .attach(&mut *select);
+                select.add_text(select_text.clone());
+                let skin_path = self.skin_path.clone();
+                select.add_click_func(move |_, _| {
+                    let file =
+                        FileDialog::new()
+                            .add_filter("skin", &["png"])
+                            .set_directory(dirs::picture_dir().map_or(String::new(), |x| {
+                                x.as_path().to_str().unwrap().to_string()
+                            }))
+                            .pick_file();
+                    if let Some(file) = file {
+                        skin_path.replace(file.to_str().map(|path| path.to_string()));
+                    }
+                    true
+                });
+            }
+            let clear = ui::ButtonBuilder::new()
+                .position(180.0, 130.0)
+                .size(40.0, 40.0)
+                .alignment(ui::VAttach::Middle, ui::HAttach::Center)
+                .create(ui_container);
+            {
+                let mut clear = clear.borrow_mut();
+                let txt = ui::TextBuilder::new()
+                    .text("X")
+                    .alignment(ui::VAttach::Middle, ui::HAttach::Center)
+                    .attach(&mut *clear);
+                clear.add_text(txt);
+                let skin_path = self.skin_path.clone();
+                clear.add_click_func(move |_, _| {
+                    skin_path.replace(None);
+                    true
+                });
+            }
+            Some(SkinElements {
+                preview,
+                _select: select,
+                select_text,
+                _clear: clear,
+            })
+        } else {
+            None
+        };
+
         // Done
         let done = ui::ButtonBuilder::new()
-            .position(110.0, 100.0)
+            .position(110.0, 190.0)
             .size(200.0, 40.0)
             .alignment(ui::VAttach::Middle, ui::HAttach::Center)
             .create(ui_container);
@@ -110,12 +245,14 @@ impl super::Screen for EditAccountEntry {
             done.add_text(txt);
             let account_name = account_name.clone();
             let account_password = account_password.clone();
+            let skin_path = self.skin_path.clone();
             let callback = self.done_callback.clone();
             done.add_click_func(move |_, game| {
                 (*callback.clone())(
                     game,
                     account_name.borrow().input.clone(),
                     account_password.borrow().input.clone(),
+                    skin_path.borrow().clone(),
                 );
                 game.screen_sys.clone().pop_screen();
                 true
@@ -124,7 +261,7 @@ impl super::Screen for EditAccountEntry {
 
         // Cancel
         let cancel = ui::ButtonBuilder::new()
-            .position(-110.0, 100.0)
+            .position(-110.0, 190.0)
             .size(200.0, 40.0)
             .alignment(ui::VAttach::Middle, ui::HAttach::Center)
             .create(ui_container);
@@ -143,8 +280,10 @@ impl super::Screen for EditAccountEntry {
 
         self.elements = Some(UIElements {
             logo,
-            _name: account_name,
+            name: account_name,
             _password: account_password,
+            uuid,
+            skin,
             _done: done,
             _cancel: cancel,
         });
@@ -167,8 +306,30 @@ impl super::Screen for EditAccountEntry {
         _ui_container: &mut ui::Container,
         _delta: f64,
     ) {
+        let name = self.elements.as_ref().unwrap().name.borrow().input.clone();
+        let uuid = format!("UUID: {}", self.display_uuid(&name));
+        let skin_path = self.skin_path.borrow().clone();
         let elements = self.elements.as_mut().unwrap();
         elements.logo.tick(renderer);
+        {
+            let mut text = elements.uuid.borrow_mut();
+            if text.text != uuid {
+                text.text = uuid;
+            }
+        }
+        if let Some(skin) = elements.skin.as_ref() {
+            let texture = skin_texture(&skin_path);
+            let mut preview = skin.preview.borrow_mut();
+            if preview.texture != texture {
+                preview.texture = texture;
+                preview.colour = if skin_path.is_some() {
+                    (255, 255, 255, 255)
+                } else {
+                    (0, 0, 0, 255)
+                };
+                skin.select_text.borrow_mut().text = skin_button_text(&skin_path);
+            }
+        }
     }
 
     fn is_closable(&self) -> bool {
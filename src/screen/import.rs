@@ -0,0 +1,709 @@
+use std::cell::{Cell, RefCell};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::sync::Arc;
+
+use crate::nbt;
+use crate::paths;
+use crate::protocol::Serializable;
+use crate::render::Renderer;
+use crate::screen::launcher::save_accounts;
+use crate::screen::{Screen, ScreenSystem};
+use crate::shared::launcher;
+use crate::ui;
+use leafish_protocol::protocol::login::{Account, AccountType};
+use log::warn;
+use parking_lot::Mutex;
+use serde_json::Value;
+
+/// The maximum amount of entries shown on a page of the preview.
+const PER_PAGE: usize = 10;
+
+#[derive(Clone)]
+pub struct ImportedServer {
+    pub name: String,
+    pub address: String,
+    pub source: String,
+}
+
+#[derive(Clone)]
+pub struct ImportedAccount {
+    pub account: Account,
+    pub source: String,
+}
+
+#[derive(Clone)]
+enum ImportEntry {
+    Server(ImportedServer),
+    Account(ImportedAccount),
+}
+
+impl ImportEntry {
+    fn description(&self) -> String {
+        match self {
+            ImportEntry::Server(server) => {
+                format!("{} ({}) - {}", server.name, server.address, server.source)
+            }
+            ImportEntry::Account(account) => {
+                let ty = match account.account.account_type {
+                    AccountType::Microsoft => "Microsoft, this session only",
+                    AccountType::None => "Offline",
+                    AccountType::Custom(ref ty) => ty.as_str(),
+                };
+                format!("{} ({}) - {}", account.account.name, ty, account.source)
+            }
+        }
+    }
+}
+
+/// Reads the servers of the vanilla launcher and every Prism instance.
+pub fn find_servers() -> Vec<ImportedServer> {
+    let mut servers = vec![];
+    if let Some(dir) = launcher::mojang_dir() {
+        servers.extend(read_servers_dat(&dir.join("servers.dat"), "Vanilla"));
+    }
+    for dir in launcher::prism_dirs() {
+        for (instance, game_dir) in prism_instances(&dir) {
+            servers.extend(read_servers_dat(
+                &game_dir.join("servers.dat"),
+                &format!("Prism: {}", instance),
+            ));
+        }
+    }
+    servers
+}
+
+/// Reads the accounts of the vanilla launcher and Prism.
+pub fn find_accounts() -> Vec<ImportedAccount> {
+    let mut accounts = vec![];
+    if let Some(dir) = launcher::mojang_dir() {
+        for file in &[
+            "launcher_accounts.json",
+            "launcher_accounts_microsoft_store.json",
+        ] {
+            if let Ok(content) = fs::read_to_string(dir.join(file)) {
+                accounts.extend(parse_vanilla_accounts(&content));
+            }
+        }
+    }
+    for dir in launcher::prism_dirs() {
+        if let Ok(content) = fs::read_to_string(dir.join("accounts.json")) {
+            accounts.extend(parse_prism_accounts(&content));
+        }
+    }
+    accounts
+}
+
+/// Returns the name and game directory of every instance in a Prism directory.
+fn prism_instances(dir: &Path) -> Vec<(String, PathBuf)> {
+    let mut instances = vec![];
+    let entries = match fs::read_dir(dir.join("instances")) {
+        Ok(entries) => entries,
+        Err(_) => return instances,
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        // Older MultiMC based instances use "minecraft" instead of ".minecraft"
+        let game_dir = [".minecraft", "minecraft"]
+            .iter()
+            .map(|name| path.join(name))
+            .find(|path| path.is_dir());
+        if let Some(game_dir) = game_dir {
+            instances.push((entry.file_name().to_string_lossy().into_owned(), game_dir));
+        }
+    }
+    instances
+}
+
+fn read_servers_dat(path: &Path, source: &str) -> Vec<ImportedServer> {
+    let mut file = match fs::File::open(path) {
+        Ok(file) => file,
+        Err(_) => return vec![],
+    };
+    match Option::<nbt::NamedTag>::read_from(&mut file) {
+        Ok(Some(tag)) => parse_servers_dat(&tag.1, source),
+        Ok(None) => vec![],
+        Err(err) => {
+            warn!("Failed to read {}: {}", path.display(), err);
+            vec![]
+        }
+    }
+}
+
+fn parse_servers_dat(root: &nbt::Tag, source: &str) -> Vec<ImportedServer> {
+    let servers = match root.get("servers").and_then(|servers| servers.as_list()) {
+        Some(servers) => servers,
+        None => return vec![],
+    };
+    servers
+        .iter()
+        .filter_map(|server| {
+            let address = server.get("ip")?.as_str()?.to_owned();
+            let name = server
+                .get("name")
+                .and_then(|name| name.as_str())
+                .unwrap_or(&address)
+                .to_owned();
+            Some(ImportedServer {
+                name,
+                address,
+                source: source.to_owned(),
+            })
+        })
+        .collect()
+}
+
+fn microsoft_account(name: &str, uuid: &str, token: &str, source: &str) -> ImportedAccount {
+    ImportedAccount {
+        account: Account {
+            name: name.to_owned(),
+            uuid: Some(uuid.to_owned()),
+            verification_tokens: vec![name.to_owned(), "".to_owned(), token.to_owned()],
+            head_img_data: None,
+            account_type: AccountType::Microsoft,
+            skin_path: None,
+        },
+        source: source.to_owned(),
+    }
+}
+
+fn parse_vanilla_accounts(content: &str) -> Vec<ImportedAccount> {
+    let json: Value = match serde_json::from_str(content) {
+        Ok(json) => json,
+        Err(_) => return vec![],
+    };
+    let accounts = match json
+        .get("accounts")
+        .and_then(|accounts| accounts.as_object())
+    {
+        Some(accounts) => accounts,
+        None => return vec![],
+    };
+    accounts
+        .values()
+        .filter_map(|account| {
+            // Legacy mojang accounts can't be used anymore
+            if account.get("type")?.as_str()? != "Xbox" {
+                return None;
+            }
+            let profile = account.get("minecraftProfile")?;
+            Some(microsoft_account(
+                profile.get("name")?.as_str()?,
+                profile.get("id")?.as_str()?,
+                account.get("accessToken")?.as_str()?,
+                "Vanilla",
+            ))
+        })
+        .collect()
+}
+
+fn parse_prism_accounts(content: &str) -> Vec<ImportedAccount> {
+    let json: Value = match serde_json::from_str(content) {
+        Ok(json) => json,
+        Err(_) => return vec![],
+    };
+    let accounts = match json
+        .get("accounts")
+        .and_then(|accounts| accounts.as_array())
+    {
+        Some(accounts) => accounts,
+        None => return vec![],
+    };
+    accounts
+        .iter()
+        .filter_map(|account| {
+            let profile = account.get("profile")?;
+            let name = profile.get("name")?.as_str()?;
+            match account.get("type")?.as_str()? {
+                "MSA" => Some(microsoft_account(
+                    name,
+                    profile.get("id")?.as_str()?,
+                    account.get("ygg")?.get("token")?.as_str()?,
+                    "Prism",
+                )),
+                "Offline" => Account::login(name, "", "", AccountType::None)
+                    .ok()
+                    .map(|account| ImportedAccount {
+                        account,
+                        source: "Prism".to_owned(),
+                    }),
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+fn append_servers(servers: &[&ImportedServer]) {
+    let mut servers_info = match fs::File::open(paths::get_data_dir().join("servers.json")) {
+        Ok(val) => serde_json::from_reader(val).unwrap(),
+        Err(_) => {
+            let mut info = BTreeMap::default();
+            info.insert("servers".to_owned(), Value::Array(vec![]));
+            Value::Object(info.into_iter().collect())
+        }
+    };
+
+    {
+        let list = servers_info
+            .as_object_mut()
+            .unwrap()
+            .get_mut("servers")
+            .unwrap()
+            .as_array_mut()
+            .unwrap();
+        for server in servers {
+            let mut entry = BTreeMap::default();
+            entry.insert("name".to_owned(), Value::String(server.name.clone()));
+            entry.insert("address".to_owned(), Value::String(server.address.clone()));
+            list.push(Value::Object(entry.into_iter().collect()));
+        }
+    }
+
+    let mut out = fs::File::create(paths::get_data_dir().join("servers.json")).unwrap();
+    serde_json::to_writer_pretty(&mut out, &servers_info).unwrap();
+}
+
+fn known_server_addresses() -> Vec<String> {
+    let file = match fs::File::open(paths::get_data_dir().join("servers.json")) {
+        Ok(val) => val,
+        Err(_) => return vec![],
+    };
+    let servers_info: Value = match serde_json::from_reader(file) {
+        Ok(val) => val,
+        Err(_) => return vec![],
+    };
+    servers_info
+        .get("servers")
+        .and_then(|servers| servers.as_array())
+        .map(|servers| {
+            servers
+                .iter()
+                .filter_map(|server| server.get("address")?.as_str().map(|s| s.to_owned()))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Shows everything that can be imported from other launchers and
+/// only writes the entries the user left selected once confirmed.
+pub struct Import {
+    elements: Option<UIElements>,
+    entries: Rc<Vec<ImportEntry>>,
+    selected: Rc<RefCell<Vec<bool>>>,
+    accounts: Option<Arc<Mutex<Vec<Account>>>>,
+}
+
+impl Clone for Import {
+    fn clone(&self) -> Self {
+        Self {
+            elements: None,
+            entries: self.entries.clone(),
+            selected: self.selected.clone(),
+            accounts: self.accounts.clone(),
+        }
+    }
+}
+
+struct UIElements {
+    logo: ui::logo::Logo,
+
+    _title: ui::TextRef,
+    pages: Rc<Pages>,
+    _page_buttons: Vec<ui::ButtonRef>,
+    _import: ui::ButtonRef,
+    _cancel: ui::ButtonRef,
+}
+
+/// The rows of the preview, reused for every page of entries.
+struct Pages {
+    entries: Rc<Vec<ImportEntry>>,
+    selected: Rc<RefCell<Vec<bool>>>,
+    current: Rc<Cell<usize>>,
+    rows: Vec<(ui::ImageRef, ui::TextRef)>,
+    label: Option<ui::TextRef>,
+}
+
+impl Pages {
+    fn count(&self) -> usize {
+        ((self.entries.len() + PER_PAGE - 1) / PER_PAGE).max(1)
+    }
+
+    /// Fills the rows with the entries of `page`, leaving the rows past
+    /// the last entry blank.
+    fn show(&self, page: usize) {
+        let page = page.min(self.count() - 1);
+        self.current.set(page);
+        let selected = self.selected.borrow();
+        for (row, (back, text)) in self.rows.iter().enumerate() {
+            let idx = page * PER_PAGE + row;
+            let mut back = back.borrow_mut();
+            let mut text = text.borrow_mut();
+            match self.entries.get(idx) {
+                Some(entry) => {
+                    back.colour = Import::entry_colour(selected[idx]);
+                    text.text = entry.description();
+                }
+                None => {
+                    back.colour = (0, 0, 0, 0);
+                    text.text = String::new();
+                }
+            }
+        }
+        if let Some(label) = &self.label {
+            label.borrow_mut().text = format!("Page {} of {}", page + 1, self.count());
+        }
+    }
+
+    fn scroll(&self, pages: isize) {
+        let page = self.current.get() as isize + pages;
+        self.show(page.max(0) as usize);
+    }
+}
+
+impl Import {
+    /// Import flow for the server list, servers already present
+    /// in `servers.json` are skipped.
+    pub fn servers() -> Self {
+        let known = known_server_addresses();
+        let mut entries: Vec<ImportEntry> = vec![];
+        for server in find_servers() {
+            let duplicate = known.contains(&server.address)
+                || entries.iter().any(|entry| match entry {
+                    ImportEntry::Server(other) => other.address == server.address,
+                    _ => false,
+                });
+            if !duplicate {
+                entries.push(ImportEntry::Server(server));
+            }
+        }
+        Self::new(entries, None)
+    }
+
+    /// Import flow for the launcher, accounts which are already known
+    /// (by name and type) are skipped.
+    pub fn accounts(accounts: Arc<Mutex<Vec<Account>>>) -> Self {
+        let mut entries: Vec<ImportEntry> = vec![];
+        {
+            let known = accounts.lock();
+            for imported in find_accounts() {
+                let same = |other: &Account| {
+                    other.name == imported.account.name
+                        && other.account_type == imported.account.account_type
+                };
+                let duplicate = known.iter().any(same)
+                    || entries.iter().any(|entry| match entry {
+                        ImportEntry::Account(other) => same(&other.account),
+                        _ => false,
+                    });
+                if !duplicate {
+                    entries.push(ImportEntry::Account(imported));
+                }
+            }
+        }
+        Self::new(entries, Some(accounts))
+    }
+
+    fn new(entries: Vec<ImportEntry>, accounts: Option<Arc<Mutex<Vec<Account>>>>) -> Self {
+        let selected = vec![true; entries.len()];
+        Self {
+            elements: None,
+            entries: Rc::new(entries),
+            selected: Rc::new(RefCell::new(selected)),
+            accounts,
+        }
+    }
+
+    fn entry_colour(selected: bool) -> (u8, u8, u8, u8) {
+        if selected {
+            (0, 80, 0, 150)
+        } else {
+            (0, 0, 0, 150)
+        }
+    }
+}
+
+impl super::Screen for Import {
+    fn on_active(
+        &mut self,
+        _screen_sys: &ScreenSystem,
+        renderer: Arc<Renderer>,
+        ui_container: &mut ui::Container,
+    ) {
+        let logo = ui::logo::Logo::new(renderer.resources.clone(), ui_container);
+
+        let title = ui::TextBuilder::new()
+            .text(if self.entries.is_empty() {
+                "Found nothing new to import"
+            } else {
+                "Select what to import"
+            })
+            .position(0.0, -30.0)
+            .alignment(ui::VAttach::Middle, ui::HAttach::Center)
+            .create(ui_container);
+
+        let current = Rc::new(Cell::new(0));
+        let mut rows = vec![];
+        for row in 0..PER_PAGE.min(self.entries.len()) {
+            let back = ui::ImageBuilder::new()
+                .texture("leafish:solid")
+                .position(0.0, row as f64 * 25.0)
+                .size(600.0, 22.0)
+                .alignment(ui::VAttach::Middle, ui::HAttach::Center)
+                .create(ui_container);
+            let text = {
+                let mut back = back.borrow_mut();
+                let text = ui::TextBuilder::new()
+                    .text("")
+                    .position(5.0, 0.0)
+                    .colour((200, 200, 200, 255))
+                    .alignment(ui::VAttach::Middle, ui::HAttach::Left)
+                    .attach(&mut *back);
+                let selected = self.selected.clone();
+                let current = current.clone();
+                back.add_click_func(move |this, _| {
+                    let idx = current.get() * PER_PAGE + row;
+                    let mut selected = selected.borrow_mut();
+                    if idx < selected.len() {
+                        selected[idx] = !selected[idx];
+                        this.colour = Self::entry_colour(selected[idx]);
+                    }
+                    true
+                });
+                text
+            };
+            rows.push((back, text));
+        }
+        let mut buttons_y = rows.len() as f64 * 25.0 + 50.0;
+
+        // Every entry can be toggled, a page at a time
+        let paged = self.entries.len() > PER_PAGE;
+        let label = if paged {
+            Some(
+                ui::TextBuilder::new()
+                    .position(0.0, buttons_y - 20.0)
+                    .colour((150, 150, 150, 255))
+                    .alignment(ui::VAttach::Middle, ui::HAttach::Center)
+                    .create(ui_container),
+            )
+        } else {
+            None
+        };
+        let pages = Rc::new(Pages {
+            entries: self.entries.clone(),
+            selected: self.selected.clone(),
+            current,
+            rows,
+            label,
+        });
+        pages.show(0);
+
+        let mut page_buttons = vec![];
+        if paged {
+            for (text, x, step) in [("<", -150.0, -1), (">", 150.0, 1)] {
+                let button = ui::ButtonBuilder::new()
+                    .position(x, buttons_y - 20.0)
+                    .size(40.0, 30.0)
+                    .alignment(ui::VAttach::Middle, ui::HAttach::Center)
+                    .create(ui_container);
+                {
+                    let mut button = button.borrow_mut();
+                    let txt = ui::TextBuilder::new()
+                        .text(text)
+                        .alignment(ui::VAttach::Middle, ui::HAttach::Center)
+                        .attach(&mut *button);
+                    button.add_text(txt);
+                    let pages = pages.clone();
+                    button.add_click_func(move |_, _| {
+                        pages.scroll(step);
+                        true
+                    });
+                }
+                page_buttons.push(button);
+            }
+            buttons_y += 30.0;
+        }
+
+        // Import
+        let import = ui::ButtonBuilder::new()
+            .position(110.0, buttons_y)
+            .size(200.0, 40.0)
+            .alignment(ui::VAttach::Middle, ui::HAttach::Center)
+            .create(ui_container);
+        {
+            let mut import = import.borrow_mut();
+            let txt = ui::TextBuilder::new()
+                .text("Import")
+                .alignment(ui::VAttach::Middle, ui::HAttach::Center)
+                .attach(&mut *import);
+            import.add_text(txt);
+            import.disabled = self.entries.is_empty();
+            let entries = self.entries.clone();
+            let selected = self.selected.clone();
+            let accounts = self.accounts.clone();
+            import.add_click_func(move |_, game| {
+                let selected = selected.borrow();
+                let chosen = entries
+                    .iter()
+                    .zip(selected.iter())
+                    .filter(|(_, selected)| **selected)
+                    .map(|(entry, _)| entry);
+                let mut servers = vec![];
+                let mut new_accounts = vec![];
+                for entry in chosen {
+                    match entry {
+                        ImportEntry::Server(server) => servers.push(server),
+                        ImportEntry::Account(account) => new_accounts.push(account),
+                    }
+                }
+                if !servers.is_empty() {
+                    append_servers(&servers);
+                }
+                if let Some(accounts) = accounts.as_ref() {
+                    if !new_accounts.is_empty() {
+                        let mut accounts = accounts.lock();
+                        accounts.extend(new_accounts.into_iter().map(|a| a.account.clone()));
+                        save_accounts(&accounts);
+                    }
+                }
+                game.screen_sys.clone().pop_screen();
+                true
+            });
+        }
+
+        // Cancel
+        let cancel = ui::ButtonBuilder::new()
+            .position(-110.0, buttons_y)
+            .size(200.0, 40.0)
+            .alignment(ui::VAttach::Middle, ui::HAttach::Center)
+            .create(ui_container);
+        {
+            let mut cancel = cancel.borrow_mut();
+            let txt = ui::TextBuilder::new()
+                .text("Cancel")
+                .alignment(ui::VAttach::Middle, ui::HAttach::Center)
+                .attach(&mut *cancel);
+            cancel.add_text(txt);
+            cancel.add_click_func(|_, game| {
+                game.screen_sys.clone().pop_screen();
+                true
+            });
+        }
+
+        self.elements = Some(UIElements {
+            logo,
+            _title: title,
+            pages,
+            _page_buttons: page_buttons,
+            _import: import,
+            _cancel: cancel,
+        });
+    }
+
+    fn on_deactive(
+        &mut self,
+        _screen_sys: &ScreenSystem,
+        _renderer: Arc<Renderer>,
+        _ui_container: &mut ui::Container,
+    ) {
+        // Clean up
+        self.elements = None
+    }
+
+    fn tick(
+        &mut self,
+        _screen_sys: &ScreenSystem,
+        renderer: Arc<Renderer>,
+        _ui_container: &mut ui::Container,
+        _delta: f64,
+    ) {
+        let elements = self.elements.as_mut().unwrap();
+        elements.logo.tick(renderer);
+    }
+
+    fn on_scroll(&mut self, _: f64, y: f64) {
+        if let Some(elements) = self.elements.as_ref() {
+            if y > 0.0 {
+                elements.pages.scroll(-1);
+            } else if y < 0.0 {
+                elements.pages.scroll(1);
+            }
+        }
+    }
+
+    fn is_closable(&self) -> bool {
+        true
+    }
+
+    fn clone_screen(&self) -> Box<dyn Screen> {
+        Box::new(self.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn vanilla_accounts() {
+        let accounts = parse_vanilla_accounts(
+            r#"{
+                "accounts": {
+                    "a": {
+                        "accessToken": "token",
+                        "minecraftProfile": { "id": "069a79f444e94726a5befca90e38aaf5", "name": "Notch" },
+                        "type": "Xbox",
+                        "username": "notch@example.com"
+                    },
+                    "b": {
+                        "accessToken": "old",
+                        "minecraftProfile": { "id": "853c80ef3c3749fdaa49938b674adae6", "name": "jeb_" },
+                        "type": "Mojang"
+                    }
+                }
+            }"#,
+        );
+        assert_eq!(accounts.len(), 1);
+        let account = &accounts[0].account;
+        assert_eq!(account.name, "Notch");
+        assert!(account.account_type == AccountType::Microsoft);
+        assert_eq!(account.verification_tokens[2], "token");
+    }
+
+    #[test]
+    fn prism_accounts() {
+        let accounts = parse_prism_accounts(
+            r#"{
+                "accounts": [
+                    { "type": "MSA", "profile": { "id": "069a79f444e94726a5befca90e38aaf5", "name": "Notch" }, "ygg": { "token": "token" } },
+                    { "type": "Offline", "profile": { "id": "x", "name": "Steve" } }
+                ],
+                "formatVersion": 3
+            }"#,
+        );
+        assert_eq!(accounts.len(), 2);
+        assert!(accounts[1].account.account_type == AccountType::None);
+        assert_eq!(
+            accounts[1].account.uuid.as_deref(),
+            Some("5627dd98-e6be-3c21-b8a8-e92344183641")
+        );
+    }
+
+    #[test]
+    fn servers_dat() {
+        let mut server = nbt::Tag::new_compound();
+        server.put("name", nbt::Tag::String("Test".to_owned()));
+        server.put("ip", nbt::Tag::String("localhost:25565".to_owned()));
+        let mut unnamed = nbt::Tag::new_compound();
+        unnamed.put("ip", nbt::Tag::String("example.com".to_owned()));
+        let mut root = nbt::Tag::new_compound();
+        root.put("servers", nbt::Tag::List(vec![server, unnamed]));
+
+        let servers = parse_servers_dat(&root, "Vanilla");
+        assert_eq!(servers.len(), 2);
+        assert_eq!(servers[0].name, "Test");
+        assert_eq!(servers[0].address, "localhost:25565");
+        assert_eq!(servers[1].name, "example.com");
+    }
+}
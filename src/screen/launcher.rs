@@ -37,6 +37,7 @@ pub struct Launcher {
     disclaimer: Option<ui::TextRef>,
     accounts: Arc<Mutex<Vec<Account>>>,
     add: Option<ui::ButtonRef>,
+    import: Option<ui::ButtonRef>,
     background_selection: Option<ui::ButtonRef>,
     screen_sys: Arc<ScreenSystem>,
     active_account: Arc<Mutex<Option<Account>>>,
@@ -71,6 +72,7 @@ impl Launcher {
             disclaimer: None,
             accounts,
             add: None,
+            import: None,
             background_selection: None,
             screen_sys,
             active_account,
@@ -160,6 +162,30 @@ impl super::Screen for Launcher {
             })
         }
         self.add.replace(add);
+
+        // Import accounts from other launchers
+        let import = ui::ButtonBuilder::new()
+            .position(300.0, -50.0 - 15.0)
+            .size(100.0, 30.0)
+            .alignment(ui::VAttach::Middle, ui::HAttach::Center)
+            .draw_index(2)
+            .create(ui_container);
+        {
+            let mut import = import.borrow_mut();
+            let txt = ui::TextBuilder::new()
+                .text("Import")
+                .alignment(ui::VAttach::Middle, ui::HAttach::Center)
+                .attach(&mut *import);
+            import.add_text(txt);
+            let accounts = self.accounts.clone();
+            import.add_click_func(move |_, game| {
+                game.screen_sys
+                    .clone()
+                    .add_screen(Box::new(super::import::Import::accounts(accounts.clone())));
+                true
+            })
+        }
+        self.import.replace(import);
         let background_selection = ui::ButtonBuilder::new()
             .position(10.0, 25.0)
             .size(200.0, 30.0)
@@ -201,6 +227,8 @@ impl super::Screen for Launcher {
             let account_account_name_text = account.verification_tokens.get(0).unwrap().clone();
             let account_password_text = account.verification_tokens.get(1).unwrap().clone();
             let account_type = account.account_type.clone();
+            let account_uuid = account.uuid.clone();
+            let account_skin_path = account.skin_path.clone();
             // Everything is attached to this
             let back = ui::ImageBuilder::new()
                 .texture("leafish:solid")
@@ -252,7 +280,10 @@ impl super::Screen for Launcher {
                                     account_account_name_text.clone(),
                                     account_password_text.clone(),
                                 )),
-                                Rc::new(move |game, name, password| {
+                                account_type.clone(),
+                                account.uuid.clone(),
+                                account.skin_path.clone(),
+                                Rc::new(move |game, name, password, skin_path| {
                                     let client_token =
                                         game.settings.get_string(StringSetting::AuthClientToken);
                                     let account = crate::screen::login::try_login(
@@ -266,7 +297,8 @@ impl super::Screen for Launcher {
                                     let accounts = accounts.clone();
                                     let mut accounts = accounts.lock();
                                     match account {
-                                        Ok(account) => {
+                                        Ok(mut account) => {
+                                            account.skin_path = skin_path;
                                             drop(std::mem::replace(&mut (*accounts)[idx], account));
                                             save_accounts(&accounts);
                                         }
@@ -369,6 +401,8 @@ impl super::Screen for Launcher {
                 let aname = account_name_text.clone();
                 let apw = account_password_text;
                 let account_type = account_type.clone();
+                let uuid = account_uuid;
+                let skin_path = account_skin_path;
                 let accounts = self.accounts.clone();
                 btn.add_click_func(move |_, game| {
                     let accounts = accounts.clone();
@@ -376,7 +410,10 @@ impl super::Screen for Launcher {
                     game.screen_sys.clone().add_screen(Box::new(
                         super::edit_account::EditAccountEntry::new(
                             Some((aname.clone(), apw.clone())),
-                            Rc::new(move |game, name, password| {
+                            account_type.clone(),
+                            uuid.clone(),
+                            skin_path.clone(),
+                            Rc::new(move |game, name, password, skin_path| {
                                 let client_token =
                                     game.settings.get_string(StringSetting::AuthClientToken);
                                 let account = crate::screen::login::try_login(
@@ -390,7 +427,8 @@ impl super::Screen for Launcher {
                                 let accounts = accounts.clone();
                                 let mut accounts = accounts.lock();
                                 match account {
-                                    Ok(account) => {
+                                    Ok(mut account) => {
+                                        account.skin_path = skin_path;
                                         drop(std::mem::replace(&mut (*accounts)[idx], account));
                                         save_accounts(&accounts);
                                     }
@@ -429,6 +467,7 @@ impl super::Screen for Launcher {
         self.disclaimer.take();
         self.rendered_accounts.clear();
         self.add.take();
+        self.import.take();
         self.background_selection.take();
     }
 
@@ -479,7 +518,7 @@ impl super::Screen for Launcher {
     }
 }
 
-fn save_accounts(accounts: &[Account]) {
+pub fn save_accounts(accounts: &[Account]) {
     let mut file = File::create(paths::get_config_dir().join("accounts.cfg")).unwrap();
     // filter out microsoft accounts as these will become invalid after ~1 day, so the launcher has to
     // provide us with a fresh token on startup
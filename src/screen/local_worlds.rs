@@ -0,0 +1,294 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::sync::Arc;
+use std::time::SystemTime;
+
+use crate::paths;
+use crate::render::Renderer;
+use crate::screen::{Screen, ScreenSystem, ScreenType};
+use crate::shared::launcher;
+use crate::ui;
+use crate::world::anvil;
+use crate::Game;
+use log::warn;
+use winit::keyboard::{Key, NamedKey, PhysicalKey};
+
+/// The maximum amount of saves listed at once.
+const MAX_SHOWN: usize = 10;
+
+struct LocalWorld {
+    name: String,
+    dir: PathBuf,
+    source: &'static str,
+    modified: SystemTime,
+}
+
+/// The saves of the vanilla launcher and the worlds saved by us, most
+/// recently played first.
+fn find_worlds() -> Vec<LocalWorld> {
+    let mut roots = vec![];
+    if let Some(dir) = launcher::mojang_dir() {
+        roots.push((dir.join("saves"), "Vanilla"));
+    }
+    roots.push((paths::get_data_dir().join("saves"), "Leafish"));
+
+    let mut worlds = vec![];
+    for (root, source) in roots {
+        let entries = match fs::read_dir(&root) {
+            Ok(entries) => entries,
+            Err(_) => continue,
+        };
+        for entry in entries.flatten() {
+            let dir = entry.path();
+            if let Some(world) = read_world(&dir, source) {
+                worlds.push(world);
+            }
+        }
+    }
+    worlds.sort_by(|a, b| b.modified.cmp(&a.modified));
+    worlds
+}
+
+fn read_world(dir: &Path, source: &'static str) -> Option<LocalWorld> {
+    let modified = fs::metadata(dir.join("level.dat"))
+        .and_then(|meta| meta.modified())
+        .ok()?;
+    let name = anvil::read_level(dir)
+        .ok()
+        .map(|level| level.name)
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| dir.file_name().unwrap_or_default().to_string_lossy().into());
+    Some(LocalWorld {
+        name,
+        dir: dir.to_owned(),
+        source,
+        modified,
+    })
+}
+
+/// Lists the saves which can be looked at without a server.
+pub struct LocalWorlds {
+    elements: Option<UIElements>,
+    worlds: Rc<Vec<LocalWorld>>,
+}
+
+impl Clone for LocalWorlds {
+    fn clone(&self) -> Self {
+        Self {
+            elements: None,
+            worlds: self.worlds.clone(),
+        }
+    }
+}
+
+struct UIElements {
+    logo: ui::logo::Logo,
+
+    _title: ui::TextRef,
+    _entries: Vec<ui::ImageRef>,
+    _back: ui::ButtonRef,
+}
+
+impl LocalWorlds {
+    /// Looks for saves each time the screen is opened.
+    pub fn find() -> Self {
+        Self {
+            elements: None,
+            worlds: Rc::new(find_worlds()),
+        }
+    }
+}
+
+impl super::Screen for LocalWorlds {
+    fn on_active(
+        &mut self,
+        _screen_sys: &ScreenSystem,
+        renderer: Arc<Renderer>,
+        ui_container: &mut ui::Container,
+    ) {
+        let logo = ui::logo::Logo::new(renderer.resources.clone(), ui_container);
+
+        let title = ui::TextBuilder::new()
+            .text(if self.worlds.is_empty() {
+                "Found no saves to view"
+            } else {
+                "Select a world to view"
+            })
+            .position(0.0, -30.0)
+            .alignment(ui::VAttach::Middle, ui::HAttach::Center)
+            .create(ui_container);
+
+        let mut entries = vec![];
+        for (idx, world) in self.worlds.iter().enumerate().take(MAX_SHOWN) {
+            let back = ui::ImageBuilder::new()
+                .texture("leafish:solid")
+                .colour((0, 0, 0, 150))
+                .position(0.0, idx as f64 * 25.0)
+                .size(600.0, 22.0)
+                .alignment(ui::VAttach::Middle, ui::HAttach::Center)
+                .create(ui_container);
+            {
+                let mut back = back.borrow_mut();
+                ui::TextBuilder::new()
+                    .text(format!("{} - {}", world.name, world.source))
+                    .position(5.0, 0.0)
+                    .colour((200, 200, 200, 255))
+                    .alignment(ui::VAttach::Middle, ui::HAttach::Left)
+                    .attach(&mut *back);
+                back.add_hover_func(|this, over, _| {
+                    // Keep failed entries red
+                    if this.colour.0 == 0 {
+                        this.colour.3 = if over { 200 } else { 150 };
+                    }
+                    false
+                });
+                let worlds = self.worlds.clone();
+                back.add_click_func(move |this, game| {
+                    let dir = &worlds[idx].dir;
+                    if let Err(err) = game.open_viewer(dir) {
+                        warn!("Failed to open {:?}: {}", dir, err);
+                        this.colour = (120, 0, 0, 150);
+                    }
+                    true
+                });
+            }
+            entries.push(back);
+        }
+        let buttons_y = MAX_SHOWN.min(self.worlds.len()) as f64 * 25.0 + 50.0;
+
+        let back = ui::ButtonBuilder::new()
+            .position(0.0, buttons_y)
+            .size(200.0, 40.0)
+            .alignment(ui::VAttach::Middle, ui::HAttach::Center)
+            .create(ui_container);
+        {
+            let mut back = back.borrow_mut();
+            let txt = ui::TextBuilder::new()
+                .text("Back")
+                .alignment(ui::VAttach::Middle, ui::HAttach::Center)
+                .attach(&mut *back);
+            back.add_text(txt);
+            back.add_click_func(|_, game| {
+                game.screen_sys
+                    .clone()
+                    .replace_screen(Box::new(super::ServerList::new(None)));
+                true
+            });
+        }
+
+        self.elements = Some(UIElements {
+            logo,
+            _title: title,
+            _entries: entries,
+            _back: back,
+        });
+    }
+
+    fn on_deactive(
+        &mut self,
+        _screen_sys: &ScreenSystem,
+        _renderer: Arc<Renderer>,
+        _ui_container: &mut ui::Container,
+    ) {
+        // Clean up
+        self.elements = None
+    }
+
+    fn tick(
+        &mut self,
+        _screen_sys: &ScreenSystem,
+        renderer: Arc<Renderer>,
+        _ui_container: &mut ui::Container,
+        _delta: f64,
+    ) {
+        let elements = self.elements.as_mut().unwrap();
+        elements.logo.tick(renderer);
+    }
+
+    fn clone_screen(&self) -> Box<dyn Screen> {
+        Box::new(self.clone())
+    }
+}
+
+/// Shown while flying through a save, passes movement keys on to the
+/// viewer and leaves it on escape.
+pub struct Viewing {
+    name: String,
+    hint: Option<ui::TextRef>,
+}
+
+impl Clone for Viewing {
+    fn clone(&self) -> Self {
+        Self {
+            name: self.name.clone(),
+            hint: None,
+        }
+    }
+}
+
+impl Viewing {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_owned(),
+            hint: None,
+        }
+    }
+}
+
+impl super::Screen for Viewing {
+    fn on_active(
+        &mut self,
+        _screen_sys: &ScreenSystem,
+        _renderer: Arc<Renderer>,
+        ui_container: &mut ui::Container,
+    ) {
+        self.hint = Some(
+            ui::TextBuilder::new()
+                .text(format!("Viewing {} - press Escape to leave", self.name))
+                .position(5.0, 5.0)
+                .colour((255, 255, 255, 200))
+                .create(ui_container),
+        );
+    }
+
+    fn on_deactive(
+        &mut self,
+        _screen_sys: &ScreenSystem,
+        _renderer: Arc<Renderer>,
+        _ui_container: &mut ui::Container,
+    ) {
+        self.hint = None;
+    }
+
+    fn tick(
+        &mut self,
+        _screen_sys: &ScreenSystem,
+        _renderer: Arc<Renderer>,
+        _ui_container: &mut ui::Container,
+        _delta: f64,
+    ) {
+    }
+
+    fn on_key_press(&mut self, key: (Key, PhysicalKey), down: bool, repeat: bool, game: &Game) {
+        if key.0 == Key::Named(NamedKey::Escape) && down && !repeat {
+            game.close_viewer();
+            return;
+        }
+        if let PhysicalKey::Code(code) = key.1 {
+            if let Some(action_key) = game.keybinds.get(code, &key.0) {
+                if let Some(viewer) = game.viewer.load().as_ref() {
+                    viewer.key_press(down, action_key.action);
+                }
+            }
+        }
+    }
+
+    fn ty(&self) -> ScreenType {
+        ScreenType::InGame
+    }
+
+    fn clone_screen(&self) -> Box<dyn Screen> {
+        Box::new(self.clone())
+    }
+}
@@ -291,6 +291,7 @@ pub fn try_login(
             verification_tokens: vec![account_name, password, token.unwrap_or_default()],
             head_img_data: None,
             account_type,
+            skin_path: None,
         },
         client_token,
     )
@@ -24,7 +24,9 @@ pub mod edit_server;
 pub mod background;
 pub mod chat;
 pub mod edit_account;
+pub mod import;
 pub mod launcher;
+pub mod local_worlds;
 pub mod respawn;
 pub mod settings_menu;
 
@@ -25,7 +25,7 @@ use crate::protocol;
 use crate::render;
 use crate::ui;
 
-use crate::render::hud::{Hud, HudContext};
+use crate::render::hud::HudContext;
 use crate::render::Renderer;
 use crate::screen::{Screen, ScreenSystem};
 use crate::ui::Container;
@@ -62,6 +62,8 @@ struct UIElements {
 
     _add_btn: ui::ButtonRef,
     _refresh_btn: ui::ButtonRef,
+    _import_btn: ui::ButtonRef,
+    _worlds_btn: ui::ButtonRef,
     _options_btn: ui::ButtonRef,
     _disclaimer: ui::TextRef,
 
@@ -165,23 +167,11 @@ impl ServerList {
                     false
                 });
                 backr.add_click_func(move |_, game| {
-                    game.screen_sys
-                        .clone()
-                        .replace_screen(Box::new(super::connecting::Connecting::new(&address)));
+                    let connecting = super::connecting::Connecting::new(&address);
+                    let status = connecting.status();
+                    game.screen_sys.clone().replace_screen(Box::new(connecting));
                     let hud_context = Arc::new(RwLock::new(HudContext::new()));
-                    let result = game.connect_to(&address, hud_context.clone());
-                    game.screen_sys.clone().pop_screen();
-                    if let Err(error) = result {
-                        game.screen_sys
-                            .clone()
-                            .add_screen(Box::new(ServerList::new(Some(Component::new(
-                                ComponentType::new(&error.to_string(), None),
-                            )))));
-                    } else {
-                        game.screen_sys
-                            .clone()
-                            .add_screen(Box::new(Hud::new(hud_context)));
-                    }
+                    game.connect_to(&address, hud_context, status);
                     true
                 });
             }
@@ -428,6 +418,50 @@ impl ServerList {
             })
         }
 
+        // Import servers from other launchers
+        let import = ui::ButtonBuilder::new()
+            .position(400.0, -50.0 - 15.0)
+            .size(100.0, 30.0)
+            .alignment(ui::VAttach::Middle, ui::HAttach::Center)
+            .draw_index(2)
+            .create(ui_container);
+        {
+            let mut import = import.borrow_mut();
+            let txt = ui::TextBuilder::new()
+                .text("Import")
+                .alignment(ui::VAttach::Middle, ui::HAttach::Center)
+                .attach(&mut *import);
+            import.add_text(txt);
+            import.add_click_func(move |_, game| {
+                game.screen_sys
+                    .clone()
+                    .add_screen(Box::new(super::import::Import::servers()));
+                true
+            })
+        }
+
+        // Look at a local save without a server
+        let worlds = ui::ButtonBuilder::new()
+            .position(-275.0, -50.0 - 15.0)
+            .size(150.0, 30.0)
+            .alignment(ui::VAttach::Middle, ui::HAttach::Center)
+            .draw_index(2)
+            .create(ui_container);
+        {
+            let mut worlds = worlds.borrow_mut();
+            let txt = ui::TextBuilder::new()
+                .text("View local world")
+                .alignment(ui::VAttach::Middle, ui::HAttach::Center)
+                .attach(&mut *worlds);
+            worlds.add_text(txt);
+            worlds.add_click_func(|_, game| {
+                game.screen_sys
+                    .clone()
+                    .replace_screen(Box::new(super::local_worlds::LocalWorlds::find()));
+                true
+            })
+        }
+
         // Options menu
         let options = ui::ButtonBuilder::new()
             .position(5.0, 25.0)
@@ -507,6 +541,8 @@ impl ServerList {
 
             _add_btn: add,
             _refresh_btn: refresh,
+            _import_btn: import,
+            _worlds_btn: worlds,
             _options_btn: options,
             _disclaimer: disclaimer,
 
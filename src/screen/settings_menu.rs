@@ -1,6 +1,7 @@
 use crate::render;
 use crate::settings::SettingStore;
 use crate::ui;
+use crate::world;
 
 use crate::screen::{Screen, ScreenSystem};
 use crate::BoolSetting;
@@ -291,6 +292,9 @@ impl super::Screen for VideoSettingsMenu {
         let r_max_fps = self.settings.get_int(IntSetting::MaxFps);
         let r_fov = self.settings.get_int(IntSetting::FOV);
         let r_vsync = self.settings.get_bool(BoolSetting::Vsync);
+        let r_biome_blend = self.settings.get_int(IntSetting::BiomeBlend);
+        let r_render_distance = self.settings.get_int(IntSetting::RenderDistance);
+        let r_chunk_cache = self.settings.get_int(IntSetting::ChunkCacheSize);
 
         // Setting buttons
         // TODO: Slider
@@ -316,6 +320,35 @@ impl super::Screen for VideoSettingsMenu {
         }
         buttons.push(fov_setting);
 
+        let biome_blend_setting = ui::ButtonBuilder::new()
+            .position(-160.0, -50.0)
+            .size(300.0, 40.0)
+            .alignment(ui::VAttach::Middle, ui::HAttach::Center)
+            .create(ui_container);
+        {
+            let mut biome_blend_setting = biome_blend_setting.borrow_mut();
+            let txt = ui::TextBuilder::new()
+                .text(biome_blend_text(r_biome_blend))
+                .alignment(ui::VAttach::Middle, ui::HAttach::Center)
+                .attach(&mut *biome_blend_setting);
+            let txt_biome_blend = txt.clone();
+            biome_blend_setting.add_text(txt);
+            biome_blend_setting.add_click_func(move |_, game| {
+                let r_biome_blend = (game.settings.get_int(IntSetting::BiomeBlend) + 1)
+                    % (world::MAX_BIOME_BLEND as i32 + 1);
+                txt_biome_blend.borrow_mut().text = biome_blend_text(r_biome_blend);
+                game.settings.set_int(IntSetting::BiomeBlend, r_biome_blend);
+                if let Some(server) = game.server.load().as_ref() {
+                    game.apply_biome_blend(&server.world);
+                }
+                if let Some(viewer) = game.viewer.load().as_ref() {
+                    game.apply_biome_blend(&viewer.world);
+                }
+                true
+            });
+        }
+        buttons.push(biome_blend_setting);
+
         let vsync_setting = ui::ButtonBuilder::new()
             .position(-160.0, 0.0)
             .size(300.0, 40.0)
@@ -364,6 +397,60 @@ impl super::Screen for VideoSettingsMenu {
         }
         buttons.push(fps_setting);
 
+        let render_distance_setting = ui::ButtonBuilder::new()
+            .position(-160.0, 50.0)
+            .size(300.0, 40.0)
+            .alignment(ui::VAttach::Middle, ui::HAttach::Center)
+            .create(ui_container);
+        {
+            let mut render_distance_setting = render_distance_setting.borrow_mut();
+            let txt = ui::TextBuilder::new()
+                .text(render_distance_text(r_render_distance))
+                .alignment(ui::VAttach::Middle, ui::HAttach::Center)
+                .attach(&mut *render_distance_setting);
+            let txt_render_distance = txt.clone();
+            render_distance_setting.add_text(txt);
+            render_distance_setting.add_click_func(move |_, game| {
+                let mut r_render_distance = game.settings.get_int(IntSetting::RenderDistance) + 2;
+                if r_render_distance > world::MAX_RENDER_DISTANCE as i32 {
+                    r_render_distance = world::MIN_RENDER_DISTANCE as i32;
+                }
+                txt_render_distance.borrow_mut().text = render_distance_text(r_render_distance);
+                game.settings
+                    .set_int(IntSetting::RenderDistance, r_render_distance);
+                true
+            });
+        }
+        buttons.push(render_distance_setting);
+
+        let chunk_cache_setting = ui::ButtonBuilder::new()
+            .position(160.0, 50.0)
+            .size(300.0, 40.0)
+            .alignment(ui::VAttach::Middle, ui::HAttach::Center)
+            .create(ui_container);
+        {
+            let mut chunk_cache_setting = chunk_cache_setting.borrow_mut();
+            let txt = ui::TextBuilder::new()
+                .text(chunk_cache_text(r_chunk_cache))
+                .alignment(ui::VAttach::Middle, ui::HAttach::Center)
+                .attach(&mut *chunk_cache_setting);
+            let txt_chunk_cache = txt.clone();
+            chunk_cache_setting.add_text(txt);
+            chunk_cache_setting.add_click_func(move |_, game| {
+                // Takes effect on the next join
+                let r_chunk_cache = match game.settings.get_int(IntSetting::ChunkCacheSize) {
+                    0 => 64,
+                    size if size < 1024 => size * 4,
+                    _ => 0,
+                };
+                txt_chunk_cache.borrow_mut().text = chunk_cache_text(r_chunk_cache);
+                game.settings
+                    .set_int(IntSetting::ChunkCacheSize, r_chunk_cache);
+                true
+            });
+        }
+        buttons.push(chunk_cache_setting);
+
         let done_button = ui::ButtonBuilder::new()
             .position(0.0, 50.0)
             .size(300.0, 40.0)
@@ -890,3 +977,21 @@ impl super::Screen for ControlsMenu {
         Box::new(self.clone())
     }
 }
+
+fn biome_blend_text(radius: i32) -> String {
+    match radius {
+        0 => "Biome Blend: Off".into(),
+        radius => format!("Biome Blend: {0}x{0}", radius * 2 + 1),
+    }
+}
+
+fn render_distance_text(distance: i32) -> String {
+    format!("Render Distance: {} chunks", distance)
+}
+
+fn chunk_cache_text(size: i32) -> String {
+    match size {
+        0 => "Chunk Cache: Off".into(),
+        size => format!("Chunk Cache: {} MB", size),
+    }
+}
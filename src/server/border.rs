@@ -0,0 +1,111 @@
+use crate::render;
+use crate::render::model;
+use crate::world::WorldBorder;
+use cgmath::{Matrix4, Vector3};
+use instant::Instant;
+use std::sync::Arc;
+
+/// The blocks one repeat of the force field texture covers.
+const TILE: f32 = 2.0;
+/// How many tiles each wall reaches from the camera, along the wall and
+/// up and down.
+const TILES: i32 = 16;
+/// Walls further away from the camera than this aren't drawn.
+const VIEW_DISTANCE: f64 = 48.0;
+/// The milliseconds the texture takes to scroll across one tile.
+const SCROLL_PERIOD: u128 = 3000;
+
+/// The translucent walls drawn around the camera where it is close to the
+/// world border.
+pub struct BorderModel {
+    model: model::ModelHandle,
+    created: Instant,
+}
+
+impl BorderModel {
+    pub fn new(renderer: Arc<render::Renderer>) -> BorderModel {
+        BorderModel {
+            model: BorderModel::generate_walls(&renderer),
+            created: Instant::now(),
+        }
+    }
+
+    pub fn tick(&mut self, renderer: Arc<render::Renderer>, border: &WorldBorder) {
+        let now = Instant::now();
+        let camera = renderer.camera.lock().pos;
+        let (min_x, min_z, max_x, max_z) = border.bounds_at(now);
+        let (r, g, b) = border.status_at(now).colour();
+
+        let scroll = (self.created.elapsed().as_millis() % SCROLL_PERIOD) as f32
+            / SCROLL_PERIOD as f32
+            * TILE;
+        let snap = |v: f64| (v / TILE as f64).floor() as f32 * TILE;
+        let y = snap(camera.y) + scroll;
+        let along_x = snap(camera.x) + scroll;
+        let along_z = snap(camera.z) + scroll;
+
+        // The west and east walls are built along z, the north and south
+        // ones along x
+        let walls = [
+            (camera.x - min_x, Vector3::new(min_x as f32, -y, along_z)),
+            (max_x - camera.x, Vector3::new(max_x as f32, -y, along_z)),
+            (camera.z - min_z, Vector3::new(along_x, -y, min_z as f32)),
+            (max_z - camera.z, Vector3::new(along_x, -y, max_z as f32)),
+        ];
+
+        let mut models = renderer.models.lock();
+        let model = models.get_model(&self.model).unwrap();
+        for (i, (distance, disp)) in walls.iter().enumerate() {
+            let distance = distance.abs();
+            if distance >= VIEW_DISTANCE {
+                // Collapsing the wall is cheaper than drawing it invisibly
+                model.matrix[i] = Matrix4::from_scale(0.0);
+                continue;
+            }
+            let alpha = (1.0 - distance / VIEW_DISTANCE).powi(4) as f32;
+            model.matrix[i] = Matrix4::from_translation(*disp);
+            model.colors[i] = [r as f32 / 255.0, g as f32 / 255.0, b as f32 / 255.0, alpha];
+        }
+    }
+
+    fn generate_walls(renderer: &Arc<render::Renderer>) -> model::ModelHandle {
+        let tex = render::Renderer::get_texture(renderer.get_textures_ref(), "misc/forcefield");
+        let mut parts = vec![];
+        for wall in 0..4 {
+            let mut verts = vec![];
+            for u in -TILES..TILES {
+                for v in -TILES..TILES {
+                    // Both windings so the wall can be seen from either side
+                    for &front in &[true, false] {
+                        for &(du, dv) in &[(0, 0), (0, 1), (1, 0), (1, 1)] {
+                            let (du, dv) = if front { (du, dv) } else { (1 - du, dv) };
+                            let (cu, cv) = (u + du, v + dv);
+                            // Fade out towards the edges of the patch
+                            let edge = cu.abs().max(cv.abs()) as f32 / TILES as f32;
+                            let along = cu as f32 * TILE;
+                            let (x, z) = if wall < 2 { (0.0, along) } else { (along, 0.0) };
+                            verts.push(model::Vertex {
+                                x,
+                                y: cv as f32 * TILE,
+                                z,
+                                texture_x: du as f64,
+                                texture_y: 1.0 - dv as f64,
+                                texture: tex.clone(),
+                                r: 255,
+                                g: 255,
+                                b: 255,
+                                a: ((1.0 - edge) * 255.0) as u8,
+                                id: 0,
+                            });
+                        }
+                    }
+                }
+            }
+            parts.push(verts);
+        }
+        renderer
+            .models
+            .lock()
+            .create_model(model::DEFAULT, parts, renderer.clone())
+    }
+}
@@ -14,13 +14,27 @@
 
 use crate::ecs::{Manager, SystemExecStage};
 use crate::entity;
+use crate::entity::equipment::{Equipment, Slot};
+use crate::entity::experience_orb::ExperienceOrbModel;
+use crate::entity::item::ItemModel;
+use crate::entity::metadata::{CustomName, ItemStack};
+use crate::entity::mob::{Animation, MobDefinitions};
+use crate::entity::painting::{Motive, PaintingModel};
 use crate::entity::player::{create_local, MovementDelta, PlayerModel, PlayerMovement};
-use crate::entity::{EntityType, GameInfo, Gravity, MouseButtons, TargetPosition, TargetRotation};
+use crate::entity::vehicle::{Driven, Vehicle};
+use crate::entity::{
+    Bounds, Effects, EntityType, GameInfo, Gravity, HeadYaw, MouseButtons, MovementSpeed, Pickup,
+    TargetPosition, TargetRotation,
+};
 use crate::format;
 use crate::inventory::material::versions::to_material;
 use crate::inventory::Inventory;
 use crate::inventory::{inventory_from_type, InventoryContext, InventoryType, Item};
+use crate::model;
+use crate::nbt;
 use crate::particle::block_break_effect::{BlockBreakEffect, BlockEffectData};
+use crate::particle::explosion::ExplosionData;
+use crate::paths;
 use crate::protocol::{self, forge, mapped_packet, packet};
 use crate::render;
 use crate::render::hud::HudContext;
@@ -29,10 +43,12 @@ use crate::resources;
 use crate::screen::chat::{Chat, ChatContext};
 use crate::screen::respawn::Respawn;
 use crate::screen::ScreenSystem;
-use crate::settings::Actionkey;
+use crate::settings::{Actionkey, IntSetting};
 use crate::shared::Position;
+use crate::sound::SoundEvent;
 use crate::types::hash::FNVHash;
-use crate::types::GameMode;
+use crate::types::{self, GameMode};
+use crate::world::cache::ChunkCache;
 use crate::world::{self, World};
 use crate::world::{CPos, LightData, LightUpdate};
 use crate::{ecs, Game};
@@ -43,18 +59,21 @@ use base64::Engine;
 use bevy_ecs::prelude::Entity;
 use bevy_ecs::schedule::{IntoSystemConfigs, Schedule};
 use bevy_ecs::system::{Commands, Res, ResMut, Resource};
+use bevy_ecs::world::EntityWorldMut;
 use cgmath::prelude::*;
 use cgmath::Vector3;
 use crossbeam_channel::unbounded;
 use crossbeam_channel::{Receiver, Sender};
 use dashmap::DashMap;
-use instant::{Duration, Instant};
+use instant::Instant;
 use leafish_protocol::format::Component;
 use leafish_protocol::item::Stack;
-use leafish_protocol::protocol::login::Account;
+use leafish_protocol::protocol::login::{Account, AccountType};
 use leafish_protocol::protocol::mapped_packet::MappablePacket;
 use leafish_protocol::protocol::mapped_packet::MappedPacket;
-use leafish_protocol::protocol::packet::{send_client_status, send_drop_item, ClientStatus, Hand};
+use leafish_protocol::protocol::packet::{
+    send_client_status, send_drop_item, ClientStatus, Hand, UseEntityAction,
+};
 use leafish_protocol::protocol::Conn;
 use log::{debug, error, info, warn};
 use parking_lot::Mutex;
@@ -66,17 +85,24 @@ use std::collections::HashMap;
 use std::hash::BuildHasherDefault;
 use std::io::Cursor;
 use std::str::FromStr;
-use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, AtomicUsize, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, AtomicU8, AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::thread;
 use std::time::{SystemTime, UNIX_EPOCH};
 
+use self::border::BorderModel;
 use self::sun::SunModel;
+use self::target::Target;
 
+mod border;
 pub mod plugin_messages;
 mod sun;
 pub mod target;
 
+/// Upper bound on how many light updates are applied while holding the
+/// chunk lock, so chunk building doesn't stall behind a huge batch.
+const MAX_LIGHT_UPDATE_BATCH: usize = 4096;
+
 #[derive(Default)]
 pub struct DisconnectData {
     pub disconnect_reason: Option<format::Component>,
@@ -105,6 +131,7 @@ impl Default for WorldData {
 
 pub struct Server {
     uuid: protocol::UUID,
+    local_skin_url: Option<String>,
     pub conn: Arc<RwLock<Option<protocol::Conn>>>,
     pub(crate) disconnect_gracefully: AtomicBool,
     pub protocol_version: i32,
@@ -125,6 +152,12 @@ pub struct Server {
 
     tick_timer: AtomicF64,
     entity_tick_timer: AtomicF64,
+    /// The view distance we ask the server for, in chunks.
+    render_distance: AtomicU8,
+    /// The view distance the server told us it uses, 0 if it hasn't.
+    server_view_distance: AtomicU8,
+    /// The center and view distance chunks were last unloaded around.
+    unloaded_around: Mutex<Option<(CPos, u8)>>,
     pub received_chat_at: ArcSwapOption<Instant>,
 
     target_info: Arc<RwLock<target::Info>>,
@@ -162,10 +195,26 @@ impl Server {
         forge_mods: Vec<forge::ForgeMod>,
         fml_network_version: Option<i64>,
         renderer: Arc<Renderer>,
+        block_models: Arc<RwLock<model::Factory>>,
         hud_context: Arc<RwLock<HudContext>>,
         screen_sys: Arc<ScreenSystem>,
+        chunk_cache_size: i32,
     ) -> Result<Arc<Server>, protocol::Error> {
         let mut conn = protocol::Conn::new(address, protocol_version)?;
+        let chunk_cache = (chunk_cache_size > 0).then(|| {
+            Arc::new(ChunkCache::new(
+                address,
+                protocol_version,
+                chunk_cache_size as u64 * 1024 * 1024,
+            ))
+        });
+        let local_skin_url = match account.account_type {
+            AccountType::None => account
+                .skin_path
+                .as_ref()
+                .map(|path| format!("file://{}", path)),
+            _ => None,
+        };
 
         let tag = match fml_network_version {
             Some(1) => "\0FML\0",
@@ -216,10 +265,13 @@ impl Server {
                         protocol_version,
                         forge_mods,
                         uuid,
+                        local_skin_url,
                         resources,
                         renderer,
+                        block_models,
                         hud_context,
                         screen_sys,
+                        chunk_cache,
                     );
                     return Ok(server);
                 }
@@ -232,10 +284,13 @@ impl Server {
                         protocol_version,
                         forge_mods,
                         val.uuid,
+                        local_skin_url,
                         resources,
                         renderer,
+                        block_models,
                         hud_context,
                         screen_sys,
+                        chunk_cache,
                     );
 
                     return Ok(server);
@@ -368,10 +423,13 @@ impl Server {
             protocol_version,
             forge_mods,
             uuid,
+            local_skin_url,
             resources,
             renderer,
+            block_models,
             hud_context,
             screen_sys,
+            chunk_cache,
         );
 
         Ok(server)
@@ -382,10 +440,13 @@ impl Server {
         protocol_version: i32,
         forge_mods: Vec<forge::ForgeMod>,
         uuid: protocol::UUID,
+        local_skin_url: Option<String>,
         resources: Arc<RwLock<resources::Manager>>,
         renderer: Arc<Renderer>,
+        block_models: Arc<RwLock<model::Factory>>,
         hud_context: Arc<RwLock<HudContext>>,
         screen_sys: Arc<ScreenSystem>,
+        chunk_cache: Option<Arc<ChunkCache>>,
     ) -> Arc<Server> {
         let server_callback = Arc::new(Mutex::new(None));
         let inner_server = server_callback.clone();
@@ -399,6 +460,7 @@ impl Server {
             protocol_version,
             forge_mods,
             uuid,
+            local_skin_url,
             resources,
             conn,
             light_updater,
@@ -407,8 +469,10 @@ impl Server {
             hud_context,
             screen_sys,
             renderer,
+            block_models,
         ));
         server.hud_context.write().server = Some(server.clone());
+        server.world.set_chunk_cache(chunk_cache);
 
         inner_server.replace(server.clone());
         render_list_computer.0.send(true).unwrap();
@@ -499,6 +563,17 @@ impl Server {
                             MappedPacket::ChunkUnload(chunk_unload) => {
                                 server.on_chunk_unload(chunk_unload);
                             }
+                            MappedPacket::UpdateViewPosition(view_position) => {
+                                server
+                                    .world
+                                    .set_view_center(view_position.chunk_x, view_position.chunk_z);
+                            }
+                            MappedPacket::UpdateViewDistance(view_distance) => {
+                                server.server_view_distance.store(
+                                    view_distance.view_distance.clamp(0, u8::MAX as i32) as u8,
+                                    Ordering::Release,
+                                );
+                            }
                             MappedPacket::EntityDestroy(entity_destroy) => {
                                 server.on_entity_destroy(entity_destroy);
                             }
@@ -510,6 +585,7 @@ impl Server {
                                     look.entity_id,
                                     look.yaw as f64,
                                     look.pitch as f64,
+                                    look.on_ground,
                                 );
                             }
                             MappedPacket::EntityHeadLook(look) => {
@@ -519,14 +595,32 @@ impl Server {
                                     let mut entities = server.entities.write();
                                     let mut entity =
                                         entities.world.get_entity_mut(*entity).unwrap();
-                                    let mut rotation = entity.get_mut::<TargetRotation>().unwrap();
-                                    rotation.yaw = -(look.head_yaw as f64 / 256.0) * PI * 2.0;
+                                    let yaw = -(look.head_yaw as f64 / 256.0) * PI * 2.0;
+                                    if entity.contains::<HeadYaw>() {
+                                        entity.get_mut::<HeadYaw>().unwrap().0 = yaw;
+                                    } else {
+                                        let pitch = entity.get::<TargetRotation>().unwrap().pitch;
+                                        entity::rotate_to(&mut entity, yaw, pitch);
+                                    }
+                                }
+                            }
+                            MappedPacket::EntityStatus(status) => {
+                                // Played the hurt animation before 1.19.4
+                                if status.entity_status == 2 {
+                                    server.on_entity_hurt(status.entity_id);
+                                }
+                            }
+                            MappedPacket::Animation(animation) => {
+                                // Taking damage
+                                if animation.animation_id == 1 {
+                                    server.on_entity_hurt(animation.entity_id);
                                 }
                             }
                             MappedPacket::JoinGame(join) => {
                                 let protocol::mapped_packet::play::clientbound::JoinGame {
                                     gamemode,
                                     entity_id,
+                                    dimension_codec,
                                     dimension_id,
                                     dimension_name,
                                     dimension,
@@ -536,17 +630,15 @@ impl Server {
 
                                 server.on_game_join(gamemode, entity_id);
 
-                                let dimension = dimension_id
-                                    .map(world::Dimension::from_index)
-                                    .or_else(|| {
-                                        dimension_name.map(|d| world::Dimension::from_name(&d))
-                                    })
-                                    .or_else(|| world_name.map(|d| world::Dimension::from_name(&d)))
-                                    .or_else(|| dimension.map(|d| world::Dimension::from_tag(&d)));
-
-                                if let Some(dimension) = dimension {
-                                    server.world.set_dimension(dimension);
+                                if let Some(codec) = dimension_codec {
+                                    server.world.set_dimension_codec(&codec);
                                 }
+                                server.world.change_dimension(
+                                    dimension_id,
+                                    dimension_name,
+                                    world_name,
+                                    dimension,
+                                );
                             }
                             MappedPacket::TeleportPlayer(teleport) => {
                                 server.on_teleport_player(teleport);
@@ -557,7 +649,10 @@ impl Server {
                             MappedPacket::SpawnMob(spawn) => {
                                 use std::f64::consts::PI;
                                 server.on_entity_spawn(
-                                    spawn.ty as i16,
+                                    entity::versions::to_entity_type(
+                                        spawn.ty as i16,
+                                        server.mapped_protocol_version,
+                                    ),
                                     spawn.entity_id,
                                     spawn.x,
                                     spawn.y,
@@ -565,11 +660,24 @@ impl Server {
                                     -(spawn.yaw as f64 / 256.0) * PI * 2.0,
                                     -(spawn.pitch as f64 / 256.0) * PI * 2.0,
                                 );
+                                server.on_entity_velocity(
+                                    spawn.entity_id,
+                                    spawn.velocity_x,
+                                    spawn.velocity_y,
+                                    spawn.velocity_z,
+                                );
+                                if let Some(metadata) = &spawn.metadata {
+                                    server.on_entity_metadata(spawn.entity_id, metadata);
+                                }
                             }
                             MappedPacket::SpawnObject(spawn) => {
                                 use std::f64::consts::PI;
                                 server.on_entity_spawn(
-                                    spawn.ty as i16,
+                                    entity::versions::object_to_entity_type(
+                                        spawn.ty as i16,
+                                        spawn.data,
+                                        server.mapped_protocol_version,
+                                    ),
                                     spawn.entity_id,
                                     spawn.x,
                                     spawn.y,
@@ -577,6 +685,42 @@ impl Server {
                                     -(spawn.yaw as f64 / 256.0) * PI * 2.0,
                                     -(spawn.pitch as f64 / 256.0) * PI * 2.0,
                                 );
+                                server.on_entity_velocity(
+                                    spawn.entity_id,
+                                    spawn.velocity_x,
+                                    spawn.velocity_y,
+                                    spawn.velocity_z,
+                                );
+                            }
+                            MappedPacket::SpawnExperienceOrb(spawn) => {
+                                server.on_experience_orb_spawn(spawn);
+                            }
+                            MappedPacket::SpawnPainting(spawn) => {
+                                server.on_painting_spawn(spawn);
+                            }
+                            MappedPacket::CollectItem(collect) => {
+                                server.on_collect_item(
+                                    collect.collected_entity_id,
+                                    collect.collector_entity_id,
+                                    collect.number_of_items,
+                                );
+                            }
+                            MappedPacket::EntityEquipment_Single(equipment) => {
+                                server.on_entity_equipment(
+                                    equipment.entity_id,
+                                    vec![(equipment.slot, equipment.item)],
+                                );
+                            }
+                            MappedPacket::EntityEquipment_Array(equipment) => {
+                                server.on_entity_equipment(
+                                    equipment.entity_id,
+                                    equipment
+                                        .equipments
+                                        .equipments
+                                        .into_iter()
+                                        .map(|equipment| (equipment.slot as i32, equipment.item))
+                                        .collect(),
+                                );
                             }
                             MappedPacket::EntityTeleport(entity_teleport) => {
                                 server.on_entity_teleport(
@@ -586,7 +730,7 @@ impl Server {
                                     entity_teleport.z,
                                     entity_teleport.yaw as f64,
                                     entity_teleport.pitch as f64,
-                                    entity_teleport.on_ground.unwrap_or(true), // TODO: how is this default supposed to be set? (for 1.7)
+                                    entity_teleport.on_ground,
                                 );
                             }
                             MappedPacket::EntityLookAndMove(lookmove) => {
@@ -597,6 +741,7 @@ impl Server {
                                     lookmove.delta_z,
                                     lookmove.yaw as f64,
                                     lookmove.pitch as f64,
+                                    lookmove.on_ground,
                                 );
                             }
                             MappedPacket::SpawnPlayer(spawn) => {
@@ -631,6 +776,15 @@ impl Server {
                                     spawn.pitch as f64,
                                     spawn.yaw as f64,
                                 );
+                                if let Some(metadata) = &spawn.metadata {
+                                    server.on_entity_metadata(spawn.entity_id, metadata);
+                                }
+                            }
+                            MappedPacket::EntityMetadata(entity_metadata) => {
+                                server.on_entity_metadata(
+                                    entity_metadata.entity_id,
+                                    &entity_metadata.metadata,
+                                );
                             }
                             MappedPacket::PlayerInfo(player_info) => {
                                 server.on_player_info(player_info);
@@ -664,6 +818,23 @@ impl Server {
                             MappedPacket::ChangeGameState(game_state) => {
                                 server.on_game_state_change(game_state);
                             }
+                            MappedPacket::EntityProperties(properties) => {
+                                server.on_entity_properties(properties);
+                            }
+                            MappedPacket::EntityEffect(effect) => {
+                                server.on_entity_effect(
+                                    effect.entity_id,
+                                    effect.effect_id,
+                                    Some(effect.amplifier),
+                                );
+                            }
+                            MappedPacket::EntityRemoveEffect(remove_effect) => {
+                                server.on_entity_effect(
+                                    remove_effect.entity_id,
+                                    remove_effect.effect_id,
+                                    None,
+                                );
+                            }
                             MappedPacket::UpdateHealth(update_health) => {
                                 server.on_update_health(
                                     update_health.health,
@@ -674,6 +845,21 @@ impl Server {
                             MappedPacket::TimeUpdate(time_update) => {
                                 server.on_time_update(time_update);
                             }
+                            MappedPacket::WorldBorder(world_border) => {
+                                server.on_world_border(world_border);
+                            }
+                            MappedPacket::Explosion(explosion) => {
+                                server.on_explosion(explosion);
+                            }
+                            MappedPacket::BlockAction(block_action) => {
+                                server.world.add_block_entity_action(
+                                    world::BlockEntityAction::BlockAction(
+                                        block_action.location,
+                                        block_action.byte1,
+                                        block_action.byte2,
+                                    ),
+                                );
+                            }
                             MappedPacket::Disconnect(disconnect) => {
                                 server.disconnect(Some(disconnect.reason));
                             }
@@ -739,41 +925,51 @@ impl Server {
                                     InventoryType::from_id(version, open.ty.unwrap())
                                 };
 
-                                if let Some(inv_type) = inv_type {
-                                    let inventory = inventory_from_type(
-                                        inv_type,
-                                        open.title,
-                                        &server.renderer,
-                                        server.inventory_context.read().base_slots.clone(),
-                                        open.id,
-                                    );
-                                    if let Some(inventory) = inventory {
-                                        server.inventory_context.write().open_inventory(
-                                            inventory.clone(),
-                                            &server.screen_sys,
-                                            server.inventory_context.clone(),
-                                            true,
+                                match (inv_type, open.entity_id) {
+                                    (
+                                        Some(InventoryType::Horse(slot_count, _)),
+                                        Some(entity_id),
+                                    ) => {
+                                        server.on_horse_window_open(
+                                            open.id,
+                                            slot_count,
+                                            entity_id,
+                                            Some(open.title),
                                         );
                                     }
+                                    (Some(inv_type), _) => {
+                                        server.open_window(inv_type, open.title, open.id);
+                                    }
+                                    (None, _) => {}
                                 }
                             }
-                            MappedPacket::EntityVelocity(_velocity) => {
-                                // TODO: Only apply the velocity to the local player due to jittering of other players
-                                /* if let Some(entity) =
-                                    server.entity_map.clone().read().get(&velocity.entity_id)
-                                {
-                                    let entity_velocity = server
-                                        .entities
-                                        .clone()
-                                        .write()
-                                        .get_component_mut(*entity, server.velocity)
-                                        .unwrap();
-                                    entity_velocity.velocity = Vector3::new(
-                                        velocity.velocity_x as f64 / 8000.0,
-                                        velocity.velocity_y as f64 / 8000.0,
-                                        velocity.velocity_z as f64 / 8000.0,
-                                    );
-                                }*/
+                            MappedPacket::WindowOpenHorse(open) => {
+                                server.on_horse_window_open(
+                                    open.window_id as i32,
+                                    open.number_of_slots as u8,
+                                    open.entity_id,
+                                    None,
+                                );
+                            }
+                            MappedPacket::SetPassengers(set) => {
+                                server.on_set_passengers(set.entity_id, &set.passengers);
+                            }
+                            MappedPacket::EntityAttach(attach) => {
+                                // Leashes don't carry anyone
+                                if attach.leash != Some(true) {
+                                    server.on_entity_attach(attach.entity_id, attach.vehicle);
+                                }
+                            }
+                            MappedPacket::VehicleTeleport(teleport) => {
+                                server.on_vehicle_teleport(teleport);
+                            }
+                            MappedPacket::EntityVelocity(velocity) => {
+                                server.on_entity_velocity(
+                                    velocity.entity_id,
+                                    velocity.velocity_x,
+                                    velocity.velocity_y,
+                                    velocity.velocity_z,
+                                );
                             }
                             MappedPacket::BlockBreakAnimation(block_break) => {
                                 if block_break.stage >= 10 || block_break.stage < 1 {
@@ -841,33 +1037,17 @@ impl Server {
         });
     }
 
-    fn spawn_light_updater(_server: Arc<Mutex<Option<Arc<Server>>>>) -> Sender<LightUpdate> {
+    fn spawn_light_updater(server: Arc<Mutex<Option<Arc<Server>>>>) -> Sender<LightUpdate> {
         let (tx, rx) = unbounded();
-        thread::spawn(move || loop {
-            /*let server = server.clone().lock().as_ref().unwrap().clone();
-            let mut done = false; // TODO: Improve performance!
-            while !done {
-                let start = Instant::now();
-                let mut updates_performed = 0;
-                let world_cloned = server.world.clone();
-                let mut interrupt = false;
-                while let Ok(update) = rx.try_recv() {
-                    updates_performed += 1;
-                    world_cloned.do_light_update(update);
-                    if (updates_performed & 0xFFF == 0) && start.elapsed().subsec_nanos() >= 5000000
-                    {
-                        // 5 ms for light updates
-                        interrupt = true;
-                        break;
-                    }
-                }
-                if !interrupt {
-                    done = true;
+        thread::spawn(move || {
+            while let Ok(update) = rx.recv() {
+                let mut updates = vec![update];
+                updates.extend(rx.try_iter().take(MAX_LIGHT_UPDATE_BATCH - 1));
+                let server = server.lock().clone();
+                if let Some(server) = server {
+                    server.world.do_light_updates(updates);
                 }
-                thread::sleep(Duration::from_millis(1));
-            }*/
-            while let Ok(_update) = rx.try_recv() {}
-            thread::sleep(Duration::from_millis(1000));
+            }
         });
         tx
     }
@@ -896,6 +1076,7 @@ impl Server {
         protocol_version: i32,
         forge_mods: Vec<forge::ForgeMod>,
         uuid: protocol::UUID,
+        local_skin_url: Option<String>,
         resources: Arc<RwLock<resources::Manager>>,
         conn: Arc<RwLock<Option<protocol::Conn>>>,
         light_updater: Sender<LightUpdate>,
@@ -904,6 +1085,7 @@ impl Server {
         hud_context: Arc<RwLock<HudContext>>,
         screen_sys: Arc<ScreenSystem>,
         renderer: Arc<Renderer>,
+        block_models: Arc<RwLock<model::Factory>>,
     ) -> Self {
         let world = Arc::new(world::World::new(protocol_version, light_updater));
         let mapped_protocol_version = Version::from_id(protocol_version as u32);
@@ -930,6 +1112,12 @@ impl Server {
         entities.world.insert_resource(DeltaResource(0.0));
         entities.world.insert_resource(WorldData::default());
         entities.world.insert_resource(RenderCtxResource::default());
+        entities
+            .world
+            .insert_resource(MobDefinitions::new(resources.clone()));
+        entities
+            .world
+            .insert_resource(BlockModelsResource(block_models));
         entity::add_systems(
             &mut entities.schedule.write(),
             &mut entities.render_schedule.write(),
@@ -941,6 +1129,7 @@ impl Server {
         let version = resources.read().version();
         Self {
             uuid,
+            local_skin_url,
             conn,
             disconnect_gracefully: Default::default(),
             protocol_version,
@@ -963,6 +1152,9 @@ impl Server {
 
             tick_timer: AtomicF64::new(0.0),
             entity_tick_timer: AtomicF64::new(0.0),
+            render_distance: AtomicU8::new(8),
+            server_view_distance: AtomicU8::new(0),
+            unloaded_around: Mutex::new(None),
             received_chat_at: ArcSwapOption::new(None),
 
             target_info: Arc::new(RwLock::new(target::Info::new())),
@@ -1017,6 +1209,10 @@ impl Server {
             .write()
             .world
             .remove_resource::<SunModelResource>();
+        self.entities
+            .write()
+            .world
+            .remove_resource::<BorderModelResource>();
         // FIXME: remove other resources!
     }
 
@@ -1036,6 +1232,9 @@ impl Server {
                 self.hud_context
                     .write()
                     .update_fps(fps_res.0.fps.load(Ordering::Acquire));
+                self.hud_context
+                    .write()
+                    .update_chunk_memory(self.world.memory_usage());
                 fps_res.0.frame_start.store(time, Ordering::Release);
                 fps_res.0.fps.store(0, Ordering::Release);
             } else {
@@ -1045,6 +1244,15 @@ impl Server {
                     .store(fps_res.0.fps.load(Ordering::Acquire) + 1, Ordering::Release);
             }
         }
+        let render_distance = game.settings.get_int(IntSetting::RenderDistance).clamp(
+            world::MIN_RENDER_DISTANCE as i32,
+            world::MAX_RENDER_DISTANCE as i32,
+        ) as u8;
+        if self.render_distance.swap(render_distance, Ordering::AcqRel) != render_distance
+            && self.player.load().is_some()
+        {
+            self.send_client_settings();
+        }
         let renderer = self.renderer.clone();
         let chat_open = self.chat_open.load(Ordering::Acquire);
         if chat_open != self.last_chat_open.load(Ordering::Acquire) {
@@ -1070,6 +1278,11 @@ impl Server {
                         .world
                         .insert_resource(SunModelResource(SunModel::new(renderer.clone())));
                 }
+                if !entities.world.contains_resource::<BorderModelResource>() {
+                    entities
+                        .world
+                        .insert_resource(BorderModelResource(BorderModel::new(renderer.clone())));
+                }
             }
             // Copy to camera
             if let Some(player) = self.player.load().as_ref() {
@@ -1091,6 +1304,12 @@ impl Server {
                 );
                 renderer.camera.lock().yaw = rotation.yaw;
                 renderer.camera.lock().pitch = rotation.pitch;
+                let warning = self.world.world_border.read().warning_at(
+                    Instant::now(),
+                    position.position.x,
+                    position.position.z,
+                );
+                self.hud_context.write().update_border_warning(warning);
             }
         }
         self.entity_tick(delta, game.is_focused(), self.dead.load(Ordering::Acquire));
@@ -1117,19 +1336,20 @@ impl Server {
                 game.screen_sys.close_closable_screens();
                 game.screen_sys.add_screen(Box::new(Respawn::new(0))); // TODO: Use the correct score!
             }
-            if let Some((pos, bl, _, _)) = target::trace_ray(
-                &self.world,
-                4.0,
-                renderer.camera.lock().pos.to_vec(),
-                renderer.view_vector.lock().cast().unwrap(),
-                target::test_block,
-            ) {
-                self.target_info.write().update(renderer.clone(), pos, bl);
+            let target = self.target();
+            if let Some(Target::Block { pos, block, .. }) = target {
+                self.target_info
+                    .write()
+                    .update(renderer.clone(), pos, block);
             } else {
                 self.target_info.write().clear();
             }
+            self.hud_context
+                .write()
+                .update_target(target.map(|target| target.to_string()));
         } else {
             self.target_info.write().clear();
+            self.hud_context.write().update_target(None);
         }
     }
 
@@ -1184,15 +1404,17 @@ impl Server {
 
     fn calculate_sky_offset(&self) -> f32 {
         use std::f32::consts::PI;
-        let mut offset = ((1.0
-            + self
-                .entities
-                .read()
-                .world
-                .resource::<WorldData>()
-                .world_time as f32)
-            / 24000.0)
-            - 0.25;
+        let world_time = match self.world.dimension.load().fixed_time {
+            Some(fixed_time) => fixed_time as f64,
+            None => {
+                self.entities
+                    .read()
+                    .world
+                    .resource::<WorldData>()
+                    .world_time
+            }
+        };
+        let mut offset = ((1.0 + world_time as f32) / 24000.0) - 0.25;
         if offset < 0.0 {
             offset += 1.0;
         } else if offset > 1.0 {
@@ -1234,6 +1456,25 @@ impl Server {
                     .map_or(false, |v| v.on_ground)
             });
 
+            let riding = {
+                let player = entities.world.entity(player.1);
+                player.get::<Vehicle>().map(|vehicle| {
+                    let movement = player.get::<PlayerMovement>().unwrap();
+                    (vehicle.0, movement.input())
+                })
+            };
+            // Where the vehicle we drive went, if we move it ourselves
+            let driving = riding.and_then(|(vehicle, _)| {
+                let vehicle = entities.world.get_entity(vehicle)?;
+                let paddles = match vehicle.get::<Driven>()? {
+                    Driven::Boat(boat) => Some(boat.paddles),
+                    Driven::Mount(_) => None,
+                };
+                let position = vehicle.get::<TargetPosition>()?.position;
+                let rotation = vehicle.get::<entity::TargetRotation>()?;
+                Some((position, rotation.yaw, rotation.pitch, paddles))
+            });
+
             let mut player = entities.world.entity_mut(player.1);
 
             let position = player.get::<TargetPosition>().unwrap().clone();
@@ -1242,38 +1483,62 @@ impl Server {
             let pos_delta = delta.prev_pos != position.position;
             let rot_delta = delta.prev_rot != rotation;
 
-            // Sync our position to the server
-            // Use the smaller packets when possible
-            match (pos_delta, rot_delta) {
-                (true, true) => {
-                    packet::send_position_look(
-                        self.conn.write().as_mut().unwrap(),
-                        &position.position,
-                        rotation.yaw as f32,
-                        rotation.pitch as f32,
-                        on_ground,
-                    )
-                    .map_err(|_| self.disconnect_closed(None));
-                }
-                (true, false) => {
-                    packet::send_position(
-                        self.conn.write().as_mut().unwrap(),
-                        &position.position,
-                        on_ground,
-                    )
-                    .unwrap();
-                }
-                (false, true) => {
-                    packet::send_look(
-                        self.conn.write().as_mut().unwrap(),
-                        rotation.yaw as f32,
-                        rotation.pitch as f32,
-                        on_ground,
-                    )
+            // Riders only tell the server where they look and which keys they
+            // hold, and where they moved the vehicle they drive
+            if let Some((_, input)) = riding {
+                let mut conn = self.conn.write();
+                let conn = conn.as_mut().unwrap();
+                packet::send_look(conn, rotation.yaw as f32, rotation.pitch as f32, on_ground)
                     .unwrap();
+                packet::send_steer_vehicle(
+                    conn,
+                    input.strafe as f32 * 0.98,
+                    input.forward as f32 * 0.98,
+                    input.jump,
+                    input.sneak,
+                )
+                .unwrap();
+                if let Some((position, yaw, pitch, paddles)) = driving {
+                    packet::send_vehicle_move(conn, &position, yaw as f32, pitch as f32).unwrap();
+                    if let Some([left, right]) = paddles {
+                        packet::send_steer_boat(conn, left, right).unwrap();
+                    }
                 }
-                (false, false) => {
-                    packet::send_flying(self.conn.write().as_mut().unwrap(), on_ground).unwrap();
+            } else {
+                // Sync our position to the server
+                // Use the smaller packets when possible
+                match (pos_delta, rot_delta) {
+                    (true, true) => {
+                        packet::send_position_look(
+                            self.conn.write().as_mut().unwrap(),
+                            &position.position,
+                            rotation.yaw as f32,
+                            rotation.pitch as f32,
+                            on_ground,
+                        )
+                        .map_err(|_| self.disconnect_closed(None));
+                    }
+                    (true, false) => {
+                        packet::send_position(
+                            self.conn.write().as_mut().unwrap(),
+                            &position.position,
+                            on_ground,
+                        )
+                        .unwrap();
+                    }
+                    (false, true) => {
+                        packet::send_look(
+                            self.conn.write().as_mut().unwrap(),
+                            rotation.yaw as f32,
+                            rotation.pitch as f32,
+                            on_ground,
+                        )
+                        .unwrap();
+                    }
+                    (false, false) => {
+                        packet::send_flying(self.conn.write().as_mut().unwrap(), on_ground)
+                            .unwrap();
+                    }
                 }
             }
 
@@ -1286,6 +1551,129 @@ impl Server {
                 mouse_buttons.right = false;
             }
         }
+        self.unload_distant_chunks();
+    }
+
+    /// Unloads chunks the server left behind outside of the view distance,
+    /// along with their render buffers.
+    fn unload_distant_chunks(&self) {
+        let center = self.world.view_center().or_else(|| {
+            // Servers before 1.14 don't send the view position, the player's
+            // chunk is the center there
+            let player = self.player.load();
+            let entities = self.entities.read();
+            let position = entities
+                .world
+                .get::<crate::entity::Position>(player.as_ref()?.1)?
+                .position;
+            Some(CPos(
+                (position.x.floor() as i32) >> 4,
+                (position.z.floor() as i32) >> 4,
+            ))
+        });
+        let center = match center {
+            Some(center) => center,
+            None => return,
+        };
+        // Servers send the smaller of their own view distance and ours
+        let distance = match self.server_view_distance.load(Ordering::Acquire) {
+            0 => self.render_distance.load(Ordering::Acquire),
+            distance => distance.min(self.render_distance.load(Ordering::Acquire)),
+        };
+        // Chunks only end up out of range once either of them changes
+        if self
+            .unloaded_around
+            .lock()
+            .replace((center, distance))
+            .is_some_and(|around| around == (center, distance))
+        {
+            return;
+        }
+        // Servers send a ring of chunks past the view distance, so leave some
+        // room before dropping them
+        let unloaded = self.world.unload_distant_chunks(
+            center,
+            distance as i32 + 3,
+            &mut self.entities.write(),
+        );
+        if unloaded > 0 {
+            debug!("Unloaded {} chunks outside of the view distance", unloaded);
+        }
+    }
+
+    fn send_client_settings(&self) {
+        packet::send_client_settings(
+            self.conn.write().as_mut().unwrap(),
+            "en_us".to_string(),
+            self.render_distance.load(Ordering::Acquire),
+            0,
+            true,
+            127,
+            Hand::MainHand,
+        )
+        .map_err(|_| self.disconnect_closed(None));
+    }
+
+    /// Saves the loaded chunks as a world in the saves directory. The chunks
+    /// are copied right away and written out on another thread.
+    pub fn save_world(&self) {
+        let export = world::anvil::Export::new(&self.world);
+        let name = self
+            .conn
+            .read()
+            .as_ref()
+            .map_or_else(|| "world".into(), |conn| conn.host.clone());
+        let (spawn, game_mode, time, day_time) = {
+            let entities = self.entities.read();
+            let world_data = entities.world.resource::<WorldData>();
+            let player = self
+                .player
+                .load()
+                .as_ref()
+                .map(|player| entities.world.entity(player.1));
+            let spawn = player
+                .and_then(|player| player.get::<crate::entity::Position>())
+                .map_or((0, 64, 0), |position| {
+                    (
+                        position.position.x.floor() as i32,
+                        position.position.y.floor() as i32,
+                        position.position.z.floor() as i32,
+                    )
+                });
+            let game_mode = player
+                .and_then(|player| player.get::<GameMode>())
+                .map_or(0, |game_mode| (*game_mode as i32).max(0));
+            (
+                spawn,
+                game_mode,
+                world_data.world_age,
+                world_data.world_time as i64,
+            )
+        };
+        let level = world::anvil::Level {
+            name: name.clone(),
+            spawn,
+            game_mode,
+            time,
+            day_time,
+        };
+        let saved_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |time| time.as_secs());
+        let dir = paths::get_data_dir()
+            .join("saves")
+            .join(format!("{}-{}", name, saved_at));
+        let chat_ctx = self.chat_ctx.clone();
+        thread::spawn(move || {
+            let message = match export.write(&dir, &level) {
+                Ok(chunks) => format!("Saved {} chunks to {}", chunks, dir.display()),
+                Err(err) => {
+                    error!("Failed to save the world to {:?}: {:?}", dir, err);
+                    format!("Failed to save the world: {:?}", err)
+                }
+            };
+            chat_ctx.push_msg(Component::new(format::ComponentType::new(&message, None)));
+        });
     }
 
     pub fn key_press(&self, down: bool, key: Actionkey, focused: bool, ctrl_pressed: bool) -> bool {
@@ -1339,6 +1727,11 @@ impl Server {
                         .add_screen(Box::new(Chat::new(self.chat_ctx.clone())));
                     return true;
                 }
+                Actionkey::SaveWorld => {
+                    if state_changed {
+                        self.save_world();
+                    }
+                }
                 Actionkey::DropItem => {
                     if state_changed {
                         let inv = self.inventory_context.read();
@@ -1404,14 +1797,31 @@ impl Server {
 
     pub fn on_left_click(&self, focused: bool, shift: bool) {
         if focused {
+            let target = self.target();
             let mut entities = self.entities.write();
             // check if the player exists, as it might not be initialized very early on server join
             if let Some(player) = self.player.load().as_ref() {
                 let mut player = entities.world.entity_mut(player.1);
-                let mut mouse_buttons = player.get_mut::<MouseButtons>().unwrap();
+                let sneaking = player
+                    .get::<PlayerMovement>()
+                    .unwrap()
+                    .is_key_pressed(Actionkey::Sneak);
                 packet::send_arm_swing(self.conn.write().as_mut().unwrap(), Hand::MainHand)
                     .unwrap();
-                mouse_buttons.left = true;
+                if let Some(Target::Entity { id, .. }) = target {
+                    packet::send_use_entity(
+                        self.conn.write().as_mut().unwrap(),
+                        id,
+                        UseEntityAction::Attack,
+                        Hand::MainHand,
+                        sneaking,
+                    )
+                    .unwrap();
+                } else {
+                    // Entities are hit once per click, blocks are dug while held
+                    let mut mouse_buttons = player.get_mut::<MouseButtons>().unwrap();
+                    mouse_buttons.left = true;
+                }
             }
         } else {
             self.inventory_context.write().on_click(true, shift);
@@ -1462,39 +1872,57 @@ impl Server {
                 .get_item((27 + self.hud_context.read().get_slot_index()) as u16)
                 .as_ref()
                 .cloned();
-            if let Some((pos, _, face, at)) = target::trace_ray(
-                &self.world,
-                4.0,
-                self.renderer.camera.lock().pos.to_vec(),
-                self.renderer.view_vector.lock().cast().unwrap(),
-                target::test_block,
-            ) {
-                packet::send_block_place(
-                    self.conn.write().as_mut().unwrap(),
-                    pos,
-                    face.index() as u8 as i8,
-                    at,
-                    Hand::MainHand,
-                    item.clone().map(|item| item.stack),
-                )
-                .map_err(|_| self.disconnect_closed(None));
-
-                // we swing after placing a block
-                // FIXME: how should adventure be handled on higher versions? this only works ensured for v 1.8.9
-                if gamemode != GameMode::Adventure {
-                    if let Some(item) = &item {
-                        if item
-                            .material
-                            .is_placable_block(self.mapped_protocol_version, item.stack.id)
-                        {
-                            packet::send_arm_swing(
-                                self.conn.write().as_mut().unwrap(),
-                                Hand::MainHand,
-                            )
-                            .map_err(|_| self.disconnect_closed(None));
+            let sneaking = self
+                .entities
+                .read()
+                .world
+                .entity(self.player.load().as_ref().unwrap().1)
+                .get::<PlayerMovement>()
+                .unwrap()
+                .is_key_pressed(Actionkey::Sneak);
+            match self.target() {
+                Some(Target::Entity { id, at, .. }) => {
+                    // Like vanilla, the server gets the exact spot first
+                    for action in [UseEntityAction::InteractAt(at), UseEntityAction::Interact] {
+                        packet::send_use_entity(
+                            self.conn.write().as_mut().unwrap(),
+                            id,
+                            action,
+                            Hand::MainHand,
+                            sneaking,
+                        )
+                        .map_err(|_| self.disconnect_closed(None));
+                    }
+                }
+                Some(Target::Block { pos, face, at, .. }) => {
+                    packet::send_block_place(
+                        self.conn.write().as_mut().unwrap(),
+                        pos,
+                        face.index() as u8 as i8,
+                        at,
+                        Hand::MainHand,
+                        item.clone().map(|item| item.stack),
+                    )
+                    .map_err(|_| self.disconnect_closed(None));
+
+                    // we swing after placing a block
+                    // FIXME: how should adventure be handled on higher versions? this only works ensured for v 1.8.9
+                    if gamemode != GameMode::Adventure {
+                        if let Some(item) = &item {
+                            if item
+                                .material
+                                .is_placable_block(self.mapped_protocol_version, item.stack.id)
+                            {
+                                packet::send_arm_swing(
+                                    self.conn.write().as_mut().unwrap(),
+                                    Hand::MainHand,
+                                )
+                                .map_err(|_| self.disconnect_closed(None));
+                            }
                         }
                     }
                 }
+                None => {}
             }
             // only send right click when we have an item in hand
             if let Some(item) = item {
@@ -1739,11 +2167,17 @@ impl Server {
     fn on_game_join(&self, gamemode: u8, entity_id: i32) {
         let gamemode = GameMode::from_int((gamemode & 0x7) as i32);
         let player = entity::player::create_local(&mut self.entities.clone().write());
-        if let Some(info) = self.players.read().get(&self.uuid) {
+        {
+            let skin_url = self
+                .players
+                .read()
+                .get(&self.uuid)
+                .and_then(|info| info.skin_url.clone())
+                .or_else(|| self.local_skin_url.clone());
             let mut entities = self.entities.write();
             let mut player = entities.world.entity_mut(player);
             let mut model = player.get_mut::<PlayerModel>().unwrap();
-            model.set_skin(info.skin_url.clone());
+            model.set_skin(skin_url);
         }
         self.hud_context.write().update_game_mode(gamemode);
         *self
@@ -1764,16 +2198,7 @@ impl Server {
         self.entity_map.write().insert(entity_id, player);
         self.player.store(Some(Arc::new((entity_id, player))));
 
-        packet::send_client_settings(
-            self.conn.write().as_mut().unwrap(),
-            "en_us".to_string(),
-            8,
-            0,
-            true,
-            127,
-            Hand::MainHand,
-        )
-        .map_err(|_| self.disconnect_closed(None)); // TODO: Make these configurable!
+        self.send_client_settings();
 
         // Let the server know who we are
         let brand = plugin_messages::Brand {
@@ -1843,15 +2268,9 @@ impl Server {
         }
         self.entity_map.write().insert(entity_id, local_player);
 
-        let dimension = dimension
-            .map(world::Dimension::from_index)
-            .or_else(|| dimension_name.map(|d| world::Dimension::from_name(&d)))
-            .or_else(|| world_name.map(|d| world::Dimension::from_name(&d)))
-            .or_else(|| dimension_tag.map(|d| world::Dimension::from_tag(&d)));
-
-        if let Some(dimension) = dimension {
-            self.world.set_dimension(dimension);
-        }
+        self.world
+            .change_dimension(dimension, dimension_name, world_name, dimension_tag);
+        *self.unloaded_around.lock() = None;
     }
 
     // TODO: make use of "on_disconnect"
@@ -1874,6 +2293,60 @@ impl Server {
         }
     }
 
+    fn on_world_border(&self, packet: mapped_packet::play::clientbound::WorldBorder) {
+        let mut border = self.world.world_border.write();
+        // The protocol calls them radii, but they are the border's full width
+        match packet.action {
+            0 => {
+                if let Some(size) = packet.new_radius {
+                    border.set_size(size);
+                }
+            }
+            1 => {
+                if let (Some(old_size), Some(new_size), Some(speed)) =
+                    (packet.old_radius, packet.new_radius, packet.speed)
+                {
+                    border.lerp_size(old_size, new_size, speed);
+                }
+            }
+            2 => {
+                if let (Some(x), Some(z)) = (packet.x, packet.z) {
+                    border.set_center(x, z);
+                }
+            }
+            3 => {
+                if let (Some(x), Some(z)) = (packet.x, packet.z) {
+                    border.set_center(x, z);
+                }
+                if let (Some(old_size), Some(new_size), Some(speed)) =
+                    (packet.old_radius, packet.new_radius, packet.speed)
+                {
+                    border.lerp_size(old_size, new_size, speed);
+                }
+                if let Some(portal_boundary) = packet.portal_boundary {
+                    border.portal_boundary = portal_boundary;
+                }
+                if let Some(warning_time) = packet.warning_time {
+                    border.warning_time = warning_time;
+                }
+                if let Some(warning_blocks) = packet.warning_blocks {
+                    border.warning_blocks = warning_blocks;
+                }
+            }
+            4 => {
+                if let Some(warning_time) = packet.warning_time {
+                    border.warning_time = warning_time;
+                }
+            }
+            5 => {
+                if let Some(warning_blocks) = packet.warning_blocks {
+                    border.warning_blocks = warning_blocks;
+                }
+            }
+            action => warn!("Unknown world border action {}", action),
+        }
+    }
+
     fn on_game_state_change(&self, game_state: mapped_packet::play::clientbound::ChangeGameState) {
         if game_state.reason == 3 {
             if let Some(player) = self.player.load().as_ref() {
@@ -1899,7 +2372,7 @@ impl Server {
 
     fn on_entity_spawn(
         &self,
-        ty: i16,
+        entity_type: EntityType,
         entity_id: i32,
         x: f64,
         y: f64,
@@ -1907,12 +2380,247 @@ impl Server {
         yaw: f64,
         pitch: f64,
     ) {
-        let entity_type = entity::versions::to_entity_type(ty, self.mapped_protocol_version);
         if entity_type != EntityType::Unknown {
             let entity = entity_type.create_entity(&mut self.entities.write(), x, y, z, yaw, pitch);
             if let Some(entity) = entity {
                 self.entity_map.write().insert(entity_id, entity);
-                println!("spawned {} {:?}", ty, entity_type);
+            }
+        }
+    }
+
+    fn on_experience_orb_spawn(&self, spawn: mapped_packet::play::clientbound::SpawnExperienceOrb) {
+        let mut entities = self.entities.write();
+        if let Some(entity) = EntityType::ExperienceOrb.create_entity_custom_model(
+            &mut entities,
+            spawn.x,
+            spawn.y,
+            spawn.z,
+            0.0,
+            0.0,
+        ) {
+            entities
+                .world
+                .entity_mut(entity)
+                .insert(ExperienceOrbModel::new(spawn.count));
+            self.entity_map.write().insert(spawn.entity_id, entity);
+        }
+    }
+
+    fn on_painting_spawn(&self, spawn: mapped_packet::play::clientbound::SpawnPainting) {
+        let motive = match (spawn.motive, &spawn.title) {
+            (Some(id), _) => Motive::by_id(id),
+            (None, Some(title)) => Motive::by_title(title),
+            (None, None) => None,
+        };
+        let motive = match motive {
+            Some(motive) => motive,
+            None => {
+                warn!("Unknown painting {:?} {:?}", spawn.motive, spawn.title);
+                return;
+            }
+        };
+        let pos = entity::painting::center(spawn.location, motive, spawn.direction);
+        let mut entities = self.entities.write();
+        if let Some(entity) = EntityType::Painting.create_entity_custom_model(
+            &mut entities,
+            pos.x,
+            pos.y,
+            pos.z,
+            0.0,
+            0.0,
+        ) {
+            entities
+                .world
+                .entity_mut(entity)
+                .insert(PaintingModel::new(motive, spawn.direction));
+            self.entity_map.write().insert(spawn.entity_id, entity);
+        }
+    }
+
+    /// Animates the collected entity flying to its collector. When only part
+    /// of a stack is picked up (1.11+ sends the count) the entity stays with
+    /// what is left and a copy carrying the rest is animated instead.
+    fn on_collect_item(
+        &self,
+        collected_entity_id: i32,
+        collector_entity_id: i32,
+        number_of_items: Option<i32>,
+    ) {
+        let collector = match self.entity(collector_entity_id) {
+            Some(collector) => collector,
+            None => return,
+        };
+        let collected = match self.entity_map.read().get(&collected_entity_id) {
+            Some(collected) => *collected,
+            None => return,
+        };
+        let mut entities = self.entities.write();
+        let mut collected = entities.world.entity_mut(collected);
+        let position = collected.get::<entity::Position>().unwrap().position;
+        let remaining = collected
+            .get::<ItemStack>()
+            .and_then(|stack| stack.0.as_ref())
+            .zip(number_of_items)
+            .filter(|(item, count)| item.stack.count > *count as isize)
+            .map(|(item, count)| (item.clone(), count as isize));
+        if let Some((mut item, count)) = remaining {
+            let (block_light, sky_light) = collected
+                .get::<entity::Light>()
+                .map_or((0.0, 0.0), |light| (light.block_light, light.sky_light));
+            let mut left = item.clone();
+            left.stack.count -= count;
+            collected.insert(ItemStack(Some(left)));
+            item.stack.count = count;
+            entities.world.spawn((
+                entity::Position::new(position.x, position.y, position.z),
+                entity::Light {
+                    block_light,
+                    sky_light,
+                },
+                ItemStack(Some(item)),
+                ItemModel::default(),
+                Pickup::new(collector, position),
+            ));
+            return;
+        }
+        // The server destroys the entity too, but it's already being picked up
+        self.entity_map.write().remove(&collected_entity_id);
+        collected
+            .remove::<TargetPosition>()
+            .insert(Pickup::new(collector, position));
+    }
+
+    /// What the crosshair points at, whichever of a block or an entity is
+    /// closer.
+    fn target(&self) -> Option<Target> {
+        let start = self.renderer.camera.lock().pos.to_vec();
+        let dir: Vector3<f64> = self.renderer.view_vector.lock().cast().unwrap();
+        let block = target::trace_ray(&self.world, 4.0, start, dir, target::test_block).map(
+            |(pos, block, face, at)| {
+                let hit = Vector3::new(pos.x as f64, pos.y as f64, pos.z as f64) + at;
+                let distance = (hit - start).magnitude();
+                (
+                    distance,
+                    Target::Block {
+                        pos,
+                        block,
+                        face,
+                        at,
+                    },
+                )
+            },
+        );
+
+        let ids: Vec<(i32, Entity)> = self
+            .entity_map
+            .read()
+            .iter()
+            .map(|(&id, &entity)| (id, entity))
+            .collect();
+        let entities = self.entities.read();
+        // Whatever we ride can't be hit from its own seat
+        let vehicle = self
+            .player
+            .load()
+            .as_ref()
+            .and_then(|player| entities.world.get::<Vehicle>(player.1))
+            .map(|vehicle| vehicle.0);
+        let boxes = ids.into_iter().filter_map(|(id, entity)| {
+            if vehicle == Some(entity) {
+                return None;
+            }
+            let entity_ref = entities.world.get_entity(entity)?;
+            let position = entity_ref.get::<entity::Position>()?.position;
+            let bounds = entity_ref.get::<Bounds>()?.bounds;
+            Some(((id, entity, position), bounds.add_v(position)))
+        });
+        let entity = target::trace_boxes(target::ENTITY_REACH, start, dir, boxes).map(
+            |((id, entity, position), distance, hit)| {
+                let at = hit - position;
+                (distance, Target::Entity { id, entity, at })
+            },
+        );
+
+        match (block, entity) {
+            (Some(block), Some(entity)) if entity.0 < block.0 => Some(entity.1),
+            (Some(block), _) => Some(block.1),
+            (None, entity) => entity.map(|(_, target)| target),
+        }
+    }
+
+    /// The entity for the id the server uses, including our own player.
+    fn entity(&self, entity_id: i32) -> Option<Entity> {
+        match self.player.load().as_ref() {
+            Some(player) if player.0 == entity_id => Some(player.1),
+            _ => self.entity_map.read().get(&entity_id).copied(),
+        }
+    }
+
+    fn on_entity_metadata(&self, entity_id: i32, metadata: &types::Metadata) {
+        let entity = match self.entity(entity_id) {
+            Some(entity) => entity,
+            None => return,
+        };
+        let mut entities = self.entities.write();
+        if let Some(mut entity) = entities.world.get_entity_mut(entity) {
+            entity::metadata::apply(&mut entity, self.mapped_protocol_version, metadata);
+            entity::vehicle::update_driver(&mut entity, self.mapped_protocol_version);
+        }
+    }
+
+    fn on_entity_properties(&self, properties: mapped_packet::play::clientbound::EntityProperties) {
+        let entity = match self.entity(properties.entity_id) {
+            Some(entity) => entity,
+            None => return,
+        };
+        let speed = match MovementSpeed::from_properties(&properties.properties) {
+            Some(speed) => speed,
+            None => return,
+        };
+        let mut entities = self.entities.write();
+        if let Some(mut entity) = entities.world.get_entity_mut(entity) {
+            entity.insert(speed);
+        }
+    }
+
+    fn on_entity_equipment(&self, entity_id: i32, items: Vec<(i32, Option<Stack>)>) {
+        let entity = match self.entity(entity_id) {
+            Some(entity) => entity,
+            None => return,
+        };
+        let mut entities = self.entities.write();
+        let mut entity = match entities.world.get_entity_mut(entity) {
+            Some(entity) => entity,
+            None => return,
+        };
+        let mut equipment = entity.take::<Equipment>().unwrap_or_default();
+        for (slot, stack) in items {
+            let slot = match Slot::by_id(slot, self.mapped_protocol_version) {
+                Some(slot) => slot,
+                None => {
+                    warn!("Unknown equipment slot {}", slot);
+                    continue;
+                }
+            };
+            let item = stack.map(|stack| Item {
+                material: to_material(
+                    stack.id as u16,
+                    Some(stack.damage.unwrap_or(0)),
+                    None,
+                    self.mapped_protocol_version,
+                ),
+                stack,
+            });
+            equipment.set(slot, item);
+        }
+        entity.insert(equipment);
+    }
+
+    fn on_entity_hurt(&self, entity_id: i32) {
+        if let Some(entity) = self.entity_map.read().get(&entity_id) {
+            let mut entities = self.entities.write();
+            if let Some(mut animation) = entities.world.get_mut::<Animation>(*entity) {
+                animation.hurt();
             }
         }
     }
@@ -1920,8 +2628,123 @@ impl Server {
     fn on_entity_destroy(&self, entity_destroy: mapped_packet::play::clientbound::EntityDestroy) {
         for id in entity_destroy.entity_ids {
             if let Some(entity) = self.entity_map.write().remove(&id) {
-                self.entities.write().world.despawn(entity);
+                let mut entities = self.entities.write();
+                entity::vehicle::forget(&mut entities.world, self.mapped_protocol_version, entity);
+                entities.world.despawn(entity);
+            }
+        }
+    }
+
+    fn open_window(&self, inv_type: InventoryType, title: Component, id: i32) {
+        let inventory = inventory_from_type(
+            inv_type,
+            title,
+            &self.renderer,
+            self.inventory_context.read().base_slots.clone(),
+            id,
+        );
+        if let Some(inventory) = inventory {
+            self.inventory_context.write().open_inventory(
+                inventory,
+                &self.screen_sys,
+                self.inventory_context.clone(),
+                true,
+            );
+        }
+    }
+
+    /// Opens the inventory of a horse, which only horses themselves can
+    /// put armor on.
+    fn on_horse_window_open(
+        &self,
+        id: i32,
+        slot_count: u8,
+        entity_id: i32,
+        title: Option<Component>,
+    ) {
+        let (ty, name) = match self.entity(entity_id) {
+            Some(entity) => {
+                let entities = self.entities.read();
+                let ty = entities.world.get::<EntityType>(entity).copied();
+                let name = entities
+                    .world
+                    .get::<CustomName>(entity)
+                    .and_then(|name| name.name.clone());
+                (ty, name)
             }
+            None => (None, None),
+        };
+        let title = title.or(name).unwrap_or_else(|| {
+            Component::from_str(match ty {
+                Some(EntityType::Donkey) => "Donkey",
+                Some(EntityType::Mule) => "Mule",
+                Some(EntityType::SkeletonHorse) => "Skeleton Horse",
+                Some(EntityType::ZombieHorse) => "Zombie Horse",
+                _ => "Horse",
+            })
+        });
+        let armor = matches!(ty, None | Some(EntityType::Horse));
+        self.open_window(InventoryType::Horse(slot_count, armor), title, id);
+    }
+
+    fn on_set_passengers(&self, vehicle_id: i32, passenger_ids: &[i32]) {
+        let vehicle = match self.entity(vehicle_id) {
+            Some(vehicle) => vehicle,
+            None => return,
+        };
+        let passengers = passenger_ids
+            .iter()
+            .filter_map(|&id| self.entity(id))
+            .collect();
+        entity::vehicle::set_passengers(
+            &mut self.entities.write().world,
+            self.mapped_protocol_version,
+            vehicle,
+            passengers,
+        );
+    }
+
+    fn on_entity_attach(&self, passenger_id: i32, vehicle_id: i32) {
+        let passenger = match self.entity(passenger_id) {
+            Some(passenger) => passenger,
+            None => return,
+        };
+        // Getting off sends -1, or a vehicle we don't know about
+        let vehicle = self.entity(vehicle_id);
+        entity::vehicle::attach(
+            &mut self.entities.write().world,
+            self.mapped_protocol_version,
+            passenger,
+            vehicle,
+        );
+    }
+
+    fn on_vehicle_teleport(&self, teleport: mapped_packet::play::clientbound::VehicleTeleport) {
+        let player = match self.player.load().as_ref() {
+            Some(player) => player.1,
+            None => return,
+        };
+        let mut entities = self.entities.write();
+        let vehicle = match entities.world.get::<Vehicle>(player) {
+            Some(vehicle) => vehicle.0,
+            None => return,
+        };
+        entity::vehicle::teleport(
+            &mut entities.world,
+            vehicle,
+            Vector3::new(teleport.x, teleport.y, teleport.z),
+            -(teleport.yaw as f64).to_radians(),
+        );
+    }
+
+    fn on_entity_velocity(&self, entity_id: i32, x: i16, y: i16, z: i16) {
+        let entity = match self.entity(entity_id) {
+            Some(entity) => entity,
+            None => return,
+        };
+        let mut entities = self.entities.write();
+        if let Some(mut velocity) = entities.world.get_mut::<entity::Velocity>(entity) {
+            velocity.velocity = Vector3::new(x as f64, y as f64, z as f64) / 8000.0;
         }
     }
 
@@ -1933,19 +2756,19 @@ impl Server {
         z: f64,
         yaw: f64,
         pitch: f64,
-        _on_ground: bool,
+        on_ground: Option<bool>,
     ) {
         use std::f64::consts::PI;
         if let Some(entity) = self.entity_map.read().get(&entity_id) {
             let mut entities = self.entities.write();
             let mut entity = entities.world.entity_mut(*entity);
-            let mut target_position = entity.get_mut::<TargetPosition>().unwrap();
-            target_position.position.x = x;
-            target_position.position.y = y;
-            target_position.position.z = z;
-            let mut target_rotation = entity.get_mut::<TargetRotation>().unwrap();
-            target_rotation.yaw = -(yaw / 256.0) * PI * 2.0;
-            target_rotation.pitch = -(pitch / 256.0) * PI * 2.0;
+            entity::move_to(&mut entity, Vector3::new(x, y, z));
+            entity::rotate_to(
+                &mut entity,
+                -(yaw / 256.0) * PI * 2.0,
+                -(pitch / 256.0) * PI * 2.0,
+            );
+            set_on_ground(&mut entity, on_ground);
         }
     }
 
@@ -1953,21 +2776,28 @@ impl Server {
         if let Some(entity) = self.entity_map.read().get(&entity_move.entity_id) {
             let mut entities = self.entities.write();
             let mut entity = entities.world.entity_mut(*entity);
-            let mut position = entity.get_mut::<TargetPosition>().unwrap();
-            position.position.x += entity_move.delta_x;
-            position.position.y += entity_move.delta_y;
-            position.position.z += entity_move.delta_z;
+            let position = entity.get::<TargetPosition>().unwrap().position
+                + Vector3::new(
+                    entity_move.delta_x,
+                    entity_move.delta_y,
+                    entity_move.delta_z,
+                );
+            entity::move_to(&mut entity, position);
+            set_on_ground(&mut entity, entity_move.on_ground);
         }
     }
 
-    fn on_entity_look(&self, entity_id: i32, yaw: f64, pitch: f64) {
+    fn on_entity_look(&self, entity_id: i32, yaw: f64, pitch: f64, on_ground: Option<bool>) {
         use std::f64::consts::PI;
         if let Some(entity) = self.entity_map.read().get(&entity_id) {
             let mut entities = self.entities.write();
             let mut entity = entities.world.entity_mut(*entity);
-            let mut rotation = entity.get_mut::<TargetRotation>().unwrap();
-            rotation.yaw = -(yaw / 256.0) * PI * 2.0;
-            rotation.pitch = -(pitch / 256.0) * PI * 2.0;
+            entity::rotate_to(
+                &mut entity,
+                -(yaw / 256.0) * PI * 2.0,
+                -(pitch / 256.0) * PI * 2.0,
+            );
+            set_on_ground(&mut entity, on_ground);
         }
     }
 
@@ -1979,18 +2809,21 @@ impl Server {
         delta_z: f64,
         yaw: f64,
         pitch: f64,
+        on_ground: Option<bool>,
     ) {
         use std::f64::consts::PI;
         if let Some(entity) = self.entity_map.read().get(&entity_id) {
             let mut entities = self.entities.write();
             let mut entity = entities.world.entity_mut(*entity);
-            let mut position = entity.get_mut::<TargetPosition>().unwrap();
-            position.position.x += delta_x;
-            position.position.y += delta_y;
-            position.position.z += delta_z;
-            let mut rotation = entity.get_mut::<TargetRotation>().unwrap();
-            rotation.yaw = -(yaw / 256.0) * PI * 2.0;
-            rotation.pitch = -(pitch / 256.0) * PI * 2.0;
+            let position = entity.get::<TargetPosition>().unwrap().position
+                + Vector3::new(delta_x, delta_y, delta_z);
+            entity::move_to(&mut entity, position);
+            entity::rotate_to(
+                &mut entity,
+                -(yaw / 256.0) * PI * 2.0,
+                -(pitch / 256.0) * PI * 2.0,
+            );
+            set_on_ground(&mut entity, on_ground);
         }
     }
 
@@ -2006,7 +2839,9 @@ impl Server {
     ) {
         use std::f64::consts::PI;
         if let Some(entity) = self.entity_map.write().remove(&entity_id) {
-            self.entities.write().world.despawn(entity);
+            let mut entities = self.entities.write();
+            entity::vehicle::forget(&mut entities.world, self.mapped_protocol_version, entity);
+            entities.world.despawn(entity);
         }
         let world_entity = entity::player::create_remote(
             &mut self.entities.write(),
@@ -2079,6 +2914,10 @@ impl Server {
                 position.position.z,
                 teleport.z,
             );
+            let center = CPos(
+                (position.position.x.floor() as i32) >> 4,
+                (position.position.z.floor() as i32) >> 4,
+            );
             let mut rotation = player_entity.get_mut::<crate::entity::Rotation>().unwrap();
             rotation.yaw = calculate_relative_teleport(
                 TeleportFlag::RelYaw,
@@ -2112,6 +2951,11 @@ impl Server {
                     teleport_id: protocol::VarInt(teleport_id),
                 });
             }
+
+            if let Some(cache) = self.world.chunk_cache() {
+                let distance = self.render_distance.load(Ordering::Acquire) as i32;
+                cache.load_around(&self.world, center, distance);
+            }
         }
     }
 
@@ -2119,6 +2963,10 @@ impl Server {
         &self,
         block_update: mapped_packet::play::clientbound::UpdateBlockEntity,
     ) {
+        self.world.set_block_entity_data(
+            block_update.location,
+            block_update.nbt.as_ref().map(|nbt| nbt.1.clone()),
+        );
         match block_update.nbt {
             None => {
                 // NBT is null, so we need to remove the block entity
@@ -2133,9 +2981,7 @@ impl Server {
                     //1 => // Mob spawner
                     //2 => // Command block text
                     //3 => // Beacon
-                    //4 => // Mob head
                     //5 => // Conduit
-                    //6 => // Banner
                     //7 => // Structure
                     //8 => // Gateway
                     9 => {
@@ -2162,8 +3008,14 @@ impl Server {
                             ))),
                         );
                     }
+                    // Mob head, banner and, before 1.13, bed
+                    4 | 6 | 11 => {
+                        self.world
+                            .add_block_entity_action(world::BlockEntityAction::UpdateData(
+                                Box::new((block_update.location, nbt)),
+                            ));
+                    }
                     //10 => // Unused
-                    //11 => // Jigsaw
                     //12 => // Campfire
                     //14 => // Beehive
                     _ => {
@@ -2186,6 +3038,21 @@ impl Server {
         update_sign.line2 = update_sign.line2.try_update_with_legacy();
         update_sign.line3 = update_sign.line3.try_update_with_legacy();
         update_sign.line4 = update_sign.line4.try_update_with_legacy();
+        let mut data = nbt::Tag::new_compound();
+        data.put("id", nbt::Tag::String("Sign".into()));
+        data.put("x", nbt::Tag::Int(update_sign.location.x));
+        data.put("y", nbt::Tag::Int(update_sign.location.y));
+        data.put("z", nbt::Tag::Int(update_sign.location.z));
+        for (key, line) in [
+            ("Text1", &update_sign.line1),
+            ("Text2", &update_sign.line2),
+            ("Text3", &update_sign.line3),
+            ("Text4", &update_sign.line4),
+        ] {
+            data.put(key, nbt::Tag::String(line.to_value().to_string()));
+        }
+        self.world
+            .set_block_entity_data(update_sign.location, Some(data));
         self.world
             .add_block_entity_action(world::BlockEntityAction::UpdateSignText(Box::new((
                 update_sign.location,
@@ -2250,32 +3117,8 @@ impl Server {
                         if prop.name != "textures" {
                             continue;
                         }
-                        // Ideally we would check the signature of the blob to
-                        // verify it was from Mojang and not faked by the server
-                        // but this requires the public key which is distributed
-                        // authlib. We could download authlib on startup and extract
-                        // the key but this seems like overkill compared to just
-                        // whitelisting Mojang's texture servers instead.
-                        let skin_blob_result = &STANDARD.decode(&prop.value);
-                        let skin_blob = match skin_blob_result {
-                            Ok(val) => val,
-                            Err(err) => {
-                                error!("Failed to decode skin blob, {:?}", err);
-                                continue;
-                            }
-                        };
-                        let skin_blob: serde_json::Value = match serde_json::from_slice(skin_blob) {
-                            Ok(val) => val,
-                            Err(err) => {
-                                error!("Failed to parse skin blob, {:?}", err);
-                                continue;
-                            }
-                        };
-                        if let Some(skin_url) = skin_blob
-                            .pointer("/textures/SKIN/url")
-                            .and_then(|v| v.as_str())
-                        {
-                            info.skin_url = Some(skin_url.to_owned());
+                        if let Some(skin_url) = skin_url_from_textures(&prop.value) {
+                            info.skin_url = Some(skin_url);
                         }
                     }
 
@@ -2290,7 +3133,13 @@ impl Server {
                             .world
                             .entity_mut(self.player.load().as_ref().unwrap().1);
                         let mut model = player.get_mut::<entity::player::PlayerModel>().unwrap();
-                        model.set_skin(info.skin_url.clone());
+                        // Offline mode servers don't send skins, fall back to the
+                        // one selected for the account, if any.
+                        model.set_skin(
+                            info.skin_url
+                                .clone()
+                                .or_else(|| self.local_skin_url.clone()),
+                        );
                     }
                 }
                 UpdateGamemode { uuid, gamemode } => {
@@ -2341,11 +3190,16 @@ impl Server {
             let x = block_entity.1.get("x").unwrap().as_int().unwrap();
             let y = block_entity.1.get("y").unwrap().as_int().unwrap();
             let z = block_entity.1.get("z").unwrap().as_int().unwrap();
+            self.world
+                .set_block_entity_data(Position::new(x, y, z), Some(block_entity.1.clone()));
             if let Some(tile_id) = block_entity.1.get("id") {
                 let tile_id = tile_id.as_str().unwrap();
                 let action = match tile_id {
                     // Fake a sign update
                     "Sign" => 9,
+                    "Skull" | "minecraft:skull" => 4,
+                    "Banner" | "minecraft:banner" => 6,
+                    "Bed" | "minecraft:bed" => 11,
                     // Not something we care about, so break the loop
                     _ => continue,
                 };
@@ -2378,6 +3232,7 @@ impl Server {
                 chunk_data.new,
                 sky_light,
                 chunk_data.bitmask as u16,
+                &chunk_data.biomes,
                 chunk_data.data,
             )
             .unwrap();
@@ -2396,6 +3251,7 @@ impl Server {
                 chunk_data.new,
                 sky_light,
                 chunk_data.bitmask as u16,
+                &chunk_data.biomes.data,
                 chunk_data.data,
             )
             .unwrap();
@@ -2414,6 +3270,7 @@ impl Server {
                 chunk_data.new,
                 sky_light,
                 chunk_data.bitmask as u16,
+                &chunk_data.biomes.data,
                 chunk_data.data,
             )
             .unwrap();
@@ -2537,6 +3394,55 @@ impl Server {
         self.world.set_block(location, block)
     }
 
+    /// Clears the blocks an explosion destroyed, knocks the player back and
+    /// shows and plays the explosion.
+    fn on_explosion(&self, explosion: mapped_packet::play::clientbound::Explosion) {
+        // The affected blocks are sent relative to the block the explosion
+        // happened in, which was found by truncating before 1.13
+        let origin = |coord: f32| {
+            if self.mapped_protocol_version < Version::V1_13 {
+                coord as i32
+            } else {
+                coord.floor() as i32
+            }
+        };
+        let ox = origin(explosion.x);
+        let oy = origin(explosion.y);
+        let oz = origin(explosion.z);
+        for record in &explosion.records {
+            self.world.set_block(
+                Position::new(
+                    ox + record.x as i32,
+                    oy + record.y as i32,
+                    oz + record.z as i32,
+                ),
+                world::block::Air {},
+            );
+        }
+
+        let mut entities = self.entities.write();
+        if let Some(player) = self.player.load().as_ref() {
+            let mut player_entity = entities.world.entity_mut(player.1);
+            let mut velocity = player_entity.get_mut::<crate::entity::Velocity>().unwrap();
+            velocity.velocity += Vector3::new(
+                explosion.velocity_x as f64,
+                explosion.velocity_y as f64,
+                explosion.velocity_z as f64,
+            );
+        }
+
+        let position = Vector3::new(explosion.x as f64, explosion.y as f64, explosion.z as f64);
+        let mut entity = entities.world.spawn_empty();
+        entity.insert(ExplosionData {
+            position,
+            radius: explosion.radius,
+        });
+        entity.insert(crate::particle::ParticleType::Explosion);
+        entities
+            .world
+            .spawn(SoundEvent::new("entity.generic.explode", position));
+    }
+
     fn on_block_change(&self, block_change: mapped_packet::play::clientbound::BlockChange) {
         self.on_block_change_in_world(block_change.location, block_change.block_id)
     }
@@ -2571,6 +3477,22 @@ impl Server {
         }
     }
 
+    /// Applies or removes (without an amplifier) an effect. Only the
+    /// player's effects are kept, others don't change what we do.
+    fn on_entity_effect(&self, entity_id: i32, effect_id: i8, amplifier: Option<i8>) {
+        let player = match self.player.load().as_ref() {
+            Some(player) if player.0 == entity_id => player.1,
+            _ => return,
+        };
+        let mut entities = self.entities.write();
+        if let Some(mut effects) = entities.world.get_mut::<Effects>(player) {
+            match amplifier {
+                Some(amplifier) => effects.amplifiers.insert(effect_id, amplifier),
+                None => effects.amplifiers.remove(&effect_id),
+            };
+        }
+    }
+
     pub fn on_update_health(&self, health: f32, food: u8, saturation: u8) {
         self.hud_context
             .write()
@@ -2601,6 +3523,43 @@ fn calculate_relative_teleport(flag: TeleportFlag, flags: u8, base: f64, val: f6
     }
 }
 
+/// Lets entities the client moves know whether the server put them on the
+/// ground, which 1.7 doesn't say.
+fn set_on_ground(entity: &mut EntityWorldMut, on_ground: Option<bool>) {
+    if let (Some(on_ground), Some(mut gravity)) = (on_ground, entity.get_mut::<Gravity>()) {
+        gravity.on_ground = on_ground;
+    }
+}
+
+/// Extracts the skin URL from a base64 encoded `textures` property, as sent
+/// for players and stored in player heads.
+pub fn skin_url_from_textures(value: &str) -> Option<String> {
+    // Ideally we would check the signature of the blob to
+    // verify it was from Mojang and not faked by the server
+    // but this requires the public key which is distributed
+    // authlib. We could download authlib on startup and extract
+    // the key but this seems like overkill compared to just
+    // whitelisting Mojang's texture servers instead.
+    let skin_blob = match STANDARD.decode(value) {
+        Ok(val) => val,
+        Err(err) => {
+            error!("Failed to decode skin blob, {:?}", err);
+            return None;
+        }
+    };
+    let skin_blob: serde_json::Value = match serde_json::from_slice(&skin_blob) {
+        Ok(val) => val,
+        Err(err) => {
+            error!("Failed to parse skin blob, {:?}", err);
+            return None;
+        }
+    };
+    skin_blob
+        .pointer("/textures/SKIN/url")
+        .and_then(|v| v.as_str())
+        .map(|url| url.to_owned())
+}
+
 #[derive(Resource)]
 pub struct WorldResource(pub Arc<World>);
 
@@ -2636,6 +3595,15 @@ pub struct RenderCtx {
 #[derive(Resource)]
 pub struct SunModelResource(pub SunModel);
 
+#[derive(Resource)]
+pub struct BorderModelResource(pub BorderModel);
+
+/// Block models for block entities that draw whole blocks, such as the
+/// blocks a piston moves. Shared with the chunk builder, which also takes
+/// care of reloading them when the resources change.
+#[derive(Resource)]
+pub struct BlockModelsResource(pub Arc<RwLock<model::Factory>>);
+
 #[derive(Resource)]
 pub struct TargetResource(pub Arc<RwLock<target::Info>>);
 
@@ -2646,12 +3614,24 @@ fn tick_sun(
     mut sun: ResMut<SunModelResource>,
     renderer: Res<RendererResource>,
     world_data: ResMut<WorldData>,
+    world: Res<WorldResource>,
+) {
+    let world_time = match world.0.dimension.load().fixed_time {
+        Some(fixed_time) => fixed_time as f64,
+        None => world_data.world_time,
+    };
+    sun.0
+        .tick(renderer.0.clone(), world_time, world_data.world_age);
+}
+
+fn tick_border(
+    mut border: ResMut<BorderModelResource>,
+    renderer: Res<RendererResource>,
+    world: Res<WorldResource>,
 ) {
-    sun.0.tick(
-        renderer.0.clone(),
-        world_data.world_time,
-        world_data.world_age,
-    );
+    border
+        .0
+        .tick(renderer.0.clone(), &world.0.world_border.read());
 }
 
 fn tick_world(mut commands: Commands, world: Res<WorldResource>) {
@@ -2682,6 +3662,7 @@ fn tick_time(mut world_data: ResMut<WorldData>, delta: Res<DeltaResource>) {
 fn add_systems(sched: &mut Schedule) {
     sched
         .add_systems(tick_sun.in_set(SystemExecStage::Render))
+        .add_systems(tick_border.in_set(SystemExecStage::Render))
         .add_systems(tick_world.in_set(SystemExecStage::Normal))
         .add_systems(tick_time.in_set(SystemExecStage::Normal));
 }
@@ -3,9 +3,45 @@ use crate::render::model;
 use crate::shared::{Direction, Position};
 use crate::world;
 use crate::world::block;
-use collision::Aabb;
+use bevy_ecs::prelude::Entity;
+use cgmath::InnerSpace;
+use collision::{Aabb, Aabb3};
+use std::fmt;
 use std::sync::Arc;
 
+/// How far away entities can be attacked or interacted with.
+pub const ENTITY_REACH: f64 = 3.0;
+
+/// What the crosshair points at.
+#[derive(Clone, Copy)]
+pub enum Target {
+    Block {
+        pos: Position,
+        block: block::Block,
+        face: Direction,
+        /// Where the block was hit, relative to its position.
+        at: cgmath::Vector3<f64>,
+    },
+    Entity {
+        /// The id the server knows the entity by.
+        id: i32,
+        entity: Entity,
+        /// Where the entity was hit, relative to its position.
+        at: cgmath::Vector3<f64>,
+    },
+}
+
+impl fmt::Display for Target {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Target::Block { pos, face, .. } => {
+                write!(f, "block {}, {}, {} ({:?})", pos.x, pos.y, pos.z, face)
+            }
+            Target::Entity { id, .. } => write!(f, "entity {}", id),
+        }
+    }
+}
+
 pub struct Info {
     model: Option<model::ModelHandle>,
     last_block: block::Block,
@@ -235,6 +271,38 @@ fn intersects_line(
     Some(coord)
 }
 
+/// The first of the boxes the ray from `s` along `d` hits within `max`
+/// blocks, with how far away and where it was hit.
+pub fn trace_boxes<T, I>(
+    max: f64,
+    s: cgmath::Vector3<f64>,
+    d: cgmath::Vector3<f64>,
+    boxes: I,
+) -> Option<(T, f64, cgmath::Vector3<f64>)>
+where
+    I: IntoIterator<Item = (T, Aabb3<f64>)>,
+{
+    let mut closest = None;
+    for (value, bound) in boxes {
+        let hit = match intersects_line(bound, s, d) {
+            Some(hit) => hit,
+            None => continue,
+        };
+        let distance = (hit - s).magnitude();
+        if distance > max {
+            continue;
+        }
+        let closer = match &closest {
+            Some((_, closest, _)) => distance < *closest,
+            None => true,
+        };
+        if closer {
+            closest = Some((value, distance, hit));
+        }
+    }
+    closest
+}
+
 pub fn trace_ray<F, R>(
     world: &world::World,
     max: f64,
@@ -319,3 +387,37 @@ where
 
     None
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cgmath::{Point3, Vector3};
+
+    fn unit_box(x: f64) -> Aabb3<f64> {
+        Aabb3::new(Point3::new(x, 0.0, 0.0), Point3::new(x + 1.0, 1.0, 1.0))
+    }
+
+    #[test]
+    fn boxes_closest_hit_wins() {
+        let s = Vector3::new(0.0, 0.5, 0.5);
+        let d = Vector3::new(1.0, 0.0, 0.0);
+        let hit = trace_boxes(
+            5.0,
+            s,
+            d,
+            vec![("far", unit_box(3.0)), ("near", unit_box(1.0))],
+        );
+        let (value, distance, at) = hit.unwrap();
+        assert_eq!(value, "near");
+        assert_eq!(distance, 1.0);
+        assert_eq!(at, Vector3::new(1.0, 0.5, 0.5));
+    }
+
+    #[test]
+    fn boxes_out_of_reach_or_behind() {
+        let s = Vector3::new(0.0, 0.5, 0.5);
+        let d = Vector3::new(1.0, 0.0, 0.0);
+        assert!(trace_boxes(2.0, s, d, vec![((), unit_box(3.0))]).is_none());
+        assert!(trace_boxes(5.0, s, d, vec![((), unit_box(-3.0))]).is_none());
+    }
+}
@@ -37,6 +37,9 @@ pub enum IntSetting {
     FOV,
     MasterVolume,
     DefaultProtocolVersion,
+    BiomeBlend,
+    RenderDistance,
+    ChunkCacheSize,
 }
 
 #[derive(PartialEq, PartialOrd, Hash, Eq, Ord, Clone, Copy)]
@@ -20,6 +20,33 @@ pub fn default_vars() -> Vec<(SettingType, ConfigVar)> {
                 value: SettingValue::Num(90),
             },
         ),
+        (
+            SettingType::Int(IntSetting::BiomeBlend),
+            ConfigVar {
+                name: "biome_blend",
+                description: "Radius of the grass, foliage and water colour blending (0-7)",
+                serializable: true,
+                value: SettingValue::Num(1),
+            },
+        ),
+        (
+            SettingType::Int(IntSetting::RenderDistance),
+            ConfigVar {
+                name: "render_distance",
+                description: "How many chunks around the player are loaded (2-32)",
+                serializable: true,
+                value: SettingValue::Num(8),
+            },
+        ),
+        (
+            SettingType::Int(IntSetting::ChunkCacheSize),
+            ConfigVar {
+                name: "chunk_cache_size",
+                description: "Megabytes of chunks kept on disk to show on rejoin (0 disables)",
+                serializable: true,
+                value: SettingValue::Num(0),
+            },
+        ),
         (
             SettingType::Bool(BoolSetting::Vsync),
             ConfigVar {
@@ -100,6 +100,14 @@ pub fn create_keybinds() -> Vec<(Key, Keybind)> {
                 action: Actionkey::ToggleChat,
             },
         ),
+        (
+            Key::Named(NamedKey::F6),
+            Keybind {
+                name: "keybind_save_world",
+                description: "Keybinding for saving the loaded chunks as a world",
+                action: Actionkey::SaveWorld,
+            },
+        ),
         (
             Key::Character(SmolStr::new_inline("1")),
             Keybind {
@@ -185,6 +185,7 @@ pub enum Actionkey {
     ToggleHud,
     ToggleDebug,
     ToggleChat,
+    SaveWorld,
     Hotbar1,
     Hotbar2,
     Hotbar3,
@@ -212,6 +213,7 @@ impl FromStr for Actionkey {
             "keybind_toggle_hud" => Ok(Actionkey::ToggleHud),
             "keybind_toggle_debug_info" => Ok(Actionkey::ToggleDebug),
             "keybind_toggle_chat" => Ok(Actionkey::ToggleChat),
+            "keybind_save_world" => Ok(Actionkey::SaveWorld),
             "keybind_hotbar_1" => Ok(Actionkey::Hotbar1),
             "keybind_hotbar_2" => Ok(Actionkey::Hotbar2),
             "keybind_hotbar_3" => Ok(Actionkey::Hotbar3),
@@ -227,7 +229,7 @@ impl FromStr for Actionkey {
 }
 
 impl Actionkey {
-    const VALUES: [Actionkey; 21] = [
+    const VALUES: [Actionkey; 22] = [
         Actionkey::Forward,
         Actionkey::Backward,
         Actionkey::Left,
@@ -240,6 +242,7 @@ impl Actionkey {
         Actionkey::ToggleHud,
         Actionkey::ToggleDebug,
         Actionkey::ToggleChat,
+        Actionkey::SaveWorld,
         Actionkey::Hotbar1,
         Actionkey::Hotbar2,
         Actionkey::Hotbar3,
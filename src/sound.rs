@@ -0,0 +1,46 @@
+use crate::ecs::SystemExecStage;
+use bevy_ecs::prelude::*;
+use cgmath::Vector3;
+use log::debug;
+
+pub fn add_systems(sched: &mut Schedule) {
+    sched.add_systems(
+        sound_played
+            .in_set(SystemExecStage::Render)
+            .after(SystemExecStage::Normal),
+    );
+}
+
+/// A sound to play once at a position in the world, named by its sound
+/// event (e.g. `entity.generic.explode`). Entities carrying one are
+/// despawned after it was played.
+#[derive(Component, Clone, Debug)]
+pub struct SoundEvent {
+    pub name: &'static str,
+    pub position: Vector3<f64>,
+    pub volume: f32,
+    pub pitch: f32,
+}
+
+impl SoundEvent {
+    pub fn new(name: &'static str, position: Vector3<f64>) -> Self {
+        Self {
+            name,
+            position,
+            volume: 1.0,
+            pitch: 1.0,
+        }
+    }
+}
+
+/// Hands the sounds to the audio output. There is no audio output yet, so
+/// they are only logged.
+pub fn sound_played(mut commands: Commands, query: Query<(Entity, &SoundEvent)>) {
+    for (entity, sound) in query.iter() {
+        debug!(
+            "Sound {} at {:?} (volume {}, pitch {})",
+            sound.name, sound.position, sound.volume, sound.pitch
+        );
+        commands.entity(entity).despawn();
+    }
+}
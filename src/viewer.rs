@@ -0,0 +1,272 @@
+use crate::render::Renderer;
+use crate::settings::{Actionkey, IntSetting};
+use crate::world::anvil::{self, Region};
+use crate::world::{self, CPos, LightUpdate, World};
+use crate::Game;
+use cgmath::Point3;
+use crossbeam_channel::{unbounded, Receiver, Sender};
+use leafish_protocol::protocol;
+use log::warn;
+use parking_lot::Mutex;
+use std::collections::{HashMap, HashSet};
+use std::f64::consts::PI;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Weak};
+use std::thread;
+
+/// The protocol version worlds are viewed as. Its block ids are only used
+/// for saves from before 1.13, which store blocks by id.
+const LEGACY_PROTOCOL_VERSION: i32 = 340;
+const FLAT_PROTOCOL_VERSION: i32 = 759;
+
+/// Blocks the camera moves per frame, and how much faster sprinting is.
+const FLY_SPEED: f64 = 0.25;
+const SPRINT_FACTOR: f64 = 4.0;
+/// Chunks are only unloaded this far outside the render distance, so
+/// flying back and forth doesn't keep reloading them.
+const UNLOAD_MARGIN: i32 = 2;
+/// How close, in blocks, the camera gets to the top or bottom of the world
+/// before the sections of a taller save are shifted to keep it centered.
+const SHIFT_MARGIN: f64 = 64.0;
+
+/// A save opened without a server, flown through with a free camera.
+pub struct Viewer {
+    pub world: Arc<World>,
+    pub name: String,
+
+    camera: Mutex<FreeCamera>,
+    light_updates: Receiver<LightUpdate>,
+    chunk_loader: Sender<(CPos, i32)>,
+    render_list_computer: Sender<bool>,
+    /// The heights of the save's sections. The world only holds 16 of
+    /// them, moved up by `section_offset` while they're loaded.
+    sections: Range<i32>,
+    section_offset: Arc<Mutex<i32>>,
+}
+
+struct FreeCamera {
+    /// Where the camera is in the save, which is off from the world by
+    /// the section offset.
+    position: Point3<f64>,
+    yaw: f64,
+    pitch: f64,
+    pressed_keys: HashSet<Actionkey>,
+    /// The chunk and render distance chunks were last loaded around.
+    loaded_around: Option<(CPos, i32)>,
+}
+
+impl Viewer {
+    /// Opens the save in `dir`, starting at its spawn. Chunks are loaded in
+    /// the background as the camera moves.
+    pub fn open(dir: &Path, renderer: Arc<Renderer>) -> Result<Arc<Viewer>, protocol::Error> {
+        let level = anvil::read_level(dir)?;
+        let protocol_version = if anvil::is_legacy_save(dir) {
+            LEGACY_PROTOCOL_VERSION
+        } else {
+            FLAT_PROTOCOL_VERSION
+        };
+        let (light_sender, light_updates) = unbounded();
+        let world = Arc::new(World::new(protocol_version, light_sender));
+        let (x, y, z) = level.spawn;
+        let sections = anvil::section_range(dir);
+        let section_offset = Arc::new(Mutex::new(centering_offset(&sections, y as f64)));
+        Ok(Arc::new(Viewer {
+            chunk_loader: spawn_chunk_loader(
+                Arc::downgrade(&world),
+                dir.to_owned(),
+                section_offset.clone(),
+            ),
+            render_list_computer: spawn_render_list_computer(Arc::downgrade(&world), renderer),
+            sections,
+            section_offset,
+            world,
+            name: level.name,
+            camera: Mutex::new(FreeCamera {
+                position: Point3::new(x as f64 + 0.5, y as f64 + 1.62, z as f64 + 0.5),
+                yaw: 0.0,
+                pitch: PI,
+                pressed_keys: HashSet::new(),
+                loaded_around: None,
+            }),
+            light_updates,
+        }))
+    }
+
+    pub fn tick(&self, delta: f64, game: &Game) {
+        let updates: Vec<LightUpdate> = self.light_updates.try_iter().collect();
+        if !updates.is_empty() {
+            self.world.do_light_updates(updates);
+        }
+
+        let mut camera = self.camera.lock();
+        let pressed = |key| camera.pressed_keys.contains(&key) as i32 as f64;
+        let forward = pressed(Actionkey::Forward) - pressed(Actionkey::Backward);
+        let strafe = pressed(Actionkey::Left) - pressed(Actionkey::Right);
+        let rise = pressed(Actionkey::Jump) - pressed(Actionkey::Sneak);
+        let speed = FLY_SPEED
+            * delta
+            * if camera.pressed_keys.contains(&Actionkey::Sprint) {
+                SPRINT_FACTOR
+            } else {
+                1.0
+            };
+        if forward != 0.0 || strafe != 0.0 {
+            let yaw = camera.yaw - (PI / 2.0) + strafe.atan2(forward);
+            camera.position.x += yaw.cos() * speed;
+            camera.position.z -= yaw.sin() * speed;
+        }
+        camera.position.y += rise * speed;
+
+        let mut section_offset = *self.section_offset.lock();
+        let world_y = camera.position.y + section_offset as f64 * 16.0;
+        if !(SHIFT_MARGIN..256.0 - SHIFT_MARGIN).contains(&world_y) {
+            let wanted = centering_offset(&self.sections, camera.position.y);
+            if wanted != section_offset {
+                section_offset = wanted;
+                // Locked until the chunks are gone, so the loader can't
+                // add one at the old height in between
+                let mut offset = self.section_offset.lock();
+                *offset = wanted;
+                self.world.chunks.write().clear();
+                camera.loaded_around = None;
+            }
+        }
+
+        {
+            let mut view = game.renderer.camera.lock();
+            view.pos =
+                camera.position + cgmath::Vector3::new(0.0, section_offset as f64 * 16.0, 0.0);
+            view.yaw = camera.yaw;
+            view.pitch = camera.pitch;
+        }
+
+        let center = CPos(
+            (camera.position.x.floor() as i32) >> 4,
+            (camera.position.z.floor() as i32) >> 4,
+        );
+        let distance = game.settings.get_int(IntSetting::RenderDistance).clamp(
+            world::MIN_RENDER_DISTANCE as i32,
+            world::MAX_RENDER_DISTANCE as i32,
+        );
+        if camera.loaded_around != Some((center, distance)) {
+            camera.loaded_around = Some((center, distance));
+            // Unloaded here, the render buffers have to be freed on this thread
+            self.world.chunks.write().retain(|pos, _| {
+                (pos.0 - center.0).abs().max((pos.1 - center.1).abs()) <= distance + UNLOAD_MARGIN
+            });
+            let _ = self.chunk_loader.send((center, distance));
+        }
+    }
+
+    pub fn key_press(&self, down: bool, key: Actionkey) {
+        let mut camera = self.camera.lock();
+        if down {
+            camera.pressed_keys.insert(key);
+        } else {
+            camera.pressed_keys.remove(&key);
+        }
+    }
+
+    pub fn rotate(&self, yaw: f64, pitch: f64) {
+        let mut camera = self.camera.lock();
+        camera.yaw -= yaw;
+        camera.pitch = (camera.pitch - pitch).clamp((PI / 2.0) + 0.01, (PI / 2.0) * 3.0 - 0.01);
+    }
+
+    pub fn compute_render_list(&self) {
+        let _ = self.render_list_computer.send(true);
+    }
+
+    /// Unloads every chunk, has to be called on the render thread before
+    /// the viewer is dropped so their render buffers are freed there.
+    pub fn close(&self) {
+        self.world.chunks.write().clear();
+    }
+}
+
+/// How far the sections of a save with `sections` have to be moved up so
+/// the 16 that fit into the world are centered on the height `y`.
+fn centering_offset(sections: &Range<i32>, y: f64) -> i32 {
+    let lowest = ((y.floor() as i32) >> 4) - 8;
+    -(lowest.clamp(sections.start, (sections.end - 16).max(sections.start)))
+}
+
+/// Loads the chunks of the save within the render distance around the
+/// requested chunk, nearest first.
+fn spawn_chunk_loader(
+    world: Weak<World>,
+    dir: PathBuf,
+    section_offset: Arc<Mutex<i32>>,
+) -> Sender<(CPos, i32)> {
+    let (tx, rx) = unbounded::<(CPos, i32)>();
+    thread::spawn(move || {
+        let mut regions: HashMap<(i32, i32), Option<Region>> = HashMap::new();
+        // Chunks the save doesn't have, or that failed to load
+        let mut missing: HashSet<CPos> = HashSet::new();
+        while let Ok(mut request) = rx.recv() {
+            // Only the latest position matters
+            while let Ok(newer) = rx.try_recv() {
+                request = newer;
+            }
+            let (center, distance) = request;
+            let world = match world.upgrade() {
+                Some(world) => world,
+                None => return,
+            };
+            regions.retain(|&(x, z), _| {
+                (x - (center.0 >> 5)).abs().max((z - (center.1 >> 5)).abs()) <= (distance >> 5) + 1
+            });
+
+            let mut positions: Vec<CPos> = (-distance..=distance)
+                .flat_map(|x| (-distance..=distance).map(move |z| CPos(center.0 + x, center.1 + z)))
+                .filter(|pos| !missing.contains(pos) && !world.is_chunk_loaded(pos.0, pos.1))
+                .collect();
+            positions.sort_by_key(|pos| (pos.0 - center.0).pow(2) + (pos.1 - center.1).pow(2));
+            for pos in positions {
+                if !rx.is_empty() {
+                    // The camera moved on, start over around it
+                    break;
+                }
+                let region = regions.entry((pos.0 >> 5, pos.1 >> 5)).or_insert_with(|| {
+                    let name = format!("r.{}.{}.mca", pos.0 >> 5, pos.1 >> 5);
+                    Region::open(&dir.join("region").join(name)).ok()
+                });
+                let loaded = match region.as_ref().map(|region| region.chunk(pos.0, pos.1)) {
+                    Some(Ok(Some(tag))) => {
+                        // Held while the chunk goes in, see `Viewer::tick`
+                        let offset = section_offset.lock();
+                        anvil::load_chunk(&world, &tag, *offset).map(|_| true)
+                    }
+                    Some(Err(err)) => Err(err),
+                    _ => Ok(false),
+                };
+                match loaded {
+                    Ok(true) => {}
+                    Ok(false) => {
+                        missing.insert(pos);
+                    }
+                    Err(err) => {
+                        warn!("Failed to load chunk {},{}: {}", pos.0, pos.1, err);
+                        missing.insert(pos);
+                    }
+                }
+            }
+        }
+    });
+    tx
+}
+
+fn spawn_render_list_computer(world: Weak<World>, renderer: Arc<Renderer>) -> Sender<bool> {
+    let (tx, rx) = unbounded();
+    thread::spawn(move || {
+        while rx.recv().is_ok() {
+            match world.upgrade() {
+                Some(world) => world.compute_render_list(renderer.clone()),
+                None => return,
+            }
+            while rx.try_recv().is_ok() {}
+        }
+    });
+    tx
+}
@@ -0,0 +1,959 @@
+use crate::shared::Position;
+use crate::world::{biome, block, CPos, Chunk, ChunkSection, ChunkSectionSnapshot, World};
+use byteorder::{BigEndian, ByteOrder, WriteBytesExt};
+use flate2::read::{GzDecoder, ZlibDecoder};
+use flate2::write::{GzEncoder, ZlibEncoder};
+use flate2::Compression;
+use leafish_protocol::nbt::{NamedTag, Tag};
+use leafish_protocol::protocol::{self, Serializable};
+use leafish_protocol::types::{bit, nibble};
+use leafish_shared::Version;
+use log::warn;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Write;
+use std::ops::Range;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Size of a sector in a region file.
+const SECTOR_SIZE: usize = 4096;
+/// A chunk can't span more sectors than fit in its location entry.
+const MAX_CHUNK_SECTORS: usize = 255;
+/// The `version` of an Anvil `level.dat`.
+const ANVIL_VERSION: i32 = 19133;
+/// The first data version storing blocks as a palette of states (17w47a).
+const FLATTENING_DATA_VERSION: i32 = 1451;
+/// From this data version on (20w17a) block states no longer span two longs.
+const PADDED_DATA_VERSION: i32 = 2529;
+/// Saves from 1.18 on have overworld sections from y -64 up to y 319.
+const TALL_WORLD_DATA_VERSION: i32 = 2860;
+
+/// What goes into the `level.dat` of a saved world.
+pub struct Level {
+    pub name: String,
+    pub spawn: (i32, i32, i32),
+    pub game_mode: i32,
+    pub time: i64,
+    pub day_time: i64,
+}
+
+/// A copy of the loaded chunks, to be written out as an Anvil world in the
+/// format of the version the server runs.
+///
+/// Chunks are always written as the overworld, whichever dimension they
+/// came from.
+pub struct Export {
+    chunks: Vec<SavedChunk>,
+    id_map: Arc<block::VanillaIDMap>,
+    version: Version,
+}
+
+/// The parts of a chunk that get saved. Render buffers stay behind, they
+/// can only be freed on the render thread.
+struct SavedChunk {
+    position: CPos,
+    sections: Vec<ChunkSectionSnapshot>,
+    biomes: biome::BiomeStorage,
+    heightmap: [u8; 16 * 16],
+    block_entities: Vec<(Position, Tag)>,
+}
+
+impl SavedChunk {
+    fn capture(chunk: &Chunk) -> SavedChunk {
+        SavedChunk {
+            position: chunk.position,
+            sections: chunk
+                .sections
+                .iter()
+                .flatten()
+                .map(|section| section.capture_snapshot(&chunk.biomes))
+                .collect(),
+            biomes: chunk.biomes.clone(),
+            heightmap: chunk.heightmap,
+            block_entities: chunk
+                .block_entity_data
+                .iter()
+                .map(|(pos, data)| (*pos, data.clone()))
+                .collect(),
+        }
+    }
+}
+
+impl Export {
+    pub fn new(world: &World) -> Export {
+        Export {
+            chunks: world
+                .chunks
+                .read()
+                .values()
+                .map(SavedChunk::capture)
+                .collect(),
+            id_map: world.id_map.clone(),
+            version: Version::from_id(world.protocol_version as u32),
+        }
+    }
+
+    /// A copy of just the chunk at `position`, if it's loaded.
+    pub(super) fn chunk(world: &World, position: CPos) -> Option<Export> {
+        let chunk = SavedChunk::capture(world.chunks.read().get(&position)?);
+        Some(Export {
+            chunks: vec![chunk],
+            id_map: world.id_map.clone(),
+            version: Version::from_id(world.protocol_version as u32),
+        })
+    }
+
+    /// The zlib compressed NBT of each chunk, as stored in region files.
+    pub(super) fn compressed_chunks(&self) -> Result<Vec<(CPos, Vec<u8>)>, protocol::Error> {
+        let mut compressed = vec![];
+        for chunk in &self.chunks {
+            let mut data = ZlibEncoder::new(vec![], Compression::default());
+            Some(NamedTag("".into(), self.chunk_tag(chunk))).write_to(&mut data)?;
+            compressed.push((chunk.position, data.finish()?));
+        }
+        Ok(compressed)
+    }
+
+    /// Writes the world into `dir`, returning how many chunks were saved.
+    pub fn write(&self, dir: &Path, level: &Level) -> Result<usize, protocol::Error> {
+        let region_dir = dir.join("region");
+        fs::create_dir_all(&region_dir)?;
+
+        let mut regions: BTreeMap<(i32, i32), Vec<(usize, Vec<u8>)>> = BTreeMap::new();
+        for (CPos(x, z), data) in self.compressed_chunks()? {
+            regions
+                .entry((x >> 5, z >> 5))
+                .or_default()
+                .push(((((z & 31) << 5) | (x & 31)) as usize, data));
+        }
+
+        let mut saved = 0;
+        for ((x, z), chunks) in regions {
+            saved += write_region(&region_dir.join(format!("r.{}.{}.mca", x, z)), chunks)?;
+        }
+
+        let mut file = GzEncoder::new(
+            fs::File::create(dir.join("level.dat"))?,
+            Compression::default(),
+        );
+        Some(NamedTag("".into(), level_tag(level))).write_to(&mut file)?;
+        file.finish()?;
+        Ok(saved)
+    }
+
+    fn chunk_tag(&self, chunk: &SavedChunk) -> Tag {
+        let mut level = Tag::new_compound();
+        level.put("xPos", Tag::Int(chunk.position.0));
+        level.put("zPos", Tag::Int(chunk.position.1));
+        level.put("LastUpdate", Tag::Long(0));
+        level.put("InhabitedTime", Tag::Long(0));
+        // Keep the game from decorating or relighting what we saved
+        level.put("TerrainPopulated", Tag::Byte(1));
+        level.put("LightPopulated", Tag::Byte(1));
+        if self.version >= Version::V1_14 {
+            level.put("Status", Tag::String("full".into()));
+            level.put("isLightOn", Tag::Byte(1));
+        } else if self.version >= Version::V1_13 {
+            level.put("Status", Tag::String("postprocessed".into()));
+        }
+
+        let sections = chunk
+            .sections
+            .iter()
+            .map(|section| {
+                let mut tag = if self.version >= Version::V1_13 {
+                    self.flat_section_tag(section)
+                } else {
+                    self.legacy_section_tag(section)
+                };
+                tag.put("Y", Tag::Byte(section.y as i8));
+                tag.put(
+                    "BlockLight",
+                    Tag::ByteArray(section.block_light.data.clone()),
+                );
+                tag.put("SkyLight", Tag::ByteArray(section.sky_light.data.clone()));
+                tag
+            })
+            .collect();
+        level.put("Sections", Tag::List(sections));
+
+        let biome = |x, z| chunk.biomes.get(x, 64, z) as i32;
+        if self.version >= Version::V1_15 {
+            let biomes = (0..1024)
+                .map(|idx| {
+                    chunk
+                        .biomes
+                        .get((idx & 3) << 2, (idx >> 4) << 2, ((idx >> 2) & 3) << 2)
+                })
+                .map(|id| id as i32)
+                .collect();
+            level.put("Biomes", Tag::IntArray(biomes));
+        } else if self.version >= Version::V1_13 {
+            let biomes = (0..256).map(|idx| biome(idx & 15, idx >> 4)).collect();
+            level.put("Biomes", Tag::IntArray(biomes));
+        } else {
+            let biomes = (0..256)
+                .map(|idx| biome(idx & 15, idx >> 4) as u8)
+                .collect();
+            level.put("Biomes", Tag::ByteArray(biomes));
+            let heightmap = chunk.heightmap.iter().map(|&y| y as i32 + 1).collect();
+            level.put("HeightMap", Tag::IntArray(heightmap));
+        }
+
+        level.put("Entities", Tag::new_list());
+        let block_entities = chunk
+            .block_entities
+            .iter()
+            .map(|(pos, data)| {
+                let mut data = data.clone();
+                data.put("x", Tag::Int(pos.x));
+                data.put("y", Tag::Int(pos.y));
+                data.put("z", Tag::Int(pos.z));
+                data
+            })
+            .collect();
+        level.put("TileEntities", Tag::List(block_entities));
+
+        let mut root = Tag::new_compound();
+        if let Some(data_version) = data_version(self.version) {
+            root.put("DataVersion", Tag::Int(data_version));
+        }
+        root.put("Level", level);
+        root
+    }
+
+    /// A section as block ids and data values, before 1.13.
+    fn legacy_section_tag(&self, section: &ChunkSectionSnapshot) -> Tag {
+        let mut blocks = vec![0; 4096];
+        let mut add = nibble::Array::new(4096);
+        let mut data = nibble::Array::new(4096);
+        let mut has_add = false;
+        for (idx, id) in blocks.iter_mut().enumerate() {
+            let vanilla_id = self
+                .id_map
+                .to_vanilla_id(section.blocks.get(idx))
+                .unwrap_or(0);
+            *id = (vanilla_id >> 4) as u8;
+            data.set(idx, (vanilla_id & 0xF) as u8);
+            if vanilla_id >> 12 != 0 {
+                add.set(idx, (vanilla_id >> 12) as u8);
+                has_add = true;
+            }
+        }
+
+        let mut tag = Tag::new_compound();
+        tag.put("Blocks", Tag::ByteArray(blocks));
+        tag.put("Data", Tag::ByteArray(data.data));
+        if has_add {
+            tag.put("Add", Tag::ByteArray(add.data));
+        }
+        tag
+    }
+
+    /// A section as a palette of block states, from 1.13 onwards.
+    fn flat_section_tag(&self, section: &ChunkSectionSnapshot) -> Tag {
+        let mut palette = vec![];
+        let indices: Vec<usize> = (0..4096)
+            .map(|idx| {
+                let block = section.blocks.get(idx);
+                palette.iter().position(|&b| b == block).unwrap_or_else(|| {
+                    palette.push(block);
+                    palette.len() - 1
+                })
+            })
+            .collect();
+
+        // Since 1.16 entries no longer span two longs
+        let bits = (usize::BITS - (palette.len() - 1).leading_zeros()).max(4) as usize;
+        let padded = self.version >= Version::V1_16;
+        let longs = if padded {
+            (4096 + 64 / bits - 1) / (64 / bits)
+        } else {
+            4096 * bits / 64
+        };
+        let mut states = bit::Map::from_raw(vec![0; longs], bits, padded);
+        for (idx, &entry) in indices.iter().enumerate() {
+            states.set(idx, entry);
+        }
+
+        let mut tag = Tag::new_compound();
+        let palette = palette
+            .into_iter()
+            .map(|block| {
+                let (name, properties) = versioned_state(block, self.version);
+                let mut entry = Tag::new_compound();
+                entry.put("Name", Tag::String(format!("minecraft:{}", name)));
+                if !properties.is_empty() {
+                    let mut props = Tag::new_compound();
+                    for (key, value) in properties {
+                        props.put(key, Tag::String(value));
+                    }
+                    entry.put("Properties", props);
+                }
+                entry
+            })
+            .collect();
+        tag.put("Palette", Tag::List(palette));
+        tag.put(
+            "BlockStates",
+            Tag::LongArray(states.into_raw().into_iter().map(|v| v as i64).collect()),
+        );
+        tag
+    }
+}
+
+/// The name and properties of a block in the given version. Blocks use the
+/// newest names, older versions had a few blocks named or laid out
+/// differently.
+fn versioned_state(
+    block: block::Block,
+    version: Version,
+) -> (&'static str, Vec<(&'static str, String)>) {
+    let (mut name, mut properties) = block.get_state();
+    if version < Version::V1_14 {
+        name = match name {
+            "oak_sign" => "sign",
+            "oak_wall_sign" => "wall_sign",
+            name => name,
+        };
+    }
+    if version < Version::V1_16 {
+        if name.ends_with("_wall") {
+            // Walls only connected or didn't, rather than being low or tall
+            for (key, value) in &mut properties {
+                if matches!(*key, "north" | "south" | "east" | "west") {
+                    *value = (*value != "none").to_string();
+                }
+            }
+        }
+        if name == "jigsaw" {
+            for (key, value) in &mut properties {
+                if *key == "orientation" {
+                    *key = "facing";
+                    *value = value.split('_').next().unwrap_or("north").into();
+                }
+            }
+        }
+    }
+    if version < Version::V1_17 {
+        name = match name {
+            "dirt_path" => "grass_path",
+            "cauldron" => {
+                properties.push(("level", "0".into()));
+                "cauldron"
+            }
+            "water_cauldron" => "cauldron",
+            name => name,
+        };
+    }
+    (name, properties)
+}
+
+/// The data version of the newest release of each version, chunks written
+/// before 1.9 don't have one.
+fn data_version(version: Version) -> Option<i32> {
+    Some(match version {
+        Version::V1_9 => 184,
+        Version::V1_10 => 512,
+        Version::V1_11 => 922,
+        Version::V1_12 => 1343,
+        Version::V1_13 => 1519,
+        Version::V1_13_2 => 1631,
+        Version::V1_14 => 1976,
+        Version::V1_15 => 2230,
+        Version::V1_16 => 2567,
+        Version::V1_16_2 => 2586,
+        v if v >= Version::V1_17 => 2730,
+        _ => return None,
+    })
+}
+
+/// The `level.dat` is written like before 1.9, which every later version
+/// knows how to upgrade. The world is a void superflat world, so nothing
+/// gets generated next to the saved chunks.
+fn level_tag(level: &Level) -> Tag {
+    let last_played = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |time| time.as_millis() as i64);
+    let mut data = Tag::new_compound();
+    data.put("version", Tag::Int(ANVIL_VERSION));
+    data.put("LevelName", Tag::String(level.name.clone()));
+    data.put("generatorName", Tag::String("flat".into()));
+    data.put("generatorOptions", Tag::String("3;minecraft:air;1;".into()));
+    data.put("RandomSeed", Tag::Long(0));
+    data.put("MapFeatures", Tag::Byte(0));
+    data.put("GameType", Tag::Int(level.game_mode));
+    data.put("hardcore", Tag::Byte(0));
+    data.put("allowCommands", Tag::Byte(1));
+    data.put("initialized", Tag::Byte(1));
+    data.put("SpawnX", Tag::Int(level.spawn.0));
+    data.put("SpawnY", Tag::Int(level.spawn.1));
+    data.put("SpawnZ", Tag::Int(level.spawn.2));
+    data.put("Time", Tag::Long(level.time));
+    data.put("DayTime", Tag::Long(level.day_time));
+    data.put("LastPlayed", Tag::Long(last_played));
+
+    let mut root = Tag::new_compound();
+    root.put("Data", data);
+    root
+}
+
+/// Writes a region file from compressed chunks and the index of their
+/// location entry, returning how many fitted.
+fn write_region(path: &Path, chunks: Vec<(usize, Vec<u8>)>) -> Result<usize, protocol::Error> {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |time| time.as_secs() as u32);
+    let mut locations = [0u32; 1024];
+    let mut timestamps = [0u32; 1024];
+    let mut body = vec![];
+    let mut saved = 0;
+    for (idx, data) in chunks {
+        // Length, compression type and the data, padded to whole sectors
+        let sectors = (data.len() + 5 + SECTOR_SIZE - 1) / SECTOR_SIZE;
+        if sectors > MAX_CHUNK_SECTORS {
+            warn!(
+                "Skipping a chunk of {} bytes, too large for {:?}",
+                data.len(),
+                path
+            );
+            continue;
+        }
+        let offset = 2 + body.len() / SECTOR_SIZE;
+        locations[idx] = ((offset as u32) << 8) | sectors as u32;
+        timestamps[idx] = timestamp;
+        body.write_u32::<BigEndian>(data.len() as u32 + 1)?;
+        body.write_u8(2)?;
+        body.write_all(&data)?;
+        body.resize(
+            body.len() + (SECTOR_SIZE - body.len() % SECTOR_SIZE) % SECTOR_SIZE,
+            0,
+        );
+        saved += 1;
+    }
+
+    let mut file = std::io::BufWriter::new(fs::File::create(path)?);
+    for location in locations.iter().chain(timestamps.iter()) {
+        file.write_u32::<BigEndian>(*location)?;
+    }
+    file.write_all(&body)?;
+    file.flush()?;
+    Ok(saved)
+}
+
+/// Reads the name, spawn and time of a save from its `level.dat`.
+pub fn read_level(dir: &Path) -> Result<Level, protocol::Error> {
+    let mut file = GzDecoder::new(fs::File::open(dir.join("level.dat"))?);
+    let root = Option::<NamedTag>::read_from(&mut file)?
+        .ok_or_else(|| protocol::Error::Err("level.dat is empty".into()))?;
+    let data = root
+        .1
+        .get("Data")
+        .ok_or_else(|| protocol::Error::Err("level.dat has no Data".into()))?;
+    let int = |key| data.get(key).and_then(Tag::as_int).unwrap_or(0);
+    let long = |key| data.get(key).and_then(Tag::as_long).unwrap_or(0);
+    Ok(Level {
+        name: data
+            .get("LevelName")
+            .and_then(Tag::as_str)
+            .unwrap_or_default()
+            .to_owned(),
+        spawn: (int("SpawnX"), int("SpawnY"), int("SpawnZ")),
+        game_mode: int("GameType"),
+        time: long("Time"),
+        day_time: long("DayTime"),
+    })
+}
+
+/// The data version in the `level.dat` of the save in `dir`.
+fn data_version(dir: &Path) -> Option<i32> {
+    fs::File::open(dir.join("level.dat"))
+        .ok()
+        .and_then(|file| Option::<NamedTag>::read_from(&mut GzDecoder::new(file)).ok())
+        .flatten()
+        .and_then(|root| root.1.get("Data")?.get("DataVersion")?.as_int())
+}
+
+/// Whether the save in `dir` stores blocks by id, like before 1.13, going
+/// by the data version in its `level.dat`.
+pub fn is_legacy_save(dir: &Path) -> bool {
+    data_version(dir).map_or(true, |version| version < FLATTENING_DATA_VERSION)
+}
+
+/// The heights of the sections the overworld of the save in `dir` has.
+pub fn section_range(dir: &Path) -> Range<i32> {
+    match data_version(dir) {
+        Some(version) if version >= TALL_WORLD_DATA_VERSION => -4..20,
+        _ => 0..16,
+    }
+}
+
+/// A region file, holding the chunks of a 32 by 32 chunk area.
+pub struct Region {
+    data: Vec<u8>,
+}
+
+impl Region {
+    pub fn open(path: &Path) -> Result<Region, protocol::Error> {
+        let data = fs::read(path)?;
+        if data.len() < 2 * SECTOR_SIZE {
+            return Err(protocol::Error::Err(format!(
+                "{:?} is too short for a region file",
+                path
+            )));
+        }
+        Ok(Region { data })
+    }
+
+    /// The NBT of the chunk, `None` if the region doesn't have it.
+    pub fn chunk(&self, x: i32, z: i32) -> Result<Option<Tag>, protocol::Error> {
+        let idx = (((z & 31) << 5) | (x & 31)) as usize;
+        let location = BigEndian::read_u32(&self.data[idx * 4..]);
+        if location == 0 {
+            return Ok(None);
+        }
+        let offset = (location >> 8) as usize * SECTOR_SIZE;
+        let truncated = || protocol::Error::Err(format!("chunk {},{} is truncated", x, z));
+        let header = self.data.get(offset..offset + 5).ok_or_else(truncated)?;
+        // The length includes the compression type
+        let length = BigEndian::read_u32(header) as usize;
+        let mut data = self
+            .data
+            .get(offset + 5..offset + 4 + length)
+            .ok_or_else(truncated)?;
+        let tag = match header[4] {
+            1 => Option::<NamedTag>::read_from(&mut GzDecoder::new(data))?,
+            2 => Option::<NamedTag>::read_from(&mut ZlibDecoder::new(data))?,
+            3 => Option::<NamedTag>::read_from(&mut data)?,
+            compression => {
+                return Err(protocol::Error::Err(format!(
+                    "chunk {},{} uses unknown compression {}",
+                    x, z, compression
+                )))
+            }
+        };
+        Ok(tag.map(|tag| tag.1))
+    }
+}
+
+/// Loads a chunk read from a region file into the world, replacing whatever
+/// was at its position. Its light is taken as saved, block entities are
+/// only kept as NBT.
+///
+/// `section_offset` is added to the height of every section, saves taller
+/// than the world can only be loaded 16 sections at a time.
+pub fn load_chunk(world: &World, root: &Tag, section_offset: i32) -> Result<CPos, protocol::Error> {
+    let (chunk, mask) = read_chunk(world, root, section_offset)?;
+    let position = chunk.position;
+    insert_chunk(world, chunk, mask, true);
+    Ok(position)
+}
+
+/// Adds the chunk to the world. Unless `replace` is set a chunk that's
+/// already loaded is kept instead.
+pub(super) fn insert_chunk(world: &World, chunk: Chunk, mask: u16, replace: bool) {
+    let position = chunk.position;
+    {
+        let mut chunks = world.chunks.write();
+        if !replace && chunks.contains_key(&position) {
+            return;
+        }
+        chunks.insert(position, chunk);
+    }
+    world.dirty_chunks_by_bitmask(position.0, position.1, mask);
+    if world.biome_blend() > 0 {
+        world.dirty_neighbour_columns(position.0, position.1);
+    }
+}
+
+/// Reads the chunk out of its NBT, along with the mask of sections it has.
+/// Sections that end up outside the world after moving them up by
+/// `section_offset` are left out.
+pub(super) fn read_chunk(
+    world: &World,
+    root: &Tag,
+    section_offset: i32,
+) -> Result<(Chunk, u16), protocol::Error> {
+    let data_version = root.get("DataVersion").and_then(Tag::as_int).unwrap_or(0);
+    // Since 1.18 the chunk is no longer wrapped in a Level compound
+    let level = root.get("Level").unwrap_or(root);
+    let coord = |key| {
+        level
+            .get(key)
+            .and_then(Tag::as_int)
+            .ok_or_else(|| protocol::Error::Err(format!("chunk has no {}", key)))
+    };
+    let position = CPos(coord("xPos")?, coord("zPos")?);
+    let mut chunk = Chunk::new(position);
+
+    let sections = level
+        .get("Sections")
+        .or_else(|| level.get("sections"))
+        .and_then(Tag::as_list)
+        .unwrap_or_default();
+    let mut mask = 0u16;
+    for tag in sections {
+        let y = match tag.get("Y").and_then(Tag::as_byte) {
+            Some(y) if (0..16).contains(&(y as i32 + section_offset)) => {
+                (y as i32 + section_offset) as usize
+            }
+            _ => continue,
+        };
+        let sky_light = tag.get("SkyLight").and_then(Tag::as_byte_array);
+        let mut section = ChunkSection::new(y as u8, sky_light.is_none());
+        let has_blocks = if let Some(blocks) = tag.get("Blocks").and_then(Tag::as_byte_array) {
+            read_legacy_blocks(world, &mut section, tag, blocks);
+            true
+        } else {
+            read_flat_blocks(&mut section, tag, data_version)
+        };
+        let block_light = tag.get("BlockLight").and_then(Tag::as_byte_array);
+        if !has_blocks && block_light.is_none() && sky_light.is_none() {
+            continue;
+        }
+        if let Some(light) = block_light.filter(|light| light.len() == 2048) {
+            section.block_light.data.copy_from_slice(light);
+        }
+        if let Some(light) = sky_light.filter(|light| light.len() == 2048) {
+            section.sky_light.data.copy_from_slice(light);
+        }
+        section.dirty = true;
+        chunk.sections[y] = Some(section);
+        mask |= 1 << y;
+    }
+
+    // Biomes saved since 1.18 are named per section, those are left out
+    match level.get("Biomes") {
+        Some(Tag::ByteArray(ids)) => {
+            let ids: Vec<i32> = ids.iter().map(|&id| id as i32).collect();
+            chunk.biomes = biome::BiomeStorage::from_2d(&ids);
+        }
+        Some(Tag::IntArray(ids)) if ids.len() == 256 => {
+            chunk.biomes = biome::BiomeStorage::from_2d(ids);
+        }
+        Some(Tag::IntArray(ids)) => chunk.biomes = biome::BiomeStorage::from_3d(ids),
+        _ => {}
+    }
+
+    let block_entities = level
+        .get("TileEntities")
+        .or_else(|| level.get("block_entities"))
+        .and_then(Tag::as_list)
+        .unwrap_or_default();
+    for data in block_entities {
+        let coord = |key| data.get(key).and_then(Tag::as_int);
+        if let (Some(x), Some(y), Some(z)) = (coord("x"), coord("y"), coord("z")) {
+            chunk
+                .block_entity_data
+                .insert(Position::new(x, y + section_offset * 16, z), data.clone());
+        }
+    }
+
+    chunk.calculate_heightmap();
+    Ok((chunk, mask))
+}
+
+/// Reads a section stored as block ids and data values, before 1.13.
+fn read_legacy_blocks(world: &World, section: &mut ChunkSection, tag: &Tag, blocks: &[u8]) {
+    let nibbles = |key| {
+        let mut array = nibble::Array::new(4096);
+        if let Some(data) = tag
+            .get(key)
+            .and_then(Tag::as_byte_array)
+            .filter(|data| data.len() == 2048)
+        {
+            array.data.copy_from_slice(data);
+        }
+        array
+    };
+    let data = nibbles("Data");
+    let add = nibbles("Add");
+    let modded = world.modded_block_ids.load();
+    for (idx, &id) in blocks.iter().enumerate().take(4096) {
+        let id = ((add.get(idx) as usize) << 12) | ((id as usize) << 4) | data.get(idx) as usize;
+        section
+            .blocks
+            .set(idx, world.id_map.by_vanilla_id(id, &modded));
+    }
+}
+
+/// Reads a section stored as a palette of block states, returning whether
+/// it had any. 1.18 moved the palette into a `block_states` compound.
+fn read_flat_blocks(section: &mut ChunkSection, tag: &Tag, data_version: i32) -> bool {
+    let (palette, states) = match tag.get("block_states") {
+        Some(block_states) => (
+            block_states.get("palette"),
+            block_states.get("data").and_then(Tag::as_long_array),
+        ),
+        None => (
+            tag.get("Palette"),
+            tag.get("BlockStates").and_then(Tag::as_long_array),
+        ),
+    };
+    let palette: Vec<block::Block> = match palette.and_then(Tag::as_list) {
+        Some(palette) if !palette.is_empty() => palette
+            .iter()
+            .map(|entry| block_from_state(entry, data_version))
+            .collect(),
+        _ => return false,
+    };
+
+    // A single state palette doesn't need any data
+    let states = match states.filter(|states| !states.is_empty()) {
+        Some(states) => states,
+        None => {
+            for idx in 0..4096 {
+                section.blocks.set(idx, palette[0]);
+            }
+            return true;
+        }
+    };
+    let bits = (usize::BITS - (palette.len() - 1).leading_zeros()).max(4) as usize;
+    let padded = data_version >= PADDED_DATA_VERSION;
+    let longs = if padded {
+        (4096 + 64 / bits - 1) / (64 / bits)
+    } else {
+        4096 * bits / 64
+    };
+    if states.len() < longs {
+        warn!("Section {} has too few block states", section.y);
+        return false;
+    }
+    let states = bit::Map::from_raw(states.iter().map(|&v| v as u64).collect(), bits, padded);
+    for idx in 0..4096 {
+        let block = palette
+            .get(states.get(idx))
+            .copied()
+            .unwrap_or(block::Missing {});
+        section.blocks.set(idx, block);
+    }
+    true
+}
+
+/// The block of a palette entry, saved by a version with the data version.
+fn block_from_state(entry: &Tag, data_version: i32) -> block::Block {
+    let name = entry.get("Name").and_then(Tag::as_str).unwrap_or_default();
+    let name = name.strip_prefix("minecraft:").unwrap_or(name);
+    let mut properties: Vec<(String, String)> = entry
+        .get("Properties")
+        .and_then(Tag::as_compound)
+        .map(|properties| {
+            properties
+                .iter()
+                .filter_map(|(key, value)| Some((key.clone(), value.as_str()?.to_owned())))
+                .collect()
+        })
+        .unwrap_or_default();
+    let name = upgraded_state(name, &mut properties, release_of(data_version));
+    let properties: Vec<(&str, &str)> = properties
+        .iter()
+        .map(|(key, value)| (key.as_str(), value.as_str()))
+        .collect();
+    block::by_state(name, &properties).unwrap_or(block::Missing {})
+}
+
+/// The release a data version belongs to, snapshots count towards the
+/// release before them.
+fn release_of(data_version: i32) -> Version {
+    match data_version {
+        v if v < 1952 => Version::V1_13,
+        v if v < 2225 => Version::V1_14,
+        v if v < 2566 => Version::V1_15,
+        v if v < 2724 => Version::V1_16,
+        _ => Version::V1_17,
+    }
+}
+
+/// The newest name and properties of a block saved by an older version,
+/// undoing `versioned_state`.
+fn upgraded_state<'a>(
+    name: &'a str,
+    properties: &mut Vec<(String, String)>,
+    version: Version,
+) -> &'a str {
+    let mut name = name;
+    if version < Version::V1_14 {
+        name = match name {
+            "sign" => "oak_sign",
+            "wall_sign" => "oak_wall_sign",
+            name => name,
+        };
+    }
+    if version < Version::V1_16 {
+        if name.ends_with("_wall") {
+            for (key, value) in properties.iter_mut() {
+                if matches!(key.as_str(), "north" | "south" | "east" | "west") {
+                    *value = if value == "true" { "low" } else { "none" }.into();
+                }
+            }
+        }
+        if name == "jigsaw" {
+            for (key, value) in properties.iter_mut() {
+                if key == "facing" {
+                    *key = "orientation".into();
+                    *value = match value.as_str() {
+                        "up" => "up_north".into(),
+                        "down" => "down_south".into(),
+                        facing => format!("{}_up", facing),
+                    };
+                }
+            }
+        }
+    }
+    if version < Version::V1_17 {
+        if name == "grass_path" {
+            name = "dirt_path";
+        } else if name == "cauldron" {
+            let filled = properties
+                .iter()
+                .any(|(key, value)| key == "level" && value != "0");
+            if filled {
+                name = "water_cauldron";
+            } else {
+                properties.retain(|(key, _)| key != "level");
+            }
+        }
+    }
+    name
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn walls_before_1_16_connect_or_dont() {
+        let wall = block::CobblestoneWall {
+            up: true,
+            north: block::WallSide::Low,
+            south: block::WallSide::None,
+            east: block::WallSide::Tall,
+            west: block::WallSide::None,
+            waterlogged: false,
+        };
+        let (name, properties) = versioned_state(wall, Version::V1_15);
+        assert_eq!(name, "cobblestone_wall");
+        let get = |key| {
+            properties
+                .iter()
+                .find(|(k, _)| *k == key)
+                .map(|(_, v)| v.as_str())
+        };
+        assert_eq!(get("north"), Some("true"));
+        assert_eq!(get("south"), Some("false"));
+        assert_eq!(get("east"), Some("true"));
+        let (_, properties) = versioned_state(wall, Version::V1_16);
+        assert!(properties.contains(&("north", "low".into())));
+    }
+
+    #[test]
+    fn region_locations_point_at_chunks() {
+        let path = std::env::temp_dir().join("leafish-anvil-test.mca");
+        let saved = write_region(&path, vec![(0, vec![1; 10]), (33, vec![2; 5000])]).unwrap();
+        assert_eq!(saved, 2);
+        let file = fs::read(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        let location = |idx: usize| {
+            let mut bytes = [0; 4];
+            bytes.copy_from_slice(&file[idx * 4..idx * 4 + 4]);
+            u32::from_be_bytes(bytes)
+        };
+        assert_eq!(location(0), (2 << 8) | 1);
+        assert_eq!(location(33), (3 << 8) | 2);
+        assert_eq!(file.len(), 5 * SECTOR_SIZE);
+        assert_eq!(
+            &file[3 * SECTOR_SIZE..3 * SECTOR_SIZE + 5],
+            &[0, 0, 19, 137, 2]
+        );
+    }
+
+    #[test]
+    fn older_states_upgrade_to_the_newest() {
+        let wall = block::CobblestoneWall {
+            up: true,
+            north: block::WallSide::Low,
+            south: block::WallSide::None,
+            east: block::WallSide::Low,
+            west: block::WallSide::None,
+            waterlogged: false,
+        };
+        let (name, properties) = versioned_state(wall, Version::V1_15);
+        let mut properties: Vec<(String, String)> = properties
+            .into_iter()
+            .map(|(key, value)| (key.to_owned(), value))
+            .collect();
+        let name = upgraded_state(name, &mut properties, Version::V1_15);
+        let properties: Vec<(&str, &str)> = properties
+            .iter()
+            .map(|(key, value)| (key.as_str(), value.as_str()))
+            .collect();
+        assert_eq!(block::by_state(name, &properties), Some(wall));
+
+        assert_eq!(
+            upgraded_state("grass_path", &mut vec![], Version::V1_16),
+            "dirt_path"
+        );
+        assert_eq!(
+            upgraded_state("sign", &mut vec![], Version::V1_13),
+            "oak_sign"
+        );
+        let mut properties = vec![("level".to_owned(), "2".to_owned())];
+        assert_eq!(
+            upgraded_state("cauldron", &mut properties, Version::V1_16),
+            "water_cauldron"
+        );
+        let mut properties = vec![("level".to_owned(), "0".to_owned())];
+        assert_eq!(
+            upgraded_state("cauldron", &mut properties, Version::V1_16),
+            "cauldron"
+        );
+        assert!(properties.is_empty());
+    }
+
+    #[test]
+    fn saved_chunks_load_back() {
+        for &protocol_version in &[340, 754] {
+            // Blocks set before 1.14 queue light updates
+            let (light_updates, _receiver) = crossbeam_channel::unbounded();
+            let world = World::new(protocol_version, light_updates);
+            for (idx, pos) in [(1, 0, 2), (15, 70, 15), (3, 255, 9)].iter().enumerate() {
+                let block = [block::Stone {}, block::Glowstone {}, block::Bedrock {}][idx];
+                world.set_block(Position::new(pos.0, pos.1, pos.2), block);
+            }
+            let export = Export::new(&world);
+            let tag = export.chunk_tag(&export.chunks[0]);
+
+            let loaded = World::new(protocol_version, crossbeam_channel::unbounded().0);
+            assert_eq!(load_chunk(&loaded, &tag, 0).unwrap(), CPos(0, 0));
+            for x in 0..16 {
+                for z in 0..16 {
+                    for y in 0..256 {
+                        let pos = Position::new(x, y, z);
+                        assert_eq!(loaded.get_block(pos), world.get_block(pos));
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn section_offset_moves_sections() {
+        let world = World::new(754, crossbeam_channel::unbounded().0);
+        world.set_block(Position::new(1, 0, 1), block::Stone {});
+        world.set_block(Position::new(1, 255, 1), block::Bedrock {});
+        let export = Export::new(&world);
+        let tag = export.chunk_tag(&export.chunks[0]);
+
+        let up = World::new(754, crossbeam_channel::unbounded().0);
+        load_chunk(&up, &tag, 4).unwrap();
+        assert_eq!(up.get_block(Position::new(1, 64, 1)), block::Stone {});
+        assert_eq!(up.get_block(Position::new(1, 255, 1)), block::Air {});
+
+        let down = World::new(754, crossbeam_channel::unbounded().0);
+        load_chunk(&down, &tag, -4).unwrap();
+        assert_eq!(down.get_block(Position::new(1, 0, 1)), block::Air {});
+        assert_eq!(down.get_block(Position::new(1, 191, 1)), block::Bedrock {});
+    }
+}
@@ -1,11 +1,62 @@
-use image::Rgba;
+use std::collections::HashMap;
+
+use image::{GenericImageView, Rgba};
 use lazy_static::lazy_static;
+use leafish_protocol::nbt::Tag;
+
+/// Water is tinted with this unless the biome says otherwise.
+const DEFAULT_WATER_COLOUR: [u8; 3] = [63, 118, 228];
+
+/// Colours a biome overrides instead of looking them up in the colour maps.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BiomeColours {
+    pub grass: Option<[u8; 3]>,
+    pub foliage: Option<[u8; 3]>,
+    pub water: Option<[u8; 3]>,
+    pub sky: Option<[u8; 3]>,
+    pub fog: Option<[u8; 3]>,
+}
+
+impl BiomeColours {
+    const NONE: BiomeColours = BiomeColours {
+        grass: None,
+        foliage: None,
+        water: None,
+        sky: None,
+        fog: None,
+    };
+
+    fn from_effects(effects: &Tag) -> BiomeColours {
+        let get = |key: &str| {
+            effects
+                .get(key)
+                .and_then(Tag::as_int)
+                .map(|c| [(c >> 16) as u8, (c >> 8) as u8, c as u8])
+        };
+        BiomeColours {
+            grass: get("grass_color"),
+            foliage: get("foliage_color"),
+            water: get("water_color"),
+            sky: get("sky_color"),
+            fog: get("fog_color"),
+        }
+    }
+}
+
+/// Which of a biome's colours a block is tinted with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Tint {
+    Grass,
+    Foliage,
+    Water,
+}
 
 #[derive(Clone, Copy)]
 pub struct Biome {
     pub id: usize,
     pub temperature: i16,
     pub moisture: i16,
+    pub colours: BiomeColours,
 }
 
 impl Biome {
@@ -14,6 +65,7 @@ impl Biome {
             id,
             temperature: t,
             moisture: m * t,
+            colours: BiomeColours::NONE,
         }
     }
 
@@ -21,6 +73,23 @@ impl Biome {
         *BY_ID.get(id).unwrap_or(&INVALID)
     }
 
+    /// Reads a biome element from the biome registry of 1.16.2+ servers.
+    fn from_tag(id: usize, element: &Tag) -> Biome {
+        let get = |key: &str| element.get(key).and_then(Tag::as_float);
+        let mut biome = Biome::by_id(id);
+        biome.id = id;
+        if let Some(temperature) = get("temperature") {
+            biome.temperature = (temperature * 100.0) as i16;
+        }
+        if let Some(downfall) = get("downfall") {
+            biome.moisture = (downfall * 100.0) as i16;
+        }
+        if let Some(effects) = element.get("effects") {
+            biome.colours = BiomeColours::from_effects(effects);
+        }
+        biome
+    }
+
     pub fn get_color_index(self) -> usize {
         let t = (self.temperature as f64 / 100f64).clamp(0.0, 1.0);
         let m = (self.moisture as f64 / 100f64).clamp(0.0, 1.0);
@@ -39,6 +108,130 @@ impl Biome {
             col
         }
     }
+
+    fn lookup_color(self, img: &image::DynamicImage) -> Rgba<u8> {
+        let color_index = self.get_color_index();
+        let ix = (color_index & 0xFF).clamp(0, 255);
+        let iy = (color_index >> 8).clamp(0, 255);
+        img.get_pixel(ix as u32, iy as u32)
+    }
+
+    /// The grass colour, either the biome's own or the one from the grass
+    /// colour map.
+    pub fn grass_color(self, img: &image::DynamicImage) -> Rgba<u8> {
+        let col = match self.colours.grass {
+            Some([r, g, b]) => Rgba([r, g, b, 255]),
+            None => self.lookup_color(img),
+        };
+        self.process_color(col)
+    }
+
+    /// The foliage colour, either the biome's own or the one from the
+    /// foliage colour map.
+    pub fn foliage_color(self, img: &image::DynamicImage) -> Rgba<u8> {
+        let col = match self.colours.foliage {
+            Some([r, g, b]) => Rgba([r, g, b, 255]),
+            None => self.lookup_color(img),
+        };
+        self.process_color(col)
+    }
+
+    pub fn water_color(self) -> Rgba<u8> {
+        let [r, g, b] = self.colours.water.unwrap_or(DEFAULT_WATER_COLOUR);
+        Rgba([r, g, b, 255])
+    }
+}
+
+/// The biomes a server uses, read from the biome registry of its
+/// dimension codec. Biomes it doesn't list fall back to the vanilla table.
+#[derive(Default)]
+pub struct BiomeRegistry {
+    biomes: HashMap<usize, Biome>,
+}
+
+impl BiomeRegistry {
+    pub fn from_codec(codec: &Tag) -> BiomeRegistry {
+        let mut biomes = HashMap::new();
+        if let Some(entries) = codec
+            .get("minecraft:worldgen/biome")
+            .and_then(|registry| registry.get("value"))
+            .and_then(Tag::as_list)
+        {
+            for entry in entries {
+                let id = entry.get("id").and_then(Tag::as_int);
+                if let (Some(id), Some(element)) = (id, entry.get("element")) {
+                    let id = id as usize;
+                    biomes.insert(id, Biome::from_tag(id, element));
+                }
+            }
+        }
+        BiomeRegistry { biomes }
+    }
+
+    pub fn by_id(&self, id: usize) -> Biome {
+        self.biomes
+            .get(&id)
+            .copied()
+            .unwrap_or_else(|| Biome::by_id(id))
+    }
+}
+
+/// Number of 4x4x4 biome cells in a chunk.
+const CELL_COUNT: usize = 4 * 4 * 64;
+/// Number of 4x4x4 biome cells in a section.
+pub const SECTION_CELL_COUNT: usize = 4 * 4 * 4;
+
+/// The biome ids of a chunk, one per cell of 4x4x4 blocks.
+#[derive(Clone)]
+pub struct BiomeStorage {
+    cells: [u16; CELL_COUNT],
+}
+
+impl Default for BiomeStorage {
+    fn default() -> Self {
+        BiomeStorage {
+            cells: [0; CELL_COUNT],
+        }
+    }
+}
+
+impl BiomeStorage {
+    /// Builds the cells from the 1024 ids sent by 1.15+ servers.
+    pub fn from_3d(ids: &[i32]) -> BiomeStorage {
+        let mut storage = BiomeStorage::default();
+        for (cell, &id) in storage.cells.iter_mut().zip(ids) {
+            *cell = id as u16;
+        }
+        storage
+    }
+
+    /// Builds the cells from one id per block column, as sent before 1.15.
+    /// Each cell takes the biome of the column nearest to its centre.
+    pub fn from_2d(ids: &[i32]) -> BiomeStorage {
+        let mut storage = BiomeStorage::default();
+        for (idx, cell) in storage.cells.iter_mut().enumerate() {
+            let x = ((idx & 3) << 2) + 2;
+            let z = (((idx >> 2) & 3) << 2) + 2;
+            *cell = ids.get((z << 4) | x).map_or(0, |&id| id as u16);
+        }
+        storage
+    }
+
+    fn index(x: i32, y: i32, z: i32) -> usize {
+        (((y.clamp(0, 255) >> 2) << 4) | ((z >> 2) << 2) | (x >> 2)) as usize
+    }
+
+    /// The biome id at the given block in the chunk.
+    pub fn get(&self, x: i32, y: i32, z: i32) -> usize {
+        self.cells[Self::index(x, y, z)] as usize
+    }
+
+    /// The cells covering the section at `y`.
+    pub fn section(&self, y: usize) -> [u16; SECTION_CELL_COUNT] {
+        let mut cells = [0; SECTION_CELL_COUNT];
+        cells.copy_from_slice(&self.cells[y * SECTION_CELL_COUNT..(y + 1) * SECTION_CELL_COUNT]);
+        cells
+    }
 }
 
 macro_rules! define_biomes {
@@ -127,3 +320,75 @@ pub const MESA_PLATEAU_MOUNTAINS: Biome = Biome::new(167, 200, 0);
 
 pub const INVALID: Biome = Biome::new(255, 0, 0);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn compound(entries: Vec<(&str, Tag)>) -> Tag {
+        let mut tag = Tag::new_compound();
+        for (name, value) in entries {
+            tag.put(name, value);
+        }
+        tag
+    }
+
+    #[test]
+    fn parse_biome_registry() {
+        let effects = compound(vec![
+            ("sky_color", Tag::Int(0x78A7FF)),
+            ("fog_color", Tag::Int(0xC0D8FF)),
+            ("water_color", Tag::Int(0x3F76E4)),
+            ("grass_color", Tag::Int(0x123456)),
+        ]);
+        let element = compound(vec![
+            ("temperature", Tag::Float(0.8)),
+            ("downfall", Tag::Float(0.4)),
+            ("effects", effects),
+        ]);
+        let entry = compound(vec![
+            ("name", Tag::String("custom:painted_plains".to_owned())),
+            ("id", Tag::Int(300)),
+            ("element", element),
+        ]);
+        let codec = compound(vec![(
+            "minecraft:worldgen/biome",
+            compound(vec![
+                ("type", Tag::String("minecraft:worldgen/biome".to_owned())),
+                ("value", Tag::List(vec![entry])),
+            ]),
+        )]);
+
+        let registry = BiomeRegistry::from_codec(&codec);
+        let custom = registry.by_id(300);
+        assert_eq!(custom.id, 300);
+        assert_eq!(custom.temperature, 80);
+        assert_eq!(custom.moisture, 40);
+        assert_eq!(custom.colours.grass, Some([0x12, 0x34, 0x56]));
+        assert_eq!(custom.colours.foliage, None);
+        assert_eq!(custom.colours.sky, Some([0x78, 0xA7, 0xFF]));
+        assert_eq!(custom.colours.fog, Some([0xC0, 0xD8, 0xFF]));
+        assert_eq!(custom.water_color(), Rgba([0x3F, 0x76, 0xE4, 255]));
+
+        // Biomes the server doesn't list come from the vanilla table
+        assert_eq!(registry.by_id(DESERT.id).temperature, DESERT.temperature);
+    }
+
+    #[test]
+    fn biome_cells() {
+        let mut ids = [0; 1024];
+        // The cell holding blocks 4..8, 20..24, 8..12
+        ids[(5 << 4) | (2 << 2) | 1] = 7;
+        let storage = BiomeStorage::from_3d(&ids);
+        assert_eq!(storage.get(5, 21, 11), 7);
+        assert_eq!(storage.get(3, 21, 11), 0);
+        assert_eq!(storage.section(1)[(1 << 4) | (2 << 2) | 1], 7);
+
+        let mut columns = [1; 256];
+        columns[(6 << 4) | 2] = 4;
+        let storage = BiomeStorage::from_2d(&columns);
+        assert_eq!(storage.get(1, 0, 5), 4);
+        assert_eq!(storage.get(1, 255, 5), 4);
+        assert_eq!(storage.get(5, 100, 5), 1);
+    }
+}
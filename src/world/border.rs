@@ -0,0 +1,221 @@
+use std::time::Duration;
+
+use cgmath::Point3;
+use collision::Aabb3;
+use instant::Instant;
+
+/// The size vanilla uses when the server never sets one.
+const DEFAULT_SIZE: f64 = 59_999_968.0;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BorderStatus {
+    Growing,
+    Shrinking,
+    Stationary,
+}
+
+impl BorderStatus {
+    /// The colour the border is drawn in.
+    pub fn colour(self) -> (u8, u8, u8) {
+        match self {
+            BorderStatus::Growing => (64, 255, 128),
+            BorderStatus::Shrinking => (255, 48, 48),
+            BorderStatus::Stationary => (32, 160, 255),
+        }
+    }
+}
+
+/// The world border as last described by the server: a square around its
+/// centre whose size may be moving towards a new one.
+#[derive(Clone, Debug)]
+pub struct WorldBorder {
+    pub center_x: f64,
+    pub center_z: f64,
+    old_size: f64,
+    new_size: f64,
+    lerp_start: Instant,
+    lerp_duration: Duration,
+    /// How far from the centre nether portals may teleport players.
+    pub portal_boundary: i32,
+    /// Players get warned this many seconds before a shrinking border
+    /// reaches them.
+    pub warning_time: i32,
+    /// Players get warned when they are this close to the border.
+    pub warning_blocks: i32,
+}
+
+impl Default for WorldBorder {
+    fn default() -> Self {
+        Self {
+            center_x: 0.0,
+            center_z: 0.0,
+            old_size: DEFAULT_SIZE,
+            new_size: DEFAULT_SIZE,
+            lerp_start: Instant::now(),
+            lerp_duration: Duration::ZERO,
+            portal_boundary: 29_999_984,
+            warning_time: 15,
+            warning_blocks: 5,
+        }
+    }
+}
+
+impl WorldBorder {
+    pub fn set_size(&mut self, size: f64) {
+        self.lerp_size(size, size, 0);
+    }
+
+    /// Moves the size from `old_size` to `new_size` over `millis`
+    /// milliseconds, starting now.
+    pub fn lerp_size(&mut self, old_size: f64, new_size: f64, millis: i64) {
+        self.old_size = old_size;
+        self.new_size = new_size;
+        self.lerp_start = Instant::now();
+        self.lerp_duration = Duration::from_millis(millis.max(0) as u64);
+    }
+
+    pub fn set_center(&mut self, x: f64, z: f64) {
+        self.center_x = x;
+        self.center_z = z;
+    }
+
+    /// How far through the size change the border is at `now`, `None` once
+    /// it stopped moving.
+    fn progress(&self, now: Instant) -> Option<f64> {
+        let elapsed = now.saturating_duration_since(self.lerp_start);
+        if elapsed < self.lerp_duration {
+            Some(elapsed.as_secs_f64() / self.lerp_duration.as_secs_f64())
+        } else {
+            None
+        }
+    }
+
+    pub fn size_at(&self, now: Instant) -> f64 {
+        match self.progress(now) {
+            Some(progress) => self.old_size + (self.new_size - self.old_size) * progress,
+            None => self.new_size,
+        }
+    }
+
+    pub fn status_at(&self, now: Instant) -> BorderStatus {
+        match self.progress(now) {
+            Some(_) if self.new_size < self.old_size => BorderStatus::Shrinking,
+            Some(_) if self.new_size > self.old_size => BorderStatus::Growing,
+            _ => BorderStatus::Stationary,
+        }
+    }
+
+    /// The blocks per millisecond the border moves at while changing size.
+    fn lerp_speed(&self, now: Instant) -> f64 {
+        match self.progress(now) {
+            Some(_) => {
+                (self.new_size - self.old_size).abs() / self.lerp_duration.as_millis() as f64
+            }
+            None => 0.0,
+        }
+    }
+
+    /// The smallest and largest x and z coordinates inside the border.
+    pub fn bounds_at(&self, now: Instant) -> (f64, f64, f64, f64) {
+        let half = self.size_at(now) / 2.0;
+        (
+            self.center_x - half,
+            self.center_z - half,
+            self.center_x + half,
+            self.center_z + half,
+        )
+    }
+
+    /// The distance from the position to the closest edge, negative when the
+    /// position is outside.
+    pub fn distance_at(&self, now: Instant, x: f64, z: f64) -> f64 {
+        let (min_x, min_z, max_x, max_z) = self.bounds_at(now);
+        (x - min_x).min(max_x - x).min(z - min_z).min(max_z - z)
+    }
+
+    pub fn contains_at(&self, now: Instant, x: f64, z: f64) -> bool {
+        self.distance_at(now, x, z) > 0.0
+    }
+
+    /// How strongly a player at the position should be warned about the
+    /// border, from 0 (not at all) to 1.
+    pub fn warning_at(&self, now: Instant, x: f64, z: f64) -> f32 {
+        let distance = self.distance_at(now, x, z);
+        // A shrinking border warns as far out as it gets within the warning
+        // time
+        let moving = (self.lerp_speed(now) * self.warning_time as f64 * 1000.0)
+            .min((self.new_size - self.size_at(now)).abs());
+        let range = (self.warning_blocks as f64).max(moving);
+        if distance < range {
+            (1.0 - distance / range).min(1.0) as f32
+        } else {
+            0.0
+        }
+    }
+
+    /// Solid walls just outside each edge of the border, reaching from
+    /// `min_y` to `max_y`.
+    pub fn collision_boxes_at(&self, now: Instant, min_y: f64, max_y: f64) -> [Aabb3<f64>; 4] {
+        let (min_x, min_z, max_x, max_z) = self.bounds_at(now);
+        [
+            Aabb3::new(
+                Point3::new(min_x - 1.0, min_y, min_z - 1.0),
+                Point3::new(min_x, max_y, max_z + 1.0),
+            ),
+            Aabb3::new(
+                Point3::new(max_x, min_y, min_z - 1.0),
+                Point3::new(max_x + 1.0, max_y, max_z + 1.0),
+            ),
+            Aabb3::new(
+                Point3::new(min_x - 1.0, min_y, min_z - 1.0),
+                Point3::new(max_x + 1.0, max_y, min_z),
+            ),
+            Aabb3::new(
+                Point3::new(min_x - 1.0, min_y, max_z),
+                Point3::new(max_x + 1.0, max_y, max_z + 1.0),
+            ),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lerping_size() {
+        let mut border = WorldBorder::default();
+        border.set_center(100.0, -50.0);
+        border.lerp_size(200.0, 100.0, 10_000);
+        let start = border.lerp_start;
+
+        assert_eq!(border.size_at(start), 200.0);
+        assert_eq!(border.size_at(start + Duration::from_secs(5)), 150.0);
+        assert_eq!(border.size_at(start + Duration::from_secs(20)), 100.0);
+        assert_eq!(border.status_at(start), BorderStatus::Shrinking);
+        assert_eq!(
+            border.status_at(start + Duration::from_secs(20)),
+            BorderStatus::Stationary
+        );
+
+        let later = start + Duration::from_secs(20);
+        assert_eq!(border.bounds_at(later), (50.0, -100.0, 150.0, 0.0));
+        assert_eq!(border.distance_at(later, 60.0, -50.0), 10.0);
+        assert!(!border.contains_at(later, 151.0, -50.0));
+    }
+
+    #[test]
+    fn warning() {
+        let mut border = WorldBorder::default();
+        border.set_size(100.0);
+        let now = Instant::now();
+        assert_eq!(border.warning_at(now, 0.0, 0.0), 0.0);
+        assert_eq!(border.warning_at(now, 47.5, 0.0), 0.5);
+
+        // Shrinking by 10 blocks a second reaches much further out
+        border.warning_time = 2;
+        border.lerp_size(100.0, 0.0, 10_000);
+        let now = border.lerp_start;
+        assert!(border.warning_at(now, 35.0, 0.0) > 0.0);
+    }
+}
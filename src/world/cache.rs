@@ -0,0 +1,314 @@
+use crate::paths;
+use crate::world::anvil::{self, Export};
+use crate::world::{CPos, Chunk, World};
+use crossbeam_channel::{unbounded, Receiver, Sender};
+use flate2::read::ZlibDecoder;
+use leafish_protocol::nbt::{NamedTag, Tag};
+use leafish_protocol::protocol::{self, Serializable};
+use log::{debug, warn};
+use parking_lot::{Mutex, RwLock};
+use std::collections::HashSet;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Weak};
+use std::thread;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Chunks the server sent, kept on disk per server and dimension so the area
+/// around the player shows up right away when joining again.
+///
+/// Chunks are stored as they arrive and when they are unloaded. Cached
+/// chunks never replace ones the server sent, and are replaced by them.
+pub struct ChunkCache {
+    /// The directory of the server being played on.
+    dir: PathBuf,
+    /// The directory of the dimension the player is in.
+    dimension: RwLock<PathBuf>,
+    /// Chunks the server sent in this dimension that are still loaded.
+    received: Mutex<HashSet<CPos>>,
+    writer: Sender<(PathBuf, Export)>,
+    loader: Sender<LoadRequest>,
+}
+
+struct LoadRequest {
+    world: Weak<World>,
+    dir: PathBuf,
+    center: CPos,
+    distance: i32,
+}
+
+impl ChunkCache {
+    /// Opens the cache of the server at `address`. Once the cache of all
+    /// servers together grows past `max_size` bytes, the chunks written to
+    /// longest ago are deleted.
+    pub fn new(address: &str, protocol_version: i32, max_size: u64) -> ChunkCache {
+        let root = paths::get_cache_dir().join("chunks");
+        let dir = root
+            .join(dir_name(address))
+            .join(protocol_version.to_string());
+        ChunkCache {
+            dimension: RwLock::new(dir.join("overworld")),
+            received: Mutex::new(HashSet::new()),
+            dir,
+            writer: spawn_writer(root, max_size),
+            loader: spawn_loader(),
+        }
+    }
+
+    pub fn set_dimension(&self, name: &str) {
+        let dir = self.dir.join(dir_name(name));
+        let mut dimension = self.dimension.write();
+        if *dimension != dir {
+            *dimension = dir;
+            // Chunks left over from the last dimension don't belong here
+            self.received.lock().clear();
+        }
+    }
+
+    /// Stores the chunk the server just sent. It's written out in the
+    /// background.
+    pub fn store(&self, world: &World, position: CPos) {
+        self.received.lock().insert(position);
+        self.write(world, position);
+    }
+
+    /// Stores the chunk again before it's unloaded, in case blocks changed
+    /// since it was sent.
+    pub fn store_unloaded(&self, world: &World, position: CPos) {
+        if self.received.lock().remove(&position) {
+            self.write(world, position);
+        }
+    }
+
+    fn write(&self, world: &World, position: CPos) {
+        if let Some(export) = Export::chunk(world, position) {
+            let _ = self.writer.send((self.dimension.read().clone(), export));
+        }
+    }
+
+    /// Loads the cached chunks within `distance` of `center` that aren't
+    /// loaded yet, nearest first and in the background.
+    pub fn load_around(&self, world: &Arc<World>, center: CPos, distance: i32) {
+        let _ = self.loader.send(LoadRequest {
+            world: Arc::downgrade(world),
+            dir: self.dimension.read().clone(),
+            center,
+            distance,
+        });
+    }
+}
+
+/// Turns a server address or dimension name into a directory name.
+fn dir_name(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+fn chunk_path(dir: &Path, position: CPos) -> PathBuf {
+    dir.join(format!("c.{}.{}.nbt", position.0, position.1))
+}
+
+fn spawn_writer(root: PathBuf, max_size: u64) -> Sender<(PathBuf, Export)> {
+    let (tx, rx) = unbounded::<(PathBuf, Export)>();
+    thread::spawn(move || {
+        let mut files = vec![];
+        cached_files(&root, &mut files);
+        let mut size: u64 = files.iter().map(|file| file.1).sum();
+        while let Ok((dir, export)) = rx.recv() {
+            let chunks = match export.compressed_chunks() {
+                Ok(chunks) => chunks,
+                Err(err) => {
+                    warn!("Failed to encode chunk for the cache: {}", err);
+                    continue;
+                }
+            };
+            for (position, data) in chunks {
+                let path = chunk_path(&dir, position);
+                let old_size = fs::metadata(&path).map(|meta| meta.len()).unwrap_or(0);
+                if let Err(err) = fs::create_dir_all(&dir).and_then(|_| fs::write(&path, &data)) {
+                    warn!("Failed to cache chunk in {:?}: {}", path, err);
+                    continue;
+                }
+                size = size.saturating_sub(old_size) + data.len() as u64;
+            }
+            if size > max_size {
+                // Make some room, so not every chunk causes an eviction
+                size = evict(&root, max_size / 10 * 9);
+            }
+        }
+    });
+    tx
+}
+
+/// Collects every file below `dir`, with when it was last written and its
+/// size.
+fn cached_files(dir: &Path, files: &mut Vec<(SystemTime, u64, PathBuf)>) {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return,
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        match entry.metadata() {
+            Ok(meta) if meta.is_dir() => cached_files(&path, files),
+            Ok(meta) => files.push((meta.modified().unwrap_or(UNIX_EPOCH), meta.len(), path)),
+            Err(_) => {}
+        }
+    }
+}
+
+/// Deletes the chunks written to longest ago until at most `target` bytes
+/// are left, returning how many are.
+fn evict(root: &Path, target: u64) -> u64 {
+    let mut files = vec![];
+    cached_files(root, &mut files);
+    files.sort();
+    let mut size: u64 = files.iter().map(|file| file.1).sum();
+    for (_, len, path) in files {
+        if size <= target {
+            break;
+        }
+        if fs::remove_file(&path).is_ok() {
+            size -= len;
+        }
+    }
+    size
+}
+
+fn spawn_loader() -> Sender<LoadRequest> {
+    let (tx, rx) = unbounded::<LoadRequest>();
+    thread::spawn(move || {
+        while let Ok(mut request) = rx.recv() {
+            // Only the latest position matters
+            while let Ok(newer) = rx.try_recv() {
+                request = newer;
+            }
+            load_chunks(&rx, request);
+        }
+    });
+    tx
+}
+
+fn load_chunks(rx: &Receiver<LoadRequest>, request: LoadRequest) {
+    let LoadRequest {
+        world,
+        dir,
+        center,
+        distance,
+    } = request;
+    let world = match world.upgrade() {
+        Some(world) => world,
+        None => return,
+    };
+    let mut positions: Vec<CPos> = (-distance..=distance)
+        .flat_map(|x| (-distance..=distance).map(move |z| CPos(center.0 + x, center.1 + z)))
+        .filter(|pos| !world.is_chunk_loaded(pos.0, pos.1))
+        .collect();
+    positions.sort_by_key(|pos| (pos.0 - center.0).pow(2) + (pos.1 - center.1).pow(2));
+    for position in positions {
+        let still_there = world
+            .chunk_cache()
+            .is_some_and(|cache| *cache.dimension.read() == dir);
+        if !rx.is_empty() || !still_there {
+            // The player moved on or left the dimension
+            return;
+        }
+        match read_chunk(&world, &dir, position) {
+            Ok(Some((chunk, mask))) => anvil::insert_chunk(&world, chunk, mask, false),
+            Ok(None) => {}
+            Err(err) => {
+                debug!(
+                    "Dropping cached chunk {},{}: {}",
+                    position.0, position.1, err
+                );
+                let _ = fs::remove_file(chunk_path(&dir, position));
+            }
+        }
+    }
+}
+
+/// Reads the cached chunk at `position`, `None` if it isn't cached.
+fn read_chunk(
+    world: &World,
+    dir: &Path,
+    position: CPos,
+) -> Result<Option<(Chunk, u16)>, protocol::Error> {
+    let data = match fs::read(chunk_path(dir, position)) {
+        Ok(data) => data,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(err.into()),
+    };
+    let tag = Option::<NamedTag>::read_from(&mut ZlibDecoder::new(&data[..]))?
+        .map(|tag| tag.1)
+        .unwrap_or(Tag::End);
+    let (chunk, mask) = anvil::read_chunk(world, &tag, 0)?;
+    if chunk.position != position {
+        return Err(protocol::Error::Err(format!(
+            "found chunk {},{} instead",
+            chunk.position.0, chunk.position.1
+        )));
+    }
+    Ok(Some((chunk, mask)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shared::Position;
+    use crate::world::block;
+    use std::fs::File;
+    use std::time::Duration;
+
+    #[test]
+    fn cached_chunks_load_back_where_they_belong() {
+        let dir = std::env::temp_dir().join("leafish-chunk-cache-test");
+        let world = World::new(754, crossbeam_channel::unbounded().0);
+        world.set_block(Position::new(3, 64, 5), block::Stone {});
+        let (_, data) = Export::chunk(&world, CPos(0, 0))
+            .unwrap()
+            .compressed_chunks()
+            .unwrap()
+            .remove(0);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(chunk_path(&dir, CPos(0, 0)), &data).unwrap();
+        fs::write(chunk_path(&dir, CPos(1, 0)), &data).unwrap();
+
+        let loaded = World::new(754, crossbeam_channel::unbounded().0);
+        let (chunk, _) = read_chunk(&loaded, &dir, CPos(0, 0)).unwrap().unwrap();
+        assert_eq!(chunk.get_block(3, 64, 5), block::Stone {});
+        assert!(read_chunk(&loaded, &dir, CPos(1, 0)).is_err());
+        assert!(read_chunk(&loaded, &dir, CPos(2, 0)).unwrap().is_none());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn eviction_deletes_the_oldest_chunks() {
+        let root = std::env::temp_dir().join("leafish-chunk-cache-eviction-test");
+        let dir = root.join("server").join("overworld");
+        fs::create_dir_all(&dir).unwrap();
+        for x in 0..4 {
+            let path = chunk_path(&dir, CPos(x, 0));
+            fs::write(&path, [0; 100]).unwrap();
+            File::options()
+                .write(true)
+                .open(&path)
+                .unwrap()
+                .set_modified(UNIX_EPOCH + Duration::from_secs(1000 + x as u64))
+                .unwrap();
+        }
+
+        assert_eq!(evict(&root, 250), 200);
+        for x in 0..4 {
+            assert_eq!(chunk_path(&dir, CPos(x, 0)).exists(), x >= 2);
+        }
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -1,11 +1,12 @@
-use crate::world::biome::Biome;
 use crate::world::{biome, storage, CPos, World};
 use crate::{chunk_builder, render};
 use lazy_static::lazy_static;
 pub use leafish_blocks as block;
 use leafish_protocol::types::nibble;
 use parking_lot::RwLock;
+use std::cell::RefCell;
 use std::cmp::Ordering;
+use std::collections::HashMap;
 use std::sync::Arc;
 
 #[derive(Clone)]
@@ -46,16 +47,21 @@ impl ChunkSection {
         }
     }
 
-    pub fn capture_snapshot(&self, biomes: [u8; 16 * 16]) -> ChunkSectionSnapshot {
+    pub fn capture_snapshot(&self, biomes: &biome::BiomeStorage) -> ChunkSectionSnapshot {
         ChunkSectionSnapshot {
             y: self.y,
             blocks: self.blocks.clone(),
             block_light: self.block_light.clone(),
             sky_light: self.sky_light.clone(),
-            biomes,
+            biomes: biomes.section(self.y as usize),
         }
     }
 
+    /// The number of bytes the blocks and light of this section take up.
+    pub fn memory_size(&self) -> usize {
+        self.blocks.memory_size() + self.block_light.data.len() + self.sky_light.data.len()
+    }
+
     pub fn blocks_mut(&mut self) -> &mut storage::BlockStorage {
         &mut self.blocks
     }
@@ -66,9 +72,9 @@ impl ChunkSection {
 
     pub(crate) fn set_block(&mut self, x: i32, y: i32, z: i32, b: block::Block) -> bool {
         if self.blocks.set(((y << 8) | (z << 4) | x) as usize, b) {
+            // The old light is kept, the light engine needs it to find out
+            // what has to be darkened
             self.dirty = true;
-            self.set_sky_light(x, y, z, 0); // TODO: Do we have to set this every time?
-            self.set_block_light(x, y, z, 0);
             true
         } else {
             false
@@ -98,7 +104,8 @@ pub struct ChunkSectionSnapshot {
     pub blocks: storage::BlockStorage,
     pub block_light: nibble::Array,
     pub sky_light: nibble::Array,
-    pub biomes: [u8; 16 * 16], // TODO: Remove this by using the chunk's biome!
+    /// The chunk's biome cells covering this section.
+    pub biomes: [u16; biome::SECTION_CELL_COUNT],
 }
 
 impl ChunkSectionSnapshot {
@@ -114,8 +121,8 @@ impl ChunkSectionSnapshot {
         self.sky_light.get(((y << 8) | (z << 4) | x) as usize)
     }
 
-    pub fn get_biome(&self, x: i32, z: i32) -> biome::Biome {
-        biome::Biome::by_id(self.biomes[((z << 4) | x) as usize] as usize)
+    pub fn get_biome_id(&self, x: i32, y: i32, z: i32) -> usize {
+        self.biomes[(((y >> 2) << 4) | ((z >> 2) << 2) | (x >> 2)) as usize] as usize
     }
 }
 
@@ -125,7 +132,7 @@ lazy_static! {
         blocks: storage::BlockStorage::new(16 * 16 * 16),
         block_light: nibble::Array::new(16 * 16 * 16),
         sky_light: nibble::Array::new_def(16 * 16 * 16, 0xF),
-        biomes: [0; 16 * 16], // TODO: Verify this!
+        biomes: [0; biome::SECTION_CELL_COUNT],
     };
 }
 
@@ -133,6 +140,11 @@ lazy_static! {
 #[allow(dead_code)]
 pub struct ChunkSectionSnapshotGroup {
     sections: [Option<ChunkSectionSnapshot>; 27],
+    biomes: Arc<biome::BiomeRegistry>,
+    biome_blend: i32,
+    /// Blended biome colours already worked out for this build, keyed by
+    /// tint, block column and the 4 block tall biome cell.
+    blended: RefCell<HashMap<(biome::Tint, i32, i32, i32), (u8, u8, u8)>>,
     x: i32,
     y: i32,
     z: i32,
@@ -157,9 +169,12 @@ impl ChunkSectionSnapshotGroup {
                         } else {
                             let section = &chunk.sections[(y + yo) as usize].as_ref();
                             if let Some(section) = section {
-                                Some(section.capture_snapshot(chunk.biomes))
+                                Some(section.capture_snapshot(&chunk.biomes))
                             } else {
-                                Some(EMPTY_SECTION.clone())
+                                Some(ChunkSectionSnapshot {
+                                    biomes: chunk.biomes.section((y + yo) as usize),
+                                    ..EMPTY_SECTION.clone()
+                                })
                             }
                         }
                     } else {
@@ -171,6 +186,9 @@ impl ChunkSectionSnapshotGroup {
         }
         ChunkSectionSnapshotGroup {
             sections,
+            biomes: world.biome_registry.load_full(),
+            biome_blend: world.biome_blend() as i32,
+            blended: RefCell::new(HashMap::new()),
             x: -(expand_by as i32),
             y: -(expand_by as i32),
             z: -(expand_by as i32),
@@ -216,13 +234,51 @@ impl ChunkSectionSnapshotGroup {
         section.map_or(16, |s| s.get_sky_light(x, y, z))
     }
 
-    pub fn get_biome(&self, x: i32, z: i32) -> biome::Biome {
+    /// The radius biome colours are blended over when this group was
+    /// captured, at most 7 so it stays within the neighbouring sections.
+    pub fn biome_blend(&self) -> i32 {
+        self.biome_blend
+    }
+
+    /// The `tint` colour blended around the given position, only calling
+    /// `blend` the first time a column's biome cell is asked for.
+    pub fn blended_biome<F>(
+        &self,
+        tint: biome::Tint,
+        x: i32,
+        y: i32,
+        z: i32,
+        blend: F,
+    ) -> (u8, u8, u8)
+    where
+        F: FnOnce() -> (u8, u8, u8),
+    {
+        let key = (tint, x, y >> 2, z);
+        if let Some(colour) = self.blended.borrow().get(&key) {
+            return *colour;
+        }
+        let colour = blend();
+        self.blended.borrow_mut().insert(key, colour);
+        colour
+    }
+
+    /// The biome at the given position, `None` if its chunk isn't loaded.
+    pub fn get_biome(&self, x: i32, y: i32, z: i32) -> Option<biome::Biome> {
         let chunk_x = ChunkSectionSnapshotGroup::cmp(x & !15, 0);
         let chunk_z = ChunkSectionSnapshotGroup::cmp(z & !15, 0);
-        let section = self.sections[((chunk_x + 1) + (chunk_z + 1) * 3) as usize].as_ref();
+        let chunk_y = ChunkSectionSnapshotGroup::cmp(y & !15, 0);
+        let column = |chunk_y: i32| {
+            self.sections[((chunk_x + 1) + (chunk_z + 1) * 3 + (chunk_y + 1) * 3 * 3) as usize]
+                .as_ref()
+        };
         let x = if x < 0 { 16 + x } else { x & 15 };
         let z = if z < 0 { 16 + z } else { z & 15 };
-        section.map_or(Biome::by_id(0), |s| s.get_biome(x, z))
+        // Above and below the world the biome of the closest section is used
+        let (section, y) = match column(chunk_y) {
+            Some(section) => (Some(section), if y < 0 { 16 + y } else { y & 15 }),
+            None => (column(0), y.clamp(0, 15)),
+        };
+        section.map(|s| self.biomes.by_id(s.get_biome_id(x, y, z)))
     }
 
     #[inline]
@@ -2,6 +2,7 @@ use std::cmp::Ordering;
 use std::collections::HashMap;
 use std::hash::BuildHasherDefault;
 
+use leafish_protocol::nbt;
 use leafish_protocol::types::hash::FNVHash;
 use leafish_shared::position::Position;
 
@@ -21,12 +22,14 @@ pub struct Chunk {
 
     pub(crate) sections: [Option<ChunkSection>; 16],
     pub(crate) sections_rendered_on: [u32; 16],
-    pub(crate) biomes: [u8; 16 * 16],
+    pub(crate) biomes: biome::BiomeStorage,
 
     pub(crate) heightmap: [u8; 16 * 16],
     pub(crate) heightmap_dirty: bool,
 
     pub(crate) block_entities: HashMap<Position, Entity, BuildHasherDefault<FNVHash>>,
+    /// The NBT the server sent for block entities, kept for saving the world.
+    pub(crate) block_entity_data: HashMap<Position, nbt::Tag, BuildHasherDefault<FNVHash>>,
 }
 
 impl Chunk {
@@ -38,10 +41,11 @@ impl Chunk {
                 None, None,
             ],
             sections_rendered_on: [0; 16],
-            biomes: [0; 16 * 16],
+            biomes: Default::default(),
             heightmap: [0; 16 * 16],
             heightmap_dirty: true,
             block_entities: HashMap::with_hasher(BuildHasherDefault::default()),
+            block_entity_data: HashMap::with_hasher(BuildHasherDefault::default()),
         }
     }
 
@@ -136,8 +140,8 @@ impl Chunk {
             if light == 0 {
                 return;
             }
-            let fill_sky = self.sections.iter().skip(s_idx).all(|v| v.is_none());
-            self.sections[s_idx] = Some(ChunkSection::new(s_idx as u8, fill_sky));
+            // Missing sections report full sky light, keep it that way
+            self.sections[s_idx] = Some(ChunkSection::new(s_idx as u8, true));
         }
         if let Some(sec) = self.sections[s_idx].as_mut() {
             sec.set_block_light(x, y & 0xF, z, light)
@@ -165,18 +169,16 @@ impl Chunk {
             if light == 15 {
                 return;
             }
-            let fill_sky = self.sections.iter().skip(s_idx).all(|v| v.is_none());
-            self.sections[s_idx] = Some(ChunkSection::new(s_idx as u8, fill_sky));
+            // Missing sections report full sky light, keep it that way
+            self.sections[s_idx] = Some(ChunkSection::new(s_idx as u8, true));
         }
         if let Some(sec) = self.sections[s_idx].as_mut() {
             sec.set_sky_light(x, y & 0xF, z, light)
         }
     }
 
-    // TODO: make use of "get_biome"
-    #[allow(dead_code)]
-    fn get_biome(&self, x: i32, z: i32) -> biome::Biome {
-        biome::Biome::by_id(self.biomes[((z << 4) | x) as usize] as usize)
+    pub(crate) fn get_biome(&self, x: i32, y: i32, z: i32) -> usize {
+        self.biomes.get(x, y, z)
     }
 
     pub fn capture_snapshot(&self) -> ChunkSnapshot {
@@ -187,13 +189,13 @@ impl Chunk {
         for section in self.sections.iter().enumerate() {
             if section.1.is_some() {
                 snapshot_sections[section.0] =
-                    Some(section.1.as_ref().unwrap().capture_snapshot(self.biomes));
+                    Some(section.1.as_ref().unwrap().capture_snapshot(&self.biomes));
             }
         }
         ChunkSnapshot {
             position: self.position,
             sections: snapshot_sections,
-            biomes: self.biomes,
+            biomes: self.biomes.clone(),
             heightmap: self.heightmap,
         }
     }
@@ -202,6 +204,6 @@ impl Chunk {
 pub struct ChunkSnapshot {
     pub position: CPos,
     pub sections: [Option<ChunkSectionSnapshot>; 16],
-    pub biomes: [u8; 16 * 16],
+    pub biomes: biome::BiomeStorage,
     pub heightmap: [u8; 16 * 16],
 }
@@ -0,0 +1,271 @@
+use std::collections::HashMap;
+
+use leafish_protocol::nbt::Tag;
+
+use crate::world::biome::BiomeColours;
+
+/// Which set of vanilla sky, fog and cloud effects a dimension uses.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DimensionEffects {
+    Overworld,
+    Nether,
+    End,
+}
+
+impl DimensionEffects {
+    pub fn from_name(name: &str) -> Self {
+        match name {
+            "minecraft:the_nether" => Self::Nether,
+            "minecraft:the_end" => Self::End,
+            _ => Self::Overworld,
+        }
+    }
+}
+
+/// The properties of a dimension, either one of the vanilla ones or as
+/// described by the dimension codec of 1.16+ servers.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DimensionType {
+    pub has_skylight: bool,
+    /// How bright blocks without any light are, lifting every light level
+    /// towards full brightness.
+    pub ambient_light: f32,
+    /// The time of day is frozen at this value if set.
+    pub fixed_time: Option<i64>,
+    pub min_y: i32,
+    pub height: i32,
+    pub effects: DimensionEffects,
+}
+
+impl Default for DimensionType {
+    fn default() -> Self {
+        Self::overworld()
+    }
+}
+
+impl DimensionType {
+    pub fn overworld() -> Self {
+        Self {
+            has_skylight: true,
+            ambient_light: 0.0,
+            fixed_time: None,
+            min_y: 0,
+            height: 256,
+            effects: DimensionEffects::Overworld,
+        }
+    }
+
+    pub fn nether() -> Self {
+        Self {
+            has_skylight: false,
+            ambient_light: 0.1,
+            fixed_time: Some(18000),
+            effects: DimensionEffects::Nether,
+            ..Self::overworld()
+        }
+    }
+
+    pub fn end() -> Self {
+        Self {
+            has_skylight: false,
+            fixed_time: Some(6000),
+            effects: DimensionEffects::End,
+            ..Self::overworld()
+        }
+    }
+
+    pub fn from_index(index: i32) -> Self {
+        match index {
+            -1 => Self::nether(),
+            1 => Self::end(),
+            _ => Self::overworld(),
+        }
+    }
+
+    /// The vanilla dimension type with the given name. Unknown dimensions
+    /// behave like the overworld.
+    pub fn from_name(name: &str) -> Self {
+        match DimensionEffects::from_name(name) {
+            DimensionEffects::Overworld => Self::overworld(),
+            DimensionEffects::Nether => Self::nether(),
+            DimensionEffects::End => Self::end(),
+        }
+    }
+
+    /// Reads a dimension type element, using the vanilla dimension called
+    /// `name` for anything the element leaves out.
+    pub fn from_tag(tag: &Tag, name: &str) -> Self {
+        let mut dimension = Self::from_name(name);
+        let get_bool = |key: &str| tag.get(key).and_then(Tag::as_byte).map(|v| v != 0);
+        if let Some(has_skylight) = get_bool("has_skylight") {
+            dimension.has_skylight = has_skylight;
+        }
+        if let Some(ambient_light) = tag.get("ambient_light").and_then(Tag::as_float) {
+            dimension.ambient_light = ambient_light;
+        }
+        // Only dimensions with a frozen time of day list it
+        dimension.fixed_time = tag.get("fixed_time").and_then(Tag::as_long);
+        if let Some(min_y) = tag.get("min_y").and_then(Tag::as_int) {
+            dimension.min_y = min_y;
+        }
+        if let Some(height) = tag.get("height").and_then(Tag::as_int) {
+            dimension.height = height;
+        }
+        if let Some(effects) = tag.get("effects").and_then(Tag::as_str) {
+            dimension.effects = DimensionEffects::from_name(effects);
+        }
+        dimension
+    }
+
+    /// Reads all dimension types from a dimension codec, keyed by name.
+    ///
+    /// 1.16.2+ keeps them in a `minecraft:dimension_type` registry, while
+    /// 1.16 and 1.16.1 list them directly under `dimension`.
+    pub fn parse_codec(codec: &Tag) -> HashMap<String, DimensionType> {
+        let mut types = HashMap::new();
+        if let Some(entries) = codec
+            .get("minecraft:dimension_type")
+            .and_then(|registry| registry.get("value"))
+            .and_then(Tag::as_list)
+        {
+            for entry in entries {
+                let name = entry.get("name").and_then(Tag::as_str);
+                if let (Some(name), Some(element)) = (name, entry.get("element")) {
+                    types.insert(name.to_owned(), Self::from_tag(element, name));
+                }
+            }
+        } else if let Some(entries) = codec.get("dimension").and_then(Tag::as_list) {
+            for entry in entries {
+                if let Some(name) = entry.get("name").and_then(Tag::as_str) {
+                    types.insert(name.to_owned(), Self::from_tag(entry, name));
+                }
+            }
+        }
+        types
+    }
+
+    pub fn has_sky_light(&self) -> bool {
+        self.has_skylight
+    }
+
+    /// The block range the world has data for, limited to what chunks can
+    /// store.
+    pub fn y_range(&self) -> std::ops::Range<i32> {
+        self.min_y.max(0)..(self.min_y + self.height).min(256)
+    }
+
+    /// The height clouds are drawn at, only the overworld effects have
+    /// them.
+    pub fn cloud_height(&self) -> Option<f32> {
+        match self.effects {
+            DimensionEffects::Overworld => Some(128.0),
+            DimensionEffects::Nether | DimensionEffects::End => None,
+        }
+    }
+
+    /// The colour the world is cleared to, `sky_offset` being how bright the
+    /// sky is at the current time of day and `biome` the colours of the
+    /// biome the camera is in. Like the fog, it takes the biome's fog colour,
+    /// falling back to its sky colour for biomes without one.
+    pub fn clear_colour(&self, sky_offset: f32, biome: &BiomeColours) -> (f32, f32, f32) {
+        match self.effects {
+            DimensionEffects::Overworld => {
+                let time_offset = sky_offset * 0.9;
+                let [r, g, b] = biome.fog.or(biome.sky).unwrap_or([122, 165, 247]);
+                (
+                    (r as f32 / 255.0) * time_offset,
+                    (g as f32 / 255.0) * time_offset,
+                    (b as f32 / 255.0) * time_offset,
+                )
+            }
+            DimensionEffects::Nether => (51.0 / 255.0, 8.0 / 255.0, 8.0 / 255.0),
+            DimensionEffects::End => (20.0 / 255.0, 16.0 / 255.0, 28.0 / 255.0),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn compound(entries: Vec<(&str, Tag)>) -> Tag {
+        let mut tag = Tag::new_compound();
+        for (name, value) in entries {
+            tag.put(name, value);
+        }
+        tag
+    }
+
+    #[test]
+    fn parse_registry_codec() {
+        let custom = compound(vec![
+            ("has_skylight", Tag::Byte(1)),
+            ("has_ceiling", Tag::Byte(0)),
+            ("ambient_light", Tag::Float(0.5)),
+            ("fixed_time", Tag::Long(1000)),
+            ("min_y", Tag::Int(0)),
+            ("height", Tag::Int(128)),
+            ("effects", Tag::String("minecraft:the_end".to_owned())),
+        ]);
+        let entry = compound(vec![
+            ("name", Tag::String("custom:sky_end".to_owned())),
+            ("id", Tag::Int(4)),
+            ("element", custom),
+        ]);
+        let codec = compound(vec![(
+            "minecraft:dimension_type",
+            compound(vec![
+                ("type", Tag::String("minecraft:dimension_type".to_owned())),
+                ("value", Tag::List(vec![entry])),
+            ]),
+        )]);
+
+        let types = DimensionType::parse_codec(&codec);
+        let custom = &types["custom:sky_end"];
+        assert!(custom.has_sky_light());
+        assert_eq!(custom.ambient_light, 0.5);
+        assert_eq!(custom.fixed_time, Some(1000));
+        assert_eq!(custom.y_range(), 0..128);
+        assert_eq!(custom.effects, DimensionEffects::End);
+        assert_eq!(custom.cloud_height(), None);
+    }
+
+    #[test]
+    fn clear_to_the_biome_fog() {
+        let overworld = DimensionType::overworld();
+        let mut colours = BiomeColours {
+            grass: None,
+            foliage: None,
+            water: None,
+            sky: Some([255, 0, 0]),
+            fog: None,
+        };
+        assert_eq!(overworld.clear_colour(1.0, &colours), (0.9, 0.0, 0.0));
+        colours.fog = Some([0, 255, 0]);
+        assert_eq!(overworld.clear_colour(1.0, &colours), (0.0, 0.9, 0.0));
+        assert_eq!(
+            DimensionType::nether().clear_colour(1.0, &colours),
+            (51.0 / 255.0, 8.0 / 255.0, 8.0 / 255.0)
+        );
+    }
+
+    #[test]
+    fn parse_legacy_codec() {
+        let nether = compound(vec![
+            ("name", Tag::String("minecraft:the_nether".to_owned())),
+            ("has_skylight", Tag::Byte(0)),
+            ("ambient_light", Tag::Float(0.1)),
+            ("fixed_time", Tag::Long(18000)),
+        ]);
+        let overworld = compound(vec![
+            ("name", Tag::String("minecraft:overworld".to_owned())),
+            ("has_skylight", Tag::Byte(1)),
+        ]);
+        let codec = compound(vec![("dimension", Tag::List(vec![nether, overworld]))]);
+
+        let types = DimensionType::parse_codec(&codec);
+        assert_eq!(types["minecraft:the_nether"], DimensionType::nether());
+        assert_eq!(types["minecraft:overworld"], DimensionType::overworld());
+        assert_eq!(types["minecraft:overworld"].cloud_height(), Some(128.0));
+    }
+}
@@ -1,8 +1,12 @@
+use std::cmp;
+use std::collections::{BTreeMap, HashSet, VecDeque};
 use std::io::Cursor;
+use std::ops::Range;
 
+use leafish_shared::direction::Direction;
 use leafish_shared::position::Position;
 
-use crate::world::World;
+use crate::world::{block, CPos, Chunk};
 
 pub struct LightData {
     pub arrays: Cursor<Vec<u8>>,
@@ -16,27 +20,184 @@ pub enum LightType {
     Sky,
 }
 
-// TODO: make use of "get_light" and "set_light"
-impl LightType {
-    #[allow(dead_code)]
-    pub(crate) fn get_light(self, world: &World, pos: Position) -> u8 {
-        match self {
-            LightType::Block => world.get_block_light(pos),
-            LightType::Sky => world.get_sky_light(pos),
+pub struct LightUpdate {
+    pub(crate) ty: LightType,
+    pub(crate) pos: Position,
+}
+
+const DIRECTIONS: [Direction; 6] = [
+    Direction::Down,
+    Direction::Up,
+    Direction::North,
+    Direction::South,
+    Direction::West,
+    Direction::East,
+];
+
+/// Breadth first light propagation over a set of loaded chunks.
+///
+/// Lowered light is first cleared outwards from the changed positions,
+/// then everything bordering the cleared area (and any new or remaining
+/// light sources) is spread out again.
+pub(crate) struct LightEngine<'a> {
+    chunks: &'a mut BTreeMap<CPos, Chunk>,
+    /// The heights the dimension has blocks at.
+    y_range: Range<i32>,
+    /// Sections whose light changed, these have to be rebuilt.
+    pub(crate) dirty: HashSet<(i32, i32, i32)>,
+}
+
+impl<'a> LightEngine<'a> {
+    pub(crate) fn new(chunks: &'a mut BTreeMap<CPos, Chunk>, y_range: Range<i32>) -> Self {
+        Self {
+            chunks,
+            y_range,
+            dirty: HashSet::new(),
         }
     }
-    #[allow(dead_code)]
-    pub(crate) fn set_light(self, world: &World, pos: Position, light: u8) {
-        match self {
-            LightType::Block => world.set_block_light(pos, light),
-            LightType::Sky => world.set_sky_light(pos, light),
+
+    fn is_loaded(&self, pos: Position) -> bool {
+        self.y_range.contains(&pos.y) && self.chunks.contains_key(&CPos(pos.x >> 4, pos.z >> 4))
+    }
+
+    fn get_material(&self, pos: Position) -> block::Material {
+        match self.chunks.get(&CPos(pos.x >> 4, pos.z >> 4)) {
+            Some(chunk) => chunk.get_block(pos.x & 0xF, pos.y, pos.z & 0xF),
+            None => block::Missing {},
         }
+        .get_material()
     }
-}
 
-// TODO: make use of "ty: LightType" and "pos: Position"
-#[allow(dead_code)]
-pub struct LightUpdate {
-    pub(crate) ty: LightType,
-    pub(crate) pos: Position,
+    fn get_light(&self, ty: LightType, pos: Position) -> u8 {
+        if pos.y >= self.y_range.end {
+            return if ty == LightType::Sky { 15 } else { 0 };
+        } else if pos.y < self.y_range.start {
+            return 0;
+        }
+        match (ty, self.chunks.get(&CPos(pos.x >> 4, pos.z >> 4))) {
+            (LightType::Block, Some(chunk)) => {
+                chunk.get_block_light(pos.x & 0xF, pos.y, pos.z & 0xF)
+            }
+            (LightType::Sky, Some(chunk)) => chunk.get_sky_light(pos.x & 0xF, pos.y, pos.z & 0xF),
+            // Don't pull light in from chunks we know nothing about
+            (_, None) => 0,
+        }
+    }
+
+    fn set_light(&mut self, ty: LightType, pos: Position, light: u8) {
+        if let Some(chunk) = self.chunks.get_mut(&CPos(pos.x >> 4, pos.z >> 4)) {
+            match ty {
+                LightType::Block => chunk.set_block_light(pos.x & 0xF, pos.y, pos.z & 0xF, light),
+                LightType::Sky => chunk.set_sky_light(pos.x & 0xF, pos.y, pos.z & 0xF, light),
+            }
+        }
+        // Neighbouring sections sample this block for smooth lighting
+        for sy in ((pos.y - 1) >> 4).max(0)..=((pos.y + 1) >> 4).min(15) {
+            for sz in (pos.z - 1) >> 4..=(pos.z + 1) >> 4 {
+                for sx in (pos.x - 1) >> 4..=(pos.x + 1) >> 4 {
+                    self.dirty.insert((sx, sy, sz));
+                }
+            }
+        }
+    }
+
+    /// The light a block made of `material` receives from a neighbour with
+    /// level `light`, travelling in direction `dir`.
+    fn spread(ty: LightType, dir: Direction, light: u8, material: &block::Material) -> u8 {
+        // Sky light doesn't decrease when going down at full brightness
+        if ty == LightType::Sky
+            && dir == Direction::Down
+            && light == 15
+            && material.absorbed_light == 0
+        {
+            15
+        } else {
+            light.saturating_sub(cmp::max(1, material.absorbed_light))
+        }
+    }
+
+    /// The light level `pos` should have given its neighbours.
+    fn expected_light(&self, ty: LightType, pos: Position) -> u8 {
+        let material = self.get_material(pos);
+        let mut best = if ty == LightType::Block {
+            material.emitted_light
+        } else {
+            0
+        };
+        for dir in DIRECTIONS {
+            let light = self.get_light(ty, pos.shift(dir.opposite()));
+            best = cmp::max(best, Self::spread(ty, dir, light, &material));
+        }
+        best
+    }
+
+    /// Recomputes the light around all the given positions.
+    pub(crate) fn update(&mut self, ty: LightType, positions: impl IntoIterator<Item = Position>) {
+        let mut darken = VecDeque::new();
+        let mut brighten = VecDeque::new();
+
+        for pos in positions {
+            if !self.is_loaded(pos) {
+                continue;
+            }
+            let current = self.get_light(ty, pos);
+            let expected = self.expected_light(ty, pos);
+            if expected < current {
+                self.set_light(ty, pos, 0);
+                darken.push_back((pos, current));
+            } else if expected > current {
+                self.set_light(ty, pos, expected);
+                brighten.push_back(pos);
+            }
+        }
+
+        // Remove all light that came from the darkened blocks, remembering
+        // the blocks that are lit from somewhere else.
+        while let Some((pos, light)) = darken.pop_front() {
+            for dir in DIRECTIONS {
+                let to = pos.shift(dir);
+                if !self.is_loaded(to) {
+                    continue;
+                }
+                let other = self.get_light(ty, to);
+                if other == 0 {
+                    continue;
+                }
+                let lit_by_pos = other < light
+                    || (ty == LightType::Sky && dir == Direction::Down && light == 15);
+                if lit_by_pos {
+                    self.set_light(ty, to, 0);
+                    darken.push_back((to, other));
+                } else {
+                    brighten.push_back(to);
+                }
+            }
+            if ty == LightType::Block {
+                let emitted = self.get_material(pos).emitted_light;
+                if emitted > 0 {
+                    self.set_light(ty, pos, emitted);
+                    brighten.push_back(pos);
+                }
+            }
+        }
+
+        while let Some(pos) = brighten.pop_front() {
+            let light = self.get_light(ty, pos);
+            if light <= 1 {
+                continue;
+            }
+            for dir in DIRECTIONS {
+                let to = pos.shift(dir);
+                if !self.is_loaded(to) {
+                    continue;
+                }
+                let material = self.get_material(to);
+                let spread = Self::spread(ty, dir, light, &material);
+                if spread > self.get_light(ty, to) {
+                    self.set_light(ty, to, spread);
+                    brighten.push_back(to);
+                }
+            }
+        }
+    }
 }
@@ -12,12 +12,12 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use arc_swap::ArcSwap;
+use arc_swap::{ArcSwap, ArcSwapOption};
 use bevy_ecs::entity::Entity;
 use bevy_ecs::system::{Command, Commands};
 pub use leafish_blocks as block;
 use leafish_protocol::format::Component;
-use leafish_protocol::nbt::NamedTag;
+use leafish_protocol::nbt::{self, NamedTag};
 
 use crate::shared::Position;
 use crate::{chunk_builder, ecs, format, render};
@@ -35,20 +35,36 @@ use leafish_protocol::types::nibble;
 use leafish_shared::direction::Direction;
 use log::warn;
 use parking_lot::RwLock;
-use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
 use std::io::{BufRead, Cursor, Read};
 use std::sync::Arc;
 
-pub use self::{chunk::*, lighting::*};
+pub use self::{border::*, chunk::*, dimension::*, lighting::*};
 use crate::entity::block_entity::sign::SignInfo;
 use leafish_protocol::protocol::{Serializable, VarInt};
-use std::sync::atomic::Ordering;
+use std::sync::atomic::{AtomicU8, Ordering};
 
+pub mod anvil;
 pub mod biome;
+mod border;
+pub mod cache;
 mod chunk;
+mod dimension;
 mod lighting;
 mod storage;
 
+/// The largest biome blend radius, blending further would reach past the
+/// neighbouring sections a chunk section is built with.
+pub const MAX_BIOME_BLEND: u8 = 7;
+
+/// The range of render distances, in chunks, that can be asked of a server.
+pub const MIN_RENDER_DISTANCE: u8 = 2;
+pub const MAX_RENDER_DISTANCE: u8 = 32;
+
+/// How many queued light updates are applied per write lock on the chunks,
+/// so chunk loading and meshing get a turn between batches.
+const LIGHT_UPDATES_PER_LOCK: usize = 256;
+
 #[derive(Clone, Debug)]
 pub enum BlockEntityAction {
     Create(Position),
@@ -62,6 +78,11 @@ pub enum BlockEntityAction {
             format::Component,
         )>,
     ),
+    /// A block action, its type and parameter, for the block at the
+    /// position.
+    BlockAction(Position, u8, u8),
+    /// New NBT data for the block entity at the position.
+    UpdateData(Box<(Position, NamedTag)>),
 }
 
 pub struct World {
@@ -78,7 +99,26 @@ pub struct World {
     pub modded_block_ids: ArcSwap<HashMap<usize, String>>,
     pub id_map: Arc<block::VanillaIDMap>,
 
-    pub dimension: ArcSwap<Dimension>,
+    pub dimension: ArcSwap<DimensionType>,
+    /// Dimension types announced by the server's dimension codec.
+    dimension_types: RwLock<HashMap<String, DimensionType>>,
+    pub biome_registry: ArcSwap<biome::BiomeRegistry>,
+    /// How many blocks around each block biome colours are averaged over.
+    biome_blend: AtomicU8,
+    pub world_border: RwLock<WorldBorder>,
+    /// The chunk the server is centering the loaded area around.
+    view_center: RwLock<Option<CPos>>,
+    chunk_cache: ArcSwapOption<cache::ChunkCache>,
+}
+
+/// How much memory the loaded chunks take up.
+#[derive(Clone, Copy, Default)]
+pub struct MemoryUsage {
+    pub chunks: usize,
+    /// Bytes used by block and light data.
+    pub chunk_bytes: usize,
+    /// Bytes of vertex data uploaded for rendering.
+    pub render_bytes: usize,
 }
 
 impl World {
@@ -94,6 +134,12 @@ impl World {
             render_list: Arc::new(Default::default()),
             block_entity_actions: unbounded(),
             dimension: ArcSwap::new(Arc::new(Default::default())),
+            dimension_types: RwLock::new(HashMap::new()),
+            biome_registry: ArcSwap::new(Arc::new(Default::default())),
+            biome_blend: AtomicU8::new(1),
+            world_border: RwLock::new(Default::default()),
+            view_center: RwLock::new(None),
+            chunk_cache: ArcSwapOption::new(None),
         }
     }
 
@@ -160,6 +206,10 @@ impl World {
                     }
                 }
             }
+        } else if self.protocol_version < 477 {
+            // Servers only send light updates since 1.14
+            self.update_light(pos, LightType::Block);
+            self.update_light(pos, LightType::Sky);
         }
     }
 
@@ -203,6 +253,30 @@ impl World {
         }
     }
 
+    pub fn get_biome(&self, pos: Position) -> biome::Biome {
+        let biome = match self.chunks.read().get(&CPos(pos.x >> 4, pos.z >> 4)) {
+            Some(chunk) => chunk.get_biome(pos.x & 0xF, pos.y, pos.z & 0xF),
+            None => 0,
+        };
+        self.biome_registry.load().by_id(biome)
+    }
+
+    pub fn biome_blend(&self) -> u8 {
+        self.biome_blend.load(Ordering::Relaxed)
+    }
+
+    /// Changes the biome blend radius, rebuilding every section if it
+    /// differs from the current one.
+    pub fn set_biome_blend(&self, radius: u8) {
+        if self.biome_blend.swap(radius, Ordering::Relaxed) != radius {
+            for chunk in self.chunks.write().values_mut() {
+                for section in chunk.sections.iter_mut().flatten() {
+                    section.dirty = true;
+                }
+            }
+        }
+    }
+
     pub(crate) fn set_block_light(&self, pos: Position, light: u8) {
         let cpos = CPos(pos.x >> 4, pos.z >> 4);
         let mut chunks = self.chunks.write();
@@ -235,6 +309,13 @@ impl World {
         self.light_updates.send(LightUpdate { ty, pos }).unwrap();
     }
 
+    pub fn get_block_entity(&self, pos: Position) -> Option<Entity> {
+        self.chunks
+            .read()
+            .get(&CPos(pos.x >> 4, pos.z >> 4))
+            .and_then(|chunk| chunk.block_entities.get(&pos).copied())
+    }
+
     pub fn add_block_entity_action(&self, action: BlockEntityAction) {
         self.block_entity_actions.0.send(action).unwrap();
     }
@@ -269,6 +350,15 @@ impl World {
                         }
                     }
                 }
+                BlockEntityAction::BlockAction(pos, action, param) => {
+                    block_entity::on_block_action(cmds, self, pos, action, param);
+                }
+                BlockEntityAction::UpdateData(bx) => {
+                    let (pos, nbt) = *bx;
+                    if let Some(entity) = self.get_block_entity(pos) {
+                        cmds.add(block_entity::UpdateDataCmd { entity, nbt });
+                    }
+                }
                 BlockEntityAction::UpdateSignText(bx) => {
                     let (pos, line1, line2, line3, line4) = *bx;
                     if let Some(chunk) = self.chunks.write().get(&CPos(pos.x >> 4, pos.z >> 4)) {
@@ -281,60 +371,28 @@ impl World {
         }
     }
 
-    // TODO: make use of "do_light_update"
-    #[allow(dead_code)]
-    pub(crate) fn do_light_update(&self, update: LightUpdate) {
-        use std::cmp;
-        if update.pos.y < 0
-            || update.pos.y > 255
-            || !self.is_chunk_loaded(update.pos.x >> 4, update.pos.z >> 4)
-        {
-            return;
-        }
-
-        let block = self.get_block(update.pos).get_material();
-        // Find the brightest source of light nearby
-        let mut best = update.ty.get_light(self, update.pos);
-        let old = best;
-        for dir in Direction::all() {
-            let light = update.ty.get_light(self, update.pos.shift(dir));
-            if light > best {
-                best = light;
+    /// Applies a batch of queued light updates, marking the sections whose
+    /// light changed as dirty.
+    pub(crate) fn do_light_updates(&self, updates: Vec<LightUpdate>) {
+        let (block_updates, sky_updates): (Vec<_>, Vec<_>) = updates
+            .into_iter()
+            .partition(|update| update.ty == LightType::Block);
+        let dimension = self.dimension.load();
+        let mut dirty = HashSet::new();
+        let mut apply = |ty, updates: &[LightUpdate]| {
+            for batch in updates.chunks(LIGHT_UPDATES_PER_LOCK) {
+                let mut chunks = self.chunks.write();
+                let mut engine = LightEngine::new(&mut chunks, dimension.y_range());
+                engine.update(ty, batch.iter().map(|update| update.pos));
+                dirty.extend(engine.dirty);
             }
+        };
+        apply(LightType::Block, &block_updates);
+        if dimension.has_sky_light() {
+            apply(LightType::Sky, &sky_updates);
         }
-        best = best.saturating_sub(cmp::max(1, block.absorbed_light));
-        // If the light from the block itself is brighter than the light passing through
-        // it use that.
-        if update.ty == LightType::Block && block.emitted_light != 0 {
-            best = cmp::max(best, block.emitted_light);
-        }
-        // Sky light doesn't decrease when going down at full brightness
-        if update.ty == LightType::Sky
-            && block.absorbed_light == 0
-            && update.ty.get_light(self, update.pos.shift(Direction::Up)) == 15
-        {
-            best = 15;
-        }
-
-        // Nothing to do, we are already at the right value
-        if best == old {
-            return;
-        }
-        // Use our new light value
-        update.ty.set_light(self, update.pos, best);
-        // Flag surrounding chunks as dirty
-        for yy in -1..2 {
-            for zz in -1..2 {
-                for xx in -1..2 {
-                    let bp = update.pos + (xx, yy, zz);
-                    self.set_dirty(bp.x >> 4, bp.y >> 4, bp.z >> 4);
-                }
-            }
-        }
-
-        // Update surrounding blocks
-        for dir in Direction::all() {
-            self.update_light(update.pos.shift(dir), update.ty);
+        for (x, y, z) in dirty {
+            self.set_dirty(x, y, z);
         }
     }
 
@@ -810,10 +868,13 @@ impl World {
         if sec.is_none() {
             return None;
         }
-        return Some(sec.as_ref().unwrap().capture_snapshot(chunk.biomes));
+        return Some(sec.as_ref().unwrap().capture_snapshot(&chunk.biomes));
     }
 
     pub fn unload_chunk(&self, x: i32, z: i32, m: &mut ecs::Manager) {
+        if let Some(cache) = self.chunk_cache() {
+            cache.store_unloaded(self, CPos(x, z));
+        }
         if let Some(chunk) = self.chunks.write().remove(&CPos(x, z)) {
             for entity in chunk.block_entities.values() {
                 m.world.despawn(*entity);
@@ -821,6 +882,70 @@ impl World {
         }
     }
 
+    /// Keeps the NBT of the block entity at the position, or forgets it.
+    pub fn set_block_entity_data(&self, pos: Position, data: Option<nbt::Tag>) {
+        let cpos = CPos(pos.x >> 4, pos.z >> 4);
+        if let Some(chunk) = self.chunks.write().get_mut(&cpos) {
+            match data {
+                Some(data) => chunk.block_entity_data.insert(pos, data),
+                None => chunk.block_entity_data.remove(&pos),
+            };
+        }
+    }
+
+    pub fn chunk_cache(&self) -> Option<Arc<cache::ChunkCache>> {
+        self.chunk_cache.load_full()
+    }
+
+    pub fn set_chunk_cache(&self, cache: Option<Arc<cache::ChunkCache>>) {
+        self.chunk_cache.store(cache);
+    }
+
+    pub fn view_center(&self) -> Option<CPos> {
+        *self.view_center.read()
+    }
+
+    pub fn set_view_center(&self, x: i32, z: i32) {
+        *self.view_center.write() = Some(CPos(x, z));
+    }
+
+    /// Unloads every chunk further than `distance` chunks away from the
+    /// center, returning how many were unloaded.
+    pub fn unload_distant_chunks(
+        &self,
+        center: CPos,
+        distance: i32,
+        m: &mut ecs::Manager,
+    ) -> usize {
+        let distant: Vec<CPos> = self
+            .chunks
+            .read()
+            .keys()
+            .filter(|pos| (pos.0 - center.0).abs().max((pos.1 - center.1).abs()) > distance)
+            .copied()
+            .collect();
+        for pos in &distant {
+            self.unload_chunk(pos.0, pos.1, m);
+        }
+        distant.len()
+    }
+
+    pub fn memory_usage(&self) -> MemoryUsage {
+        let chunks = self.chunks.read();
+        let mut usage = MemoryUsage {
+            chunks: chunks.len(),
+            ..Default::default()
+        };
+        for section in chunks
+            .values()
+            .flat_map(|chunk| chunk.sections.iter().flatten())
+        {
+            usage.chunk_bytes += section.memory_size();
+            usage.render_bytes += section.render_buffer.read().memory_size();
+        }
+        usage
+    }
+
     pub fn load_chunk(
         &self,
         x: i32,
@@ -828,6 +953,7 @@ impl World {
         new: bool,
         skylight: bool,
         read_biomes: bool,
+        biomes_3d: Option<&[i32]>,
         mask: u16,
         mask_add: u16,
         data: &mut Cursor<Vec<u8>>,
@@ -889,15 +1015,32 @@ impl World {
 
             if new && read_biomes {
                 // read biomes is always true (as param) except for load_chunk_19
-                data.read_exact(&mut chunk.biomes)?;
+                let mut biomes = [0; 16 * 16];
+                for biome in biomes.iter_mut() {
+                    // 1.13 widened the ids to an int per column
+                    *biome = if self.protocol_version >= 393 {
+                        data.read_i32::<byteorder::BigEndian>()?
+                    } else {
+                        data.read_u8()? as i32
+                    };
+                }
+                chunk.biomes = biome::BiomeStorage::from_2d(&biomes);
+            } else if let Some(biomes) = biomes_3d.filter(|_| new) {
+                chunk.biomes = biome::BiomeStorage::from_3d(biomes);
             }
 
             chunk.calculate_heightmap();
 
             self.chunks.write().insert(cpos, chunk);
         }
+        if let Some(cache) = self.chunk_cache() {
+            cache.store(self, cpos);
+        }
 
         self.dirty_chunks_by_bitmask(x, z, mask);
+        if new && self.biome_blend() > 0 {
+            self.dirty_neighbour_columns(x, z);
+        }
         Ok(())
     }
 
@@ -1220,6 +1363,24 @@ impl World {
         }
     }
 
+    /// Blended biome colours reach across chunk borders, so every section
+    /// around a newly loaded chunk has to pick up its biomes.
+    fn dirty_neighbour_columns(&self, x: i32, z: i32) {
+        let mut chunks = self.chunks.write();
+        for xo in -1..2 {
+            for zo in -1..2 {
+                if xo == 0 && zo == 0 {
+                    continue;
+                }
+                if let Some(chunk) = chunks.get_mut(&CPos(x + xo, z + zo)) {
+                    for section in chunk.sections.iter_mut().flatten() {
+                        section.dirty = true;
+                    }
+                }
+            }
+        }
+    }
+
     pub fn load_chunk18(
         &self,
         x: i32,
@@ -1229,7 +1390,7 @@ impl World {
         mask: u16,
         data: &mut std::io::Cursor<Vec<u8>>,
     ) -> Result<(), protocol::Error> {
-        self.load_chunk(x, z, new, skylight, new, mask, 0, data, 18)
+        self.load_chunk(x, z, new, skylight, new, None, mask, 0, data, 18)
     }
 
     pub fn load_chunks17(
@@ -1300,7 +1461,7 @@ impl World {
         mask_add: u16,
         data: &mut std::io::Cursor<Vec<u8>>,
     ) -> Result<(), protocol::Error> {
-        self.load_chunk(x, z, new, skylight, new, mask, mask_add, data, 17)
+        self.load_chunk(x, z, new, skylight, new, None, mask, mask_add, data, 17)
     }
 
     // TODO: Fix weird outlier phantom(unreal) light sources showing up in the corners of 1.12 chunks!
@@ -1368,7 +1529,7 @@ impl World {
         mask: u16,
         data: Vec<u8>,
     ) -> Result<(), protocol::Error> {
-        self.load_chunk19_or_115(true, x, z, new, sky_light, mask, data)
+        self.load_chunk19_or_115(true, None, x, z, new, sky_light, mask, data)
     }
 
     pub fn load_chunk115(
@@ -1378,15 +1539,17 @@ impl World {
         new: bool,
         sky_light: bool,
         mask: u16,
+        biomes: &[i32],
         data: Vec<u8>,
     ) -> Result<(), protocol::Error> {
-        self.load_chunk19_or_115(false, x, z, new, sky_light, mask, data)
+        self.load_chunk19_or_115(false, Some(biomes), x, z, new, sky_light, mask, data)
     }
 
     #[allow(clippy::or_fun_call)]
     fn load_chunk19_or_115(
         &self,
         read_biomes: bool,
+        biomes_3d: Option<&[i32]>,
         x: i32,
         z: i32,
         new: bool,
@@ -1400,6 +1563,7 @@ impl World {
             new,
             sky_light,
             read_biomes,
+            biomes_3d,
             mask,
             0,
             &mut Cursor::new(data),
@@ -1419,58 +1583,57 @@ impl World {
         }
     }
 
-    pub fn set_dimension(&self, new_dimension: Dimension) {
+    pub fn set_dimension(&self, new_dimension: DimensionType) {
         self.dimension.store(Arc::new(new_dimension));
     }
-}
 
-impl block::WorldAccess for World {
-    fn get_block(&self, pos: Position) -> block::Block {
-        World::get_block(self, pos)
+    pub fn set_dimension_codec(&self, codec: &NamedTag) {
+        *self.dimension_types.write() = DimensionType::parse_codec(&codec.1);
+        self.biome_registry
+            .store(Arc::new(biome::BiomeRegistry::from_codec(&codec.1)));
     }
-}
 
-#[derive(Debug)]
-pub enum DimensionID {
-    Index(i32),
-    Name(String),
-    Tag(NamedTag),
-}
-
-#[derive(Default, Debug)]
-pub enum Dimension {
-    #[default]
-    Overworld,
-    Nether,
-    End,
-    Other(DimensionID),
-}
-
-impl Dimension {
-    pub fn from_index(index: i32) -> Self {
-        match index {
-            -1 => Self::Nether,
-            0 => Self::Overworld,
-            1 => Self::End,
-            _ => Self::Other(DimensionID::Index(index)),
+    /// Switches to the dimension described by whichever of the JoinGame or
+    /// Respawn fields the server's version sends.
+    pub fn change_dimension(
+        &self,
+        index: Option<i32>,
+        name: Option<String>,
+        world_name: Option<String>,
+        tag: Option<NamedTag>,
+    ) {
+        // The new world sends its own view position, if any
+        *self.view_center.write() = None;
+        let name = name.or(world_name);
+        if let Some(cache) = self.chunk_cache() {
+            if let Some(key) = name
+                .clone()
+                .or_else(|| index.map(|index| index.to_string()))
+            {
+                cache.set_dimension(&key);
+            }
         }
-    }
-
-    pub fn from_name(name: &str) -> Self {
-        match name {
-            "minecraft:the_nether" => Self::Nether,
-            "minecraft:overworld" => Self::Overworld,
-            "minecraft:the_end" => Self::End,
-            _ => Self::Other(DimensionID::Name(name.to_string())),
+        let dimension = tag
+            .map(|tag| DimensionType::from_tag(&tag.1, name.as_deref().unwrap_or_default()))
+            .or_else(|| index.map(DimensionType::from_index))
+            .or_else(|| {
+                name.map(|name| {
+                    self.dimension_types
+                        .read()
+                        .get(&name)
+                        .cloned()
+                        .unwrap_or_else(|| DimensionType::from_name(&name))
+                })
+            });
+        if let Some(dimension) = dimension {
+            self.set_dimension(dimension);
         }
     }
+}
 
-    pub fn from_tag(tag: &NamedTag) -> Self {
-        Self::Other(DimensionID::Tag(tag.clone()))
-    }
-
-    pub fn has_sky_light(&self) -> bool {
-        matches!(*self, Dimension::Overworld)
+impl block::WorldAccess for World {
+    fn get_block(&self, pos: Position) -> block::Block {
+        World::get_block(self, pos)
     }
 }
 
@@ -1518,6 +1681,7 @@ mod tests {
                 new,
                 skylight,
                 read_biomes,
+                None,
                 mask,
                 mask_add,
                 &mut data,
@@ -1731,4 +1895,109 @@ mod tests {
         let data = include_bytes!("testdata/chunk_1.16.4_nether.bin");
         load_chunk(&world, 0, 0, true, false, false, 247, 0, data, 19);
     }
+
+    #[test]
+    fn light_updates_1_12_2() {
+        let world = build_world(340);
+        let data = include_bytes!("testdata/chunk_1.12.2.bin");
+        load_chunk(&world, 0, 0, true, true, true, 31, 0, data, 19);
+
+        let pos = Position::new(8, 120, 8);
+        let block_update = || {
+            vec![LightUpdate {
+                ty: LightType::Block,
+                pos,
+            }]
+        };
+        let sky_update = || {
+            vec![LightUpdate {
+                ty: LightType::Sky,
+                pos,
+            }]
+        };
+
+        // A torch lights up its surroundings and goes dark again
+        let torch = world.id_map.by_vanilla_id((50 << 4) | 5, &HashMap::new());
+        let emitted = torch.get_material().emitted_light;
+        assert!(emitted > 3);
+        world.set_block_raw(pos, torch);
+        world.do_light_updates(block_update());
+        assert_eq!(world.get_block_light(pos), emitted);
+        assert_eq!(world.get_block_light(pos + (3, 0, 0)), emitted - 3);
+        assert_eq!(world.get_block_light(pos + (1, -1, 1)), emitted - 3);
+        assert!(world.is_section_dirty((0, 7, 0)));
+
+        world.set_block_raw(pos, block::Air {});
+        world.do_light_updates(block_update());
+        assert_eq!(world.get_block_light(pos), 0);
+        assert_eq!(world.get_block_light(pos + (3, 0, 0)), 0);
+        assert_eq!(world.get_block_light(pos + (1, -1, 1)), 0);
+
+        // A solid block shades the column below it
+        let stone = world.id_map.by_vanilla_id(1 << 4, &HashMap::new());
+        world.set_block_raw(pos, stone);
+        world.do_light_updates(sky_update());
+        assert_eq!(world.get_sky_light(pos), 0);
+        assert_eq!(world.get_sky_light(pos + (0, -1, 0)), 14);
+        assert_eq!(world.get_sky_light(pos + (0, -20, 0)), 14);
+        assert_eq!(world.get_sky_light(pos + (1, -1, 0)), 15);
+
+        world.set_block_raw(pos, block::Air {});
+        world.do_light_updates(sky_update());
+        assert_eq!(world.get_sky_light(pos), 15);
+        assert_eq!(world.get_sky_light(pos + (0, -20, 0)), 15);
+    }
+
+    #[test]
+    fn light_spreads_around_removed_blocks() {
+        let world = build_world(340);
+        let data = include_bytes!("testdata/chunk_1.12.2.bin");
+        load_chunk(&world, 0, 0, true, true, true, 31, 0, data, 19);
+        let stone = world.id_map.by_vanilla_id(1 << 4, &HashMap::new());
+        let updates = |ty, positions: &[Position]| {
+            positions
+                .iter()
+                .map(|&pos| LightUpdate { ty, pos })
+                .collect::<Vec<_>>()
+        };
+
+        // Torch light has to go over a block in the way, and goes straight
+        // through once it's removed
+        let pos = Position::new(8, 120, 8);
+        let wall = pos + (1, 0, 0);
+        let torch = world.id_map.by_vanilla_id((50 << 4) | 5, &HashMap::new());
+        let emitted = torch.get_material().emitted_light;
+        world.set_block_raw(wall, stone);
+        world.set_block_raw(pos, torch);
+        world.do_light_updates(updates(LightType::Block, &[wall, pos]));
+        assert_eq!(world.get_block_light(wall), 0);
+        assert_eq!(world.get_block_light(pos + (2, 0, 0)), emitted - 4);
+
+        world.set_block_raw(wall, block::Air {});
+        world.do_light_updates(updates(LightType::Block, &[wall]));
+        assert_eq!(world.get_block_light(wall), emitted - 1);
+        assert_eq!(world.get_block_light(pos + (2, 0, 0)), emitted - 2);
+
+        // Under a roof sky light only comes in from the sides, until the
+        // roof is taken away again
+        let roof: Vec<_> = (-2..=2)
+            .flat_map(|x| (-2..=2).map(move |z| Position::new(8 + x, 125, 8 + z)))
+            .collect();
+        for &pos in &roof {
+            world.set_block_raw(pos, stone);
+        }
+        world.do_light_updates(updates(LightType::Sky, &roof));
+        let below = Position::new(8, 124, 8);
+        assert_eq!(world.get_sky_light(below), 12);
+        assert_eq!(world.get_sky_light(below + (0, -3, 0)), 12);
+        assert_eq!(world.get_sky_light(below + (2, 0, 0)), 14);
+        assert_eq!(world.get_sky_light(below + (3, 0, 0)), 15);
+
+        for &pos in &roof {
+            world.set_block_raw(pos, block::Air {});
+        }
+        world.do_light_updates(updates(LightType::Sky, &roof));
+        assert_eq!(world.get_sky_light(below), 15);
+        assert_eq!(world.get_sky_light(below + (0, -3, 0)), 15);
+    }
 }
@@ -1,8 +1,21 @@
+use crate::types::hash::FNVHash;
 use crate::world::block;
+use leafish_protocol::types::bit;
+use std::collections::HashMap;
+use std::hash::BuildHasherDefault;
 
+/// Block storage for a section, kept as indices into a local palette
+/// packed into as few bits per entry as the palette needs.
 #[derive(Clone)]
 pub struct BlockStorage {
-    blocks: Vec<block::Block>,
+    blocks: bit::Map,
+    /// Palette entries along with the number of blocks using them. Entries
+    /// whose count drops to zero are reused by the next new block.
+    palette: Vec<(usize, block::Block)>,
+    /// The palette entry of every block currently in use.
+    ids: HashMap<block::Block, usize, BuildHasherDefault<FNVHash>>,
+    /// Palette entries no block uses anymore.
+    free: Vec<usize>,
 }
 
 impl BlockStorage {
@@ -11,21 +24,167 @@ impl BlockStorage {
     }
 
     pub fn new_default(size: usize, def: block::Block) -> Self {
+        let mut ids = HashMap::with_hasher(BuildHasherDefault::default());
+        ids.insert(def, 0);
         Self {
-            blocks: vec![def; size],
+            blocks: bit::Map::new(size, 4),
+            palette: vec![(size, def)],
+            ids,
+            free: vec![],
         }
     }
 
     pub fn get(&self, idx: usize) -> block::Block {
-        self.blocks[idx]
+        self.palette[self.blocks.get(idx)].1
     }
 
     pub fn set(&mut self, idx: usize, b: block::Block) -> bool {
-        if self.blocks[idx] == b {
-            false
-        } else {
-            self.blocks[idx] = b;
-            true
+        let old = self.blocks.get(idx);
+        if self.palette[old].1 == b {
+            return false;
         }
+        self.palette[old].0 -= 1;
+        if self.palette[old].0 == 0 {
+            self.ids.remove(&self.palette[old].1);
+            self.free.push(old);
+        }
+
+        let id = match self.ids.get(&b) {
+            Some(&id) => id,
+            None => {
+                let id = match self.free.pop() {
+                    Some(id) => {
+                        self.palette[id].1 = b;
+                        id
+                    }
+                    None => {
+                        self.palette.push((0, b));
+                        self.palette.len() - 1
+                    }
+                };
+                if id >= 1 << self.blocks.bit_size {
+                    self.blocks = self.blocks.resize(self.blocks.bit_size + 1);
+                }
+                self.ids.insert(b, id);
+                id
+            }
+        };
+        self.palette[id].0 += 1;
+        self.blocks.set(idx, id);
+
+        // Only shrink once a quarter of the palette is left so blocks
+        // changing back and forth don't repack the section every time
+        if self.blocks.bit_size > 4 && self.ids.len() * 4 <= 1 << self.blocks.bit_size {
+            self.compact();
+        }
+        true
+    }
+
+    /// Drops the unused palette entries and packs the blocks into as few
+    /// bits as the ones left need.
+    fn compact(&mut self) {
+        let mut remap = vec![0; self.palette.len()];
+        let mut palette = Vec::with_capacity(self.ids.len());
+        for (id, &(count, block)) in self.palette.iter().enumerate() {
+            if count > 0 {
+                remap[id] = palette.len();
+                self.ids.insert(block, palette.len());
+                palette.push((count, block));
+            }
+        }
+        let mut bits = 4;
+        while palette.len() > 1 << bits {
+            bits += 1;
+        }
+        // Every block is counted by its palette entry, so the counts add
+        // up to the size of the storage
+        let size = palette.iter().map(|&(count, _)| count).sum();
+        for i in 0..size {
+            self.blocks.set(i, remap[self.blocks.get(i)]);
+        }
+        self.blocks = self.blocks.resize(bits);
+        self.palette = palette;
+        self.free.clear();
+    }
+
+    /// Whether every block in the storage is the same, without having to
+    /// look at them one by one.
+    #[cfg(test)]
+    pub fn is_uniform(&self) -> bool {
+        self.ids.len() == 1
+    }
+
+    /// The number of bits currently used per block.
+    #[cfg(test)]
+    pub fn bits_per_entry(&self) -> usize {
+        self.blocks.bit_size
+    }
+
+    /// The number of bytes the storage takes up, including its palette.
+    pub fn memory_size(&self) -> usize {
+        self.blocks.memory_size()
+            + self.palette.len() * std::mem::size_of::<(usize, block::Block)>()
+            + self.ids.len() * std::mem::size_of::<(block::Block, usize)>()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn palette_grows_and_reuses_entries() {
+        let id_map = block::VanillaIDMap::new(404);
+        let modded = Default::default();
+        let mut storage = BlockStorage::new(4096);
+        assert!(storage.is_uniform());
+        assert_eq!(storage.bits_per_entry(), 4);
+
+        for i in 0..40 {
+            storage.set(i, id_map.by_vanilla_id(i + 1, &modded));
+        }
+        assert!(storage.bits_per_entry() > 4);
+        for i in 0..40 {
+            assert_eq!(storage.get(i), id_map.by_vanilla_id(i + 1, &modded));
+        }
+        assert_eq!(storage.get(40), block::Air {});
+
+        for i in 0..40 {
+            assert!(storage.set(i, block::Air {}));
+        }
+        assert!(storage.is_uniform());
+        assert_eq!(storage.bits_per_entry(), 4);
+        let stone = id_map.by_vanilla_id(1, &modded);
+        assert!(storage.set(4095, stone));
+        assert!(!storage.set(4095, stone));
+        assert_eq!(storage.get(4095), stone);
+        assert_eq!(storage.bits_per_entry(), 4);
+    }
+
+    #[test]
+    fn palette_shrinks_once_mostly_unused() {
+        let id_map = block::VanillaIDMap::new(404);
+        let modded = Default::default();
+        let mut storage = BlockStorage::new(4096);
+        for i in 0..40 {
+            storage.set(i * 100, id_map.by_vanilla_id(i + 1, &modded));
+        }
+        assert_eq!(storage.bits_per_entry(), 6);
+
+        // Air and the last 16 blocks fill more than a quarter of 6 bits
+        for i in 0..24 {
+            storage.set(i * 100, block::Air {});
+        }
+        assert_eq!(storage.bits_per_entry(), 6);
+        storage.set(2400, block::Air {});
+        assert_eq!(storage.bits_per_entry(), 4);
+
+        for i in 25..40 {
+            assert_eq!(storage.get(i * 100), id_map.by_vanilla_id(i + 1, &modded));
+        }
+        assert_eq!(storage.get(0), block::Air {});
+        assert_eq!(storage.get(4095), block::Air {});
+        storage.set(4095, id_map.by_vanilla_id(1, &modded));
+        assert_eq!(storage.get(4095), id_map.by_vanilla_id(1, &modded));
     }
 }